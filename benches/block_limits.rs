@@ -69,6 +69,14 @@ impl HeadersDB for TestHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+
+    fn get_miner_reward_for_block(&self, _id_bhh: &StacksBlockId) -> Option<u128> {
+        Some(1)
+    }
+
+    fn get_total_fees_for_block(&self, _id_bhh: &StacksBlockId) -> Option<u128> {
+        Some(0)
+    }
 }
 
 fn as_hash160(inp: u32) -> [u8; 20] {
@@ -4,33 +4,65 @@ extern crate blockstack_lib;
 extern crate rand;
 
 use blockstack_lib::chainstate::burn::BlockHeaderHash;
-use blockstack_lib::chainstate::stacks::index::{marf::MARF, storage::TrieFileStorage, MARFValue};
+use blockstack_lib::chainstate::stacks::index::{
+    file::TrieFile,
+    marf::{MarfConnection, MARF},
+    storage::TrieFileStorage,
+    MARFValue, MarfTrieId,
+};
 
 use criterion::Criterion;
 use rand::prelude::*;
 use std::fs;
 
+/// Where the MARF keeps its tries: as blobs in SQLite, or in a blob file next to it
+const BACKENDS: &[(&str, bool)] = &[("sqlite", false), ("blobs", true)];
+
+/// Node cache sizes to compare read-heavy workloads with
+const NODE_CACHES: &[(&str, usize)] = &[("nocache", 0), ("cache", 64 * 1024 * 1024)];
+
+fn make_block_header(i: u32) -> BlockHeaderHash {
+    let mut block_header = i.to_le_bytes().to_vec();
+    block_header.resize(32, 0);
+    BlockHeaderHash::from_bytes(block_header.as_slice()).unwrap()
+}
+
+fn open_marf(filename: &str, blobs: bool) -> MARF<BlockHeaderHash> {
+    let storage = if blobs {
+        TrieFileStorage::open_with_blobs(filename).unwrap()
+    } else {
+        TrieFileStorage::open(filename).unwrap()
+    };
+    MARF::from_storage(storage)
+}
+
 fn benchmark_marf_usage(
     filename: &str,
     blocks: u32,
     writes_per_block: u32,
     reads_per_block: u32,
     batch: bool,
+    blobs: bool,
+    cache_size: usize,
 ) {
-    if fs::metadata(filename).is_ok() {
-        fs::remove_file(filename).unwrap();
-    };
-    let f = TrieFileStorage::new(filename).unwrap();
-    let mut block_header = BlockHeaderHash::from_bytes(&[0u8; 32]).unwrap();
-    let mut marf = MARF::from_storage(f);
-    marf.begin(&TrieFileStorage::block_sentinel(), &block_header)
-        .unwrap();
+    for path in [filename.to_string(), TrieFile::blobs_path(filename)].iter() {
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        };
+    }
+    let mut marf = open_marf(filename, blobs);
+    marf.set_node_cache_size(cache_size);
+    let mut parent = BlockHeaderHash::sentinel();
 
     let mut rng = rand::thread_rng();
 
     let mut values = vec![];
 
     for i in 0..blocks {
+        let block_header = make_block_header(i);
+        let mut tx = marf.begin_tx().unwrap();
+        tx.begin(&parent, &block_header).unwrap();
+
         if batch {
             let mut batch_keys = Vec::new();
             let mut batch_vals = Vec::new();
@@ -42,80 +74,114 @@ fn benchmark_marf_usage(
                 batch_vals.push(MARFValue(value.clone()));
                 values.push((key, MARFValue(value)));
             }
-            marf.insert_batch(&batch_keys, batch_vals).unwrap();
+            tx.insert_batch(&batch_keys, batch_vals).unwrap();
         } else {
             for k in 0..writes_per_block {
                 let key = format!("{}::{}", i, k);
                 let mut value = [0u8; 40];
                 rng.fill_bytes(&mut value);
-                marf.insert(&key, MARFValue(value.clone())).unwrap();
+                tx.insert_batch(&vec![key.clone()], vec![MARFValue(value.clone())])
+                    .unwrap();
                 values.push((key, MARFValue(value)));
             }
         }
 
         for _k in 0..reads_per_block {
             let (key, value) = values.as_slice().choose(&mut rng).unwrap();
-            assert_eq!(marf.get(&block_header, key).unwrap().unwrap(), *value);
+            assert_eq!(tx.get(&block_header, key).unwrap().unwrap(), *value);
         }
 
-        let mut next_block_header = (i + 1).to_le_bytes().to_vec();
-        next_block_header.resize(32, 0);
-        let next_block_header = BlockHeaderHash::from_bytes(next_block_header.as_slice()).unwrap();
-
-        marf.commit().unwrap();
-        marf.begin(&block_header, &next_block_header).unwrap();
-        block_header = next_block_header;
+        tx.commit_to(&block_header).unwrap();
+        parent = block_header;
     }
-    marf.commit().unwrap();
 }
 
-fn benchmark_marf_read(filename: &str, reads: u32, block: u32, writes_per_block: u32) {
-    let f = TrieFileStorage::new(filename).unwrap();
-    let mut block_header = block.to_le_bytes().to_vec();
-    block_header.resize(32, 0);
-    let block_header = BlockHeaderHash::from_bytes(block_header.as_slice()).unwrap();
+fn benchmark_marf_read(filename: &str, reads: u32, blocks: u32, writes_per_block: u32) {
+    // opens the blob file, if the MARF was set up with one
+    let mut marf = open_marf(filename, false);
+    read_random_keys(&mut marf, reads, blocks, writes_per_block);
+}
 
-    let mut marf = MARF::from_storage(f);
+fn read_random_keys(
+    marf: &mut MARF<BlockHeaderHash>,
+    reads: u32,
+    blocks: u32,
+    writes_per_block: u32,
+) {
+    let tip = make_block_header(blocks - 1);
 
     let mut rng = rand::thread_rng();
 
     for _i in 0..reads {
-        let i: u32 = rng.gen_range(0, block);
+        let i: u32 = rng.gen_range(0, blocks);
         let k: u32 = rng.gen_range(0, writes_per_block);
         let key = format!("{}::{}", i, k);
-        marf.get(&block_header, &key).unwrap().unwrap();
+        marf.get(&tip, &key).unwrap().unwrap();
     }
 }
 
 pub fn basic_usage_benchmark(c: &mut Criterion) {
-    c.bench_function("marf_setup_1000b_5kW", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/db.1k.sqlite", 1000, 5000, 0, false))
-    });
-    c.bench_function("marf_setup_400b_5kW", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/db.400.sqlite", 1000, 5000, 0, false))
-    });
-    c.bench_function("marf_read_1000b_1kW", |b| {
-        b.iter(|| benchmark_marf_read("/tmp/db.1k.sqlite", 1000, 1000, 5000))
-    });
-    c.bench_function("marf_read_400b_1kW", |b| {
-        b.iter(|| benchmark_marf_read("/tmp/db.400.sqlite", 1000, 400, 5000))
-    });
-
-    c.bench_function("marf_usage_1b_10kW_0kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 1, 10000, 0, false))
-    });
-    c.bench_function("marf_usage_10b_1kW_2kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 10, 1000, 2000, false))
-    });
-    c.bench_function("marf_usage_100b_5kW_20kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 20, 5000, 20000, false))
-    });
-    c.bench_function("marf_usage_batches_10b_1kW_2kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 10, 1000, 2000, true))
-    });
+    for (backend, blobs) in BACKENDS.iter() {
+        let db_1k = format!("/tmp/db.1k.{}.sqlite", backend);
+        let db_400 = format!("/tmp/db.400.{}.sqlite", backend);
+        let db_usage = format!("/tmp/foo.bar.z.{}.sqlite", backend);
+
+        c.bench_function(&format!("marf_setup_1000b_5kW_{}", backend), |b| {
+            b.iter(|| benchmark_marf_usage(&db_1k, 1000, 5000, 0, false, *blobs, 0))
+        });
+        c.bench_function(&format!("marf_setup_400b_5kW_{}", backend), |b| {
+            b.iter(|| benchmark_marf_usage(&db_400, 400, 5000, 0, false, *blobs, 0))
+        });
+        c.bench_function(&format!("marf_read_1000b_1kW_{}", backend), |b| {
+            b.iter(|| benchmark_marf_read(&db_1k, 1000, 1000, 5000))
+        });
+        c.bench_function(&format!("marf_read_400b_1kW_{}", backend), |b| {
+            b.iter(|| benchmark_marf_read(&db_400, 1000, 400, 5000))
+        });
+
+        c.bench_function(&format!("marf_usage_1b_10kW_0kR_{}", backend), |b| {
+            b.iter(|| benchmark_marf_usage(&db_usage, 1, 10000, 0, false, *blobs, 0))
+        });
+        c.bench_function(&format!("marf_usage_10b_1kW_2kR_{}", backend), |b| {
+            b.iter(|| benchmark_marf_usage(&db_usage, 10, 1000, 2000, false, *blobs, 0))
+        });
+        c.bench_function(&format!("marf_usage_100b_5kW_20kR_{}", backend), |b| {
+            b.iter(|| benchmark_marf_usage(&db_usage, 20, 5000, 20000, false, *blobs, 0))
+        });
+        c.bench_function(
+            &format!("marf_usage_batches_10b_1kW_2kR_{}", backend),
+            |b| b.iter(|| benchmark_marf_usage(&db_usage, 10, 1000, 2000, true, *blobs, 0)),
+        );
+    }
+}
+
+pub fn read_heavy_benchmark(c: &mut Criterion) {
+    for (backend, blobs) in BACKENDS.iter() {
+        let db_400 = format!("/tmp/db.400.{}.sqlite", backend);
+        let db_usage = format!("/tmp/foo.bar.z.{}.sqlite", backend);
+
+        for (cache, cache_size) in NODE_CACHES.iter() {
+            // the MARF stays open across iterations, so reads after the first can hit the cache
+            let mut marf = open_marf(&db_400, false);
+            marf.set_node_cache_size(*cache_size);
+            c.bench_function(
+                &format!("marf_read_heavy_400b_10kR_{}_{}", backend, cache),
+                |b| b.iter(|| read_random_keys(&mut marf, 10000, 400, 5000)),
+            );
+
+            c.bench_function(
+                &format!("marf_usage_20b_1kW_50kR_{}_{}", backend, cache),
+                |b| {
+                    b.iter(|| {
+                        benchmark_marf_usage(&db_usage, 20, 1000, 50000, false, *blobs, *cache_size)
+                    })
+                },
+            );
+        }
+    }
 }
 
 pub fn scaling_read_ratio(_c: &mut Criterion) {}
 
-criterion_group!(benches, basic_usage_benchmark);
+criterion_group!(benches, basic_usage_benchmark, read_heavy_benchmark);
 criterion_main!(benches);
@@ -262,6 +262,14 @@ impl HeadersDB for TestSimHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         Some(MINER_ADDR.clone())
     }
+    fn get_miner_reward_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128> {
+        self.get_burn_block_height_for_block(id_bhh)?;
+        Some(1000)
+    }
+    fn get_total_fees_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128> {
+        self.get_burn_block_height_for_block(id_bhh)?;
+        Some(0)
+    }
 }
 
 #[test]
@@ -521,6 +529,20 @@ fn delegation_tests() {
             Value::UInt(USTX_PER_HOLDER - *MIN_THRESHOLD + 1)
         );
 
+        assert_eq!(
+            env.eval_read_only(
+                &POX_CONTRACT_TESTNET,
+                &format!("(stx-account '{})", &Value::from(&USER_KEYS[0]))
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "{{ locked: {}, unlocked: {}, unlock-height: u450 }}",
+                Value::UInt(*MIN_THRESHOLD - 1),
+                Value::UInt(USTX_PER_HOLDER - *MIN_THRESHOLD + 1)
+            ))
+        );
+
         // try to commit our partial stacking...
         assert_eq!(
             env.execute_transaction(
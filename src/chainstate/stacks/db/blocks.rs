@@ -261,6 +261,9 @@ impl From<db_error> for MemPoolRejection {
 pub const MINIMUM_TX_FEE: u64 = 1;
 pub const MINIMUM_TX_FEE_RATE_PER_BYTE: u64 = 1;
 
+/// How many MARF nodes' worth of pruning work to do per processed block (non-archival nodes only)
+pub const MARF_PRUNE_NODES_PER_BLOCK: u64 = 10_000;
+
 impl StagingBlock {
     pub fn is_first_mined(&self) -> bool {
         self.parent_anchored_block_hash == FIRST_STACKS_BLOCK_HASH
@@ -3797,6 +3800,7 @@ impl StacksChainState {
                             stx_burned: 0,
                             contract_analysis: None,
                             execution_cost,
+                            stack_trace: None,
                         };
 
                         all_receipts.push(receipt);
@@ -3850,6 +3854,7 @@ impl StacksChainState {
                                 stx_burned: 0,
                                 contract_analysis: None,
                                 execution_cost: ExecutionCost::zero(),
+                                stack_trace: None,
                             }),
                             Err(e) => {
                                 info!("TransferStx burn op processing error.";
@@ -4222,6 +4227,11 @@ impl StacksChainState {
                     &mut clarity_tx,
                     &microblocks,
                 ) {
+                    Err((Error::PrunedStateError(pruned_block), _)) => {
+                        // not the microblocks' fault
+                        clarity_tx.rollback_block();
+                        return Err(Error::PrunedStateError(pruned_block));
+                    }
                     Err((e, offending_mblock_header_hash)) => {
                         let msg = format!(
                             "Invalid Stacks microblocks {},{} (offender {}): {:?}",
@@ -4272,6 +4282,11 @@ impl StacksChainState {
             // process anchored block
             let (block_fees, block_burns, txs_receipts) =
                 match StacksChainState::process_block_transactions(&mut clarity_tx, &block) {
+                    Err(Error::PrunedStateError(pruned_block)) => {
+                        // not the block's fault
+                        clarity_tx.rollback_block();
+                        return Err(Error::PrunedStateError(pruned_block));
+                    }
                     Err(e) => {
                         let msg = format!("Invalid Stacks block {}: {:?}", block.block_hash(), &e);
                         warn!("{}", &msg);
@@ -4763,6 +4778,18 @@ impl StacksChainState {
             &user_supports,
         ) {
             Ok(next_chain_tip_info) => next_chain_tip_info,
+            Err(Error::PrunedStateError(pruned_block)) => {
+                // this node has pruned state that the block reads, so it can't tell whether or
+                // not the block is valid.  It must not mark the block as processed, and it
+                // can't process any more blocks in this fork.
+                error!(
+                    "Cannot process block {}/{}: it reads the pruned state of block {}",
+                    &next_staging_block.consensus_hash,
+                    &block.block_hash(),
+                    &pruned_block
+                );
+                return Err(Error::PrunedStateError(pruned_block));
+            }
             Err(e) => {
                 // something's wrong with this epoch -- either a microblock was invalid, or the
                 // anchored block was invalid.  Either way, the anchored block will _never be_
@@ -4873,6 +4900,57 @@ impl StacksChainState {
         Ok((Some(epoch_receipt), None))
     }
 
+    /// Non-archival nodes only: drop the Clarity state of the blocks `marf_prune_depth` or more
+    /// blocks below the canonical Stacks chain tip.  A new pruning pass begins every
+    /// `marf_prune_depth` blocks, and each batch of processed blocks only does a bounded step of
+    /// its work, so that no one block pays for a walk over all of the live state.
+    fn prune_clarity_state(
+        &mut self,
+        sort_tx: &SortitionHandleTx,
+        processed: &[(Option<StacksEpochReceipt>, Option<TransactionPayload>)],
+    ) -> Result<(), Error> {
+        let prune_depth = match self.marf_prune_depth {
+            Some(depth) if depth > 0 => depth,
+            _ => {
+                return Ok(());
+            }
+        };
+
+        let mut num_processed = 0;
+        let mut begin_pass = false;
+        for (receipt_opt, _) in processed.iter() {
+            if let Some(receipt) = receipt_opt {
+                num_processed += 1;
+                if receipt.header.block_height % (prune_depth as u64) == 0 {
+                    begin_pass = true;
+                }
+            }
+        }
+        if num_processed == 0 || !(begin_pass || self.clarity_state.is_pruning()) {
+            return Ok(());
+        }
+
+        let (consensus_hash, block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sort_tx)?;
+        let tip = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+        match self.clarity_state.prune(
+            &tip,
+            prune_depth,
+            MARF_PRUNE_NODES_PER_BLOCK * num_processed,
+        ) {
+            Ok(num_pruned) => {
+                debug!(
+                    "Pruned Clarity state of {} blocks below {}",
+                    num_pruned, &tip
+                );
+            }
+            Err(e) => {
+                warn!("Failed to prune Clarity state below {}: {:?}", &tip, &e);
+            }
+        }
+        Ok(())
+    }
+
     /// Process staging blocks at the canonical chain tip,
     ///  this only needs to be used in contexts that aren't
     ///  PoX aware (i.e., unit tests, and old stacks-node loops),
@@ -4943,6 +5021,8 @@ impl StacksChainState {
             }
         }
 
+        self.prune_clarity_state(&sort_tx, &ret)?;
+
         sort_tx.commit()?;
 
         let blocks_path = self.blocks_path.clone();
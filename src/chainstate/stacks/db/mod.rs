@@ -18,6 +18,7 @@ pub mod accounts;
 pub mod blocks;
 pub mod contracts;
 pub mod headers;
+pub mod snapshot;
 pub mod transactions;
 pub mod unconfirmed;
 
@@ -53,7 +54,9 @@ use chainstate::stacks::index::{MARFValue, MarfTrieId, TrieHash};
 use chainstate::stacks::Error;
 use chainstate::stacks::*;
 
+use chainstate::stacks::index::file::TrieFile;
 use chainstate::stacks::index::storage::TrieFileStorage;
+use chainstate::stacks::index::Error as marf_error;
 
 use chainstate::burn::db::sortdb::BlockHeaderCache;
 
@@ -108,6 +111,9 @@ pub struct StacksChainState {
     pub root_path: String,
     pub block_limit: ExecutionCost,
     pub unconfirmed_state: Option<UnconfirmedState>,
+    /// If set, the Clarity state of the blocks this many or more blocks below the canonical
+    /// chain tip is pruned (non-archival mode).
+    pub marf_prune_depth: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -675,6 +681,12 @@ pub struct ChainStateBootData {
         Option<Box<dyn FnOnce() -> Box<dyn Iterator<Item = ChainstateBNSNamespace>>>>,
     pub get_bulk_initial_names:
         Option<Box<dyn FnOnce() -> Box<dyn Iterator<Item = ChainstateBNSName>>>>,
+    /// If set, a new chainstate keeps the confirmed tries of its MARFs in blob files (see
+    /// TrieFile) from the start.
+    pub marf_blob_files: bool,
+    /// If set, a new chainstate indexes the keys of data map entries and NFTs from the start, so
+    /// that their entries can be listed (see MarfedKV::set_key_index()).
+    pub clarity_key_index: bool,
 }
 
 impl ChainStateBootData {
@@ -693,6 +705,8 @@ impl ChainStateBootData {
             get_bulk_initial_balances: None,
             get_bulk_initial_namespaces: None,
             get_bulk_initial_names: None,
+            marf_blob_files: false,
+            clarity_key_index: false,
         }
     }
 }
@@ -1365,6 +1379,23 @@ impl StacksChainState {
             Err(_) => true,
         };
 
+        let marf_blob_files = boot_data
+            .as_ref()
+            .map(|boot_data| boot_data.marf_blob_files)
+            .unwrap_or(false);
+        if init_required && marf_blob_files {
+            // new chainstate -- the MARFs will use these blob files once they are created
+            StacksChainState::mkdirs(&PathBuf::from(&clarity_state_index_root))?;
+            for marf_path in [&header_index_root, &clarity_state_index_marf].iter() {
+                match TrieFile::create(marf_path) {
+                    Ok(_) | Err(marf_error::ExistsError) => {}
+                    Err(e) => {
+                        return Err(Error::MARFError(e));
+                    }
+                }
+            }
+        }
+
         let state_index = StacksChainState::open_db(mainnet, chain_id, &header_index_root)?;
 
         let vm_state = MarfedKV::open(
@@ -1376,7 +1407,18 @@ impl StacksChainState {
         )
         .map_err(|e| Error::ClarityError(e.into()))?;
 
-        let clarity_state = ClarityInstance::new(mainnet, vm_state, block_limit.clone());
+        let mut clarity_state = ClarityInstance::new(mainnet, vm_state, block_limit.clone());
+        if init_required
+            && boot_data
+                .as_ref()
+                .map(|boot_data| boot_data.clarity_key_index)
+                .unwrap_or(false)
+        {
+            // index the keys written by the boot code too
+            clarity_state
+                .set_key_index(true)
+                .map_err(|e| Error::ClarityError(e.into()))?;
+        }
 
         let mut chainstate = StacksChainState {
             mainnet: mainnet,
@@ -1389,6 +1431,7 @@ impl StacksChainState {
             root_path: path_str.to_string(),
             block_limit: block_limit,
             unconfirmed_state: None,
+            marf_prune_depth: None,
         };
 
         let mut receipts = vec![];
@@ -1417,6 +1460,24 @@ impl StacksChainState {
         }
     }
 
+    /// Cache up to (approximately) `max_size` bytes of MARF nodes in all, split evenly between the
+    /// chainstate index and the Clarity state.  A size of 0 disables the caches.
+    pub fn set_marf_cache_size(&mut self, max_size: usize) {
+        let index_size = max_size / 2;
+        self.state_index.set_node_cache_size(index_size);
+        self.clarity_state
+            .set_marf_cache_size(max_size - index_size);
+    }
+
+    /// Maintain (or stop maintaining) the index of the keys of data map entries and NFTs in the
+    /// Clarity state, which lets their entries be listed.  The index can't be backfilled, so it is
+    /// only complete if it was enabled when the chainstate was created (see ChainStateBootData).
+    pub fn set_clarity_key_index(&mut self, enabled: bool) -> Result<(), Error> {
+        self.clarity_state
+            .set_key_index(enabled)
+            .map_err(|e| Error::ClarityError(e.into()))
+    }
+
     /// Begin a transaction against the (indexed) stacks chainstate DB.
     /// Does not create a Clarity instance.
     pub fn index_tx_begin<'a>(&'a mut self) -> Result<StacksDBTx<'a>, Error> {
@@ -1979,6 +2040,8 @@ pub mod test {
             get_bulk_initial_balances: None,
             get_bulk_initial_names: None,
             get_bulk_initial_namespaces: None,
+            marf_blob_files: false,
+            clarity_key_index: false,
         };
 
         StacksChainState::open_and_exec(
@@ -2025,6 +2088,81 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_instantiate_chainstate_with_blob_files() {
+        let path = chainstate_path("instantiate-chainstate-with-blob-files");
+        match fs::metadata(&path) {
+            Ok(_) => {
+                fs::remove_dir_all(&path).unwrap();
+            }
+            Err(_) => {}
+        };
+
+        let mut boot_data = ChainStateBootData {
+            initial_balances: vec![],
+            post_flight_callback: None,
+            first_burnchain_block_hash: BurnchainHeaderHash::zero(),
+            first_burnchain_block_height: 0,
+            first_burnchain_block_timestamp: 0,
+            get_bulk_initial_lockups: None,
+            get_bulk_initial_balances: None,
+            get_bulk_initial_names: None,
+            get_bulk_initial_namespaces: None,
+            marf_blob_files: true,
+            clarity_key_index: false,
+        };
+        let chainstate = StacksChainState::open_and_exec(
+            false,
+            0x80000000,
+            &path,
+            Some(&mut boot_data),
+            ExecutionCost::max_value(),
+        )
+        .unwrap()
+        .0;
+
+        // both MARFs stored the boot block's trie in their blob files
+        let mut index_path = PathBuf::from(&chainstate.clarity_state_index_root);
+        index_path.pop();
+        index_path.push("index");
+        for marf_path in [
+            index_path.to_str().unwrap(),
+            chainstate.clarity_state_index_path.as_str(),
+        ]
+        .iter()
+        {
+            assert!(TrieFile::exists(marf_path));
+            let storage = TrieFileStorage::<StacksBlockId>::open_readonly(marf_path).unwrap();
+            assert!(storage.has_blobs());
+            assert!(
+                fs::metadata(&TrieFile::blobs_path(marf_path))
+                    .unwrap()
+                    .len()
+                    > 0
+            );
+        }
+
+        // reopening the chainstate keeps using them
+        let mut chainstate =
+            open_chainstate(false, 0x80000000, "instantiate-chainstate-with-blob-files");
+        let mut conn = chainstate.block_begin(
+            &NULL_BURN_STATE_DB,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        for (boot_contract_name, _) in STACKS_BOOT_CODE_TESTNET.iter() {
+            let boot_contract_id = QualifiedContractIdentifier::new(
+                boot_code_test_addr().into(),
+                ContractName::try_from(boot_contract_name.to_string()).unwrap(),
+            );
+            let contract_res =
+                StacksChainState::get_contract(&mut conn, &boot_contract_id).unwrap();
+            assert!(contract_res.is_some());
+        }
+    }
+
     #[test]
     fn test_chainstate_sampled_genesis_consistency() {
         // Test root hash for the test chainstate data set
@@ -2076,6 +2214,8 @@ pub mod test {
                         }),
                 )
             })),
+            marf_blob_files: false,
+            clarity_key_index: false,
         };
 
         let path = chainstate_path("genesis-consistency-chainstate-test");
@@ -2166,6 +2306,8 @@ pub mod test {
                         }),
                 )
             })),
+            marf_blob_files: false,
+            clarity_key_index: false,
         };
 
         let path = chainstate_path("genesis-consistency-chainstate");
@@ -0,0 +1,732 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chainstate snapshots, for bootstrapping a node without syncing from genesis.
+//!
+//! A snapshot archive holds copies of the databases a node needs to continue syncing: the
+//! headers DB (which is also the headers MARF and the staging block index), the Clarity MARF
+//! (which also holds the Clarity metadata and side-store), the sortition DB, and the
+//! burnchain DB, as well as the blob files of any of those MARFs that keep their tries in one.
+//! Block and microblock files are not included.
+//!
+//! Archive format:
+//! 0        8           12                  12+N
+//! |--------|-----------|-------------------|----------------------------|
+//!   magic    N (u32 BE)   manifest (JSON)     file contents, in manifest order
+//!
+//! The manifest pins the Stacks block the snapshot was taken at, along with the root hashes of
+//! the Clarity and headers MARFs at that block, and the size and SHA512/256 hash of each file.
+//! Both export and import check these against the databases themselves.
+//!
+//! Since the database files are copied whole, they hold the state of every block the exporting
+//! node had processed, so a snapshot is always taken at the canonical Stacks chain tip: that is
+//! where a node that imports it will resume.
+
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags, NO_PARAMS};
+use sha2::{Digest, Sha512Trunc256};
+
+use burnchains::BurnchainHeaderHash;
+use chainstate::burn::db::sortdb::SortitionDB;
+use chainstate::burn::{BlockHeaderHash, ConsensusHash};
+use chainstate::stacks::db::{DBConfig, StacksChainState};
+use chainstate::stacks::index::file::TrieFile;
+use chainstate::stacks::index::marf::{MarfConnection, MARF};
+use chainstate::stacks::index::storage::TrieFileStorage;
+use chainstate::stacks::index::TrieHash;
+use chainstate::stacks::{Error, StacksBlockHeader, StacksBlockId};
+use util::db::{query_row, tx_busy_handler};
+use util::hash::Sha512Trunc256Sum;
+
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"STXSNAP\x00";
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Database files in the chainstate directory (i.e., `chain-*-mainnet` or `chain-*-testnet`)
+const CHAINSTATE_FILES: &[&str] = &["vm/index", "vm/clarity/marf"];
+/// Database files in the burnchain directory (i.e., the one with `sortition.db` and `burnchain.db`)
+const BURNCHAIN_FILES: &[&str] = &["sortition.db/marf", "burnchain.db"];
+
+/// Database files which are MARFs, and so may have a blob file alongside them
+const MARF_FILES: &[&str] = &[
+    "chainstate/vm/index",
+    "chainstate/vm/clarity/marf",
+    "burnchain/sortition.db/marf",
+];
+
+const CHAINSTATE_PREFIX: &str = "chainstate/";
+const BURNCHAIN_PREFIX: &str = "burnchain/";
+
+const COPY_BUFFER_SIZE: usize = 65536;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Path of the file, relative to the chainstate or burnchain directory and prefixed with
+    /// `chainstate/` or `burnchain/` respectively.
+    pub path: String,
+    pub size: u64,
+    pub hash: Sha512Trunc256Sum,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub mainnet: bool,
+    pub chain_id: u32,
+    pub index_block_hash: StacksBlockId,
+    pub consensus_hash: ConsensusHash,
+    pub block_hash: BlockHeaderHash,
+    pub block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub burn_header_height: u32,
+    /// Root hash of the Clarity MARF at this block
+    pub clarity_root_hash: TrieHash,
+    /// Root hash of the headers MARF at this block
+    pub headers_root_hash: TrieHash,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// A connection which holds a database's write lock while the database file is read, so the
+/// file is not modified underneath us.  Readers are not blocked.
+struct SnapshotDBLock {
+    path: PathBuf,
+    conn: Connection,
+}
+
+impl SnapshotDBLock {
+    fn open(path: PathBuf) -> Result<SnapshotDBLock, Error> {
+        if fs::metadata(&path).is_err() {
+            return Err(Error::InvalidSnapshot(format!(
+                "No such database: {}",
+                path.display()
+            )));
+        }
+        let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        conn.busy_handler(Some(tx_busy_handler))?;
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+        Ok(SnapshotDBLock { path, conn })
+    }
+}
+
+impl Drop for SnapshotDBLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.conn.execute_batch("ROLLBACK") {
+            warn!(
+                "Failed to release snapshot lock on {}: {:?}",
+                self.path.display(),
+                &e
+            );
+        }
+    }
+}
+
+/// Get the archive name and path of a MARF's blob file.
+fn blob_file_path(archive_name: &str, path: &Path) -> (String, PathBuf) {
+    (
+        format!("{}.blobs", archive_name),
+        PathBuf::from(TrieFile::blobs_path(&path.to_string_lossy())),
+    )
+}
+
+fn snapshot_file_paths(chainstate_dir: &Path, burnchain_dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut paths = vec![];
+    for file in CHAINSTATE_FILES.iter() {
+        paths.push((
+            format!("{}{}", CHAINSTATE_PREFIX, file),
+            chainstate_dir.join(file),
+        ));
+    }
+    for file in BURNCHAIN_FILES.iter() {
+        paths.push((
+            format!("{}{}", BURNCHAIN_PREFIX, file),
+            burnchain_dir.join(file),
+        ));
+    }
+    paths
+}
+
+/// Copy `size` bytes from `r` to `w` (if given), returning their hash.
+fn copy_and_hash<R: Read, W: Write>(
+    r: &mut R,
+    mut w: Option<&mut W>,
+    size: u64,
+) -> Result<Sha512Trunc256Sum, Error> {
+    let mut hasher = Sha512Trunc256::new();
+    let mut buf = vec![0u8; COPY_BUFFER_SIZE];
+    let mut remaining = size;
+    while remaining > 0 {
+        let to_read = if remaining < (COPY_BUFFER_SIZE as u64) {
+            remaining as usize
+        } else {
+            COPY_BUFFER_SIZE
+        };
+        r.read_exact(&mut buf[0..to_read])
+            .map_err(Error::ReadError)?;
+        hasher.input(&buf[0..to_read]);
+        if let Some(ref mut w) = w {
+            w.write_all(&buf[0..to_read]).map_err(Error::WriteError)?;
+        }
+        remaining -= to_read as u64;
+    }
+    Ok(Sha512Trunc256Sum::from_hasher(hasher))
+}
+
+fn hash_file(path: &Path) -> Result<(u64, Sha512Trunc256Sum), Error> {
+    let size = fs::metadata(path).map_err(Error::ReadError)?.len();
+    let mut fd = File::open(path).map_err(Error::ReadError)?;
+    let hash = copy_and_hash::<_, File>(&mut fd, None, size)?;
+    Ok((size, hash))
+}
+
+/// Read the state of the canonical Stacks chain tip out of the chainstate and burnchain
+/// databases, checking that the MARF root hashes agree with the tip's header.  The returned
+/// manifest has no entries.
+fn read_snapshot_tip(
+    chainstate_dir: &Path,
+    burnchain_dir: &Path,
+) -> Result<SnapshotManifest, Error> {
+    let headers_path = chainstate_dir.join(CHAINSTATE_FILES[0]);
+    let clarity_path = chainstate_dir.join(CHAINSTATE_FILES[1]);
+    let sortition_path = burnchain_dir.join(BURNCHAIN_FILES[0]);
+
+    let sortition_conn =
+        Connection::open_with_flags(&sortition_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let (consensus_hash, block_hash) =
+        SortitionDB::get_canonical_stacks_chain_tip_hash(&sortition_conn)?;
+    let index_block_hash = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+
+    let mut headers_marf: MARF<StacksBlockId> = MARF::from_storage(TrieFileStorage::open_readonly(
+        &headers_path.to_string_lossy(),
+    )?);
+    let db_config = query_row::<DBConfig, _>(
+        headers_marf.sqlite_conn(),
+        "SELECT * FROM db_config LIMIT 1",
+        NO_PARAMS,
+    )?
+    .ok_or_else(|| Error::InvalidSnapshot("No chainstate DB config".to_string()))?;
+
+    let header_info = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+        headers_marf.sqlite_conn(),
+        &index_block_hash,
+    )?
+    .ok_or_else(|| Error::InvalidSnapshot(format!("No processed block {}", index_block_hash)))?;
+
+    let headers_root_hash = headers_marf.get_root_hash_at(&index_block_hash)?;
+    if headers_root_hash != header_info.index_root {
+        return Err(Error::InvalidSnapshot(format!(
+            "Headers MARF root hash {} at {} does not match header index root {}",
+            &headers_root_hash, &index_block_hash, &header_info.index_root
+        )));
+    }
+
+    let mut clarity_marf: MARF<StacksBlockId> = MARF::from_storage(TrieFileStorage::open_readonly(
+        &clarity_path.to_string_lossy(),
+    )?);
+    let clarity_root_hash = clarity_marf.get_root_hash_at(&index_block_hash)?;
+
+    // the boot code's header does not commit to the Clarity state
+    if header_info.block_height > 0
+        && clarity_root_hash != header_info.anchored_header.state_index_root
+    {
+        return Err(Error::InvalidSnapshot(format!(
+            "Clarity MARF root hash {} at {} does not match header state index root {}",
+            &clarity_root_hash, &index_block_hash, &header_info.anchored_header.state_index_root
+        )));
+    }
+
+    let snapshot =
+        SortitionDB::get_block_snapshot_consensus(&sortition_conn, &header_info.consensus_hash)?
+            .ok_or_else(|| {
+                Error::InvalidSnapshot(format!(
+                    "No sortition with consensus hash {}",
+                    &header_info.consensus_hash
+                ))
+            })?;
+    if snapshot.burn_header_hash != header_info.burn_header_hash {
+        return Err(Error::InvalidSnapshot(format!(
+            "Sortition {} is in burnchain block {}, but block {} is in {}",
+            &header_info.consensus_hash,
+            &snapshot.burn_header_hash,
+            &index_block_hash,
+            &header_info.burn_header_hash
+        )));
+    }
+
+    Ok(SnapshotManifest {
+        version: SNAPSHOT_VERSION,
+        mainnet: db_config.mainnet,
+        chain_id: db_config.chain_id,
+        index_block_hash,
+        consensus_hash: header_info.consensus_hash.clone(),
+        block_hash: header_info.anchored_header.block_hash(),
+        block_height: header_info.block_height,
+        burn_header_hash: header_info.burn_header_hash.clone(),
+        burn_header_height: header_info.burn_header_height,
+        clarity_root_hash,
+        headers_root_hash,
+        entries: vec![],
+    })
+}
+
+/// Write a snapshot of the chainstate at the canonical Stacks chain tip to `archive_path`.
+/// `chainstate_dir` is the `chain-*` directory within the node's chainstate directory, and
+/// `burnchain_dir` is the directory that holds `sortition.db` and `burnchain.db`.  The
+/// databases are locked against writes while they are copied, so the tip can't move.
+pub fn export_snapshot(
+    chainstate_dir: &str,
+    burnchain_dir: &str,
+    archive_path: &str,
+) -> Result<SnapshotManifest, Error> {
+    let chainstate_dir = PathBuf::from(chainstate_dir);
+    let burnchain_dir = PathBuf::from(burnchain_dir);
+
+    if fs::metadata(archive_path).is_ok() {
+        return Err(Error::InvalidSnapshot(format!(
+            "Archive {} already exists",
+            archive_path
+        )));
+    }
+
+    // blob files are only appended to by writers holding their database's lock
+    let mut locks = vec![];
+    let mut files = vec![];
+    for (archive_name, path) in snapshot_file_paths(&chainstate_dir, &burnchain_dir).into_iter() {
+        locks.push(SnapshotDBLock::open(path.clone())?);
+        let blob_file = blob_file_path(&archive_name, &path);
+        files.push((archive_name.clone(), path));
+        if MARF_FILES.contains(&archive_name.as_str()) && fs::metadata(&blob_file.1).is_ok() {
+            files.push(blob_file);
+        }
+    }
+
+    let mut manifest = read_snapshot_tip(&chainstate_dir, &burnchain_dir)?;
+    for (archive_name, path) in files.iter() {
+        let (size, hash) = hash_file(path)?;
+        manifest.entries.push(SnapshotEntry {
+            path: archive_name.clone(),
+            size,
+            hash,
+        });
+    }
+
+    let paths: Vec<&Path> = files.iter().map(|(_, path)| path.as_path()).collect();
+    if let Err(e) = write_snapshot_archive(archive_path, &manifest, &paths) {
+        let _ = fs::remove_file(archive_path);
+        return Err(e);
+    }
+
+    info!(
+        "Exported snapshot of block {} (height {}) to {}",
+        &manifest.index_block_hash, manifest.block_height, archive_path
+    );
+    Ok(manifest)
+}
+
+fn write_snapshot_archive(
+    archive_path: &str,
+    manifest: &SnapshotManifest,
+    paths: &[&Path],
+) -> Result<(), Error> {
+    let manifest_bytes = serde_json::to_vec(manifest)
+        .map_err(|e| Error::InvalidSnapshot(format!("Failed to encode manifest: {:?}", &e)))?;
+
+    let mut fd = File::create(archive_path).map_err(Error::WriteError)?;
+    fd.write_all(SNAPSHOT_MAGIC).map_err(Error::WriteError)?;
+    fd.write_all(&(manifest_bytes.len() as u32).to_be_bytes())
+        .map_err(Error::WriteError)?;
+    fd.write_all(&manifest_bytes).map_err(Error::WriteError)?;
+
+    for (path, entry) in paths.iter().zip(manifest.entries.iter()) {
+        let mut input = File::open(path).map_err(Error::ReadError)?;
+        let hash = copy_and_hash(&mut input, Some(&mut fd), entry.size)?;
+        if hash != entry.hash {
+            return Err(Error::InvalidSnapshot(format!(
+                "{} changed while it was being archived",
+                path.display()
+            )));
+        }
+    }
+    fd.sync_all().map_err(Error::WriteError)
+}
+
+/// Read a snapshot archive's manifest, leaving `fd` at the start of the file contents.
+pub fn read_snapshot_manifest<R: Read>(fd: &mut R) -> Result<SnapshotManifest, Error> {
+    let mut magic = [0u8; 8];
+    fd.read_exact(&mut magic).map_err(Error::ReadError)?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err(Error::InvalidSnapshot("Not a snapshot archive".to_string()));
+    }
+
+    let mut len_bytes = [0u8; 4];
+    fd.read_exact(&mut len_bytes).map_err(Error::ReadError)?;
+    let mut manifest_bytes = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+    fd.read_exact(&mut manifest_bytes)
+        .map_err(Error::ReadError)?;
+
+    let manifest: SnapshotManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| Error::InvalidSnapshot(format!("Failed to decode manifest: {:?}", &e)))?;
+    if manifest.version != SNAPSHOT_VERSION {
+        return Err(Error::InvalidSnapshot(format!(
+            "Unsupported snapshot version {}",
+            manifest.version
+        )));
+    }
+    Ok(manifest)
+}
+
+fn extract_snapshot<R: Read>(
+    fd: &mut R,
+    manifest: &SnapshotManifest,
+    chainstate_dir: &Path,
+    burnchain_dir: &Path,
+) -> Result<(), Error> {
+    // every database must be present, in order, and each MARF may be followed by its blob file
+    let mut expected = vec![];
+    for (archive_name, path) in snapshot_file_paths(chainstate_dir, burnchain_dir).into_iter() {
+        let blob_file = blob_file_path(&archive_name, &path);
+        let is_marf = MARF_FILES.contains(&archive_name.as_str());
+        expected.push((archive_name, path, true));
+        if is_marf {
+            expected.push((blob_file.0, blob_file.1, false));
+        }
+    }
+
+    let mut expected_iter = expected.iter();
+    for entry in manifest.entries.iter() {
+        let path = loop {
+            match expected_iter.next() {
+                Some((archive_name, path, _)) if &entry.path == archive_name => break path,
+                Some((archive_name, _, true)) => {
+                    return Err(Error::InvalidSnapshot(format!(
+                        "Unexpected file {} (expected {})",
+                        &entry.path, archive_name
+                    )));
+                }
+                Some((_, _, false)) => continue,
+                None => {
+                    return Err(Error::InvalidSnapshot(format!(
+                        "Unexpected file {}",
+                        &entry.path
+                    )));
+                }
+            }
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(Error::WriteError)?;
+        }
+        let mut output = File::create(path).map_err(Error::WriteError)?;
+        let hash = copy_and_hash(fd, Some(&mut output), entry.size)?;
+        if hash != entry.hash {
+            return Err(Error::InvalidSnapshot(format!(
+                "Hash mismatch for {}: expected {}, got {}",
+                &entry.path, &entry.hash, &hash
+            )));
+        }
+        output.sync_all().map_err(Error::WriteError)?;
+    }
+    if let Some((archive_name, _, _)) = expected_iter.find(|(_, _, required)| *required) {
+        return Err(Error::InvalidSnapshot(format!(
+            "Missing file {}",
+            archive_name
+        )));
+    }
+
+    let mut trailing = [0u8; 1];
+    if fd.read(&mut trailing).map_err(Error::ReadError)? != 0 {
+        return Err(Error::InvalidSnapshot(
+            "Trailing data after the last file".to_string(),
+        ));
+    }
+
+    // this also checks that the snapshot's block is the imported canonical tip
+    let mut imported = read_snapshot_tip(chainstate_dir, burnchain_dir)?;
+    imported.entries = manifest.entries.clone();
+    if &imported != manifest {
+        return Err(Error::InvalidSnapshot(format!(
+            "Imported state does not match the manifest: {:?} != {:?}",
+            &imported, manifest
+        )));
+    }
+    Ok(())
+}
+
+fn staging_dir(dir: &Path) -> PathBuf {
+    let mut staging = dir.as_os_str().to_owned();
+    staging.push(".importing");
+    PathBuf::from(staging)
+}
+
+/// Unpack a snapshot archive into `chainstate_dir` and `burnchain_dir` (see export_snapshot()),
+/// which must not yet exist.  The files are unpacked to staging directories and checked against
+/// the manifest before they are moved into place, so a bad archive leaves nothing behind.
+pub fn import_snapshot(
+    archive_path: &str,
+    chainstate_dir: &str,
+    burnchain_dir: &str,
+) -> Result<SnapshotManifest, Error> {
+    let chainstate_dir = PathBuf::from(chainstate_dir);
+    let burnchain_dir = PathBuf::from(burnchain_dir);
+    for dir in [&chainstate_dir, &burnchain_dir].iter() {
+        if fs::metadata(dir).is_ok() || fs::metadata(staging_dir(dir)).is_ok() {
+            return Err(Error::InvalidSnapshot(format!(
+                "{} already exists",
+                dir.display()
+            )));
+        }
+    }
+
+    let mut fd = File::open(archive_path).map_err(Error::ReadError)?;
+    let manifest = read_snapshot_manifest(&mut fd)?;
+
+    let chainstate_staging = staging_dir(&chainstate_dir);
+    let burnchain_staging = staging_dir(&burnchain_dir);
+    if let Err(e) = extract_snapshot(&mut fd, &manifest, &chainstate_staging, &burnchain_staging) {
+        let _ = fs::remove_dir_all(&chainstate_staging);
+        let _ = fs::remove_dir_all(&burnchain_staging);
+        return Err(e);
+    }
+
+    // the staging directories are siblings of the destinations, so their parents exist.
+    fs::rename(&chainstate_staging, &chainstate_dir).map_err(Error::WriteError)?;
+    fs::rename(&burnchain_staging, &burnchain_dir).map_err(Error::WriteError)?;
+
+    // block files are not included, but the chainstate expects their directory.
+    fs::create_dir_all(chainstate_dir.join("blocks")).map_err(Error::WriteError)?;
+
+    info!(
+        "Imported snapshot of block {} (height {}) from {}",
+        &manifest.index_block_hash, manifest.block_height, archive_path
+    );
+    Ok(manifest)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    use burnchains::db::BurnchainDB;
+    use chainstate::stacks::db::test::instantiate_chainstate;
+    use chainstate::stacks::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
+    use net::test::*;
+
+    fn reset_dir(path: &str) {
+        if fs::metadata(path).is_ok() {
+            fs::remove_dir_all(path).unwrap();
+        }
+    }
+
+    /// The `chain-*` directory of a chainstate
+    fn chain_dir(chainstate: &StacksChainState) -> String {
+        Path::new(&chainstate.blocks_path)
+            .parent()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_snapshot_export_import() {
+        let chainstate = instantiate_chainstate(false, 0x80000000, "snapshot-export-import");
+        let burnchain_dir = "/tmp/blockstack-test-snapshot-export-import-burnchain";
+        reset_dir(burnchain_dir);
+        fs::create_dir_all(burnchain_dir).unwrap();
+
+        let _sortdb = SortitionDB::connect(
+            &format!("{}/sortition.db", burnchain_dir),
+            0,
+            &BurnchainHeaderHash::zero(),
+            0,
+            true,
+        )
+        .unwrap();
+        let _burnchain_db = BurnchainDB::connect(
+            &format!("{}/burnchain.db", burnchain_dir),
+            0,
+            &BurnchainHeaderHash::zero(),
+            0,
+            true,
+        )
+        .unwrap();
+
+        let archive_path = "/tmp/blockstack-test-snapshot-export-import.snapshot";
+        if fs::metadata(archive_path).is_ok() {
+            fs::remove_file(archive_path).unwrap();
+        }
+
+        let genesis = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+        let chainstate_dir = chain_dir(&chainstate);
+
+        // the canonical tip is the genesis block
+        let manifest = export_snapshot(&chainstate_dir, burnchain_dir, archive_path).unwrap();
+        assert_eq!(manifest.index_block_hash, genesis);
+        assert_eq!(manifest.block_height, 0);
+        assert_eq!(
+            manifest.entries.len(),
+            CHAINSTATE_FILES.len() + BURNCHAIN_FILES.len()
+        );
+
+        // won't clobber an existing archive
+        assert!(export_snapshot(&chainstate_dir, burnchain_dir, archive_path).is_err());
+
+        // a failed export leaves no archive behind
+        let bad_archive_path = "/tmp/blockstack-test-snapshot-export-import-bad.snapshot";
+        if fs::metadata(bad_archive_path).is_ok() {
+            fs::remove_file(bad_archive_path).unwrap();
+        }
+        assert!(export_snapshot(&chainstate.root_path, burnchain_dir, bad_archive_path).is_err());
+        assert!(fs::metadata(bad_archive_path).is_err());
+
+        let mut fd = File::open(archive_path).unwrap();
+        assert_eq!(read_snapshot_manifest(&mut fd).unwrap(), manifest);
+
+        // import it
+        let import_dir = "/tmp/blockstack-test-snapshot-export-import-imported";
+        reset_dir(import_dir);
+        fs::create_dir_all(import_dir).unwrap();
+        let import_chainstate_dir = format!("{}/chainstate", import_dir);
+        let import_burnchain_dir = format!("{}/burnchain", import_dir);
+
+        let imported =
+            import_snapshot(archive_path, &import_chainstate_dir, &import_burnchain_dir).unwrap();
+        assert_eq!(imported, manifest);
+        assert!(fs::metadata(&format!("{}/blocks", import_chainstate_dir)).is_ok());
+        assert!(fs::metadata(&staging_dir(Path::new(&import_chainstate_dir))).is_err());
+        assert!(fs::metadata(&staging_dir(Path::new(&import_burnchain_dir))).is_err());
+
+        // the imported state is the exported state
+        let mut reread = read_snapshot_tip(
+            Path::new(&import_chainstate_dir),
+            Path::new(&import_burnchain_dir),
+        )
+        .unwrap();
+        reread.entries = manifest.entries.clone();
+        assert_eq!(reread, manifest);
+
+        // won't clobber an existing chainstate
+        assert!(
+            import_snapshot(archive_path, &import_chainstate_dir, &import_burnchain_dir).is_err()
+        );
+
+        // corrupt the last byte of the archive
+        let mut bytes = fs::read(archive_path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(bad_archive_path, &bytes).unwrap();
+
+        let bad_chainstate_dir = format!("{}/bad-chainstate", import_dir);
+        let bad_burnchain_dir = format!("{}/bad-burnchain", import_dir);
+        match import_snapshot(bad_archive_path, &bad_chainstate_dir, &bad_burnchain_dir) {
+            Err(Error::InvalidSnapshot(_)) => {}
+            x => panic!("Expected InvalidSnapshot, got {:?}", &x),
+        }
+        for dir in [&bad_chainstate_dir, &bad_burnchain_dir].iter() {
+            assert!(fs::metadata(dir).is_err());
+            assert!(fs::metadata(&staging_dir(Path::new(dir))).is_err());
+        }
+
+        // trailing data is rejected
+        let mut bytes = fs::read(archive_path).unwrap();
+        bytes.push(0);
+        fs::write(bad_archive_path, &bytes).unwrap();
+        assert!(
+            import_snapshot(bad_archive_path, &bad_chainstate_dir, &bad_burnchain_dir).is_err()
+        );
+        assert!(fs::metadata(&bad_chainstate_dir).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_export_import_at_tip() {
+        let peer_config = TestPeerConfig::new("test_snapshot_export_import_at_tip", 4270, 4271);
+        let mut peer = TestPeer::new(peer_config);
+
+        for _ in 0..3 {
+            let (burn_ops, stacks_block, microblocks) = peer.make_default_tenure();
+            peer.next_burnchain_block(burn_ops);
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+        }
+
+        let (consensus_hash, block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb().conn()).unwrap();
+        let tip = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+
+        let chainstate_dir = chain_dir(peer.chainstate());
+        let burnchain_db_path = peer.config.burnchain.get_db_path();
+        let burnchain_dir = Path::new(&burnchain_db_path)
+            .parent()
+            .unwrap()
+            .to_str()
+            .unwrap();
+
+        let archive_path = "/tmp/blockstack-test-snapshot-export-import-at-tip.snapshot";
+        if fs::metadata(archive_path).is_ok() {
+            fs::remove_file(archive_path).unwrap();
+        }
+        let manifest = export_snapshot(&chainstate_dir, burnchain_dir, archive_path).unwrap();
+        assert_eq!(manifest.index_block_hash, tip);
+        assert_eq!(manifest.consensus_hash, consensus_hash);
+        assert_eq!(manifest.block_hash, block_hash);
+        assert_eq!(manifest.block_height, 3);
+
+        let import_dir = "/tmp/blockstack-test-snapshot-export-import-at-tip-imported";
+        reset_dir(import_dir);
+        let chain_dir_name = Path::new(&chainstate_dir).file_name().unwrap();
+        let import_chainstate_dir = Path::new(import_dir)
+            .join("chainstate")
+            .join(chain_dir_name);
+        let import_burnchain_dir = format!("{}/burnchain", import_dir);
+        fs::create_dir_all(import_chainstate_dir.parent().unwrap()).unwrap();
+
+        let imported = import_snapshot(
+            archive_path,
+            import_chainstate_dir.to_str().unwrap(),
+            &import_burnchain_dir,
+        )
+        .unwrap();
+        assert_eq!(imported, manifest);
+
+        // the imported chainstate picks up at the tip
+        let (imported_chainstate, _) = StacksChainState::open(
+            false,
+            peer.config.network_id,
+            &format!("{}/chainstate", import_dir),
+        )
+        .unwrap();
+        let header_info = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            imported_chainstate.db(),
+            &tip,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(header_info.block_height, 3);
+
+        let sortition_conn = Connection::open_with_flags(
+            Path::new(&import_burnchain_dir).join(BURNCHAIN_FILES[0]),
+            OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )
+        .unwrap();
+        assert_eq!(
+            SortitionDB::get_canonical_stacks_chain_tip_hash(&sortition_conn).unwrap(),
+            (consensus_hash, block_hash)
+        );
+    }
+}
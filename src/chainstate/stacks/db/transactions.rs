@@ -36,6 +36,7 @@ use util::strings::{StacksString, VecDisplay};
 use util::hash::to_hex;
 
 use chainstate::burn::db::sortdb::*;
+use chainstate::burn::BlockHeaderHash;
 
 use net::Error as net_error;
 
@@ -44,7 +45,7 @@ use vm::types::{
     StandardPrincipalData, TupleData, TypeSignature, Value,
 };
 
-use vm::contexts::{AssetMap, AssetMapEntry, Environment};
+use vm::contexts::{AssetMap, AssetMapEntry, Environment, StackTrace};
 
 use vm::analysis::run_analysis;
 use vm::ast::build_ast;
@@ -60,6 +61,7 @@ use vm::clarity::{
 };
 
 use vm::errors::Error as InterpreterError;
+use vm::errors::InterpreterError as VmInterpreterError;
 
 pub use vm::analysis::errors::CheckErrors;
 use vm::analysis::types::ContractAnalysis;
@@ -97,6 +99,7 @@ impl StacksTransactionReceipt {
             contract_analysis: None,
             transaction: tx.into(),
             execution_cost: cost,
+            stack_trace: None,
         }
     }
 
@@ -115,6 +118,7 @@ impl StacksTransactionReceipt {
             stx_burned: burned,
             contract_analysis: None,
             execution_cost: cost,
+            stack_trace: None,
         }
     }
 
@@ -133,6 +137,7 @@ impl StacksTransactionReceipt {
             stx_burned: burned,
             contract_analysis: None,
             execution_cost: cost,
+            stack_trace: None,
         }
     }
 
@@ -151,6 +156,7 @@ impl StacksTransactionReceipt {
             stx_burned: burned,
             contract_analysis: Some(analysis),
             execution_cost: cost,
+            stack_trace: None,
         }
     }
 
@@ -169,6 +175,7 @@ impl StacksTransactionReceipt {
             stx_burned: burned,
             contract_analysis: Some(analysis),
             execution_cost: cost,
+            stack_trace: None,
         }
     }
 
@@ -181,6 +188,7 @@ impl StacksTransactionReceipt {
             stx_burned: 0,
             contract_analysis: None,
             execution_cost: ExecutionCost::zero(),
+            stack_trace: None,
         }
     }
 
@@ -196,6 +204,7 @@ impl StacksTransactionReceipt {
             stx_burned: 0,
             contract_analysis: None,
             execution_cost: analysis_cost,
+            stack_trace: None,
         }
     }
 
@@ -212,6 +221,7 @@ impl StacksTransactionReceipt {
             stx_burned: 0,
             contract_analysis: None,
             execution_cost: cost,
+            stack_trace: None,
         }
     }
 }
@@ -261,6 +271,9 @@ enum ClarityRuntimeTxError {
     AbortedByCallback(Option<Value>, AssetMap, Vec<StacksTransactionEvent>),
     CostError(ExecutionCost, ExecutionCost),
     Rejectable(clarity_error),
+    /// the transaction reads state that this (non-archival) node has pruned, so the node can't
+    /// tell whether or not it is valid.
+    StatePruned(BlockHeaderHash),
 }
 
 fn handle_clarity_runtime_error(error: clarity_error) -> ClarityRuntimeTxError {
@@ -272,7 +285,7 @@ fn handle_clarity_runtime_error(error: clarity_error) -> ClarityRuntimeTxError {
                 err_type: "runtime error",
             }
         }
-        clarity_error::Interpreter(InterpreterError::ShortReturn(_)) => {
+        clarity_error::Interpreter(InterpreterError::ShortReturn(..)) => {
             ClarityRuntimeTxError::Acceptable {
                 error,
                 err_type: "short return/panic",
@@ -282,10 +295,24 @@ fn handle_clarity_runtime_error(error: clarity_error) -> ClarityRuntimeTxError {
             ClarityRuntimeTxError::AbortedByCallback(val, assets, events)
         }
         clarity_error::CostError(cost, budget) => ClarityRuntimeTxError::CostError(cost, budget),
+        // the node can't evaluate this transaction, which says nothing about its validity
+        clarity_error::Interpreter(InterpreterError::Interpreter(
+            VmInterpreterError::BlockPruned(block_hash),
+        )) => ClarityRuntimeTxError::StatePruned(block_hash),
         unhandled_error => ClarityRuntimeTxError::Rejectable(unhandled_error),
     }
 }
 
+/// The call path which led to an acceptable runtime error, if it was recorded.
+fn get_runtime_error_stack_trace(error: &clarity_error) -> Option<StackTrace> {
+    match error {
+        clarity_error::Interpreter(InterpreterError::Runtime(_, Some(stack_trace))) => {
+            Some(stack_trace.clone())
+        }
+        _ => None,
+    }
+}
+
 impl StacksChainState {
     /// Get the payer account
     fn get_payer_account<T: ClarityConnection>(
@@ -886,7 +913,7 @@ impl StacksChainState {
                     .sub(&cost_before)
                     .expect("BUG: total block cost decreased");
 
-                let (result, asset_map, events) = match contract_call_resp {
+                let (result, asset_map, events, stack_trace) = match contract_call_resp {
                     Ok((return_value, asset_map, events)) => {
                         info!("Contract-call successfully processed";
                               "contract_name" => %contract_id,
@@ -894,7 +921,7 @@ impl StacksChainState {
                               "function_args" => %VecDisplay(&contract_call.function_args),
                               "return_value" => %return_value,
                               "cost" => ?total_cost);
-                        (return_value, asset_map, events)
+                        (return_value, asset_map, events, None)
                     }
                     Err(e) => match handle_clarity_runtime_error(e) {
                         ClarityRuntimeTxError::Acceptable { error, err_type } => {
@@ -903,7 +930,8 @@ impl StacksChainState {
                                       "function_name" => %contract_call.function_name,
                                       "function_args" => %VecDisplay(&contract_call.function_args),
                                       "error" => ?error);
-                            (Value::err_none(), AssetMap::new(), vec![])
+                            let stack_trace = get_runtime_error_stack_trace(&error);
+                            (Value::err_none(), AssetMap::new(), vec![], stack_trace)
                         }
                         ClarityRuntimeTxError::AbortedByCallback(value, assets, events) => {
                             let receipt = StacksTransactionReceipt::from_condition_aborted_contract_call(
@@ -926,16 +954,25 @@ impl StacksChainState {
                                        "error" => ?e);
                             return Err(Error::ClarityError(e));
                         }
+                        ClarityRuntimeTxError::StatePruned(block_hash) => {
+                            error!("Transaction reads pruned state: this node can't process it";
+                                       "txid" => %tx.txid(),
+                                       "contract_name" => %contract_id,
+                                       "function_name" => %contract_call.function_name,
+                                       "pruned_block" => %block_hash);
+                            return Err(Error::PrunedStateError(block_hash));
+                        }
                     },
                 };
 
-                let receipt = StacksTransactionReceipt::from_contract_call(
+                let mut receipt = StacksTransactionReceipt::from_contract_call(
                     tx.clone(),
                     events,
                     result,
                     asset_map.get_stx_burned_total(),
                     total_cost,
                 );
+                receipt.stack_trace = stack_trace;
                 Ok(receipt)
             }
             TransactionPayload::SmartContract(ref smart_contract) => {
@@ -1029,15 +1066,16 @@ impl StacksChainState {
                     .sub(&cost_before)
                     .expect("BUG: total block cost decreased");
 
-                let (asset_map, events) = match initialize_resp {
-                    Ok(x) => x,
+                let (asset_map, events, stack_trace) = match initialize_resp {
+                    Ok((asset_map, events)) => (asset_map, events, None),
                     Err(e) => match handle_clarity_runtime_error(e) {
                         ClarityRuntimeTxError::Acceptable { error, err_type } => {
                             info!("Smart-contract processed with {}", err_type;
                                       "contract" => %contract_id,
                                       "code" => %contract_code_str,
                                       "error" => ?error);
-                            (AssetMap::new(), vec![])
+                            let stack_trace = get_runtime_error_stack_trace(&error);
+                            (AssetMap::new(), vec![], stack_trace)
                         }
                         ClarityRuntimeTxError::AbortedByCallback(_, assets, events) => {
                             let receipt =
@@ -1064,6 +1102,13 @@ impl StacksChainState {
                                        "error" => ?e);
                             return Err(Error::ClarityError(e));
                         }
+                        ClarityRuntimeTxError::StatePruned(block_hash) => {
+                            error!("Transaction reads pruned state: this node can't process it";
+                                       "txid" => %tx.txid(),
+                                       "contract_name" => %contract_id,
+                                       "pruned_block" => %block_hash);
+                            return Err(Error::PrunedStateError(block_hash));
+                        }
                     },
                 };
 
@@ -1072,13 +1117,14 @@ impl StacksChainState {
                     .save_analysis(&contract_id, &contract_analysis)
                     .expect("FATAL: failed to store contract analysis");
 
-                let receipt = StacksTransactionReceipt::from_smart_contract(
+                let mut receipt = StacksTransactionReceipt::from_smart_contract(
                     tx.clone(),
                     events,
                     asset_map.get_stx_burned_total(),
                     contract_analysis,
                     total_cost,
                 );
+                receipt.stack_trace = stack_trace;
                 Ok(receipt)
             }
             TransactionPayload::PoisonMicroblock(ref mblock_header_1, ref mblock_header_2) => {
@@ -2161,6 +2207,123 @@ pub mod test {
         conn.commit_block();
     }
 
+    #[test]
+    fn process_contract_call_at_pruned_block() {
+        let contract = "
+        (define-data-var bar int 1)
+        (define-public (get-bar-at (block (buff 32)))
+          (ok (at-block block (var-get bar))))";
+
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "process-contract-call-at-pruned-block");
+
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let addr = auth.origin().address_testnet();
+
+        let mut tx_contract = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            auth.clone(),
+            TransactionPayload::new_smart_contract(
+                &"hello-world".to_string(),
+                &contract.to_string(),
+            )
+            .unwrap(),
+        );
+
+        tx_contract.chain_id = 0x80000000;
+        tx_contract.set_tx_fee(0);
+
+        let mut signer = StacksTransactionSigner::new(&tx_contract);
+        signer.sign_origin(&privk).unwrap();
+
+        let signed_tx = signer.get_tx().unwrap();
+
+        let make_call = |nonce: u64, block: u8| {
+            let block_id = StacksBlockHeader::make_index_block_hash(
+                &ConsensusHash([block; 20]),
+                &BlockHeaderHash([block; 32]),
+            );
+            let mut tx_contract_call = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                auth.clone(),
+                TransactionPayload::new_contract_call(
+                    addr.clone(),
+                    "hello-world",
+                    "get-bar-at",
+                    vec![Value::buff_from(block_id.0.to_vec()).unwrap()],
+                )
+                .unwrap(),
+            );
+
+            tx_contract_call.chain_id = 0x80000000;
+            tx_contract_call.set_tx_fee(0);
+            tx_contract_call.set_origin_nonce(nonce);
+
+            let mut signer = StacksTransactionSigner::new(&tx_contract_call);
+            signer.sign_origin(&privk).unwrap();
+            signer.get_tx().unwrap()
+        };
+
+        let mut parent_consensus_hash = FIRST_BURNCHAIN_CONSENSUS_HASH.clone();
+        let mut parent_block_hash = FIRST_STACKS_BLOCK_HASH.clone();
+        for i in 1..6u8 {
+            let mut conn = chainstate.block_begin(
+                &NULL_BURN_STATE_DB,
+                &parent_consensus_hash,
+                &parent_block_hash,
+                &ConsensusHash([i; 20]),
+                &BlockHeaderHash([i; 32]),
+            );
+            if i == 1 {
+                StacksChainState::process_transaction(&mut conn, &signed_tx, false).unwrap();
+            }
+            conn.commit_block();
+
+            parent_consensus_hash = ConsensusHash([i; 20]);
+            parent_block_hash = BlockHeaderHash([i; 32]);
+        }
+
+        // only keep the state of blocks 4 and 5
+        let tip =
+            StacksBlockHeader::make_index_block_hash(&parent_consensus_hash, &parent_block_hash);
+        chainstate
+            .clarity_state
+            .prune(&tip, 2, u64::max_value())
+            .unwrap();
+
+        let mut conn = chainstate.block_begin(
+            &NULL_BURN_STATE_DB,
+            &parent_consensus_hash,
+            &parent_block_hash,
+            &ConsensusHash([6u8; 20]),
+            &BlockHeaderHash([6u8; 32]),
+        );
+
+        let (_, receipt) =
+            StacksChainState::process_transaction(&mut conn, &make_call(1, 4), false).unwrap();
+        assert_eq!(receipt.result, Value::okay(Value::Int(1)).unwrap());
+
+        // reading pruned state doesn't make the transaction invalid -- the node just can't
+        // process it.
+        let pruned_block_id = StacksBlockHeader::make_index_block_hash(
+            &ConsensusHash([2u8; 20]),
+            &BlockHeaderHash([2u8; 32]),
+        );
+        match StacksChainState::process_transaction(&mut conn, &make_call(2, 2), false) {
+            Err(Error::PrunedStateError(block_hash)) => {
+                assert_eq!(block_hash, BlockHeaderHash(pruned_block_id.0));
+            }
+            Err(e) => panic!("Unexpected error: {:?}", &e),
+            Ok(_) => panic!("Processed a transaction that reads pruned state"),
+        }
+
+        conn.rollback_block();
+    }
+
     #[test]
     fn process_smart_contract_user_aborts_2257() {
         let contract = "(asserts! false (err 1))";
@@ -62,7 +62,8 @@ impl UnconfirmedState {
     /// Make a new unconfirmed state, but don't do anything with it yet.  Caller should immediately
     /// call .refresh() to instatiate and store the underlying state trie.
     fn new(chainstate: &StacksChainState, tip: StacksBlockId) -> Result<UnconfirmedState, Error> {
-        let marf = MarfedKV::open_unconfirmed(&chainstate.clarity_state_index_root, None)?;
+        let mut marf = MarfedKV::open_unconfirmed(&chainstate.clarity_state_index_root, None)?;
+        marf.set_key_index(chainstate.clarity_state.is_key_index_enabled())?;
 
         let clarity_instance =
             ClarityInstance::new(chainstate.mainnet, marf, chainstate.block_limit.clone());
@@ -89,7 +90,8 @@ impl UnconfirmedState {
         chainstate: &StacksChainState,
         tip: StacksBlockId,
     ) -> Result<UnconfirmedState, Error> {
-        let marf = MarfedKV::open_unconfirmed(&chainstate.clarity_state_index_root, None)?;
+        let mut marf = MarfedKV::open_unconfirmed(&chainstate.clarity_state_index_root, None)?;
+        marf.set_key_index(chainstate.clarity_state.is_key_index_enabled())?;
 
         let clarity_instance =
             ClarityInstance::new(chainstate.mainnet, marf, chainstate.block_limit.clone());
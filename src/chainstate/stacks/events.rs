@@ -19,6 +19,7 @@ use burnchains::Txid;
 use chainstate::stacks::StacksTransaction;
 use net::StacksMessageCodec;
 use vm::analysis::ContractAnalysis;
+use vm::contexts::StackTrace;
 use vm::costs::ExecutionCost;
 use vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
@@ -60,6 +61,8 @@ pub struct StacksTransactionReceipt {
     pub stx_burned: u128,
     pub contract_analysis: Option<ContractAnalysis>,
     pub execution_cost: ExecutionCost,
+    /// The call path of the runtime error which aborted the transaction, if any.
+    pub stack_trace: Option<StackTrace>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
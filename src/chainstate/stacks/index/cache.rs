@@ -0,0 +1,340 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem;
+
+use chainstate::stacks::index::node::{TrieNode256, TrieNode48, TrieNodeType};
+use chainstate::stacks::index::TrieHash;
+
+use monitoring;
+
+/// A node is identified by the local identifier of the trie it is in, and its offset in that trie.
+type NodeKey = (u32, u32);
+
+struct CachedNode {
+    node: TrieNodeType,
+    hash: TrieHash,
+    /// approximate number of bytes of memory this entry uses (see TrieNodeCache::entry_size())
+    size: usize,
+    /// when this entry was last used
+    tick: u64,
+}
+
+///
+/// TrieNodeCache is a least-recently-used cache of the nodes (and their hashes) read from the
+///   confirmed tries of a MARF, bounded by the (approximate) number of bytes of memory it uses.
+///   A cache with a size of 0 is disabled.
+/// The contents of a confirmed trie never change once it is committed, so its nodes can be cached
+///   for as long as the storage is open.  The nodes of unconfirmed tries are never cached, since
+///   they are rewritten in place; neither are the nodes of confirmed tries written in a transaction
+///   that has not committed yet, since if it rolls back, their identifiers will be reused.
+///
+pub struct TrieNodeCache {
+    max_size: usize,
+    size: usize,
+    tick: u64,
+
+    nodes: HashMap<NodeKey, CachedNode>,
+    /// node keys, least-recently-used first
+    lru: BTreeMap<u64, NodeKey>,
+
+    /// whether or not the trie with a given identifier is a committed, confirmed trie
+    cacheable: HashMap<u32, bool>,
+    /// identifiers of the tries written since the last commit
+    uncommitted: HashSet<u32>,
+
+    hits: u64,
+    misses: u64,
+}
+
+impl TrieNodeCache {
+    pub fn new(max_size: usize) -> TrieNodeCache {
+        TrieNodeCache {
+            max_size: max_size,
+            size: 0,
+            tick: 0,
+            nodes: HashMap::new(),
+            lru: BTreeMap::new(),
+            cacheable: HashMap::new(),
+            uncommitted: HashSet::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_size > 0
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of cache hits and misses so far
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    /// Change the size of the cache, evicting nodes if it shrinks.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.evict();
+    }
+
+    /// Can the nodes of the trie with the given identifier be cached?  Returns None if it is not
+    /// yet known whether or not the trie is confirmed.
+    pub fn is_cacheable(&self, block_id: u32) -> Option<bool> {
+        if self.uncommitted.contains(&block_id) {
+            return Some(false);
+        }
+        self.cacheable.get(&block_id).cloned()
+    }
+
+    /// Record whether or not the trie with the given identifier is a committed, confirmed trie.
+    pub fn set_cacheable(&mut self, block_id: u32, cacheable: bool) {
+        if !self.uncommitted.contains(&block_id) {
+            self.cacheable.insert(block_id, cacheable);
+        }
+    }
+
+    /// Note that a trie was just written with the given identifier, in a transaction which has
+    /// not yet committed.
+    pub fn add_uncommitted(&mut self, block_id: u32) {
+        self.cacheable.remove(&block_id);
+        self.uncommitted.insert(block_id);
+    }
+
+    /// The transaction that wrote the uncommitted tries has finished.  If it committed, their
+    /// nodes can now be cached; if not, their identifiers may be reused, so they must be looked
+    /// up again.
+    pub fn clear_uncommitted(&mut self) {
+        self.uncommitted.clear();
+    }
+
+    fn touch(&mut self, key: NodeKey) -> Option<&CachedNode> {
+        let tick = self.tick;
+        match self.nodes.get_mut(&key) {
+            Some(entry) => {
+                self.lru.remove(&entry.tick);
+                self.lru.insert(tick, key);
+                entry.tick = tick;
+                self.tick += 1;
+                Some(entry)
+            }
+            None => None,
+        }
+    }
+
+    fn record_lookup(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+            monitoring::increment_marf_cache_hits_counter();
+        } else {
+            self.misses += 1;
+            monitoring::increment_marf_cache_misses_counter();
+        }
+    }
+
+    /// Look up a node and its hash.
+    pub fn get(&mut self, block_id: u32, ptr: u32) -> Option<(TrieNodeType, TrieHash)> {
+        let res = self
+            .touch((block_id, ptr))
+            .map(|entry| (entry.node.clone(), entry.hash.clone()));
+        self.record_lookup(res.is_some());
+        res
+    }
+
+    /// Look up a node's hash.
+    pub fn get_hash(&mut self, block_id: u32, ptr: u32) -> Option<TrieHash> {
+        let res = self.touch((block_id, ptr)).map(|entry| entry.hash.clone());
+        self.record_lookup(res.is_some());
+        res
+    }
+
+    /// Approximate number of bytes of memory used to cache a node: the cache entry itself (which
+    /// holds the node inline), plus whatever the node owns on the heap -- its path, and for
+    /// Node48s and Node256s, the boxed node itself.  This is not the node's serialized size;
+    /// a cached leaf takes up several times more memory than it does on disk.
+    fn entry_size(node: &TrieNodeType) -> usize {
+        let boxed_size = match node {
+            TrieNodeType::Node48(_) => mem::size_of::<TrieNode48>(),
+            TrieNodeType::Node256(_) => mem::size_of::<TrieNode256>(),
+            _ => 0,
+        };
+        // each map entry also costs a hash table control byte
+        mem::size_of::<(NodeKey, CachedNode)>()
+            + 1
+            + mem::size_of::<(u64, NodeKey)>()
+            + boxed_size
+            + node.path_bytes().capacity()
+    }
+
+    /// Cache a node and its hash, evicting the least-recently-used nodes to make room for it.
+    pub fn insert(&mut self, block_id: u32, ptr: u32, node: &TrieNodeType, hash: &TrieHash) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let key = (block_id, ptr);
+        let node = node.clone();
+        let size = TrieNodeCache::entry_size(&node);
+
+        let entry = CachedNode {
+            node: node,
+            hash: hash.clone(),
+            size: size,
+            tick: self.tick,
+        };
+        if let Some(old_entry) = self.nodes.insert(key, entry) {
+            self.lru.remove(&old_entry.tick);
+            self.size -= old_entry.size;
+        }
+        self.lru.insert(self.tick, key);
+        self.size += size;
+        self.tick += 1;
+
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.size > self.max_size {
+            let (tick, key) = match self.lru.iter().next() {
+                Some((tick, key)) => (*tick, *key),
+                None => break,
+            };
+            self.lru.remove(&tick);
+            if let Some(entry) = self.nodes.remove(&key) {
+                self.size -= entry.size;
+            }
+        }
+    }
+
+    /// Drop all cached nodes, and everything known about which tries are cacheable.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.lru.clear();
+        self.cacheable.clear();
+        self.size = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chainstate::stacks::index::node::{TrieLeaf, TrieNode4};
+    use chainstate::stacks::index::MARFValue;
+
+    fn leaf(i: u8) -> TrieNodeType {
+        TrieNodeType::Leaf(TrieLeaf::from_value(&vec![], MARFValue([i; 40])))
+    }
+
+    #[test]
+    fn test_trie_node_cache_lru() {
+        let leaf_size = {
+            let mut cache = TrieNodeCache::new(usize::max_value());
+            cache.insert(1, 0, &leaf(0), &TrieHash([0u8; 32]));
+            cache.size()
+        };
+
+        // room for 3 leaves
+        let mut cache = TrieNodeCache::new(3 * leaf_size);
+        for i in 0..3 {
+            cache.insert(1, i as u32, &leaf(i), &TrieHash([i; 32]));
+        }
+        assert_eq!(cache.len(), 3);
+
+        // use node 0, so node 1 is the least-recently used
+        assert_eq!(cache.get(1, 0), Some((leaf(0), TrieHash([0u8; 32]))));
+
+        cache.insert(2, 0, &leaf(3), &TrieHash([3u8; 32]));
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.size(), 3 * leaf_size);
+
+        assert_eq!(cache.get_hash(1, 1), None);
+        assert_eq!(cache.get_hash(1, 0), Some(TrieHash([0u8; 32])));
+        assert_eq!(cache.get_hash(1, 2), Some(TrieHash([2u8; 32])));
+        assert_eq!(cache.get(2, 0), Some((leaf(3), TrieHash([3u8; 32]))));
+        assert_eq!(cache.stats(), (4, 1));
+
+        // a node that is bigger than a leaf evicts more than one leaf
+        let node = TrieNodeType::Node4(TrieNode4::new(&vec![1u8; 32]));
+        cache.insert(2, 1, &node, &TrieHash([4u8; 32]));
+        assert!(cache.size() <= 3 * leaf_size);
+        assert!(cache.len() < 3);
+        assert_eq!(cache.get(2, 1), Some((node, TrieHash([4u8; 32]))));
+
+        cache.set_max_size(0);
+        assert!(!cache.is_enabled());
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.size(), 0);
+
+        cache.insert(1, 0, &leaf(0), &TrieHash([0u8; 32]));
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn test_trie_node_cache_memory_size() {
+        let mut cache = TrieNodeCache::new(usize::max_value());
+
+        // a cached leaf takes up more memory than its serialized form
+        cache.insert(1, 0, &leaf(0), &TrieHash([0u8; 32]));
+        assert!(cache.size() > leaf(0).byte_len());
+        assert!(cache.size() >= mem::size_of::<TrieNodeType>() + mem::size_of::<TrieHash>());
+        let leaf_size = cache.size();
+
+        // a Node256 is boxed, and its pointers are counted
+        let node = TrieNodeType::Node256(Box::new(TrieNode256::new(&vec![1u8; 32])));
+        cache.insert(1, 1, &node, &TrieHash([1u8; 32]));
+        assert!(cache.size() >= leaf_size + mem::size_of::<TrieNode256>() + 32);
+        let size = cache.size();
+
+        // replacing an entry does not count it twice
+        cache.insert(1, 1, &node, &TrieHash([1u8; 32]));
+        assert_eq!(cache.size(), size);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_trie_node_cache_uncommitted() {
+        let mut cache = TrieNodeCache::new(1024 * 1024);
+        assert_eq!(cache.is_cacheable(1), None);
+
+        cache.set_cacheable(1, true);
+        cache.set_cacheable(2, false);
+        assert_eq!(cache.is_cacheable(1), Some(true));
+        assert_eq!(cache.is_cacheable(2), Some(false));
+
+        // a trie written in an open transaction is not cacheable until it commits
+        cache.add_uncommitted(3);
+        cache.set_cacheable(3, true);
+        assert_eq!(cache.is_cacheable(3), Some(false));
+
+        cache.clear_uncommitted();
+        assert_eq!(cache.is_cacheable(3), None);
+
+        cache.clear();
+        assert_eq!(cache.is_cacheable(1), None);
+    }
+}
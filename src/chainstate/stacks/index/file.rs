@@ -0,0 +1,296 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use chainstate::stacks::index::Error;
+
+use util::log;
+
+/// Number of bytes to read from the blob file at a time.  Nodes are decoded with many small
+/// reads, so we read ahead rather than go to the file for each one.
+const READ_AHEAD_SIZE: usize = 4096;
+
+///
+/// TrieFile is an append-only file of trie blobs, kept alongside a MARF's SQLite database as an
+///   alternative to storing each trie as a blob in the marf_data table.  The database row of a
+///   trie stored here holds only the offset and length of its blob in this file.
+/// Blobs are only written while the database's write transaction is open -- appended to the
+///   end of the file, or moved into space that no committed row refers to when the file is
+///   compacted after pruning.  The bytes of a blob are synced to disk before the row that refers
+///   to them can be committed, so a crash can leave unreferenced bytes in the file, but never a
+///   row that refers to missing or overwritten bytes.
+///
+pub struct TrieFile {
+    path: String,
+    fd: File,
+}
+
+impl TrieFile {
+    /// Path to the blob file of the MARF whose database is at `db_path`.
+    pub fn blobs_path(db_path: &str) -> String {
+        format!("{}.blobs", db_path)
+    }
+
+    /// Does the MARF whose database is at `db_path` have a blob file?
+    pub fn exists(db_path: &str) -> bool {
+        db_path != ":memory:" && fs::metadata(&TrieFile::blobs_path(db_path)).is_ok()
+    }
+
+    /// Open the blob file of the MARF whose database is at `db_path`, if there is one.
+    pub fn open(db_path: &str, readonly: bool) -> Result<Option<TrieFile>, Error> {
+        if !TrieFile::exists(db_path) {
+            return Ok(None);
+        }
+
+        let path = TrieFile::blobs_path(db_path);
+        let fd = OpenOptions::new().read(true).write(!readonly).open(&path)?;
+
+        debug!("Opened trie blob file {}", &path);
+        Ok(Some(TrieFile { path, fd }))
+    }
+
+    /// Create an empty blob file for the MARF whose database is at `db_path`.
+    /// Fails if there already is one.
+    pub fn create(db_path: &str) -> Result<TrieFile, Error> {
+        if db_path == ":memory:" {
+            return Err(Error::NotFoundError);
+        }
+
+        let path = TrieFile::blobs_path(db_path);
+        let fd = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| {
+                if e.kind() == io::ErrorKind::AlreadyExists {
+                    Error::ExistsError
+                } else {
+                    Error::IOError(e)
+                }
+            })?;
+
+        debug!("Created trie blob file {}", &path);
+        Ok(TrieFile { path, fd })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn size(&self) -> Result<u64, Error> {
+        Ok(self.fd.metadata()?.len())
+    }
+
+    /// Append a trie's blob to the end of the file, and return the offset at which it was
+    /// written.  The caller must sync() the file before committing the rows that refer to it.
+    pub fn append_trie_blob(&self, data: &[u8]) -> Result<u64, Error> {
+        let mut fd = &self.fd;
+        let offset = fd.seek(SeekFrom::End(0))?;
+        fd.write_all(data)?;
+
+        trace!(
+            "Appended {} bytes at offset {} of {}",
+            data.len(),
+            offset,
+            &self.path
+        );
+        Ok(offset)
+    }
+
+    /// Write a trie's blob at the given offset, which must be in space that no committed row
+    /// refers to.  The caller must sync() the file before committing the rows that refer to it.
+    pub fn write_trie_blob_at(&self, offset: u64, data: &[u8]) -> Result<(), Error> {
+        let mut fd = &self.fd;
+        fd.seek(SeekFrom::Start(offset))?;
+        fd.write_all(data)?;
+
+        trace!(
+            "Wrote {} bytes at offset {} of {}",
+            data.len(),
+            offset,
+            &self.path
+        );
+        Ok(())
+    }
+
+    pub fn sync(&self) -> Result<(), Error> {
+        self.fd.sync_data()?;
+        Ok(())
+    }
+
+    /// Discard everything past the first `size` bytes of the file -- i.e., bytes left behind by
+    /// a write whose transaction never committed, or freed by compaction.
+    pub fn truncate(&self, size: u64) -> Result<(), Error> {
+        self.fd.set_len(size)?;
+        self.fd.sync_all()?;
+        Ok(())
+    }
+
+    /// Get a reader over the blob of the trie stored at `offset`.  Seeks are relative to the
+    /// start of the blob, so the node-decoding routines can read from it just as they would
+    /// from a SQLite blob.
+    pub fn open_trie_blob<'a>(&'a self, offset: u64, length: u64) -> TrieBlobReader<'a> {
+        TrieBlobReader {
+            fd: &self.fd,
+            offset: offset,
+            length: length,
+            pos: 0,
+            buf: vec![],
+            buf_pos: 0,
+        }
+    }
+
+    /// Read the whole blob of the trie stored at `offset`.
+    pub fn read_trie_blob(&self, offset: u64, length: u64) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![0u8; length as usize];
+        let mut fd = &self.fd;
+        fd.seek(SeekFrom::Start(offset))?;
+        fd.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// A read-only view of a single trie's blob within a TrieFile.
+pub struct TrieBlobReader<'a> {
+    fd: &'a File,
+    offset: u64,
+    length: u64,
+    pos: u64,
+
+    /// bytes read ahead from the file, starting at blob offset buf_pos
+    buf: Vec<u8>,
+    buf_pos: u64,
+}
+
+impl<'a> TrieBlobReader<'a> {
+    pub fn size(&self) -> u64 {
+        self.length
+    }
+
+    fn fill_buf(&mut self) -> io::Result<()> {
+        let len = cmp::min(READ_AHEAD_SIZE as u64, self.length - self.pos) as usize;
+        let mut fd = self.fd;
+        fd.seek(SeekFrom::Start(self.offset + self.pos))?;
+
+        self.buf.resize(len, 0);
+        fd.read_exact(&mut self.buf)?;
+        self.buf_pos = self.pos;
+        Ok(())
+    }
+}
+
+impl<'a> Read for TrieBlobReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.length || buf.len() == 0 {
+            return Ok(0);
+        }
+        if self.pos < self.buf_pos || self.pos >= self.buf_pos + (self.buf.len() as u64) {
+            self.fill_buf()?;
+        }
+
+        let start = (self.pos - self.buf_pos) as usize;
+        let nr = cmp::min(buf.len(), self.buf.len() - start);
+        buf[..nr].copy_from_slice(&self.buf[start..(start + nr)]);
+        self.pos += nr as u64;
+        Ok(nr)
+    }
+}
+
+impl<'a> Seek for TrieBlobReader<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::Current(d) => offset_pos(self.pos, d),
+            SeekFrom::End(d) => offset_pos(self.length, d),
+        };
+        match new_pos {
+            Some(p) => {
+                self.pos = p;
+                Ok(p)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )),
+        }
+    }
+}
+
+fn offset_pos(pos: u64, delta: i64) -> Option<u64> {
+    if delta < 0 {
+        pos.checked_sub(delta.wrapping_neg() as u64)
+    } else {
+        pos.checked_add(delta as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trie_blob_reader() {
+        let db_path = "/tmp/test_trie_blob_reader.sqlite";
+        if fs::metadata(&TrieFile::blobs_path(db_path)).is_ok() {
+            fs::remove_file(&TrieFile::blobs_path(db_path)).unwrap();
+        }
+
+        assert!(TrieFile::open(db_path, false).unwrap().is_none());
+        let blobs = TrieFile::create(db_path).unwrap();
+        match TrieFile::create(db_path) {
+            Err(Error::ExistsError) => {}
+            _ => panic!("Created the blob file twice"),
+        }
+
+        let blob_1: Vec<u8> = (0..10000).map(|i| (i % 251) as u8).collect();
+        let blob_2: Vec<u8> = (0..100).map(|i| (i % 13) as u8).collect();
+
+        assert_eq!(blobs.append_trie_blob(&blob_1).unwrap(), 0);
+        assert_eq!(blobs.append_trie_blob(&blob_2).unwrap(), 10000);
+        assert_eq!(blobs.size().unwrap(), 10100);
+
+        let blobs = TrieFile::open(db_path, true).unwrap().unwrap();
+        assert_eq!(blobs.read_trie_blob(10000, 100).unwrap(), blob_2);
+
+        // reads are relative to the blob and stop at its end
+        let mut reader = blobs.open_trie_blob(10000, 100);
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, blob_2);
+
+        let mut reader = blobs.open_trie_blob(0, 10000);
+        for pos in [9999u64, 0, 4095, 4096, 8000, 17].iter() {
+            reader.seek(SeekFrom::Start(*pos)).unwrap();
+            let mut buf = [0u8; 2];
+            let nr = reader.read(&mut buf).unwrap();
+            assert!(nr > 0);
+            assert_eq!(&buf[..nr], &blob_1[(*pos as usize)..(*pos as usize + nr)]);
+        }
+
+        reader.seek(SeekFrom::End(-3)).unwrap();
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, &blob_1[9997..]);
+
+        assert!(reader.seek(SeekFrom::Current(-10001)).is_err());
+    }
+}
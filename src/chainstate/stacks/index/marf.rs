@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashSet;
 use std::error;
 use std::fmt;
 use std::io;
@@ -44,6 +45,7 @@ use chainstate::stacks::index::{
 };
 
 use chainstate::stacks::index::trie::Trie;
+use chainstate::stacks::index::trie_sql;
 
 use chainstate::stacks::index::Error;
 use std::ops::DerefMut;
@@ -83,6 +85,11 @@ pub trait MarfConnection<T: MarfTrieId> {
 
     fn sqlite_conn(&self) -> &Connection;
 
+    /// Has the trie for the given block been pruned?
+    fn is_pruned(&self, block_hash: &T) -> Result<bool, Error> {
+        trie_sql::is_block_pruned(self.sqlite_conn(), block_hash)
+    }
+
     /// Resolve a key from the MARF to a MARFValue with respect to the given block height.
     fn get(&mut self, block_hash: &T, key: &str) -> Result<Option<MARFValue>, Error> {
         self.with_conn(|c| MARF::get_by_key(c, block_hash, key))
@@ -1386,6 +1393,89 @@ impl<T: MarfTrieId> MARF<T> {
         Ok(())
     }
 
+    /// Prune the tries of the blocks that are `keep` or more blocks lower than `tip`, in any
+    /// fork, keeping only the nodes still reachable from the retained tries.  Reads at a pruned
+    /// block will fail with Error::PrunedError.  Pruning is done in passes over the retained
+    /// state, and each call only does up to about `max_nodes` nodes' worth of work on the pass in
+    /// progress (beginning a new one if there is none), so a pass can take many calls.  A pass
+    /// only prunes the tries that were prunable when it began.  `keep` must be deeper than any
+    /// fork the caller expects to extend.  Returns the number of tries newly pruned.
+    pub fn prune(&mut self, tip: &T, keep: u32, max_nodes: u64) -> Result<u64, Error> {
+        if self.storage.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.open_chain_tip.is_some() {
+            error!(
+                "MARF at {} is in the process of writing; cannot prune",
+                &self.storage.db_path
+            );
+            return Err(Error::InProgressError);
+        }
+
+        let prunable = if self.storage.is_pruning() {
+            None
+        } else {
+            self.find_prunable_tries(tip, keep)?
+        };
+
+        let mut tx = self.storage.transaction()?;
+        if let Some(prunable) = prunable {
+            tx.begin_prune_pass(prunable)?;
+        }
+        let num_pruned = tx.prune_step(max_nodes)?;
+        tx.commit_tx();
+        Ok(num_pruned)
+    }
+
+    /// Is a pruning pass in progress?
+    pub fn is_pruning(&self) -> bool {
+        self.storage.is_pruning()
+    }
+
+    /// Find the local IDs of the confirmed tries at a height `keep` or more blocks lower than
+    /// `tip`, along with the tries that have already been pruned.  Returns None if there are no
+    /// new tries to prune.
+    fn find_prunable_tries(&mut self, tip: &T, keep: u32) -> Result<Option<HashSet<u32>>, Error> {
+        let mut prunable = HashSet::new();
+        let mut unpruned = vec![];
+        for (block_id, pruned) in
+            trie_sql::get_confirmed_block_identifiers(self.storage.sqlite_conn())?.into_iter()
+        {
+            if pruned {
+                prunable.insert(block_id);
+            } else {
+                let block_hash: T = trie_sql::get_block_hash(self.storage.sqlite_conn(), block_id)?;
+                unpruned.push((block_id, block_hash));
+            }
+        }
+
+        let mut storage = self.storage.connection();
+        let tip_height = MARF::get_block_height(&mut storage, tip, tip)?.ok_or_else(|| {
+            error!("Failed to get the height of the pruning tip {}", tip);
+            Error::NotFoundError
+        })?;
+        let mut any_new = false;
+        for (block_id, block_hash) in unpruned.iter() {
+            if let Some(height) = MARF::get_block_height(&mut storage, block_hash, block_hash)? {
+                if height.saturating_add(keep) <= tip_height {
+                    prunable.insert(*block_id);
+                    any_new = true;
+                }
+            }
+        }
+        if any_new {
+            Ok(Some(prunable))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Cache up to (approximately) `max_size` bytes of the nodes read from committed tries.  A
+    /// size of 0 disables the cache.
+    pub fn set_node_cache_size(&mut self, max_size: usize) {
+        self.storage.set_node_cache_size(max_size)
+    }
+
     pub fn get_block_height_of(
         &mut self,
         bhh: &T,
@@ -1473,6 +1563,7 @@ mod test {
     use chainstate::stacks::index::test::*;
 
     use chainstate::stacks::index::bits::*;
+    use chainstate::stacks::index::file::TrieFile;
     use chainstate::stacks::index::marf::*;
     use chainstate::stacks::index::node::*;
     use chainstate::stacks::index::proofs::*;
@@ -3618,4 +3709,445 @@ mod test {
             assert!(false);
         }
     }
+
+    #[test]
+    fn test_marf_prune() {
+        let f = TrieFileStorage::new_memory().unwrap();
+        let mut marf = MARF::from_storage(f);
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..21u8 {
+            let block_header = BlockHeaderHash([i; 32]);
+            marf.begin(&parent, &block_header).unwrap();
+            marf.insert(&format!("key-{}", i), MARFValue::from(i as u32))
+                .unwrap();
+            marf.insert("shared", MARFValue::from(i as u32)).unwrap();
+            marf.commit_to(&block_header).unwrap();
+            parent = block_header;
+        }
+
+        // block i is at height i - 1
+        assert_eq!(
+            marf.prune(&BlockHeaderHash([20; 32]), 5, u64::max_value())
+                .unwrap(),
+            15
+        );
+        assert!(!marf.is_pruning());
+        assert!(marf.is_pruned(&BlockHeaderHash([3; 32])).unwrap());
+        assert!(marf.is_pruned(&BlockHeaderHash([15; 32])).unwrap());
+        assert!(!marf.is_pruned(&BlockHeaderHash([16; 32])).unwrap());
+        assert!(!marf.is_pruned(&BlockHeaderHash([21; 32])).unwrap());
+
+        // nothing new to prune
+        assert_eq!(
+            marf.prune(&BlockHeaderHash([20; 32]), 5, u64::max_value())
+                .unwrap(),
+            0
+        );
+        assert!(!marf.is_pruning());
+
+        // everything is still readable at the retained tips
+        for i in 1..21u8 {
+            let value = MARF::get_by_key(
+                &mut marf.borrow_storage_backend(),
+                &BlockHeaderHash([20; 32]),
+                &format!("key-{}", i),
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(value, MARFValue::from(i as u32));
+        }
+        let value = MARF::get_by_key(
+            &mut marf.borrow_storage_backend(),
+            &BlockHeaderHash([16; 32]),
+            "shared",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(value, MARFValue::from(16u32));
+
+        // but not at the pruned ones
+        let e = MARF::get_by_key(
+            &mut marf.borrow_storage_backend(),
+            &BlockHeaderHash([3; 32]),
+            "key-3",
+        )
+        .unwrap_err();
+        if let Error::PrunedError = e {
+        } else {
+            assert!(false);
+        }
+
+        // the pruned MARF can still be extended, and pruned again
+        let block_header = BlockHeaderHash([21; 32]);
+        marf.begin(&parent, &block_header).unwrap();
+        marf.insert("key-21", MARFValue::from(21u32)).unwrap();
+        marf.insert("shared", MARFValue::from(21u32)).unwrap();
+        marf.commit_to(&block_header).unwrap();
+
+        assert_eq!(marf.prune(&block_header, 5, u64::max_value()).unwrap(), 1);
+        assert!(marf.is_pruned(&BlockHeaderHash([16; 32])).unwrap());
+
+        for i in 1..22u8 {
+            let value = MARF::get_by_key(
+                &mut marf.borrow_storage_backend(),
+                &block_header,
+                &format!("key-{}", i),
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(value, MARFValue::from(i as u32));
+        }
+        let value = MARF::get_by_key(&mut marf.borrow_storage_backend(), &block_header, "shared")
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, MARFValue::from(21u32));
+    }
+
+    #[test]
+    fn test_marf_prune_forks() {
+        let f = TrieFileStorage::new_memory().unwrap();
+        let mut marf = MARF::from_storage(f);
+
+        fn extend(marf: &mut MARF<BlockHeaderHash>, parent: &BlockHeaderHash, i: u8) {
+            let block_header = BlockHeaderHash([i; 32]);
+            marf.begin(parent, &block_header).unwrap();
+            marf.insert(&format!("key-{}", i), MARFValue::from(i as u32))
+                .unwrap();
+            marf.insert("shared", MARFValue::from(i as u32)).unwrap();
+            marf.commit_to(&block_header).unwrap();
+        }
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..11u8 {
+            extend(&mut marf, &parent, i);
+            parent = BlockHeaderHash([i; 32]);
+        }
+
+        // a short fork off of block 3, stored after most of the canonical chain
+        extend(&mut marf, &BlockHeaderHash([3; 32]), 31);
+        extend(&mut marf, &BlockHeaderHash([31; 32]), 32);
+
+        for i in 11..13u8 {
+            extend(&mut marf, &parent, i);
+            parent = BlockHeaderHash([i; 32]);
+        }
+
+        // retention goes by height below the tip, not by the order the tries were stored in
+        assert_eq!(marf.prune(&parent, 5, u64::max_value()).unwrap(), 9);
+        assert!(marf.is_pruned(&BlockHeaderHash([7; 32])).unwrap());
+        assert!(marf.is_pruned(&BlockHeaderHash([31; 32])).unwrap());
+        assert!(marf.is_pruned(&BlockHeaderHash([32; 32])).unwrap());
+        assert!(!marf.is_pruned(&BlockHeaderHash([8; 32])).unwrap());
+
+        for i in 1..13u8 {
+            let value = MARF::get_by_key(
+                &mut marf.borrow_storage_backend(),
+                &parent,
+                &format!("key-{}", i),
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(value, MARFValue::from(i as u32));
+        }
+    }
+
+    #[test]
+    fn test_marf_prune_incremental() {
+        let f = TrieFileStorage::new_memory().unwrap();
+        let mut marf = MARF::from_storage(f);
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..21u8 {
+            let block_header = BlockHeaderHash([i; 32]);
+            marf.begin(&parent, &block_header).unwrap();
+            marf.insert(&format!("key-{}", i), MARFValue::from(i as u32))
+                .unwrap();
+            marf.insert("shared", MARFValue::from(i as u32)).unwrap();
+            marf.commit_to(&block_header).unwrap();
+            parent = block_header;
+        }
+
+        // a small budget spreads the pass over many calls, and the MARF can be extended from a
+        // retained trie in between them
+        let mut num_pruned = marf.prune(&parent, 5, 4).unwrap();
+        assert!(marf.is_pruning());
+        assert!(!marf.is_pruned(&BlockHeaderHash([3; 32])).unwrap());
+
+        let block_header = BlockHeaderHash([21; 32]);
+        marf.begin(&parent, &block_header).unwrap();
+        marf.insert("key-21", MARFValue::from(21u32)).unwrap();
+        marf.insert("shared", MARFValue::from(21u32)).unwrap();
+        marf.commit_to(&block_header).unwrap();
+
+        let mut num_calls = 1;
+        while marf.is_pruning() {
+            num_pruned += marf.prune(&block_header, 5, 4).unwrap();
+            num_calls += 1;
+        }
+        assert!(num_calls > 2);
+
+        // the pass only pruned what was prunable when it began
+        assert_eq!(num_pruned, 15);
+        assert!(!marf.is_pruned(&BlockHeaderHash([16; 32])).unwrap());
+
+        for i in 1..22u8 {
+            let value = MARF::get_by_key(
+                &mut marf.borrow_storage_backend(),
+                &block_header,
+                &format!("key-{}", i),
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(value, MARFValue::from(i as u32));
+        }
+    }
+
+    #[test]
+    fn test_marf_blob_file() {
+        let path = "/tmp/test_marf_blob_file.sqlite";
+        for file in [path.to_string(), TrieFile::blobs_path(path)].iter() {
+            if fs::metadata(file).is_ok() {
+                fs::remove_file(file).unwrap();
+            }
+        }
+
+        fn extend(marf: &mut MARF<BlockHeaderHash>, parent: &BlockHeaderHash, i: u8) {
+            let block_header = BlockHeaderHash([i; 32]);
+            marf.begin(parent, &block_header).unwrap();
+            marf.insert(&format!("key-{}", i), MARFValue::from(i as u32))
+                .unwrap();
+            marf.insert("shared", MARFValue::from(i as u32)).unwrap();
+            marf.commit_to(&block_header).unwrap();
+        }
+
+        fn check(marf: &mut MARF<BlockHeaderHash>, tip: u8) {
+            for i in 1..(tip + 1) {
+                let value = marf
+                    .get(&BlockHeaderHash([tip; 32]), &format!("key-{}", i))
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(value, MARFValue::from(i as u32));
+            }
+            let value = marf
+                .get(&BlockHeaderHash([tip; 32]), "shared")
+                .unwrap()
+                .unwrap();
+            assert_eq!(value, MARFValue::from(tip as u32));
+        }
+
+        // start out with the tries in SQLite
+        let mut marf = MARF::from_path(path).unwrap();
+        assert!(!marf.storage.has_blobs());
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..11u8 {
+            extend(&mut marf, &parent, i);
+            parent = BlockHeaderHash([i; 32]);
+        }
+        check(&mut marf, 10);
+
+        // new tries go to the blob file, and the old ones remain readable
+        let mut marf = MARF::from_storage(TrieFileStorage::open_with_blobs(path).unwrap());
+        assert!(marf.storage.has_blobs());
+        for i in 11..21u8 {
+            extend(&mut marf, &parent, i);
+            parent = BlockHeaderHash([i; 32]);
+        }
+        check(&mut marf, 20);
+        assert!(fs::metadata(&TrieFile::blobs_path(path)).unwrap().len() > 0);
+
+        let root_hashes: Vec<_> = (1..21u8)
+            .map(|i| marf.get_root_hash_at(&BlockHeaderHash([i; 32])).unwrap())
+            .collect();
+
+        // move the old tries over
+        assert_eq!(marf.storage.migrate_to_blobs().unwrap(), 10);
+        assert_eq!(marf.storage.migrate_to_blobs().unwrap(), 0);
+        assert_eq!(
+            trie_sql::get_internal_block_identifiers(marf.sqlite_conn())
+                .unwrap()
+                .len(),
+            0
+        );
+
+        // once there's a blob file, it's always used
+        let mut marf = MARF::from_path(path).unwrap();
+        assert!(marf.storage.has_blobs());
+        for i in 1..21u8 {
+            assert_eq!(
+                marf.get_root_hash_at(&BlockHeaderHash([i; 32])).unwrap(),
+                root_hashes[(i - 1) as usize]
+            );
+            check(&mut marf, i);
+        }
+
+        let mut ro_marf: MARF<BlockHeaderHash> =
+            MARF::from_storage(TrieFileStorage::open_readonly(path).unwrap());
+        check(&mut ro_marf, 20);
+
+        // tries in the blob file can be pruned, and the space their blobs took up is reclaimed
+        let blobs_size = fs::metadata(&TrieFile::blobs_path(path)).unwrap().len();
+        assert_eq!(
+            marf.prune(&BlockHeaderHash([20; 32]), 5, u64::max_value())
+                .unwrap(),
+            15
+        );
+        while marf.is_pruning() {
+            assert_eq!(
+                marf.prune(&BlockHeaderHash([20; 32]), 5, u64::max_value())
+                    .unwrap(),
+                0
+            );
+        }
+        assert!(marf.is_pruned(&BlockHeaderHash([3; 32])).unwrap());
+        assert!(!marf.is_pruned(&BlockHeaderHash([18; 32])).unwrap());
+        assert!(fs::metadata(&TrieFile::blobs_path(path)).unwrap().len() < blobs_size / 2);
+        check(&mut marf, 20);
+        check(&mut ro_marf, 20);
+
+        extend(&mut marf, &parent, 21);
+        check(&mut marf, 21);
+
+        // the blob file can't go missing
+        fs::remove_file(&TrieFile::blobs_path(path)).unwrap();
+        match TrieFileStorage::<BlockHeaderHash>::open(path) {
+            Err(Error::CorruptionError(_)) => {}
+            _ => panic!("Opened a MARF without its blob file"),
+        }
+    }
+
+    #[test]
+    fn test_marf_readonly_unmigrated() {
+        let path = "/tmp/test_marf_readonly_unmigrated.sqlite";
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+
+        let mut marf = MARF::from_path(path).unwrap();
+        let block_header = BlockHeaderHash([1; 32]);
+        marf.begin(&BlockHeaderHash::sentinel(), &block_header)
+            .unwrap();
+        marf.insert("key", MARFValue::from(1u32)).unwrap();
+        marf.commit_to(&block_header).unwrap();
+
+        // recreate the marf_data table as it was before blob files were supported
+        marf.sqlite_conn()
+            .execute_batch(
+                "CREATE TABLE marf_data_old AS SELECT block_id, block_hash, data, unconfirmed FROM marf_data;
+                 DROP TABLE marf_data;
+                 ALTER TABLE marf_data_old RENAME TO marf_data;",
+            )
+            .unwrap();
+
+        // a read-only open does not migrate the table, but can still tell what's pruned
+        let mut ro_marf: MARF<BlockHeaderHash> =
+            MARF::from_storage(TrieFileStorage::open_readonly(path).unwrap());
+        assert!(!ro_marf.is_pruned(&block_header).unwrap());
+        assert_eq!(
+            trie_sql::get_confirmed_block_identifiers(ro_marf.sqlite_conn())
+                .unwrap()
+                .len(),
+            1
+        );
+        let value = ro_marf.get(&block_header, "key").unwrap().unwrap();
+        assert_eq!(value, MARFValue::from(1u32));
+    }
+
+    #[test]
+    fn test_marf_node_cache() {
+        let path = "/tmp/test_marf_node_cache.sqlite";
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+
+        fn extend(marf: &mut MARF<BlockHeaderHash>, parent: &BlockHeaderHash, i: u8) {
+            let block_header = BlockHeaderHash([i; 32]);
+            marf.begin(parent, &block_header).unwrap();
+            marf.insert(&format!("key-{}", i), MARFValue::from(i as u32))
+                .unwrap();
+            marf.insert("shared", MARFValue::from(i as u32)).unwrap();
+            marf.commit_to(&block_header).unwrap();
+        }
+
+        fn check(marf: &mut MARF<BlockHeaderHash>, tip: u8) {
+            for i in 1..(tip + 1) {
+                let value = marf
+                    .get(&BlockHeaderHash([tip; 32]), &format!("key-{}", i))
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(value, MARFValue::from(i as u32));
+            }
+            let value = marf
+                .get(&BlockHeaderHash([tip; 32]), "shared")
+                .unwrap()
+                .unwrap();
+            assert_eq!(value, MARFValue::from(tip as u32));
+        }
+
+        let mut marf = MARF::from_path(path).unwrap();
+        marf.set_node_cache_size(1024 * 1024);
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..11u8 {
+            extend(&mut marf, &parent, i);
+            parent = BlockHeaderHash([i; 32]);
+        }
+
+        // the second time around, the nodes come from the cache
+        check(&mut marf, 10);
+        let (hits, misses) = marf.storage.node_cache_stats();
+        assert!(misses > 0);
+        check(&mut marf, 10);
+        let (hits_after, _) = marf.storage.node_cache_stats();
+        assert!(hits_after > hits);
+
+        let mut ro_marf: MARF<BlockHeaderHash> =
+            MARF::from_storage(TrieFileStorage::open_readonly(path).unwrap());
+        check(&mut ro_marf, 10);
+        assert_eq!(ro_marf.storage.node_cache_stats(), (0, 0));
+
+        // a trie written by a transaction that rolls back is never cached, since its identifier
+        // gets reused
+        let dropped_header = BlockHeaderHash([100; 32]);
+        let dropped_id = {
+            let mut tx = marf.begin_tx().unwrap();
+            tx.begin(&parent, &dropped_header).unwrap();
+            tx.insert_batch(&vec!["shared".to_string()], vec![MARFValue::from(100u32)])
+                .unwrap();
+            tx.storage.flush().unwrap();
+
+            let value = MARF::get_by_key(&mut tx.storage, &dropped_header, "shared")
+                .unwrap()
+                .unwrap();
+            assert_eq!(value, MARFValue::from(100u32));
+
+            let dropped_id = tx.storage.get_block_identifier(&dropped_header).unwrap();
+            tx.drop_current();
+            dropped_id
+        };
+
+        extend(&mut marf, &parent, 11);
+        parent = BlockHeaderHash([11; 32]);
+        assert_eq!(
+            marf.borrow_storage_backend()
+                .get_block_identifier(&parent)
+                .unwrap(),
+            dropped_id
+        );
+        check(&mut marf, 11);
+
+        // cached nodes of pruned tries are dropped
+        check(&mut marf, 3);
+        assert_eq!(marf.prune(&parent, 5, u64::max_value()).unwrap(), 6);
+        match MARF::get_by_key(
+            &mut marf.borrow_storage_backend(),
+            &BlockHeaderHash([3; 32]),
+            "shared",
+        ) {
+            Err(Error::PrunedError) => {}
+            _ => panic!("Read a pruned trie"),
+        }
+        check(&mut marf, 11);
+    }
 }
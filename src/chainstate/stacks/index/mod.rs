@@ -15,6 +15,8 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 pub mod bits;
+pub mod cache;
+pub mod file;
 pub mod marf;
 pub mod node;
 pub mod proofs;
@@ -283,6 +285,7 @@ pub enum Error {
     CursorError(node::CursorError),
     RestoreMarfBlockError(Box<Error>),
     NonMatchingForks([u8; 32], [u8; 32]),
+    PrunedError,
 }
 
 impl From<io::Error> for Error {
@@ -348,6 +351,7 @@ impl fmt::Display for Error {
             Error::RequestedIdentifierForExtensionTrie => {
                 write!(f, "BUG: MARF requested the identifier for a RAM trie")
             }
+            Error::PrunedError => write!(f, "Trie data has been pruned from storage"),
         }
     }
 }
@@ -53,6 +53,13 @@ use chainstate::stacks::index::storage::{TrieFileStorage, TrieStorageConnection}
 use chainstate::stacks::index::trie::Trie;
 
 use chainstate::stacks::index::Error;
+use chainstate::stacks::StacksBlockId;
+
+use vm::contexts::OwnedEnvironment;
+use vm::database::{BurnStateDB, ClarityBackingStore, HeadersDB, ProvenBackingStore};
+use vm::errors::Error as ClarityError;
+use vm::types::{PrincipalData, QualifiedContractIdentifier};
+use vm::{SymbolicExpression, Value};
 
 use net::{codec::read_next, StacksMessageCodec};
 use util::{hash::to_hex, log};
@@ -1596,6 +1603,79 @@ impl<T: MarfTrieId> TrieMerkleProof<T> {
     }
 }
 
+/// Verify the proofs of a set of key/value pairs, e.g., the keys read by a read-only call.
+///  Returns the proven pairs, or None if any proof fails to verify against `root_hash`.
+pub fn verify_entries<T: MarfTrieId>(
+    entries: &[(String, String, TrieMerkleProof<T>)],
+    root_hash: &TrieHash,
+    root_to_block: &HashMap<TrieHash, T>,
+) -> Option<HashMap<String, String>> {
+    let mut proven = HashMap::new();
+    for (key, value, proof) in entries.iter() {
+        let path = TriePath::from_key(key);
+        let marf_value = MARFValue::from_value(value);
+        if !proof.verify(&path, &marf_value, root_hash, root_to_block) {
+            debug!("Proof for key {} does not verify", key);
+            return None;
+        }
+        proven.insert(key.clone(), value.clone());
+    }
+    Some(proven)
+}
+
+/// Re-run a read-only call against only the proven keys of `entries`. Contract code and
+///  block lookups come from `local_store`, which must hold the called contracts.
+///  Returns None if a proof fails to verify, or if the call reads any key which is not
+///  proven. The MARF has no proofs of non-inclusion, so a key which the call found absent
+///  can't be told apart from a key the prover left out, and such a call can't be verified.
+pub fn verify_read_only_call(
+    entries: &[(String, String, TrieMerkleProof<StacksBlockId>)],
+    root_hash: &TrieHash,
+    root_to_block: &HashMap<TrieHash, StacksBlockId>,
+    local_store: &mut dyn ClarityBackingStore,
+    headers_db: &dyn HeadersDB,
+    burn_state_db: &dyn BurnStateDB,
+    mainnet: bool,
+    sender: PrincipalData,
+    contract_identifier: &QualifiedContractIdentifier,
+    function: &str,
+    args: &[Value],
+) -> Option<Result<Value, ClarityError>> {
+    let proven = verify_entries(entries, root_hash, root_to_block)?;
+    let mut store = ProvenBackingStore::new(proven, local_store);
+    let args: Vec<_> = args
+        .iter()
+        .map(|x| SymbolicExpression::atom_value(x.clone()))
+        .collect();
+
+    let result = {
+        let mut clarity_db = store.as_clarity_db(headers_db, burn_state_db);
+        clarity_db.begin();
+        let mut vm_env = OwnedEnvironment::new_free(mainnet, clarity_db);
+        let result = vm_env
+            .execute_in_env(Value::Principal(sender), |env| {
+                env.execute_contract(contract_identifier, function, &args, true)
+            })
+            .map(|(value, _, _)| value);
+        let (mut clarity_db, _) = vm_env
+            .destruct()
+            .expect("Failed to recover database reference after executing read-only call");
+        clarity_db.roll_back();
+        result
+    };
+
+    if !store.unproven_reads().is_empty() {
+        debug!(
+            "Read-only call read {} keys with no proof",
+            store.unproven_reads().len();
+            "keys" => ?store.unproven_reads()
+        );
+        return None;
+    }
+
+    Some(result)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1774,4 +1854,140 @@ mod test {
         println!("DEBUG: verify(old_v)");
         assert!(!proof_5.verify(&triepath_4, &marf_value_4, &root_hash_5, &root_to_block));
     }
+
+    #[test]
+    fn test_verify_read_only_call() {
+        use vm::database::{MarfedKV, MemoryBackingStore, NULL_BURN_STATE_DB, NULL_HEADER_DB};
+
+        let contract = "(define-data-var base int 3)
+             (define-map totals int int)
+             (map-set totals 1 10)
+             (define-read-only (get-total (key int))
+               (+ (var-get base) (default-to 0 (map-get? totals key))))";
+        let contract_identifier = QualifiedContractIdentifier::local("totals").unwrap();
+        let sender = PrincipalData::from(contract_identifier.issuer.clone());
+        let block_0 = StacksBlockId([0u8; 32]);
+        let block_1 = StacksBlockId([1u8; 32]);
+
+        let mut marf_kv = MarfedKV::temporary();
+        {
+            let mut store = marf_kv.begin(&StacksBlockId::sentinel(), &block_0);
+            store
+                .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB)
+                .initialize();
+            store.test_commit();
+        }
+        {
+            let mut store = marf_kv.begin(&block_0, &block_1);
+            OwnedEnvironment::new_free(
+                false,
+                store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB),
+            )
+            .initialize_contract(contract_identifier.clone(), contract)
+            .unwrap();
+            store.test_commit();
+        }
+
+        // run the call on the MARF, recording the proofs of the keys it reads.
+        let entries = {
+            let mut store = marf_kv.begin_read_only(Some(&block_1));
+            let mut clarity_db = store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            clarity_db.begin();
+            clarity_db.begin_read_proofs();
+            let mut env = OwnedEnvironment::new_free(false, clarity_db);
+            let (result, _, _) = env
+                .execute_in_env(Value::Principal(sender.clone()), |env| {
+                    env.execute_contract(
+                        &contract_identifier,
+                        "get-total",
+                        &[SymbolicExpression::atom_value(Value::Int(1))],
+                        true,
+                    )
+                })
+                .unwrap();
+            assert_eq!(result, Value::Int(13));
+            let (mut clarity_db, _) = env.destruct().unwrap();
+            let entries = clarity_db.take_read_proofs();
+            clarity_db.roll_back();
+            entries
+        };
+        assert!(entries.len() >= 3);
+
+        let root_hash = marf_kv.get_marf().get_root_hash_at(&block_1).unwrap();
+        let root_to_block = marf_kv
+            .get_marf()
+            .borrow_storage_backend()
+            .read_root_to_block_table()
+            .unwrap();
+
+        // the verifier only has the contract's code, not its data.
+        let mut local_store = MemoryBackingStore::new();
+        OwnedEnvironment::new_free(false, local_store.as_clarity_db())
+            .initialize_contract(contract_identifier.clone(), contract)
+            .unwrap();
+
+        let result = verify_read_only_call(
+            &entries,
+            &root_hash,
+            &root_to_block,
+            &mut local_store,
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+            false,
+            sender.clone(),
+            &contract_identifier,
+            "get-total",
+            &[Value::Int(1)],
+        );
+        assert_eq!(result.unwrap().unwrap(), Value::Int(13));
+
+        // a tampered value no longer verifies.
+        let mut entries = entries;
+        let base_value = {
+            let base_entry = entries
+                .iter_mut()
+                .find(|(key, _, _)| key.ends_with("::base"))
+                .unwrap();
+            std::mem::replace(&mut base_entry.1, Value::Int(4).serialize())
+        };
+        assert!(verify_read_only_call(
+            &entries,
+            &root_hash,
+            &root_to_block,
+            &mut local_store,
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+            false,
+            sender.clone(),
+            &contract_identifier,
+            "get-total",
+            &[Value::Int(1)],
+        )
+        .is_none());
+
+        // neither does a response which leaves out a proven entry, even though the call
+        //  would succeed (with a different result) without it.
+        entries
+            .iter_mut()
+            .find(|(key, _, _)| key.ends_with("::base"))
+            .unwrap()
+            .1 = base_value;
+        let entries_len = entries.len();
+        entries.retain(|(key, _, _)| !key.contains("::totals::"));
+        assert_eq!(entries.len(), entries_len - 1);
+        assert!(verify_read_only_call(
+            &entries,
+            &root_hash,
+            &root_to_block,
+            &mut local_store,
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+            false,
+            sender,
+            &contract_identifier,
+            "get-total",
+            &[Value::Int(1)],
+        )
+        .is_none());
+    }
 }
@@ -35,6 +35,8 @@ use regex::Regex;
 use chainstate::burn::BlockHeaderHash;
 use chainstate::burn::BLOCK_HEADER_HASH_ENCODED_SIZE;
 
+use chainstate::stacks::index::cache::TrieNodeCache;
+use chainstate::stacks::index::file::TrieFile;
 use chainstate::stacks::index::{trie_sql, BlockMap, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE};
 
 use chainstate::stacks::index::bits::{
@@ -615,6 +617,7 @@ impl<T: MarfTrieId> NodeHashReader for TrieRAM<T> {
 
 pub struct TrieSqlCursor<'a> {
     db: &'a Connection,
+    blobs: Option<&'a TrieFile>,
     block_id: u32,
 }
 
@@ -625,7 +628,7 @@ pub struct TrieSqlHashMapCursor<'a, T: MarfTrieId> {
 
 impl NodeHashReader for TrieSqlCursor<'_> {
     fn read_node_hash_bytes<W: Write>(&mut self, ptr: &TriePtr, w: &mut W) -> Result<(), Error> {
-        trie_sql::read_node_hash_bytes(self.db, w, self.block_id, ptr)
+        trie_sql::read_node_hash_bytes(self.db, self.blobs, w, self.block_id, ptr)
     }
 }
 
@@ -675,6 +678,7 @@ pub struct TrieStorageTransaction<'a, T: MarfTrieId>(TrieStorageConnection<'a, T
 pub struct TrieStorageConnection<'a, T: MarfTrieId> {
     pub db_path: &'a str,
     db: SqliteConnection<'a>,
+    blobs: Option<&'a TrieFile>,
     data: &'a mut TrieStorageTransientData<T>,
 
     // used in testing in order to short-circuit block-height lookups
@@ -683,6 +687,22 @@ pub struct TrieStorageConnection<'a, T: MarfTrieId> {
     pub test_genesis_block: &'a mut Option<T>,
 }
 
+/// How many bytes of trie blobs the blob file's compaction moves per node's worth of pruning work
+const BLOB_BYTES_PER_PRUNE_WORK: u64 = 256;
+
+/// The state of an incremental pruning pass (see TrieStorageTransaction::begin_prune_pass()).
+struct PrunePass {
+    /// the tries to prune, including any which are already pruned
+    prunable: HashSet<u32>,
+    /// the nodes left to visit
+    frontier: VecDeque<(u32, TriePtr)>,
+    /// the nodes of the prunable tries which are reachable from the retained tries, by offset
+    reachable: HashMap<u32, HashMap<u32, TriePtr>>,
+    /// the prunable tries left to prune once all of their reachable nodes are found, and
+    /// whether each one is already pruned
+    sweep: VecDeque<(u32, bool)>,
+}
+
 ///
 ///  TrieStorageTransientData holds all the data that _isn't_ committed
 ///   to the underlying SQL storage. Used internally to simplify
@@ -709,6 +729,12 @@ struct TrieStorageTransientData<T: MarfTrieId> {
 
     block_hash_cache: HashMap<u32, T>,
 
+    /// nodes read from confirmed tries
+    node_cache: TrieNodeCache,
+
+    /// the pruning pass in progress, if any
+    prune_pass: Option<PrunePass>,
+
     readonly: bool,
     unconfirmed: bool,
 }
@@ -716,10 +742,13 @@ struct TrieStorageTransientData<T: MarfTrieId> {
 // disk-backed Trie.
 // Keeps the last-extended Trie in-RAM and flushes it to disk on either a call to flush() or a call
 // to extend_to_block() with a different block header hash.
+// If the MARF has a blob file (see TrieFile), confirmed Tries are flushed to it, and only their
+// offsets are stored in SQLite.  Unconfirmed and mined Tries are always stored in SQLite.
 pub struct TrieFileStorage<T: MarfTrieId> {
     pub db_path: String,
 
     db: Connection,
+    blobs: Option<TrieFile>,
     data: TrieStorageTransientData<T>,
 
     // used in testing in order to short-circuit block-height lookups
@@ -733,6 +762,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         TrieStorageConnection {
             db: SqliteConnection::ConnRef(&self.db),
             db_path: &self.db_path,
+            blobs: self.blobs.as_ref(),
             data: &mut self.data,
 
             #[cfg(test)]
@@ -749,6 +779,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         Ok(TrieStorageTransaction(TrieStorageConnection {
             db: SqliteConnection::Tx(tx),
             db_path: &self.db_path,
+            blobs: self.blobs.as_ref(),
             data: &mut self.data,
 
             #[cfg(test)]
@@ -768,6 +799,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         db_path: &str,
         readonly: bool,
         unconfirmed: bool,
+        external_blobs: bool,
     ) -> Result<TrieFileStorage<T>, Error> {
         let mut create_flag = false;
         let open_flags = if db_path != ":memory:" {
@@ -810,13 +842,33 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
         if create_flag {
             trie_sql::create_tables_if_needed(&mut db)?;
+        } else if !readonly {
+            trie_sql::migrate_tables_if_needed(&mut db)?;
         }
 
-        debug!("Opened TrieFileStorage {};", db_path);
+        let blobs = if external_blobs && !readonly && !TrieFile::exists(&db_path) {
+            Some(TrieFile::create(&db_path)?)
+        } else {
+            TrieFile::open(&db_path, readonly)?
+        };
+        if blobs.is_none() && trie_sql::has_external_blobs(&db)? {
+            return Err(Error::CorruptionError(format!(
+                "MARF {} stores tries in {}, but it does not exist",
+                &db_path,
+                &TrieFile::blobs_path(&db_path)
+            )));
+        }
+
+        debug!(
+            "Opened TrieFileStorage {}; blob file: {}",
+            db_path,
+            blobs.is_some()
+        );
 
         let ret = TrieFileStorage {
             db_path,
             db,
+            blobs,
 
             data: TrieStorageTransientData {
                 last_extended: None,
@@ -834,6 +886,8 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
                 trie_ancestor_hash_bytes_cache: None,
                 block_hash_cache: HashMap::new(),
+                node_cache: TrieNodeCache::new(0),
+                prune_pass: None,
 
                 readonly: readonly,
                 unconfirmed: unconfirmed,
@@ -854,15 +908,40 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
     }
 
     pub fn open(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
-        TrieFileStorage::open_opts(db_path, false, false)
+        TrieFileStorage::open_opts(db_path, false, false, false)
+    }
+
+    /// Open the MARF, and store newly-confirmed tries in its blob file, creating the blob file
+    /// if it does not exist yet.  Once a MARF has a blob file, it is always used.
+    pub fn open_with_blobs(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
+        TrieFileStorage::open_opts(db_path, false, false, true)
     }
 
     pub fn open_readonly(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
-        TrieFileStorage::open_opts(db_path, true, false)
+        TrieFileStorage::open_opts(db_path, true, false, false)
     }
 
     pub fn open_unconfirmed(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
-        TrieFileStorage::open_opts(db_path, false, true)
+        TrieFileStorage::open_opts(db_path, false, true, false)
+    }
+
+    /// Does this MARF store its tries in a blob file?
+    pub fn has_blobs(&self) -> bool {
+        self.blobs.is_some()
+    }
+
+    /// Move the blobs of the confirmed tries that are still in SQLite to the blob file, and
+    /// reclaim the space they used in the database.  The storage must have been opened with
+    /// open_with_blobs().  Returns the number of tries moved.
+    pub fn migrate_to_blobs(&mut self) -> Result<u64, Error> {
+        let mut tx = self.transaction()?;
+        let num_migrated = tx.migrate_trie_blobs()?;
+        tx.commit_tx();
+
+        if num_migrated > 0 {
+            self.db.execute_batch("VACUUM")?;
+        }
+        Ok(num_migrated)
     }
 
     pub fn readonly(&self) -> bool {
@@ -873,16 +952,40 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         self.data.unconfirmed
     }
 
+    /// Cache up to (approximately) `max_size` bytes of the nodes read from confirmed tries.  A
+    /// size of 0 disables the cache.  Read-only views made with reopen_readonly() do not cache
+    /// nodes.
+    pub fn set_node_cache_size(&mut self, max_size: usize) {
+        debug!(
+            "Set node cache size of TrieFileStorage {} to {} bytes",
+            &self.db_path, max_size
+        );
+        self.data.node_cache.set_max_size(max_size);
+    }
+
+    /// Is a pruning pass in progress?
+    pub fn is_pruning(&self) -> bool {
+        self.data.prune_pass.is_some()
+    }
+
+    /// Number of node cache hits and misses so far
+    pub fn node_cache_stats(&self) -> (u64, u64) {
+        self.data.node_cache.stats()
+    }
+
     pub fn reopen_readonly(&self) -> Result<TrieFileStorage<T>, Error> {
         let db = Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
         db.busy_handler(Some(tx_busy_handler))?;
 
         trace!("Make read-only view of TrieFileStorage: {}", &self.db_path);
 
+        let blobs = TrieFile::open(&self.db_path, true)?;
+
         // TODO: borrow self.last_extended and self.block_hash_cache; don't copy them
         let ret = TrieFileStorage {
             db_path: self.db_path.clone(),
             db: db,
+            blobs: blobs,
 
             data: TrieStorageTransientData {
                 last_extended: self.data.last_extended.clone(),
@@ -900,6 +1003,8 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
                 trie_ancestor_hash_bytes_cache: None,
                 block_hash_cache: self.data.block_hash_cache.clone(),
+                node_cache: TrieNodeCache::new(0),
+                prune_pass: None,
 
                 readonly: true,
                 unconfirmed: true,
@@ -927,10 +1032,13 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
             &self.db_path
         );
 
+        let blobs = TrieFile::open(&self.db_path, true)?;
+
         // TODO: borrow self.last_extended and self.block_hash_cache; don't copy them
         let ret = TrieFileStorage {
             db_path: self.db_path.to_string(),
             db: db,
+            blobs: blobs,
 
             data: TrieStorageTransientData {
                 last_extended: None,
@@ -948,6 +1056,8 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
 
                 trie_ancestor_hash_bytes_cache: None,
                 block_hash_cache: HashMap::new(),
+                node_cache: TrieNodeCache::new(0),
+                prune_pass: None,
 
                 readonly: true,
                 unconfirmed: true,
@@ -962,6 +1072,18 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         Ok(ret)
     }
 
+    /// Store the blob of a newly-confirmed trie, in the blob file if there is one.
+    fn write_confirmed_trie_blob(&self, bhh: &T, buffer: &[u8]) -> Result<u32, Error> {
+        match self.blobs {
+            Some(blobs) => {
+                let offset = blobs.append_trie_blob(buffer)?;
+                blobs.sync()?;
+                trie_sql::write_external_trie_blob(&self.db, bhh, offset, buffer.len() as u64)
+            }
+            None => trie_sql::write_trie_blob(&self.db, bhh, buffer),
+        }
+    }
+
     fn inner_flush(&mut self, flush_options: FlushOptions<'_, T>) -> Result<(), Error> {
         // save the currently-buffered Trie to disk, and atomically put it into place (possibly to
         // a different block than the one opened, as indicated by final_bhh).
@@ -987,7 +1109,9 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
                     if self.data.unconfirmed {
                         return Err(Error::UnconfirmedError);
                     }
-                    trie_sql::write_trie_blob(&self.db, &bhh, &buffer)?
+                    let block_id = self.write_confirmed_trie_blob(&bhh, &buffer)?;
+                    self.data.node_cache.add_uncommitted(block_id);
+                    block_id
                 }
                 FlushOptions::NewHeader(real_bhh) => {
                     // If we opened a block with a given hash, but want to store it as a block with a *different*
@@ -1006,7 +1130,9 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
                         // switch over state
                         self.data.cur_block = real_bhh.clone();
                     }
-                    trie_sql::write_trie_blob(&self.db, real_bhh, &buffer)?
+                    let block_id = self.write_confirmed_trie_blob(real_bhh, &buffer)?;
+                    self.data.node_cache.add_uncommitted(block_id);
+                    block_id
                 }
                 FlushOptions::MinedTable(real_bhh) => {
                     if self.data.unconfirmed {
@@ -1167,11 +1293,283 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         self.data.cur_block = T::sentinel();
         self.data.cur_block_id = None;
         self.data.last_extended = None;
+        self.data.node_cache.clear();
         self.clear_cached_ancestor_hashes_bytes();
 
         Ok(())
     }
 
+    /// Begin a pass which prunes the given confirmed tries (including any that an earlier pass
+    /// already pruned), and retains the other confirmed tries.  A pruned trie's blob is emptied,
+    /// but the nodes in it which are still reachable from the retained tries via back-pointers
+    /// are kept at their original offsets, so the back-pointers remain valid.  The root hash of
+    /// each pruned trie is also kept, so the ancestor hashes of the retained tries can still be
+    /// calculated.  Reading any other node of a pruned trie fails with Error::PrunedError.
+    ///
+    /// The pass only does its work in calls to prune_step().  Tries committed while it is in
+    /// progress must descend from a retained trie, since they may only refer to the nodes that
+    /// are reachable from it.
+    pub fn begin_prune_pass(&mut self, prunable: HashSet<u32>) -> Result<(), Error> {
+        if self.data.readonly {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.data.prune_pass.is_some() {
+            return Err(Error::InProgressError);
+        }
+
+        trie_sql::create_pruned_nodes_table_if_needed(self.sqlite_tx())?;
+
+        let root_ptr = TriePtr::new(
+            TrieNodeID::Node256 as u8,
+            0,
+            TrieStorageConnection::<T>::root_ptr_disk(),
+        );
+        let mut frontier = VecDeque::new();
+        let mut sweep = VecDeque::new();
+        for (block_id, pruned) in trie_sql::get_confirmed_block_identifiers(&self.db)?.into_iter() {
+            if prunable.contains(&block_id) {
+                sweep.push_back((block_id, pruned));
+            } else {
+                frontier.push_back((block_id, root_ptr.clone()));
+            }
+        }
+        if sweep.len() == 0 {
+            return Ok(());
+        }
+        for block_id in trie_sql::get_unconfirmed_block_identifiers(&self.db)?.into_iter() {
+            frontier.push_back((block_id, root_ptr.clone()));
+        }
+
+        debug!(
+            "Begin pruning pass over {} tries; {} tries are retained",
+            sweep.len(),
+            frontier.len()
+        );
+        self.data.prune_pass = Some(PrunePass {
+            prunable,
+            frontier,
+            reachable: HashMap::new(),
+            sweep,
+        });
+        Ok(())
+    }
+
+    /// Do up to about `max_nodes` nodes' worth of the work of the pruning pass in progress, if
+    /// there is one: first find every node of the prunable tries that is reachable from a
+    /// retained trie, then prune the tries, and then (if the MARF has a blob file) reclaim the
+    /// space their blobs took up in it.  Returns the number of tries newly pruned.
+    pub fn prune_step(&mut self, max_nodes: u64) -> Result<u64, Error> {
+        if self.data.readonly {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.data.last_extended.is_some() {
+            return Err(Error::InProgressError);
+        }
+        // if this step fails, the pass is abandoned
+        let mut pass = match self.data.prune_pass.take() {
+            Some(pass) => pass,
+            None => {
+                return Ok(0);
+            }
+        };
+
+        let mut work = 0;
+        while work < max_nodes {
+            let (block_id, ptr) = match pass.frontier.pop_front() {
+                Some(next) => next,
+                None => {
+                    break;
+                }
+            };
+
+            // the nodes of a retained trie form a tree, and so are only visited once -- but a
+            // node of a prunable trie can be reached through any number of back-pointers.
+            if pass.prunable.contains(&block_id) {
+                let nodes = pass.reachable.entry(block_id).or_insert_with(HashMap::new);
+                if nodes.contains_key(&ptr.ptr()) {
+                    continue;
+                }
+                nodes.insert(ptr.ptr(), ptr.clone());
+            }
+
+            work += 1;
+            let (node, _) = trie_sql::read_node_type(&self.db, self.blobs, block_id, &ptr)?;
+            if node.is_leaf() {
+                continue;
+            }
+            for child in node.ptrs().iter() {
+                if child.id() == TrieNodeID::Empty as u8 {
+                    continue;
+                }
+                if is_backptr(child.id()) {
+                    // a node of a retained trie is visited from that trie's own root
+                    if pass.prunable.contains(&child.back_block()) {
+                        pass.frontier
+                            .push_back((child.back_block(), child.from_backptr()));
+                    }
+                } else {
+                    pass.frontier.push_back((block_id, child.clone()));
+                }
+            }
+        }
+
+        let root_ptr = TriePtr::new(
+            TrieNodeID::Node256 as u8,
+            0,
+            TrieStorageConnection::<T>::root_ptr_disk(),
+        );
+        let mut num_pruned = 0;
+        let mut num_swept = 0;
+        while pass.frontier.len() == 0 && work < max_nodes {
+            let (block_id, already_pruned) = match pass.sweep.pop_front() {
+                Some(next) => next,
+                None => {
+                    break;
+                }
+            };
+            let nodes = pass.reachable.remove(&block_id).unwrap_or_default();
+            num_swept += 1;
+            work += 1;
+
+            if already_pruned {
+                // drop the nodes that are no longer reachable
+                for ptr in trie_sql::get_pruned_node_ptrs(&self.db, block_id)?.into_iter() {
+                    if ptr != root_ptr.ptr() && !nodes.contains_key(&ptr) {
+                        trie_sql::drop_pruned_node(&self.db, block_id, ptr)?;
+                        work += 1;
+                    }
+                }
+                continue;
+            }
+
+            let root_hash =
+                trie_sql::get_node_hash_bytes(&self.db, self.blobs, block_id, &root_ptr)?;
+            trie_sql::write_pruned_node(&self.db, block_id, root_ptr.ptr(), root_hash.as_bytes())?;
+
+            for ptr in nodes.values() {
+                let (node, hash) = trie_sql::read_node_type(&self.db, self.blobs, block_id, ptr)?;
+                let mut bytes = Cursor::new(vec![]);
+                write_nodetype_bytes(&mut bytes, &node, hash)?;
+                trie_sql::write_pruned_node(&self.db, block_id, ptr.ptr(), &bytes.into_inner())?;
+                work += 1;
+            }
+
+            trie_sql::clear_trie_blob(&self.db, block_id)?;
+            num_pruned += 1;
+        }
+
+        if num_swept > 0 {
+            // only the reachable nodes of the pruned tries can be read now
+            self.data.node_cache.clear();
+        }
+
+        let mut finished = pass.frontier.len() == 0 && pass.sweep.len() == 0;
+        if finished && self.blobs.is_some() {
+            finished = work < max_nodes && self.compact_trie_blobs(max_nodes - work)?;
+        }
+
+        if finished {
+            debug!("Finished pruning pass");
+        } else {
+            self.data.prune_pass = Some(pass);
+        }
+        Ok(num_pruned)
+    }
+
+    /// Move the blobs at the end of the blob file down into the space that the blobs of pruned
+    /// tries took up, after trimming off whatever space earlier moves freed at the end of the
+    /// file.  A blob is only ever written to space that no committed row refers to, so a crash
+    /// leaves every committed blob intact.  Does up to about `max_work` nodes' worth of work, and
+    /// returns true if there was nothing left to move.
+    fn compact_trie_blobs(&mut self, max_work: u64) -> Result<bool, Error> {
+        let blobs = match self.blobs {
+            Some(blobs) => blobs,
+            None => {
+                return Ok(true);
+            }
+        };
+
+        // no other writer can append to the file while this transaction is open
+        let committed_size = trie_sql::get_external_blobs_size(&self.db)?;
+        if blobs.size()? > committed_size {
+            blobs.truncate(committed_size)?;
+        }
+
+        let live = trie_sql::get_external_trie_blobs(&self.db)?;
+        let mut gaps = vec![];
+        let mut end = 0;
+        for &(_, offset, length) in live.iter() {
+            if offset > end {
+                gaps.push((end, offset));
+            }
+            end = offset + length;
+        }
+
+        let mut work = 0;
+        let mut moved = vec![];
+        for &(block_id, offset, length) in live.iter().rev() {
+            if work >= max_work {
+                break;
+            }
+            let gap_opt = gaps
+                .iter_mut()
+                .find(|gap| gap.0 + length <= gap.1 && gap.1 <= offset);
+            if let Some(gap) = gap_opt {
+                let data = blobs.read_trie_blob(offset, length)?;
+                blobs.write_trie_blob_at(gap.0, &data)?;
+                moved.push((block_id, gap.0, length));
+                gap.0 += length;
+                work += 1 + length / BLOB_BYTES_PER_PRUNE_WORK;
+            }
+        }
+        if moved.len() == 0 {
+            return Ok(true);
+        }
+
+        blobs.sync()?;
+        for (block_id, offset, length) in moved.iter() {
+            trie_sql::set_external_trie_blob(&self.db, *block_id, *offset, *length)?;
+        }
+
+        debug!("Moved {} trie blobs within {}", moved.len(), blobs.path());
+        Ok(false)
+    }
+
+    /// Move the blobs of the confirmed tries that are still in the marf_data table to the blob
+    /// file.  Unconfirmed and mined tries stay where they are.  Returns the number of tries
+    /// moved.
+    pub fn migrate_trie_blobs(&mut self) -> Result<u64, Error> {
+        if self.data.readonly {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.data.last_extended.is_some() {
+            return Err(Error::InProgressError);
+        }
+        let blobs = self.blobs.ok_or_else(|| {
+            error!("No blob file is open for {}", self.db_path);
+            Error::NotFoundError
+        })?;
+
+        // discard anything left behind by an interrupted migration
+        blobs.truncate(trie_sql::get_external_blobs_size(&self.db)?)?;
+
+        let block_ids = trie_sql::get_internal_block_identifiers(&self.db)?;
+        for block_id in block_ids.iter() {
+            let data = trie_sql::read_trie_blob(&self.db, *block_id)?;
+            let offset = blobs.append_trie_blob(&data)?;
+            trie_sql::set_external_trie_blob(&self.db, *block_id, offset, data.len() as u64)?;
+        }
+        blobs.sync()?;
+
+        debug!(
+            "Moved {} tries of {} to {}",
+            block_ids.len(),
+            self.db_path,
+            blobs.path()
+        );
+        Ok(block_ids.len() as u64)
+    }
+
     pub fn sqlite_tx(&self) -> &Transaction<'a> {
         match &self.0.db {
             SqliteConnection::Tx(ref tx) => tx,
@@ -1194,10 +1592,11 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         }
     }
 
-    pub fn commit_tx(self) {
+    pub fn commit_tx(mut self) {
         match self.0.db {
             SqliteConnection::Tx(tx) => {
                 tx.commit().expect("CORRUPTION: Failed to commit MARF");
+                self.0.data.node_cache.clear_uncommitted();
             }
             SqliteConnection::ConnRef(_) => {
                 unreachable!(
@@ -1207,10 +1606,11 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         }
     }
 
-    pub fn rollback(self) {
+    pub fn rollback(mut self) {
         match self.0.db {
             SqliteConnection::Tx(tx) => {
                 tx.rollback().expect("CORRUPTION: Failed to commit MARF");
+                self.0.data.node_cache.clear_uncommitted();
             }
             SqliteConnection::ConnRef(_) => {
                 unreachable!(
@@ -1308,14 +1708,15 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
             0,
             TrieStorageConnection::<T>::root_ptr_disk(),
         );
-        trie_sql::get_node_hash_bytes_by_bhh(&self.db, bhh, &root_hash_ptr)
+        trie_sql::get_node_hash_bytes_by_bhh(&self.db, self.blobs, bhh, &root_hash_ptr)
     }
 
     /// Generate a mapping between Trie root hashes and the blocks that contain them
     #[cfg(test)]
     pub fn read_root_to_block_table(&mut self) -> Result<HashMap<TrieHash, T>, Error> {
-        let mut ret =
-            HashMap::from_iter(trie_sql::read_all_block_hashes_and_roots(&self.db)?.into_iter());
+        let mut ret = HashMap::from_iter(
+            trie_sql::read_all_block_hashes_and_roots(&self.db, self.blobs)?.into_iter(),
+        );
 
         let last_extended = match self.data.last_extended.take() {
             Some((bhh, trie_ram)) => {
@@ -1534,6 +1935,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
         // otherwise, the current block is open as an FD
         let mut cursor = TrieSqlCursor {
             db: &self.db,
+            blobs: self.blobs,
             block_id: self.data.cur_block_id.ok_or_else(|| {
                 error!("Failed to get cur block as hash reader");
                 Error::NotFoundError
@@ -1569,6 +1971,20 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
         Ok(())
     }
 
+    /// Can the nodes of the trie with the given identifier be served from the node cache?  Only
+    /// the nodes of committed, confirmed tries can be.
+    fn is_node_cacheable(&mut self, block_id: u32) -> Result<bool, Error> {
+        if !self.data.node_cache.is_enabled() {
+            return Ok(false);
+        }
+        if let Some(cacheable) = self.data.node_cache.is_cacheable(block_id) {
+            return Ok(cacheable);
+        }
+        let cacheable = trie_sql::is_confirmed_block_identifier(&self.db, block_id)?;
+        self.data.node_cache.set_cacheable(block_id, cacheable);
+        Ok(cacheable)
+    }
+
     pub fn read_node_hash_bytes(&mut self, ptr: &TriePtr) -> Result<TrieHash, Error> {
         if let Some((ref last_extended, ref mut trie_ram)) = self.data.last_extended {
             // special case
@@ -1579,7 +1995,14 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
 
         // some other block or ptr, or cache miss
         match self.data.cur_block_id {
-            Some(block_id) => trie_sql::get_node_hash_bytes(&self.db, block_id, ptr),
+            Some(block_id) => {
+                if self.is_node_cacheable(block_id)? {
+                    if let Some(hash) = self.data.node_cache.get_hash(block_id, ptr.ptr()) {
+                        return Ok(hash);
+                    }
+                }
+                trie_sql::get_node_hash_bytes(&self.db, self.blobs, block_id, ptr)
+            }
             None => {
                 error!("Not found (no file is open)");
                 Err(Error::NotFoundError)
@@ -1611,7 +2034,19 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
 
         // some other block
         match self.data.cur_block_id {
-            Some(id) => trie_sql::read_node_type(&self.db, id, &clear_ptr),
+            Some(id) => {
+                if !self.is_node_cacheable(id)? {
+                    return trie_sql::read_node_type(&self.db, self.blobs, id, &clear_ptr);
+                }
+                if let Some(node_and_hash) = self.data.node_cache.get(id, clear_ptr.ptr()) {
+                    return Ok(node_and_hash);
+                }
+                let (node, hash) = trie_sql::read_node_type(&self.db, self.blobs, id, &clear_ptr)?;
+                self.data
+                    .node_cache
+                    .insert(id, clear_ptr.ptr(), &node, &hash);
+                Ok((node, hash))
+            }
             None => {
                 debug!("Not found (no file is open)");
                 Err(Error::NotFoundError)
@@ -40,11 +40,13 @@ use chainstate::burn::BLOCK_HEADER_HASH_ENCODED_SIZE;
 
 use chainstate::stacks::index::{trie_sql, BlockMap, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE};
 
+use chainstate::stacks::index::file::TrieFile;
 use chainstate::stacks::index::storage::{TrieFileStorage, TrieStorageConnection};
 
 use chainstate::stacks::index::bits::{
     get_node_byte_len, get_node_hash, read_block_identifier, read_hash_bytes,
-    read_node_hash_bytes as bits_read_node_hash_bytes, read_nodetype, write_nodetype_bytes,
+    read_node_hash_bytes as bits_read_node_hash_bytes, read_nodetype, read_nodetype_at_head,
+    write_nodetype_bytes,
 };
 
 use chainstate::stacks::index::node::{
@@ -62,7 +64,7 @@ use std::convert::{TryFrom, TryInto};
 
 use chainstate::stacks::index::Error;
 
-use util::db::tx_begin_immediate;
+use util::db::{tx_begin_immediate, u64_to_sql};
 use util::log;
 
 static SQL_MARF_DATA_TABLE: &str = "
@@ -70,7 +72,9 @@ CREATE TABLE IF NOT EXISTS marf_data (
    block_id INTEGER PRIMARY KEY, 
    block_hash TEXT UNIQUE NOT NULL,
    data BLOB NOT NULL,
-   unconfirmed INTEGER NOT NULL
+   unconfirmed INTEGER NOT NULL,
+   external_offset INTEGER DEFAULT 0 NOT NULL,
+   external_length INTEGER DEFAULT 0 NOT NULL
 );
 
 CREATE INDEX IF NOT EXISTS block_hash_marf_data ON marf_data(block_hash);
@@ -86,20 +90,88 @@ CREATE TABLE IF NOT EXISTS mined_blocks (
 CREATE INDEX IF NOT EXISTS block_hash_mined_blocks ON mined_blocks(block_hash);
 ";
 
+// a trie whose blob is kept in the MARF's blob file (see TrieFile) has an empty data blob, and
+//  a non-zero external_length.
+static SQL_MARF_DATA_EXTERNAL_COLUMNS: &str = "
+ALTER TABLE marf_data ADD COLUMN external_offset INTEGER DEFAULT 0 NOT NULL;
+ALTER TABLE marf_data ADD COLUMN external_length INTEGER DEFAULT 0 NOT NULL;
+";
+
 static SQL_EXTENSION_LOCKS_TABLE: &str = "
 CREATE TABLE IF NOT EXISTS block_extension_locks (block_hash TEXT PRIMARY KEY);
 ";
 
+// once a trie is pruned, its blob in marf_data is emptied, and the nodes which are still
+//  reachable from the unpruned tries (i.e., through back-pointers) are kept here, by their
+//  offset in the original blob. the root node of every pruned trie is kept as just its hash,
+//  so that the ancestor hashes of later tries can still be calculated.
+static SQL_MARF_PRUNED_NODES_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS marf_pruned_nodes (
+   block_id INTEGER NOT NULL,
+   ptr INTEGER NOT NULL,
+   data BLOB NOT NULL,
+   PRIMARY KEY(block_id, ptr)
+);
+";
+
 pub fn create_tables_if_needed(conn: &mut Connection) -> Result<(), Error> {
     let tx = tx_begin_immediate(conn)?;
 
     tx.execute_batch(SQL_MARF_DATA_TABLE)?;
     tx.execute_batch(SQL_MARF_MINED_TABLE)?;
     tx.execute_batch(SQL_EXTENSION_LOCKS_TABLE)?;
+    tx.execute_batch(SQL_MARF_PRUNED_NODES_TABLE)?;
+
+    tx.commit().map_err(|e| e.into())
+}
+
+fn has_external_blob_columns(conn: &Connection) -> Result<bool, Error> {
+    let mut s = conn.prepare("PRAGMA table_info(marf_data)")?;
+    let names = s.query_and_then(NO_PARAMS, |row| row.get::<_, String>("name"))?;
+    for name in names {
+        if name? == "external_offset" {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Add the columns for tries stored in a blob file to a MARF created before blob files were
+/// supported.
+pub fn migrate_tables_if_needed(conn: &mut Connection) -> Result<(), Error> {
+    if has_external_blob_columns(conn)? {
+        return Ok(());
+    }
 
+    let tx = tx_begin_immediate(conn)?;
+    if !has_external_blob_columns(&tx)? {
+        tx.execute_batch(SQL_MARF_DATA_EXTERNAL_COLUMNS)?;
+    }
     tx.commit().map_err(|e| e.into())
 }
 
+/// Are any tries stored in the MARF's blob file?
+pub fn has_external_blobs(conn: &Connection) -> Result<bool, Error> {
+    if !has_external_blob_columns(conn)? {
+        return Ok(false);
+    }
+    let found = conn
+        .query_row(
+            "SELECT 1 FROM marf_data WHERE external_length > 0 LIMIT 1",
+            NO_PARAMS,
+            |_row| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    Ok(found)
+}
+
+/// Create the table of pruned trie nodes in a MARF created before pruning was supported.
+pub fn create_pruned_nodes_table_if_needed(tx: &Transaction) -> Result<(), Error> {
+    tx.execute_batch(SQL_MARF_PRUNED_NODES_TABLE)?;
+    Ok(())
+}
+
 pub fn get_block_identifier<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Result<u32, Error> {
     conn.query_row(
         "SELECT block_id FROM marf_data WHERE block_hash = ?",
@@ -135,6 +207,18 @@ pub fn get_unconfirmed_block_identifier<T: MarfTrieId>(
     .map_err(|e| e.into())
 }
 
+/// Is the trie with the given local ID a confirmed trie?  Returns false if there is no such trie.
+pub fn is_confirmed_block_identifier(conn: &Connection, local_id: u32) -> Result<bool, Error> {
+    let unconfirmed: Option<bool> = conn
+        .query_row(
+            "SELECT unconfirmed FROM marf_data WHERE block_id = ?",
+            &[local_id],
+            |row| row.get("unconfirmed"),
+        )
+        .optional()?;
+    Ok(unconfirmed == Some(false))
+}
+
 pub fn get_block_hash<T: MarfTrieId>(conn: &Connection, local_id: u32) -> Result<T, Error> {
     let result = conn
         .query_row(
@@ -166,6 +250,36 @@ pub fn write_trie_blob<T: MarfTrieId>(
     Ok(block_id)
 }
 
+/// Insert the row of a confirmed trie whose blob was appended to the MARF's blob file.
+pub fn write_external_trie_blob<T: MarfTrieId>(
+    conn: &Connection,
+    block_hash: &T,
+    offset: u64,
+    length: u64,
+) -> Result<u32, Error> {
+    let empty: &[u8] = &[];
+    let args: &[&dyn ToSql] = &[
+        block_hash,
+        &empty,
+        &0,
+        &u64_to_sql(offset)?,
+        &u64_to_sql(length)?,
+    ];
+    let mut s = conn.prepare(
+        "INSERT INTO marf_data (block_hash, data, unconfirmed, external_offset, external_length) VALUES (?, ?, ?, ?, ?)",
+    )?;
+    let block_id = s
+        .insert(args)?
+        .try_into()
+        .expect("EXHAUSTION: MARF cannot track more than 2**31 - 1 blocks");
+
+    debug!(
+        "Wrote block trie {} to rowid {} (blob file offset {}, length {})",
+        block_hash, block_id, offset, length
+    );
+    Ok(block_id)
+}
+
 pub fn write_trie_blob_to_mined<T: MarfTrieId>(
     conn: &Connection,
     block_hash: &T,
@@ -222,113 +336,291 @@ pub fn open_trie_blob<'a>(conn: &'a Connection, block_id: u32) -> Result<Blob<'a
     Ok(blob)
 }
 
+/// Get the offset and length of a trie's blob in the MARF's blob file, if it is kept there.
+pub fn get_external_trie_offset_length(
+    conn: &Connection,
+    block_id: u32,
+) -> Result<Option<(u64, u64)>, Error> {
+    let (offset, length): (i64, i64) = conn.query_row(
+        "SELECT external_offset, external_length FROM marf_data WHERE block_id = ?",
+        &[block_id],
+        |row| Ok((row.get("external_offset")?, row.get("external_length")?)),
+    )?;
+    if length == 0 {
+        return Ok(None);
+    }
+    Ok(Some((offset as u64, length as u64)))
+}
+
+/// Get the size of the prefix of the MARF's blob file that holds the blobs of committed tries.
+pub fn get_external_blobs_size(conn: &Connection) -> Result<u64, Error> {
+    let size: i64 = conn.query_row(
+        "SELECT IFNULL(MAX(external_offset + external_length), 0) AS size FROM marf_data",
+        NO_PARAMS,
+        |row| row.get("size"),
+    )?;
+    Ok(size as u64)
+}
+
+/// Get the identifier, offset, and length of each trie whose blob is kept in the MARF's blob
+/// file, in the order they are laid out in the file.
+pub fn get_external_trie_blobs(conn: &Connection) -> Result<Vec<(u32, u64, u64)>, Error> {
+    let mut s = conn.prepare(
+        "SELECT block_id, external_offset, external_length FROM marf_data WHERE external_length > 0 ORDER BY external_offset",
+    )?;
+    let rows = s.query_and_then(NO_PARAMS, |row| -> Result<(u32, u64, u64), Error> {
+        let offset: i64 = row.get("external_offset")?;
+        let length: i64 = row.get("external_length")?;
+        Ok((row.get("block_id")?, offset as u64, length as u64))
+    })?;
+    rows.collect()
+}
+
+/// Get the identifiers of the confirmed tries whose blobs are kept in the marf_data table.
+pub fn get_internal_block_identifiers(conn: &Connection) -> Result<Vec<u32>, Error> {
+    let mut s = conn.prepare(
+        "SELECT block_id FROM marf_data WHERE unconfirmed = 0 AND length(data) > 0 ORDER BY block_id",
+    )?;
+    let rows = s.query_and_then(NO_PARAMS, |row| -> Result<u32, Error> {
+        Ok(row.get("block_id")?)
+    })?;
+    rows.collect()
+}
+
+pub fn read_trie_blob(conn: &Connection, block_id: u32) -> Result<Vec<u8>, Error> {
+    let mut blob = open_trie_blob(conn, block_id)?;
+    let mut data = Vec::with_capacity(blob.size() as usize);
+    blob.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Move a trie's blob out of the marf_data table, now that it has been appended to the MARF's
+/// blob file.
+pub fn set_external_trie_blob(
+    conn: &Connection,
+    block_id: u32,
+    offset: u64,
+    length: u64,
+) -> Result<(), Error> {
+    let empty: &[u8] = &[];
+    let args: &[&dyn ToSql] = &[
+        &empty,
+        &u64_to_sql(offset)?,
+        &u64_to_sql(length)?,
+        &block_id,
+    ];
+    conn.execute(
+        "UPDATE marf_data SET data = ?, external_offset = ?, external_length = ? WHERE block_id = ?",
+        args,
+    )?;
+    Ok(())
+}
+
 #[cfg(test)]
 pub fn read_all_block_hashes_and_roots<T: MarfTrieId>(
     conn: &Connection,
+    blobs: Option<&TrieFile>,
 ) -> Result<Vec<(TrieHash, T)>, Error> {
-    let mut s = conn.prepare("SELECT block_hash, data FROM marf_data WHERE unconfirmed = 0")?;
+    let mut s = conn.prepare("SELECT block_id, block_hash FROM marf_data WHERE unconfirmed = 0")?;
     let rows = s.query_and_then(NO_PARAMS, |row| {
+        let block_id: u32 = row.get_unwrap("block_id");
         let block_hash: T = row.get_unwrap("block_hash");
-        let data = row
-            .get_raw("data")
-            .as_blob()
-            .expect("DB Corruption: MARF data is non-blob");
-        let start = TrieStorageConnection::<T>::root_ptr_disk() as usize;
-        let trie_hash = TrieHash(read_hash_bytes(&mut &data[start..])?);
+        let root_ptr = TriePtr::new(
+            TrieNodeID::Node256 as u8,
+            0,
+            TrieStorageConnection::<T>::root_ptr_disk(),
+        );
+        let trie_hash = get_node_hash_bytes(conn, blobs, block_id, &root_ptr)?;
         Ok((trie_hash, block_hash))
     })?;
     rows.collect()
 }
 
+/// Read the bytes (hash and node) of a node of a pruned trie. Fails with PrunedError if the
+///  node was not kept when the trie was pruned.
+fn read_pruned_node_bytes(
+    conn: &Connection,
+    block_id: u32,
+    ptr: &TriePtr,
+) -> Result<Vec<u8>, Error> {
+    let args: &[&dyn ToSql] = &[&block_id, &ptr.ptr()];
+    conn.query_row(
+        "SELECT data FROM marf_pruned_nodes WHERE block_id = ? AND ptr = ?",
+        args,
+        |row| row.get("data"),
+    )
+    .optional()?
+    .ok_or(Error::PrunedError)
+}
+
 pub fn read_node_hash_bytes<W: Write>(
     conn: &Connection,
+    blobs: Option<&TrieFile>,
     w: &mut W,
     block_id: u32,
     ptr: &TriePtr,
 ) -> Result<(), Error> {
-    let mut blob = conn.blob_open(
-        rusqlite::DatabaseName::Main,
-        "marf_data",
-        "data",
-        block_id.into(),
-        true,
-    )?;
-    let hash_buff = bits_read_node_hash_bytes(&mut blob, ptr)?;
-    w.write_all(&hash_buff).map_err(|e| e.into())
+    let hash = get_node_hash_bytes(conn, blobs, block_id, ptr)?;
+    w.write_all(hash.as_bytes()).map_err(|e| e.into())
 }
 
 pub fn read_node_hash_bytes_by_bhh<W: Write, T: MarfTrieId>(
     conn: &Connection,
+    blobs: Option<&TrieFile>,
     w: &mut W,
     bhh: &T,
     ptr: &TriePtr,
 ) -> Result<(), Error> {
-    let row_id: i64 = conn.query_row(
-        "SELECT block_id FROM marf_data WHERE block_hash = ?",
-        &[bhh],
-        |r| r.get("block_id"),
-    )?;
-    let mut blob = conn.blob_open(
-        rusqlite::DatabaseName::Main,
-        "marf_data",
-        "data",
-        row_id,
-        true,
-    )?;
-    let hash_buff = bits_read_node_hash_bytes(&mut blob, ptr)?;
-    w.write_all(&hash_buff).map_err(|e| e.into())
+    let block_id = get_block_identifier(conn, bhh)?;
+    read_node_hash_bytes(conn, blobs, w, block_id, ptr)
 }
 
+/// Read a node of a trie from wherever its blob is kept -- the MARF's blob file (if it has one),
+///  the marf_data table, or (if the trie has been pruned) the marf_pruned_nodes table.
 pub fn read_node_type(
     conn: &Connection,
+    blobs: Option<&TrieFile>,
     block_id: u32,
     ptr: &TriePtr,
 ) -> Result<(TrieNodeType, TrieHash), Error> {
-    let mut blob = conn.blob_open(
-        rusqlite::DatabaseName::Main,
-        "marf_data",
-        "data",
-        block_id.into(),
-        true,
-    )?;
+    if let Some(blobs) = blobs {
+        if let Some((offset, length)) = get_external_trie_offset_length(conn, block_id)? {
+            return read_nodetype(&mut blobs.open_trie_blob(offset, length), ptr);
+        }
+    }
+    let mut blob = open_trie_blob(conn, block_id)?;
+    if blob.size() == 0 {
+        let data = read_pruned_node_bytes(conn, block_id, ptr)?;
+        if data.len() <= TRIEHASH_ENCODED_SIZE {
+            // only the hash of this node was kept
+            return Err(Error::PrunedError);
+        }
+        return read_nodetype_at_head(&mut &data[..], ptr.id());
+    }
     read_nodetype(&mut blob, ptr)
 }
 
 pub fn get_node_hash_bytes(
     conn: &Connection,
+    blobs: Option<&TrieFile>,
     block_id: u32,
     ptr: &TriePtr,
 ) -> Result<TrieHash, Error> {
-    let mut blob = conn.blob_open(
-        rusqlite::DatabaseName::Main,
-        "marf_data",
-        "data",
-        block_id.into(),
-        true,
-    )?;
+    if let Some(blobs) = blobs {
+        if let Some((offset, length)) = get_external_trie_offset_length(conn, block_id)? {
+            let hash_buff =
+                bits_read_node_hash_bytes(&mut blobs.open_trie_blob(offset, length), ptr)?;
+            return Ok(TrieHash(hash_buff));
+        }
+    }
+    let mut blob = open_trie_blob(conn, block_id)?;
+    if blob.size() == 0 {
+        let data = read_pruned_node_bytes(conn, block_id, ptr)?;
+        return Ok(TrieHash(read_hash_bytes(&mut &data[..])?));
+    }
     let hash_buff = bits_read_node_hash_bytes(&mut blob, ptr)?;
     Ok(TrieHash(hash_buff))
 }
 
 pub fn get_node_hash_bytes_by_bhh<T: MarfTrieId>(
     conn: &Connection,
+    blobs: Option<&TrieFile>,
     bhh: &T,
     ptr: &TriePtr,
 ) -> Result<TrieHash, Error> {
-    let row_id: i64 = conn.query_row(
-        "SELECT block_id FROM marf_data WHERE block_hash = ?",
-        &[bhh],
-        |r| r.get("block_id"),
+    let block_id = get_block_identifier(conn, bhh)?;
+    get_node_hash_bytes(conn, blobs, block_id, ptr)
+}
+
+/// SQL expression for whether the trie of a marf_data row has been pruned.  A MARF created
+///  before blob files were supported has no external_length column until it is opened for
+///  writing, and none of its tries are in a blob file.
+fn pruned_sql_expr(conn: &Connection) -> Result<&'static str, Error> {
+    if has_external_blob_columns(conn)? {
+        Ok("length(data) = 0 AND external_length = 0")
+    } else {
+        Ok("length(data) = 0")
+    }
+}
+
+/// Has the trie for the given block been pruned? Blocks which are not in the MARF are not
+///  considered pruned.
+pub fn is_block_pruned<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Result<bool, Error> {
+    let sql = format!(
+        "SELECT {} AS pruned FROM marf_data WHERE block_hash = ?",
+        pruned_sql_expr(conn)?
+    );
+    let pruned: Option<bool> = conn
+        .query_row(&sql, &[bhh], |row| row.get("pruned"))
+        .optional()?;
+    Ok(pruned.unwrap_or(false))
+}
+
+/// Get the identifiers of the confirmed tries, and whether each has been pruned.
+pub fn get_confirmed_block_identifiers(conn: &Connection) -> Result<Vec<(u32, bool)>, Error> {
+    let sql = format!(
+        "SELECT block_id, {} AS pruned FROM marf_data WHERE unconfirmed = 0 ORDER BY block_id",
+        pruned_sql_expr(conn)?
+    );
+    let mut s = conn.prepare(&sql)?;
+    let rows = s.query_and_then(NO_PARAMS, |row| -> Result<(u32, bool), Error> {
+        Ok((row.get("block_id")?, row.get("pruned")?))
+    })?;
+    rows.collect()
+}
+
+pub fn get_unconfirmed_block_identifiers(conn: &Connection) -> Result<Vec<u32>, Error> {
+    let mut s = conn.prepare("SELECT block_id FROM marf_data WHERE unconfirmed = 1")?;
+    let rows = s.query_and_then(NO_PARAMS, |row| -> Result<u32, Error> {
+        Ok(row.get("block_id")?)
+    })?;
+    rows.collect()
+}
+
+/// Get the offsets of the nodes kept for a pruned trie.
+pub fn get_pruned_node_ptrs(conn: &Connection, block_id: u32) -> Result<Vec<u32>, Error> {
+    let mut s = conn.prepare("SELECT ptr FROM marf_pruned_nodes WHERE block_id = ?")?;
+    let rows = s.query_and_then(&[block_id], |row| -> Result<u32, Error> {
+        Ok(row.get("ptr")?)
+    })?;
+    rows.collect()
+}
+
+pub fn write_pruned_node(
+    conn: &Connection,
+    block_id: u32,
+    ptr: u32,
+    data: &[u8],
+) -> Result<(), Error> {
+    let args: &[&dyn ToSql] = &[&block_id, &ptr, &data];
+    conn.execute(
+        "INSERT OR REPLACE INTO marf_pruned_nodes (block_id, ptr, data) VALUES (?, ?, ?)",
+        args,
     )?;
-    let mut blob = conn.blob_open(
-        rusqlite::DatabaseName::Main,
-        "marf_data",
-        "data",
-        row_id,
-        true,
+    Ok(())
+}
+
+pub fn drop_pruned_node(conn: &Connection, block_id: u32, ptr: u32) -> Result<(), Error> {
+    let args: &[&dyn ToSql] = &[&block_id, &ptr];
+    conn.execute(
+        "DELETE FROM marf_pruned_nodes WHERE block_id = ? AND ptr = ?",
+        args,
     )?;
-    let hash_buff = bits_read_node_hash_bytes(&mut blob, ptr)?;
-    Ok(TrieHash(hash_buff))
+    Ok(())
+}
+
+/// Empty the blob of a trie whose reachable nodes have been copied to marf_pruned_nodes.  If the
+///  blob is kept in the blob file, its bytes there are left in place until the file is next
+///  compacted.
+pub fn clear_trie_blob(conn: &Connection, block_id: u32) -> Result<(), Error> {
+    let empty: &[u8] = &[];
+    let args: &[&dyn ToSql] = &[&empty, &block_id];
+    conn.execute(
+        "UPDATE marf_data SET data = ?, external_offset = 0, external_length = 0 WHERE block_id = ?",
+        args,
+    )?;
+    debug!("Pruned trie blob at rowid {}", block_id);
+    Ok(())
 }
 
 pub fn tx_lock_bhh_for_extension<T: MarfTrieId>(
@@ -415,5 +707,6 @@ pub fn clear_tables(tx: &Transaction) -> Result<(), Error> {
     tx.execute("DELETE FROM block_extension_locks", NO_PARAMS)?;
     tx.execute("DELETE FROM marf_data", NO_PARAMS)?;
     tx.execute("DELETE FROM mined_blocks", NO_PARAMS)?;
+    tx.execute("DELETE FROM marf_pruned_nodes", NO_PARAMS)?;
     Ok(())
 }
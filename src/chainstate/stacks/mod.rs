@@ -173,6 +173,8 @@ pub enum Error {
     PoxAlreadyLocked,
     PoxInsufficientBalance,
     PoxNoRewardCycle,
+    InvalidSnapshot(String),
+    PrunedStateError(BlockHeaderHash),
 }
 
 impl From<marf_error> for Error {
@@ -228,6 +230,10 @@ impl fmt::Display for Error {
             Error::PoxAlreadyLocked => write!(f, "Account has already locked STX for PoX"),
             Error::PoxInsufficientBalance => write!(f, "Not enough STX to lock"),
             Error::PoxNoRewardCycle => write!(f, "No such reward cycle"),
+            Error::InvalidSnapshot(ref s) => write!(f, "Invalid chainstate snapshot: {}", s),
+            Error::PrunedStateError(ref h) => {
+                write!(f, "Clarity state at block {} has been pruned", h)
+            }
         }
     }
 }
@@ -258,6 +264,8 @@ impl error::Error for Error {
             Error::PoxAlreadyLocked => None,
             Error::PoxInsufficientBalance => None,
             Error::PoxNoRewardCycle => None,
+            Error::InvalidSnapshot(ref _s) => None,
+            Error::PrunedStateError(ref _h) => None,
         }
     }
 }
@@ -288,6 +296,8 @@ impl Error {
             Error::PoxAlreadyLocked => "PoxAlreadyLocked",
             Error::PoxInsufficientBalance => "PoxInsufficientBalance",
             Error::PoxNoRewardCycle => "PoxNoRewardCycle",
+            Error::InvalidSnapshot(ref _s) => "InvalidSnapshot",
+            Error::PrunedStateError(ref _h) => "PrunedStateError",
         }
     }
 
@@ -15,12 +15,13 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use rand::Rng;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::env;
 use std::fs;
 use std::io;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use util::log;
@@ -40,16 +41,21 @@ use util::hash::Sha512Trunc256Sum;
 
 use vm::analysis;
 use vm::analysis::contract_interface_builder::build_contract_interface;
-use vm::analysis::{errors::CheckResult, AnalysisDatabase, ContractAnalysis};
+use vm::analysis::lints::{run_lints, LintConfig, LintRule};
+use vm::analysis::{errors::CheckError, errors::CheckResult, AnalysisDatabase, ContractAnalysis};
 use vm::ast::build_ast;
+use vm::ast::formatter::format_source;
 use vm::contexts::OwnedEnvironment;
-use vm::costs::LimitedCostTracker;
+use vm::costs::profiler::ProfileMetric;
+use vm::costs::{ExecutionCost, LimitedCostTracker};
+use vm::coverage::CoverageReporter;
 use vm::database::{
     ClarityDatabase, HeadersDB, MarfedKV, MemoryBackingStore, STXBalance, SqliteConnection,
     NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
+use vm::debug::Debugger;
 use vm::errors::{Error, InterpreterResult, RuntimeErrorType};
-use vm::types::{PrincipalData, QualifiedContractIdentifier};
+use vm::types::{FunctionType, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData};
 use vm::{execute as vm_execute, SymbolicExpression, SymbolicExpressionType, Value};
 
 use address::c32::c32_address;
@@ -83,14 +89,27 @@ where command is one of:
 
   initialize         to initialize a local VM state database.
   check              to typecheck a potential contract definition.
+  fmt                to rewrite contract definitions in the canonical format. with --check, fails
+                     (without writing) if any of them are not already formatted.
   launch             to launch a initialize a new contract in the local state database.
   eval               to evaluate (in read-only mode) a program in a given contract context.
   eval_at_chaintip   like `eval`, but does not advance to a new block.
   eval_at_block      like `eval_at_chaintip`, but accepts a index-block-hash to evaluate at,
                      must be passed eval string via stdin.
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
-  repl               to typecheck and evaluate expressions in a stdin/stdout loop.
-  execute            to execute a public function of a defined contract.
+  repl               to typecheck and evaluate expressions in a stdin/stdout loop, against chain
+                     state which persists for the session (see `::help`).
+  execute            to execute a public function of a defined contract. with --trace, also prints
+                     the call tree of the execution as JSON.
+  debug              like `execute`, but steps through the call in an interactive debugger
+                     without committing its effects.
+  test               to run the `test-` prefixed public functions of the given `_test` contracts,
+                     each in a new block, against the given contracts. with --coverage, also
+                     writes the line and branch coverage of the run as an LCOV tracefile.
+  profile            like `execute`, but prints the cost of the call, attributed by function and
+                     source line, as folded stacks (e.g., for flamegraph.pl) without committing its effects.
+  map_entries        to list the entries of a data map (or the tokens of an NFT and their owners)
+                     at the chain tip, a page at a time.
   generate_address   to generate a random Stacks public address for testing purposes.
 ",
         invoked_by
@@ -145,6 +164,309 @@ fn run_analysis(
     .map_err(|(e, _)| e)
 }
 
+fn run_analysis_with_recovery(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    analysis_db: &mut AnalysisDatabase,
+    save_contract: bool,
+) -> Result<ContractAnalysis, Vec<CheckError>> {
+    analysis::run_analysis_with_recovery(
+        contract_identifier,
+        expressions,
+        analysis_db,
+        save_contract,
+        LimitedCostTracker::new_free(),
+    )
+    .map_err(|(errors, _)| errors)
+}
+
+/// Run the enabled lints over a type-checked contract, formatting each warning with the
+///  name of its rule (which is what `--allow` takes).
+fn lint_contract(contract_analysis: &ContractAnalysis, lint_config: &LintConfig) -> Vec<String> {
+    run_lints(contract_analysis, lint_config)
+        .iter()
+        .map(|warning| format!("[{}] {}", warning.rule(), &warning.diagnostic))
+        .collect()
+}
+
+/// Contracts deployed by `clarity-cli test` are published by, and their tests are
+///  called by, this principal.
+const CONTRACT_TEST_DEPLOYER: &str = "S1G2081040G2081040G2081040G208105NK8PE5";
+
+/// Contracts whose names end in `_test` are test contracts: each of their public
+///  functions named `test-*` is run as a test case.
+fn is_test_contract(contract_identifier: &QualifiedContractIdentifier) -> bool {
+    contract_identifier.name.as_str().ends_with("_test")
+}
+
+/// Type check and initialize a contract in `marf`, saving its analysis. If `coverage` is
+///  supplied, the contract is registered with it and its initialization is recorded.
+fn deploy_contract(
+    marf: &mut MemoryBackingStore,
+    contract_identifier: &QualifiedContractIdentifier,
+    contract_content: &str,
+    coverage: Option<&mut CoverageReporter>,
+) -> Result<ContractAnalysis, String> {
+    let mut ast = parse(contract_identifier, contract_content)
+        .map_err(|e| format!("Failed to parse {}:\n{}", contract_identifier, e))?;
+    let analysis = {
+        let mut analysis_db = marf.as_analysis_db();
+        run_analysis(contract_identifier, &mut ast, &mut analysis_db, true)
+            .map_err(|e| format!("Type check error in {}:\n{}", contract_identifier, e))?
+    };
+
+    let mut vm_env = OwnedEnvironment::new_cost_limited(
+        false,
+        marf.as_clarity_db(),
+        LimitedCostTracker::new_free(),
+    );
+    if let Some(coverage) = coverage {
+        coverage.register_contract(contract_identifier, &ast);
+        vm_env.add_eval_hook(coverage);
+    }
+    vm_env
+        .initialize_contract(contract_identifier.clone(), contract_content)
+        .map_err(|e| format!("Failed to initialize {}:\n{}", contract_identifier, e))?;
+
+    Ok(analysis)
+}
+
+/// Run a single test function in a new block, rolling back any of its effects (but not
+///  the block) afterwards. A test passes if it returns an `ok` response, and the `ok` value
+///  is returned.
+fn run_contract_test(
+    marf: &mut MemoryBackingStore,
+    sender: &PrincipalData,
+    contract_identifier: &QualifiedContractIdentifier,
+    test_name: &str,
+    coverage: Option<&mut CoverageReporter>,
+) -> Result<Value, String> {
+    // like a transaction in a new block: block-height goes up by one with every test.
+    marf.advance_block_height(1);
+
+    let mut db = marf.as_clarity_db();
+    db.begin();
+    let mut vm_env = OwnedEnvironment::new_cost_limited(false, db, LimitedCostTracker::new_free());
+    if let Some(coverage) = coverage {
+        vm_env.add_eval_hook(coverage);
+    }
+    let result = vm_env.execute_transaction(
+        Value::Principal(sender.clone()),
+        contract_identifier.clone(),
+        test_name,
+        &[],
+    );
+    let (mut db, _) = vm_env
+        .destruct()
+        .expect("Failed to recover database reference after executing test");
+    db.roll_back();
+
+    match result {
+        Ok((Value::Response(data), _, _)) => {
+            if data.committed {
+                Ok(*data.data)
+            } else {
+                Err(format!("returned (err {})", data.data))
+            }
+        }
+        Ok((x, _, _)) => Err(format!("returned a non-response value: {}", x)),
+        Err(error) => Err(format!("runtime error: {}", error)),
+    }
+}
+
+const REPL_HELP: &str = "REPL commands:
+
+  ::deploy <contract-name> <contract-definition.clar>  publish a contract from the current sender.
+  ::set-sender <principal>                            send later transactions from this principal.
+  ::mint-stx <principal> <amount>                     credit a principal with uSTX.
+  ::advance-blocks <count>                            advance the block height.
+  ::get-assets [principal]                            print the STX and fungible token balances of
+                                                      a principal (default: the current sender).
+  ::save <session.db>                                 write the session's chain state to a new file.
+  ::load <session.db>                                 replace the session's chain state with a saved one.
+  ::help                                              print this message.
+
+Any other input is evaluated as a Clarity expression sent by the current sender.";
+
+/// A REPL session evaluates expressions against a MemoryBackingStore which persists
+///  between inputs, so contracts, balances and block height accumulate over the session.
+struct ReplSession {
+    marf: MemoryBackingStore,
+    sender: StandardPrincipalData,
+}
+
+impl ReplSession {
+    fn new() -> ReplSession {
+        ReplSession {
+            marf: MemoryBackingStore::new_simulated_chain(),
+            sender: QualifiedContractIdentifier::transient().issuer,
+        }
+    }
+
+    /// Evaluate a single line of input, returning the output to print.
+    fn handle_input(&mut self, input: &str) -> Result<String, String> {
+        let input = input.trim();
+        if input.starts_with("::") {
+            let words: Vec<_> = input.split_whitespace().collect();
+            self.handle_command(words[0], &words[1..])
+        } else {
+            self.eval(input)
+        }
+    }
+
+    fn handle_command(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match (command, args) {
+            ("::deploy", [contract_name, contract_file]) => {
+                self.deploy(contract_name, contract_file)
+            }
+            ("::set-sender", [sender]) => {
+                self.sender = PrincipalData::parse_standard_principal(sender)
+                    .map_err(|e| format!("Invalid sender: {}", e))?;
+                Ok(format!("tx-sender: {}", self.sender))
+            }
+            ("::mint-stx", [recipient, amount]) => {
+                let recipient = PrincipalData::parse(recipient)
+                    .map_err(|e| format!("Invalid principal: {}", e))?;
+                let amount = amount
+                    .parse::<u128>()
+                    .map_err(|e| format!("Invalid amount: {}", e))?;
+                self.mint_stx(&recipient, amount)
+            }
+            ("::advance-blocks", [count]) => {
+                let count = count
+                    .parse::<u32>()
+                    .map_err(|e| format!("Invalid block count: {}", e))?;
+                self.marf.advance_block_height(count);
+                let block_height = self.marf.as_clarity_db().get_current_block_height();
+                Ok(format!("block-height: {}", block_height))
+            }
+            ("::get-assets", []) => {
+                let owner = PrincipalData::Standard(self.sender.clone());
+                self.get_assets(&owner)
+            }
+            ("::get-assets", [owner]) => {
+                let owner =
+                    PrincipalData::parse(owner).map_err(|e| format!("Invalid principal: {}", e))?;
+                self.get_assets(&owner)
+            }
+            ("::save", [path]) => {
+                self.marf
+                    .save(path)
+                    .map_err(|e| format!("Failed to save session to {}: {}", path, e))?;
+                Ok(format!("Session saved to {}", path))
+            }
+            ("::load", [path]) => {
+                self.marf = MemoryBackingStore::load(path)
+                    .map_err(|e| format!("Failed to load session from {}: {}", path, e))?;
+                Ok(format!("Session loaded from {}", path))
+            }
+            ("::help", []) => Ok(REPL_HELP.to_string()),
+            _ => Err(format!(
+                "Unknown command or wrong number of arguments: {}\n{}",
+                command, REPL_HELP
+            )),
+        }
+    }
+
+    fn eval(&mut self, content: &str) -> Result<String, String> {
+        let contract_id = QualifiedContractIdentifier::transient();
+        let mut ast = parse(&contract_id, content).map_err(|e| format!("Parse error:\n{}", e))?;
+        {
+            let mut analysis_db = self.marf.as_analysis_db();
+            run_analysis(&contract_id, &mut ast, &mut analysis_db, false)
+                .map_err(|e| format!("Type check error:\n{}", e))?;
+        }
+
+        let mut vm_env = OwnedEnvironment::new_cost_limited(
+            false,
+            self.marf.as_clarity_db(),
+            LimitedCostTracker::new_free(),
+        );
+        let (result, _, _) = vm_env
+            .execute_in_env(Value::from(self.sender.clone()), |exec_env| {
+                exec_env.eval_raw(content)
+            })
+            .map_err(|e: Error| format!("Execution error:\n{}", e))?;
+        Ok(result.to_string())
+    }
+
+    fn deploy(&mut self, contract_name: &str, contract_file: &str) -> Result<String, String> {
+        let contract_identifier =
+            QualifiedContractIdentifier::parse(&format!("{}.{}", self.sender, contract_name))
+                .map_err(|e| format!("Invalid contract name: {}", e))?;
+        let contract_content = fs::read_to_string(contract_file)
+            .map_err(|e| format!("Error reading file {}: {}", contract_file, e))?;
+
+        deploy_contract(
+            &mut self.marf,
+            &contract_identifier,
+            &contract_content,
+            None,
+        )?;
+        Ok(format!("Deployed {}", contract_identifier))
+    }
+
+    fn mint_stx(&mut self, recipient: &PrincipalData, amount: u128) -> Result<String, String> {
+        let mut db = self.marf.as_clarity_db();
+        db.begin();
+        // the liquid supply bounds every balance, so crediting can't overflow once it's incremented.
+        if let Err(e) = db.increment_ustx_liquid_supply(amount) {
+            db.roll_back();
+            return Err(format!("Failed to mint {} uSTX: {}", amount, e));
+        }
+        let mut snapshot = db.get_stx_balance_snapshot(recipient);
+        snapshot.credit(amount);
+        let balance = snapshot.get_available_balance();
+        snapshot.save();
+        db.commit();
+
+        Ok(format!("{} balance: {} uSTX", recipient, balance))
+    }
+
+    fn get_assets(&mut self, owner: &PrincipalData) -> Result<String, String> {
+        let contract_identifiers = self
+            .marf
+            .get_contract_identifiers()
+            .map_err(|e| format!("Failed to list contracts: {}", e))?;
+        let mut fungible_tokens = vec![];
+        {
+            let mut analysis_db = self.marf.as_analysis_db();
+            for contract_identifier in contract_identifiers.into_iter() {
+                if let Some(analysis) = analysis_db.load_contract(&contract_identifier) {
+                    for token_name in analysis.fungible_tokens.iter() {
+                        fungible_tokens.push((contract_identifier.clone(), token_name.clone()));
+                    }
+                }
+            }
+        }
+
+        let mut db = self.marf.as_clarity_db();
+        db.begin();
+        let mut output = vec![format!(
+            "STX: {}",
+            db.get_stx_balance_snapshot(owner).get_available_balance()
+        )];
+        for (contract_identifier, token_name) in fungible_tokens.iter() {
+            match db.get_ft_balance(contract_identifier, token_name.as_str(), owner, None) {
+                Ok(balance) => output.push(format!(
+                    "{}::{}: {}",
+                    contract_identifier, token_name, balance
+                )),
+                Err(e) => {
+                    db.roll_back();
+                    return Err(format!(
+                        "Failed to read {}::{} balance: {}",
+                        contract_identifier, token_name, e
+                    ));
+                }
+            }
+        }
+        db.roll_back();
+
+        Ok(output.join("\n"))
+    }
+}
+
 fn create_or_open_db(path: &String) -> Connection {
     let open_flags = match fs::metadata(path) {
         Err(e) => {
@@ -269,6 +591,17 @@ where
 
 // like in_block, but does _not_ advance the chain tip.  Used for read-only queries against the
 // chain tip itself.
+/// Open the VM state in `vm_filename`, indexing the keys of data map entries and NFTs so that
+///   their entries can be listed.
+fn open_vm_state(vm_filename: &str) -> MarfedKV {
+    let mut marf_kv = friendly_expect(
+        MarfedKV::open(vm_filename, None),
+        "Failed to open VM database.",
+    );
+    friendly_expect(marf_kv.set_key_index(true), "Failed to open VM database.");
+    marf_kv
+}
+
 fn at_chaintip<F, R>(db_path: &String, mut marf_kv: MarfedKV, f: F) -> R
 where
     F: FnOnce(WritableMarfStore) -> (WritableMarfStore, R),
@@ -397,6 +730,24 @@ impl HeadersDB for CLIHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_miner_reward_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128> {
+        // mock it
+        let conn = self.open();
+        if let Some(_) = get_cli_block_height(&conn, id_bhh) {
+            Some(1000)
+        } else {
+            None
+        }
+    }
+    fn get_total_fees_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128> {
+        // mock it
+        let conn = self.open();
+        if let Some(_) = get_cli_block_height(&conn, id_bhh) {
+            Some(0)
+        } else {
+            None
+        }
+    }
 }
 
 fn get_eval_input(invoked_by: &str, args: &[String]) -> EvalInput {
@@ -431,10 +782,7 @@ fn get_eval_input(invoked_by: &str, args: &[String]) -> EvalInput {
         "Failed to parse contract identifier.",
     );
 
-    let marf_kv = friendly_expect(
-        MarfedKV::open(vm_filename, None),
-        "Failed to open VM database.",
-    );
+    let marf_kv = open_vm_state(vm_filename);
     // return (marf_kv, contract_identifier, vm_filename, content);
     return EvalInput {
         marf_kv,
@@ -500,8 +848,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 panic_test!();
             };
 
-            let marf_kv =
-                friendly_expect(MarfedKV::open(db_name, None), "Failed to open VM database.");
+            let marf_kv = open_vm_state(db_name);
             let header_db = CLIHeadersDB::new(&db_name);
             in_block(db_name, marf_kv, |mut kv| {
                 {
@@ -532,10 +879,90 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 friendly_expect(c32_address(22, &random_bytes), "Failed to generate address");
             println!("{}", addr);
         }
+        "fmt" => {
+            let mut args = args.to_vec();
+            let check_only = match args.iter().position(|arg| arg == "--check") {
+                Some(ix) => {
+                    args.remove(ix);
+                    true
+                }
+                None => false,
+            };
+            if args.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [--check] [program-file.clar ...]",
+                    invoked_by, args[0]
+                );
+                eprintln!("   pass `-` to format stdin to stdout.");
+                panic_test!();
+            }
+
+            let mut unformatted = vec![];
+            for program_file in args[1..].iter() {
+                let content: String = if program_file == "-" {
+                    let mut buffer = String::new();
+                    friendly_expect(
+                        io::stdin().read_to_string(&mut buffer),
+                        "Error reading from stdin.",
+                    );
+                    buffer
+                } else {
+                    friendly_expect(
+                        fs::read_to_string(program_file),
+                        &format!("Error reading file: {}", program_file),
+                    )
+                };
+
+                let formatted = friendly_expect(
+                    format_source(&content),
+                    &format!("Failed to parse program: {}", program_file),
+                );
+
+                if check_only {
+                    if formatted != content {
+                        unformatted.push(program_file.clone());
+                    }
+                } else if program_file == "-" {
+                    print!("{}", formatted);
+                } else if formatted != content {
+                    friendly_expect(
+                        fs::write(program_file, formatted),
+                        &format!("Error writing file: {}", program_file),
+                    );
+                }
+            }
+
+            if !unformatted.is_empty() {
+                for program_file in unformatted.iter() {
+                    println!("{} is not formatted", program_file);
+                }
+                panic_test!();
+            }
+        }
         "check" => {
+            let mut args = args.to_vec();
+            let lint_config = match args.iter().position(|arg| arg == "--lint") {
+                Some(ix) => {
+                    args.remove(ix);
+                    let mut lint_config = LintConfig::new();
+                    while let Some(ix) = args.iter().position(|arg| arg == "--allow") {
+                        if ix + 1 >= args.len() {
+                            break;
+                        }
+                        let rule_name = args.remove(ix + 1);
+                        args.remove(ix);
+                        lint_config.allow(friendly_expect_opt(
+                            LintRule::lookup_by_name(&rule_name),
+                            &format!("Unknown lint rule: {}", rule_name),
+                        ));
+                    }
+                    Some(lint_config)
+                }
+                None => None,
+            };
             if args.len() < 2 {
                 eprintln!(
-                    "Usage: {} {} [program-file.clar] (vm-state.db)",
+                    "Usage: {} {} [--lint [--allow lint-rule]...] [program-file.clar] (vm-state.db)",
                     invoked_by, args[0]
                 );
                 panic_test!();
@@ -559,17 +986,15 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
 
             let mut ast = friendly_expect(parse(&contract_id, &content), "Failed to parse program");
 
+            // report every error in the program, not only the first.
             let contract_analysis = {
                 if args.len() >= 3 {
                     // use a persisted marf
-                    let marf_kv = friendly_expect(
-                        MarfedKV::open(&args[2], None),
-                        "Failed to open VM database.",
-                    );
+                    let marf_kv = open_vm_state(&args[2]);
                     let result = at_chaintip(&args[2], marf_kv, |mut marf| {
                         let result = {
                             let mut db = marf.as_analysis_db();
-                            run_analysis(&contract_id, &mut ast, &mut db, false)
+                            run_analysis_with_recovery(&contract_id, &mut ast, &mut db, false)
                         };
                         (marf, result)
                     });
@@ -577,14 +1002,22 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 } else {
                     let mut analysis_marf = MemoryBackingStore::new();
                     let mut db = analysis_marf.as_analysis_db();
-                    run_analysis(&contract_id, &mut ast, &mut db, false)
+                    run_analysis_with_recovery(&contract_id, &mut ast, &mut db, false)
                 }
             }
-            .unwrap_or_else(|e| {
-                println!("{}", &e.diagnostic);
+            .unwrap_or_else(|errors| {
+                for e in errors.iter() {
+                    println!("{}", &e.diagnostic);
+                }
                 panic_test!();
             });
 
+            if let Some(lint_config) = lint_config {
+                for warning in lint_contract(&contract_analysis, &lint_config).iter() {
+                    println!("{}", warning);
+                }
+            }
+
             match args.last() {
                 Some(s) if s == "--output_analysis" => {
                     println!(
@@ -598,19 +1031,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
             }
         }
         "repl" => {
-            let mut marf = MemoryBackingStore::new();
-            let mut vm_env = OwnedEnvironment::new_cost_limited(
-                false,
-                marf.as_clarity_db(),
-                LimitedCostTracker::new_free(),
-            );
-            let mut exec_env = vm_env.get_exec_environment(None);
-
-            let mut analysis_marf = MemoryBackingStore::new();
-            let mut analysis_db = analysis_marf.as_analysis_db();
-
-            let contract_id = QualifiedContractIdentifier::transient();
-
+            let mut session = ReplSession::new();
             let mut stdout = io::stdout();
 
             loop {
@@ -623,6 +1044,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                         panic!("Failed to flush stdout prompt string:\n{}", e);
                     });
                     match io::stdin().read_line(&mut buffer) {
+                        Ok(0) => break,
                         Ok(_) => buffer,
                         Err(error) => {
                             eprintln!("Error reading from stdin:\n{}", error);
@@ -631,31 +1053,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                     }
                 };
 
-                let mut ast = match parse(&contract_id, &content) {
-                    Ok(val) => val,
-                    Err(error) => {
-                        println!("Parse error:\n{}", error);
-                        continue;
-                    }
-                };
-
-                match run_analysis(&contract_id, &mut ast, &mut analysis_db, true) {
-                    Ok(_) => (),
-                    Err(error) => {
-                        println!("Type check error:\n{}", error);
-                        continue;
-                    }
+                if content.trim().is_empty() {
+                    continue;
                 }
 
-                let eval_result = match exec_env.eval_raw(&content) {
-                    Ok(val) => val,
-                    Err(error) => {
-                        println!("Execution error:\n{}", error);
-                        continue;
-                    }
-                };
-
-                println!("{}", eval_result);
+                match session.handle_input(&content) {
+                    Ok(output) => println!("{}", output),
+                    Err(error) => println!("{}", error),
+                }
             }
         }
         "eval_raw" => {
@@ -704,10 +1109,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
         "eval" => {
             let evalInput = get_eval_input(invoked_by, args);
             let vm_filename = if args.len() == 3 { &args[2] } else { &args[3] };
-            let marf_kv = friendly_expect(
-                MarfedKV::open(vm_filename, None),
-                "Failed to open VM database.",
-            );
+            let marf_kv = open_vm_state(vm_filename);
             let header_db = CLIHeadersDB::new(&vm_filename);
             let result = in_block(vm_filename, marf_kv, |mut marf| {
                 let result = {
@@ -737,10 +1139,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
         "eval_at_chaintip" => {
             let evalInput = get_eval_input(invoked_by, args);
             let vm_filename = if args.len() == 3 { &args[2] } else { &args[3] };
-            let marf_kv = friendly_expect(
-                MarfedKV::open(vm_filename, None),
-                "Failed to open VM database.",
-            );
+            let marf_kv = open_vm_state(vm_filename);
             let header_db = CLIHeadersDB::new(&vm_filename);
             let result = at_chaintip(vm_filename, marf_kv, |mut marf| {
                 let result = {
@@ -790,10 +1189,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
             };
 
             let vm_filename = &args[3];
-            let marf_kv = friendly_expect(
-                MarfedKV::open(vm_filename, None),
-                "Failed to open VM database.",
-            );
+            let marf_kv = open_vm_state(vm_filename);
             let header_db = CLIHeadersDB::new(&vm_filename);
             let result = at_block(chain_tip, marf_kv, |mut marf| {
                 let result = {
@@ -820,6 +1216,79 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 }
             }
         }
+        "map_entries" => {
+            let mut args = args.to_vec();
+            let after = match args.iter().position(|arg| arg == "--after") {
+                Some(ix) if ix + 1 < args.len() => {
+                    let after = args.remove(ix + 1);
+                    args.remove(ix);
+                    if after.starts_with("0x") {
+                        Some(after[2..].to_string())
+                    } else {
+                        Some(after)
+                    }
+                }
+                _ => None,
+            };
+            let limit = match args.iter().position(|arg| arg == "--limit") {
+                Some(ix) if ix + 1 < args.len() => {
+                    let limit = args.remove(ix + 1);
+                    args.remove(ix);
+                    friendly_expect(
+                        limit.parse::<u32>(),
+                        &format!("Failed to parse page limit: {}", limit),
+                    )
+                }
+                _ => 50,
+            };
+            if args.len() != 4 {
+                eprintln!(
+                    "Usage: {} {} [--after cursor] [--limit n] [vm-state.db] [contract-identifier] [map-or-nft-name]",
+                    invoked_by, args[0]
+                );
+                panic_test!();
+            }
+            let vm_filename = &args[1];
+            let marf_kv = open_vm_state(vm_filename);
+            let header_db = CLIHeadersDB::new(&vm_filename);
+
+            let contract_identifier = friendly_expect(
+                QualifiedContractIdentifier::parse(&args[2]),
+                "Failed to parse contract identifier.",
+            );
+            let name = &args[3];
+
+            let result = at_chaintip(vm_filename, marf_kv, |mut marf| {
+                let result = {
+                    let mut db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
+                    db.begin();
+                    let result = db.list_map_or_nft_entries(
+                        &contract_identifier,
+                        name,
+                        after.as_ref().map(|x| x.as_str()),
+                        limit,
+                    );
+                    db.roll_back();
+                    result
+                };
+                (marf, result)
+            });
+
+            match result {
+                Ok((entries, next)) => {
+                    for (key, value) in entries.iter() {
+                        println!("{} => {}", key, value);
+                    }
+                    if let Some(next) = next {
+                        eprintln!("More entries may follow: pass `--after 0x{}`", next);
+                    }
+                }
+                Err(error) => {
+                    eprintln!("Failed to list entries: \n{}", error);
+                    panic_test!();
+                }
+            }
+        }
         "launch" => {
             if args.len() < 4 {
                 eprintln!(
@@ -844,10 +1313,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 parse(&contract_identifier, &contract_content),
                 "Failed to parse program.",
             );
-            let marf_kv = friendly_expect(
-                MarfedKV::open(vm_filename, None),
-                "Failed to open VM database.",
-            );
+            let marf_kv = open_vm_state(vm_filename);
             let header_db = CLIHeadersDB::new(&vm_filename);
             let result = in_block(vm_filename, marf_kv, |mut marf| {
                 let analysis_result = {
@@ -896,15 +1362,18 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
             }
         }
         "execute" => {
+            let trace = args.iter().any(|arg| arg == "--trace");
+            let args: Vec<_> = args
+                .iter()
+                .filter(|arg| *arg != "--trace")
+                .cloned()
+                .collect();
             if args.len() < 5 {
-                eprintln!("Usage: {} {} [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, args[0]);
+                eprintln!("Usage: {} {} [--trace] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, args[0]);
                 panic_test!();
             }
             let vm_filename = &args[1];
-            let marf_kv = friendly_expect(
-                MarfedKV::open(vm_filename, None),
-                "Failed to open VM database.",
-            );
+            let marf_kv = open_vm_state(vm_filename);
             let header_db = CLIHeadersDB::new(&vm_filename);
 
             let contract_identifier = friendly_expect(
@@ -947,16 +1416,25 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                         db,
                         LimitedCostTracker::new_free(),
                     );
-                    vm_env.execute_transaction(
+                    if trace {
+                        vm_env.enable_tracing();
+                    }
+                    let result = vm_env.execute_transaction(
                         Value::Principal(sender),
                         contract_identifier,
                         &tx_name,
                         &arguments,
-                    )
+                    );
+                    (result, vm_env.take_trace())
                 };
                 (marf, result)
             });
 
+            let (result, call_trace) = result;
+            if let Some(call_trace) = call_trace {
+                println!("{}", call_trace.json_serialize());
+            }
+
             match result {
                 Ok((x, _, events)) => {
                     if let Value::Response(data) = x {
@@ -981,6 +1459,319 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 }
             }
         }
+        "debug" => {
+            if args.len() < 5 {
+                eprintln!("Usage: {} {} [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, args[0]);
+                panic_test!();
+            }
+            let vm_filename = &args[1];
+            let marf_kv = open_vm_state(vm_filename);
+            let header_db = CLIHeadersDB::new(&vm_filename);
+
+            let contract_identifier = friendly_expect(
+                QualifiedContractIdentifier::parse(&args[2]),
+                "Failed to parse contract identifier.",
+            );
+
+            let tx_name = &args[3];
+            let sender_in = &args[4];
+
+            let sender = {
+                if let Ok(sender) = PrincipalData::parse_standard_principal(sender_in) {
+                    PrincipalData::Standard(sender)
+                } else {
+                    eprintln!("Unexpected result parsing sender: {}", sender_in);
+                    panic_test!();
+                }
+            };
+
+            let arguments: Vec<_> = args[5..]
+                .iter()
+                .map(|argument| {
+                    let argument_parsed = friendly_expect(
+                        vm_execute(argument),
+                        &format!("Error parsing argument \"{}\"", argument),
+                    );
+                    let argument_value = friendly_expect_opt(
+                        argument_parsed,
+                        &format!("Failed to parse a value from the argument: {}", argument),
+                    );
+                    SymbolicExpression::atom_value(argument_value)
+                })
+                .collect();
+
+            println!(
+                "Debugging {}.{}. Type `help` for a list of commands.",
+                contract_identifier, tx_name
+            );
+            let mut debugger = Debugger::new();
+
+            // the debugged call is evaluated at the chain tip, and never committed.
+            let result = at_chaintip(vm_filename, marf_kv, |mut marf| {
+                let result = {
+                    let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
+                    let mut vm_env = OwnedEnvironment::new_cost_limited(
+                        false,
+                        db,
+                        LimitedCostTracker::new_free(),
+                    );
+                    vm_env.add_eval_hook(&mut debugger);
+                    vm_env.execute_transaction(
+                        Value::Principal(sender),
+                        contract_identifier,
+                        &tx_name,
+                        &arguments,
+                    )
+                };
+                (marf, result)
+            });
+
+            match result {
+                Ok((x, asset_map, events)) => {
+                    println!("Returned: {}\n{}\n{:?}", x, asset_map, events);
+                }
+                Err(error) => {
+                    eprintln!("Transaction execution error: \n{}", error);
+                    panic_test!();
+                }
+            }
+        }
+        "profile" => {
+            let mut args = args.to_vec();
+            let metric = match args.iter().position(|arg| arg == "--metric") {
+                Some(ix) if ix + 1 < args.len() => {
+                    let metric_name = args.remove(ix + 1);
+                    args.remove(ix);
+                    friendly_expect_opt(
+                        ProfileMetric::parse(&metric_name),
+                        &format!("Unknown profile metric: {}", metric_name),
+                    )
+                }
+                _ => ProfileMetric::Runtime,
+            };
+            if args.len() < 5 {
+                eprintln!("Usage: {} {} [--metric runtime|read_count|read_length|write_count|write_length] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, args[0]);
+                panic_test!();
+            }
+            let vm_filename = &args[1];
+            let marf_kv = open_vm_state(vm_filename);
+            let header_db = CLIHeadersDB::new(&vm_filename);
+
+            let contract_identifier = friendly_expect(
+                QualifiedContractIdentifier::parse(&args[2]),
+                "Failed to parse contract identifier.",
+            );
+
+            let tx_name = &args[3];
+            let sender_in = &args[4];
+
+            let sender = {
+                if let Ok(sender) = PrincipalData::parse_standard_principal(sender_in) {
+                    PrincipalData::Standard(sender)
+                } else {
+                    eprintln!("Unexpected result parsing sender: {}", sender_in);
+                    panic_test!();
+                }
+            };
+
+            let arguments: Vec<_> = args[5..]
+                .iter()
+                .map(|argument| {
+                    let argument_parsed = friendly_expect(
+                        vm_execute(argument),
+                        &format!("Error parsing argument \"{}\"", argument),
+                    );
+                    let argument_value = friendly_expect_opt(
+                        argument_parsed,
+                        &format!("Failed to parse a value from the argument: {}", argument),
+                    );
+                    SymbolicExpression::atom_value(argument_value)
+                })
+                .collect();
+
+            let cost_tracker =
+                match LimitedCostTracker::new_with_boot_costs(false, ExecutionCost::max_value()) {
+                    Ok(cost_tracker) => cost_tracker,
+                    Err(e) => {
+                        eprintln!("Failed to load the boot code cost functions: {:?}", e);
+                        panic_test!();
+                    }
+                };
+
+            // the profiled call is evaluated at the chain tip, and never committed.
+            let (result, profile, total_cost) = at_chaintip(vm_filename, marf_kv, |mut marf| {
+                let result = {
+                    let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
+                    let mut vm_env = OwnedEnvironment::new_cost_limited(false, db, cost_tracker);
+                    vm_env.enable_profiling();
+                    let result = vm_env.execute_transaction(
+                        Value::Principal(sender),
+                        contract_identifier,
+                        &tx_name,
+                        &arguments,
+                    );
+                    let profile = vm_env
+                        .take_profile()
+                        .expect("BUG: profiling was disabled during the call");
+                    let total_cost = vm_env
+                        .destruct()
+                        .map(|(_, cost_tracker)| cost_tracker.get_total());
+                    (result, profile, total_cost)
+                };
+                (marf, result)
+            });
+
+            friendly_expect(
+                profile.write_folded(metric, &mut io::stdout()),
+                "Failed to write profile.",
+            );
+            if let Some(total_cost) = total_cost {
+                eprintln!(
+                    "Total cost: {}",
+                    serde_json::to_string(&total_cost).unwrap()
+                );
+            }
+
+            match result {
+                Ok((x, _, _)) => {
+                    eprintln!("Returned: {}", x);
+                }
+                Err(error) => {
+                    eprintln!("Transaction execution error: \n{}", error);
+                    panic_test!();
+                }
+            }
+        }
+        "test" => {
+            let mut args = args.to_vec();
+            let coverage_file = match args.iter().position(|arg| arg == "--coverage") {
+                Some(ix) if ix + 1 < args.len() => {
+                    let coverage_file = args.remove(ix + 1);
+                    args.remove(ix);
+                    Some(coverage_file)
+                }
+                _ => None,
+            };
+            if args.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [--coverage lcov.info] [contract-definition.clar ...]",
+                    invoked_by, args[0]
+                );
+                panic_test!();
+            }
+
+            let sender = friendly_expect(
+                PrincipalData::parse(CONTRACT_TEST_DEPLOYER),
+                "Failed to parse test deployer address.",
+            );
+
+            let mut marf = MemoryBackingStore::new_simulated_chain();
+            let mut tests = vec![];
+            let mut coverage = coverage_file.as_ref().map(|_| CoverageReporter::new());
+            let mut source_paths = HashMap::new();
+
+            // contracts are deployed in the order given, so dependencies must come first.
+            for contract_file in args[1..].iter() {
+                let contract_name = friendly_expect_opt(
+                    Path::new(contract_file)
+                        .file_stem()
+                        .and_then(|stem| stem.to_str()),
+                    &format!("Failed to get a contract name from: {}", contract_file),
+                );
+                let contract_identifier = friendly_expect(
+                    QualifiedContractIdentifier::parse(&format!(
+                        "{}.{}",
+                        CONTRACT_TEST_DEPLOYER, contract_name
+                    )),
+                    &format!("Invalid contract name: {}", contract_name),
+                );
+                let contract_content = friendly_expect(
+                    fs::read_to_string(contract_file),
+                    &format!("Error reading file: {}", contract_file),
+                );
+
+                let analysis = friendly_expect(
+                    deploy_contract(
+                        &mut marf,
+                        &contract_identifier,
+                        &contract_content,
+                        coverage.as_mut(),
+                    ),
+                    "Failed to deploy contract.",
+                );
+                source_paths.insert(contract_identifier.clone(), contract_file.clone());
+
+                if is_test_contract(&contract_identifier) {
+                    for (function_name, function_type) in analysis.public_function_types.iter() {
+                        if !function_name.as_str().starts_with("test-") {
+                            continue;
+                        }
+                        let takes_args = match function_type {
+                            FunctionType::Fixed(function) => !function.args.is_empty(),
+                            _ => true,
+                        };
+                        tests.push((
+                            contract_identifier.clone(),
+                            function_name.clone(),
+                            takes_args,
+                        ));
+                    }
+                }
+            }
+
+            let mut passed = 0;
+            let mut failed = 0;
+            println!("running {} tests", tests.len());
+            for (contract_identifier, test_name, takes_args) in tests.iter() {
+                let result = if *takes_args {
+                    Err("test functions may not take arguments".to_string())
+                } else {
+                    run_contract_test(
+                        &mut marf,
+                        &sender,
+                        contract_identifier,
+                        test_name,
+                        coverage.as_mut(),
+                    )
+                };
+                match result {
+                    Ok(_) => {
+                        println!("test {}::{} ... ok", contract_identifier, test_name);
+                        passed += 1;
+                    }
+                    Err(reason) => {
+                        println!(
+                            "test {}::{} ... FAILED: {}",
+                            contract_identifier, test_name, reason
+                        );
+                        failed += 1;
+                    }
+                }
+            }
+
+            println!(
+                "\ntest result: {}. {} passed; {} failed",
+                if failed == 0 { "ok" } else { "FAILED" },
+                passed,
+                failed
+            );
+
+            if let (Some(coverage_file), Some(coverage)) = (coverage_file, coverage) {
+                let mut lcov = vec![];
+                friendly_expect(
+                    coverage.write_lcov("", &source_paths, &mut lcov),
+                    "Failed to serialize coverage report.",
+                );
+                friendly_expect(
+                    fs::write(&coverage_file, lcov),
+                    &format!("Failed to write coverage report: {}", coverage_file),
+                );
+                println!("coverage report written to {}", coverage_file);
+            }
+            if failed > 0 {
+                panic_test!();
+            }
+        }
         _ => print_usage(invoked_by),
     }
 }
@@ -1026,6 +1817,248 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_contract_tests() {
+        let test_dir = format!("/tmp/contract-tests_{}", rand::thread_rng().gen::<u32>());
+        fs::create_dir_all(&test_dir).unwrap();
+        let counter_name = format!("{}/counter.clar", test_dir);
+        let counter_test_name = format!("{}/counter_test.clar", test_dir);
+
+        fs::write(
+            &counter_name,
+            r#"
+(define-data-var count uint u0)
+(define-public (increment)
+  (begin
+    (var-set count (+ (var-get count) u1))
+    (ok (var-get count))))
+(define-read-only (get-count)
+  (var-get count))
+"#,
+        )
+        .unwrap();
+
+        fs::write(
+            &counter_test_name,
+            r#"
+(define-public (test-a-increment)
+  (begin
+    (unwrap! (contract-call? .counter increment) (err u1))
+    (asserts! (is-eq (contract-call? .counter get-count) u1) (err u2))
+    (ok true)))
+(define-public (test-b-isolated)
+  (if (is-eq (contract-call? .counter get-count) u0) (ok true) (err u3)))
+(define-public (test-c-block-height)
+  (if (is-eq block-height u3) (ok true) (err block-height)))
+"#,
+        )
+        .unwrap();
+
+        let lcov_name = format!("{}/lcov.info", test_dir);
+        invoke_command(
+            "test",
+            &[
+                "test".to_string(),
+                "--coverage".to_string(),
+                lcov_name.clone(),
+                counter_name.clone(),
+                counter_test_name.clone(),
+            ],
+        );
+
+        let lcov = fs::read_to_string(&lcov_name).unwrap();
+        assert!(lcov.contains(&format!("SF:{}\n", counter_name)));
+        assert!(lcov.contains(&format!("SF:{}\n", counter_test_name)));
+        // `get-count` is called by two tests, `increment` only by the first.
+        assert!(lcov.contains("\nDA:4,1\n"));
+        assert!(lcov.contains("\nDA:8,2\n"));
+        // the `if` in test-b-isolated only ever took its first branch.
+        let if_branches: Vec<_> = lcov
+            .lines()
+            .filter(|line| line.starts_with("BRDA:8,"))
+            .collect();
+        assert_eq!(if_branches.len(), 2);
+        assert!(if_branches[0].ends_with(",0,1"));
+        assert!(if_branches[1].ends_with(",1,0"));
+
+        let mut marf = MemoryBackingStore::new_simulated_chain();
+        let counter_test_id =
+            QualifiedContractIdentifier::parse(&format!("{}.counter_test", CONTRACT_TEST_DEPLOYER))
+                .unwrap();
+        let sender = PrincipalData::parse(CONTRACT_TEST_DEPLOYER).unwrap();
+        deploy_contract(
+            &mut marf,
+            &counter_test_id,
+            "(define-public (test-fails) (err u7))
+             (define-public (test-height) (ok block-height))",
+            None,
+        )
+        .unwrap();
+        // test N runs at block-height = (the height the contracts were deployed at) + N.
+        let base_height = marf.as_clarity_db().get_current_block_height() as u128;
+        assert_eq!(
+            run_contract_test(&mut marf, &sender, &counter_test_id, "test-fails", None),
+            Err("returned (err u7)".to_string())
+        );
+        for n in 2..5 {
+            assert_eq!(
+                run_contract_test(&mut marf, &sender, &counter_test_id, "test-height", None),
+                Ok(Value::UInt(base_height + n))
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_lint() {
+        let program = "(define-constant unused-limit u10)\n(define-public (withdraw (amount uint)) (begin (asserts! (is-eq tx-sender contract-caller) (err u1)) (ok amount)))";
+        let program_name = format!("/tmp/lint_{}.clar", rand::thread_rng().gen::<u32>());
+        fs::write(&program_name, program).unwrap();
+
+        let contract_id = QualifiedContractIdentifier::transient();
+        let mut ast = parse(&contract_id, program).unwrap();
+        let contract_analysis = {
+            let mut marf = MemoryBackingStore::new();
+            let mut db = marf.as_analysis_db();
+            run_analysis(&contract_id, &mut ast, &mut db, false).unwrap()
+        };
+        assert_eq!(
+            lint_contract(&contract_analysis, &LintConfig::new()),
+            vec![
+                "[unused-constant] Warning (line 1, column 1): constant 'unused-limit' is never used.\nremove it, or use it\n".to_string(),
+                "[tx-sender-authorization] Warning (line 2, column 58): public function 'withdraw' authorizes the caller by comparing tx-sender.\nany contract the sender calls can act as tx-sender; compare contract-caller instead\n".to_string(),
+            ]
+        );
+        let mut lint_config = LintConfig::new();
+        lint_config.allow(LintRule::UnusedConstant);
+        lint_config.allow(LintRule::TxSenderAuthorization);
+        assert!(lint_contract(&contract_analysis, &lint_config).is_empty());
+
+        invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--lint".to_string(),
+                program_name.clone(),
+            ],
+        );
+        invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--lint".to_string(),
+                "--allow".to_string(),
+                "unused-constant".to_string(),
+                "--allow".to_string(),
+                "tx-sender-authorization".to_string(),
+                program_name,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_fmt() {
+        let program_name = format!("/tmp/fmt_{}.clar", rand::thread_rng().gen::<u32>());
+        fs::write(
+            &program_name,
+            ";; a counter\n(define-data-var   counter uint u0)\n\n\n(define-read-only (get-counter)\n  (var-get counter))",
+        )
+        .unwrap();
+
+        invoke_command("test", &["fmt".to_string(), program_name.clone()]);
+        assert_eq!(
+            fs::read_to_string(&program_name).unwrap(),
+            ";; a counter\n(define-data-var counter uint u0)\n\n(define-read-only (get-counter) (var-get counter))\n"
+        );
+
+        invoke_command(
+            "test",
+            &["fmt".to_string(), "--check".to_string(), program_name],
+        );
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let test_dir = format!("/tmp/repl-session_{}", rand::thread_rng().gen::<u32>());
+        fs::create_dir_all(&test_dir).unwrap();
+        let token_name = format!("{}/token.clar", test_dir);
+        let session_name = format!("{}/session.db", test_dir);
+        fs::write(
+            &token_name,
+            "(define-fungible-token coin)
+             (define-public (mint (amount uint))
+               (ft-mint? coin amount tx-sender))",
+        )
+        .unwrap();
+
+        let alice = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+        let bob = "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG";
+
+        let mut session = ReplSession::new();
+        assert_eq!(session.handle_input("(+ 1 2)"), Ok("3".to_string()));
+        assert!(session
+            .handle_input("(+ 1 u2)")
+            .unwrap_err()
+            .starts_with("Type check error"));
+        assert!(session.handle_input("::bogus").is_err());
+
+        assert_eq!(
+            session.handle_input(&format!("::set-sender {}", alice)),
+            Ok(format!("tx-sender: {}", alice))
+        );
+        assert_eq!(
+            session.handle_input(&format!("::deploy token {}", token_name)),
+            Ok(format!("Deployed {}.token", alice))
+        );
+        assert_eq!(
+            session.handle_input(&format!("(contract-call? '{}.token mint u10)", alice)),
+            Ok("(ok true)".to_string())
+        );
+
+        assert_eq!(
+            session.handle_input(&format!("::mint-stx {} 1000", alice)),
+            Ok(format!("{} balance: 1000 uSTX", alice))
+        );
+        assert_eq!(
+            session.handle_input(&format!("(stx-transfer? u400 tx-sender '{})", bob)),
+            Ok("(ok true)".to_string())
+        );
+        assert_eq!(
+            session.handle_input("::get-assets"),
+            Ok(format!("STX: 600\n{}.token::coin: 10", alice))
+        );
+        assert_eq!(
+            session.handle_input(&format!("::get-assets {}", bob)),
+            Ok(format!("STX: 400\n{}.token::coin: 0", alice))
+        );
+
+        assert_eq!(
+            session.handle_input("::advance-blocks 5"),
+            Ok("block-height: 5".to_string())
+        );
+        assert_eq!(session.handle_input("block-height"), Ok("u5".to_string()));
+
+        session
+            .handle_input(&format!("::save {}", session_name))
+            .unwrap();
+        assert!(session
+            .handle_input(&format!("::save {}", session_name))
+            .is_err());
+
+        let mut restored = ReplSession::new();
+        restored
+            .handle_input(&format!("::load {}", session_name))
+            .unwrap();
+        assert_eq!(restored.handle_input("block-height"), Ok("u5".to_string()));
+        assert_eq!(
+            restored.handle_input(&format!("(stx-get-balance '{})", bob)),
+            Ok("u400".to_string())
+        );
+        assert_eq!(
+            restored.handle_input(&format!("::get-assets {}", alice)),
+            Ok(format!("STX: 600\n{}.token::coin: 10", alice))
+        );
+    }
+
     #[test]
     fn test_samples() {
         let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
@@ -1097,6 +2130,48 @@ mod test {
             ],
         );
 
+        eprintln!("execute tokens with trace");
+        invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                "--trace".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u100".to_string(),
+            ],
+        );
+
+        eprintln!("profile tokens");
+        invoke_command(
+            "test",
+            &[
+                "profile".to_string(),
+                "--metric".to_string(),
+                "write_count".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u100".to_string(),
+            ],
+        );
+
+        eprintln!("map_entries tokens");
+        invoke_command(
+            "test",
+            &[
+                "map_entries".to_string(),
+                "--limit".to_string(),
+                "1".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "tokens".to_string(),
+            ],
+        );
+
         eprintln!("eval tokens");
         invoke_command(
             "test",
@@ -0,0 +1,641 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A Language Server Protocol server for Clarity, speaking JSON-RPC over stdio.
+//!
+//! The server analyzes every contract of the editor's workspace as one project, so
+//!  that `contract-call?` targets resolve to the other contracts of the workspace.
+
+pub mod project;
+
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use vm::diagnostic::{Diagnostic, Level};
+use vm::docs::make_markdown_reference;
+use vm::representations::{Span, SymbolicExpression};
+use vm::types::{PrincipalData, Value};
+use vm::{DefineFunctions, NativeFunctions, NativeVariables};
+
+use self::project::{expressions_at, top_level_definitions, Project, ProjectContract};
+
+// JSON-RPC error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INVALID_REQUEST: i64 = -32600;
+
+// LSP enumerations
+const TEXT_DOCUMENT_SYNC_FULL: i64 = 1;
+const SEVERITY_ERROR: i64 = 1;
+const SEVERITY_WARNING: i64 = 2;
+const COMPLETION_KIND_FUNCTION: i64 = 3;
+const COMPLETION_KIND_VARIABLE: i64 = 6;
+const COMPLETION_KIND_KEYWORD: i64 = 14;
+
+type RequestResult = Result<JsonValue, (i64, String)>;
+
+pub struct LanguageServer {
+    project: Project,
+    shutdown_requested: bool,
+    exited: bool,
+}
+
+/// Read one message, framed by a `Content-Length` header. Returns None at the end of the input.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<JsonValue>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header")
+    })?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &JsonValue) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+/// Serve requests read from `input` until the client sends `exit`, or closes the input.
+pub fn run_server<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = LanguageServer::new();
+    while let Some(message) = read_message(&mut input)? {
+        for reply in server.handle_message(&message).iter() {
+            write_message(&mut output, reply)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn make_range(span: &Span) -> JsonValue {
+    // spans are 1-indexed and inclusive, while LSP ranges are 0-indexed and exclusive.
+    json!({
+        "start": {
+            "line": span.start_line.saturating_sub(1),
+            "character": span.start_column.saturating_sub(1)
+        },
+        "end": {
+            "line": span.end_line.saturating_sub(1),
+            "character": span.end_column
+        }
+    })
+}
+
+fn make_location(uri: &str, span: &Span) -> JsonValue {
+    json!({ "uri": uri, "range": make_range(span) })
+}
+
+fn make_diagnostic(diagnostic: &Diagnostic) -> JsonValue {
+    let severity = match diagnostic.level {
+        Level::Error => SEVERITY_ERROR,
+        Level::Warning => SEVERITY_WARNING,
+    };
+    let span = diagnostic.spans.get(0).cloned().unwrap_or_else(Span::zero);
+    let message = match diagnostic.suggestion {
+        Some(ref suggestion) => format!("{}\n{}", diagnostic.message, suggestion),
+        None => diagnostic.message.clone(),
+    };
+    json!({
+        "range": make_range(&span),
+        "severity": severity,
+        "source": "clarity",
+        "message": message,
+    })
+}
+
+/// The 1-indexed (line, column) of a request's `position` parameter.
+fn get_position(params: &JsonValue) -> Option<(u32, u32)> {
+    let line = params["position"]["line"].as_u64()?;
+    let character = params["position"]["character"].as_u64()?;
+    Some((line as u32 + 1, character as u32 + 1))
+}
+
+fn get_document_uri(params: &JsonValue) -> Option<&str> {
+    params["textDocument"]["uri"].as_str()
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        LanguageServer {
+            project: Project::new(),
+            shutdown_requested: false,
+            exited: false,
+        }
+    }
+
+    /// Handle one message from the client, returning the messages to send in reply
+    ///  (a response, for requests, and any diagnostics to publish).
+    pub fn handle_message(&mut self, message: &JsonValue) -> Vec<JsonValue> {
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // responses to server-initiated requests are ignored.
+            None => return vec![],
+        };
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match self.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, error_message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": error_message }
+                    }),
+                };
+                vec![response]
+            }
+            None => self.handle_notification(method, params),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: &JsonValue) -> RequestResult {
+        if self.shutdown_requested {
+            return Err((INVALID_REQUEST, "Server is shutting down".into()));
+        }
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/hover" => self.with_document(params, |server, contract, position| {
+                server.hover(contract, position)
+            }),
+            "textDocument/completion" => {
+                self.with_document(params, |server, contract, _| server.completion(contract))
+            }
+            "textDocument/definition" => self
+                .with_document(params, |server, contract, position| {
+                    server.definition(contract, position)
+                }),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        }
+    }
+
+    fn with_document<F>(&self, params: &JsonValue, handler: F) -> RequestResult
+    where
+        F: FnOnce(&LanguageServer, &ProjectContract, (u32, u32)) -> JsonValue,
+    {
+        let uri = get_document_uri(params)
+            .ok_or_else(|| (INVALID_PARAMS, "Missing textDocument".to_string()))?;
+        let position =
+            get_position(params).ok_or_else(|| (INVALID_PARAMS, "Missing position".to_string()))?;
+        match self.project.get(uri) {
+            Some(contract) => Ok(handler(self, contract, position)),
+            None => Ok(JsonValue::Null),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &JsonValue) -> Vec<JsonValue> {
+        match method {
+            "exit" => {
+                self.exited = true;
+                vec![]
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                match (document["uri"].as_str(), document["text"].as_str()) {
+                    (Some(uri), Some(text)) => self.update_document(uri, text.to_string()),
+                    _ => vec![],
+                }
+            }
+            "textDocument/didChange" => {
+                // the server only asks for full-document syncs, so the last change is the document.
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                match (get_document_uri(params), text) {
+                    (Some(uri), Some(text)) => self.update_document(uri, text.to_string()),
+                    _ => vec![],
+                }
+            }
+            "textDocument/didClose" => {
+                // closed documents fall back to their saved contents, if any.
+                let uri = match get_document_uri(params) {
+                    Some(uri) => uri,
+                    None => return vec![],
+                };
+                let saved = Url::parse(uri)
+                    .ok()
+                    .and_then(|url| url.to_file_path().ok())
+                    .and_then(|path| fs::read_to_string(path).ok());
+                match saved {
+                    Some(source) => self.update_document(uri, source),
+                    None => {
+                        self.project.remove(uri);
+                        self.project.analyze();
+                        let mut messages = self.publish_all_diagnostics();
+                        messages.push(json!({
+                            "jsonrpc": "2.0",
+                            "method": "textDocument/publishDiagnostics",
+                            "params": { "uri": uri, "diagnostics": [] }
+                        }));
+                        messages
+                    }
+                }
+            }
+            _ => vec![],
+        }
+    }
+
+    fn initialize(&mut self, params: &JsonValue) -> JsonValue {
+        let root = params["rootUri"]
+            .as_str()
+            .and_then(|uri| Url::parse(uri).ok())
+            .and_then(|url| url.to_file_path().ok())
+            .or_else(|| params["rootPath"].as_str().map(|path| path.into()));
+        if let Some(root) = root {
+            if let Err(e) = self.project.load_directory(&root) {
+                warn!("Failed to load project directory {:?}: {}", root, e);
+            }
+            self.project.analyze();
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL,
+                "hoverProvider": true,
+                "completionProvider": { "triggerCharacters": ["("] },
+                "definitionProvider": true,
+            },
+            "serverInfo": { "name": "clarity-lsp" }
+        })
+    }
+
+    fn update_document(&mut self, uri: &str, source: String) -> Vec<JsonValue> {
+        if !self.project.set_source(uri, source) {
+            return vec![];
+        }
+        self.project.analyze();
+        // a change to one contract can change the analysis of the contracts calling it.
+        self.publish_all_diagnostics()
+    }
+
+    fn publish_all_diagnostics(&self) -> Vec<JsonValue> {
+        self.project
+            .contracts()
+            .map(|contract| {
+                let diagnostics: Vec<_> =
+                    contract.diagnostics.iter().map(make_diagnostic).collect();
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": { "uri": contract.uri, "diagnostics": diagnostics }
+                })
+            })
+            .collect()
+    }
+
+    /// Show the documentation of the native under the cursor, and the inferred type of
+    ///  the innermost expression.
+    fn hover(&self, contract: &ProjectContract, (line, column): (u32, u32)) -> JsonValue {
+        let path = expressions_at(&contract.expressions, line, column);
+        let expr = match path.last() {
+            Some(expr) => expr,
+            None => return JsonValue::Null,
+        };
+
+        let mut sections = vec![];
+        if let Some(documentation) = expr
+            .match_atom()
+            .and_then(|name| make_markdown_reference(name))
+        {
+            sections.push(documentation);
+        }
+
+        // atoms in function position have no type of their own: show the application's type.
+        let typed_expr = match path.len() {
+            len if len >= 2
+                && expr.match_atom().is_some()
+                && LanguageServer::is_head(path[len - 2], expr) =>
+            {
+                path[len - 2]
+            }
+            _ => expr,
+        };
+        let inferred_type = contract
+            .analysis
+            .as_ref()
+            .and_then(|analysis| analysis.type_map.as_ref())
+            .and_then(|type_map| type_map.get_type(typed_expr));
+        if let Some(inferred_type) = inferred_type {
+            sections.insert(0, format!("```clarity\n{}\n```", inferred_type));
+        }
+
+        if sections.is_empty() {
+            return JsonValue::Null;
+        }
+        json!({
+            "contents": { "kind": "markdown", "value": sections.join("\n\n---\n\n") },
+            "range": make_range(&expr.span),
+        })
+    }
+
+    fn is_head(list: &SymbolicExpression, expr: &SymbolicExpression) -> bool {
+        list.match_list()
+            .and_then(|items| items.get(0))
+            .map(|head| head.id == expr.id)
+            .unwrap_or(false)
+    }
+
+    /// Complete the native functions, defines and keywords, along with the contract's
+    ///  own definitions.
+    fn completion(&self, contract: &ProjectContract) -> JsonValue {
+        let mut items = vec![];
+        let mut add_item = |name: &str, kind: i64| {
+            let mut item = json!({ "label": name, "kind": kind });
+            if let Some(documentation) = make_markdown_reference(name) {
+                item["documentation"] = json!({ "kind": "markdown", "value": documentation });
+            }
+            items.push(item);
+        };
+
+        for name in NativeFunctions::ALL_NAMES.iter() {
+            add_item(name, COMPLETION_KIND_FUNCTION);
+        }
+        for name in DefineFunctions::ALL_NAMES.iter() {
+            add_item(name, COMPLETION_KIND_KEYWORD);
+        }
+        for name in NativeVariables::ALL_NAMES.iter() {
+            add_item(name, COMPLETION_KIND_VARIABLE);
+        }
+        for (name, definition) in top_level_definitions(&contract.expressions) {
+            let is_function = match definition.match_list().and_then(|x| x.get(0)) {
+                Some(head) => head
+                    .match_atom()
+                    .map(|define| {
+                        define.as_str().ends_with("-public")
+                            || define.as_str().ends_with("-private")
+                            || define.as_str().ends_with("-read-only")
+                    })
+                    .unwrap_or(false),
+                None => false,
+            };
+            add_item(
+                name,
+                if is_function {
+                    COMPLETION_KIND_FUNCTION
+                } else {
+                    COMPLETION_KIND_VARIABLE
+                },
+            );
+        }
+
+        json!({ "isIncomplete": false, "items": items })
+    }
+
+    /// Resolve the name under the cursor to its definition: a definition in the same
+    ///  contract, or for `contract-call?` targets and trait references, a definition in
+    ///  another contract of the project.
+    fn definition(&self, contract: &ProjectContract, (line, column): (u32, u32)) -> JsonValue {
+        let path = expressions_at(&contract.expressions, line, column);
+        let expr = match path.last() {
+            Some(expr) => *expr,
+            None => return JsonValue::Null,
+        };
+
+        // (contract-call? .target function-name ...)
+        if let Some(parent) = path.len().checked_sub(2).map(|ix| path[ix]) {
+            let items = parent.match_list().unwrap_or(&[]);
+            let is_contract_call = items
+                .get(0)
+                .and_then(|head| head.match_atom())
+                .map(|head| head.as_str() == NativeFunctions::ContractCall.get_name_str())
+                .unwrap_or(false);
+            if is_contract_call && items.len() >= 3 && items[2].id == expr.id {
+                if let Some(Value::Principal(PrincipalData::Contract(target))) =
+                    items[1].match_literal_value()
+                {
+                    if let Some(target) = self.project.find_contract(target) {
+                        return LanguageServer::find_definition(target, expr);
+                    }
+                }
+            }
+        }
+
+        if let Some(Value::Principal(PrincipalData::Contract(target))) = expr.match_literal_value()
+        {
+            return match self.project.find_contract(target) {
+                Some(target) => make_location(&target.uri, &Span::zero()),
+                None => JsonValue::Null,
+            };
+        }
+
+        if let Some(trait_identifier) = expr.match_field() {
+            return match self
+                .project
+                .find_contract(&trait_identifier.contract_identifier)
+            {
+                Some(target) => top_level_definitions(&target.expressions)
+                    .into_iter()
+                    .find(|(name, _)| **name == trait_identifier.name)
+                    .map(|(_, definition)| make_location(&target.uri, &definition.span))
+                    .unwrap_or(JsonValue::Null),
+                None => JsonValue::Null,
+            };
+        }
+
+        LanguageServer::find_definition(contract, expr)
+    }
+
+    fn find_definition(contract: &ProjectContract, expr: &SymbolicExpression) -> JsonValue {
+        let name = match expr.match_atom() {
+            Some(name) => name,
+            None => return JsonValue::Null,
+        };
+        top_level_definitions(&contract.expressions)
+            .into_iter()
+            .find(|(defined_name, _)| *defined_name == name)
+            .map(|(_, definition)| make_location(&contract.uri, &definition.span))
+            .unwrap_or(JsonValue::Null)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn request(id: u64, method: &str, params: JsonValue) -> JsonValue {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: JsonValue) -> JsonValue {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn open(server: &mut LanguageServer, uri: &str, text: &str) -> Vec<JsonValue> {
+        server.handle_message(&notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": uri, "languageId": "clarity", "version": 1, "text": text } }),
+        ))
+    }
+
+    fn position_params(uri: &str, line: u64, character: u64) -> JsonValue {
+        json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } })
+    }
+
+    const COUNTER_URI: &str = "file:///project/counter.clar";
+    const CALLER_URI: &str = "file:///project/caller.clar";
+    const COUNTER: &str = "(define-data-var count uint u0)
+(define-public (increment)
+  (begin
+    (var-set count (+ (var-get count) u1))
+    (ok (var-get count))))";
+    const CALLER: &str = "(define-public (call)
+  (contract-call? .counter increment))";
+
+    #[test]
+    fn test_message_framing() {
+        let message = request(1, "shutdown", JsonValue::Null);
+        let mut framed = vec![];
+        write_message(&mut framed, &message).unwrap();
+        write_message(&mut framed, &notification("exit", JsonValue::Null)).unwrap();
+
+        let mut input = io::Cursor::new(framed.clone());
+        assert_eq!(read_message(&mut input).unwrap(), Some(message));
+        assert!(read_message(&mut input).unwrap().is_some());
+        assert_eq!(read_message(&mut input).unwrap(), None);
+
+        let mut output = vec![];
+        run_server(io::Cursor::new(framed), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Content-Length: "));
+        assert!(output.ends_with(r#"{"id":1,"jsonrpc":"2.0","result":null}"#));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut server = LanguageServer::new();
+        let replies = server.handle_message(&request(1, "initialize", json!({ "rootUri": null })));
+        assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+
+        let published = open(
+            &mut server,
+            COUNTER_URI,
+            "(define-public (foo) (ok (+ 1 u1)))",
+        );
+        assert_eq!(published.len(), 1);
+        let diagnostics = &published[0]["params"]["diagnostics"];
+        assert_eq!(diagnostics[0]["severity"], SEVERITY_ERROR);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 0);
+
+        let published = open(&mut server, COUNTER_URI, COUNTER);
+        assert_eq!(
+            published[0]["params"]["diagnostics"][0]["severity"],
+            SEVERITY_WARNING
+        );
+    }
+
+    #[test]
+    fn test_hover_and_completion() {
+        let mut server = LanguageServer::new();
+        open(&mut server, COUNTER_URI, COUNTER);
+
+        // hovering over `var-get` in `(var-get count)`, on line 4
+        let hover = server.handle_message(&request(
+            2,
+            "textDocument/hover",
+            position_params(COUNTER_URI, 3, 23),
+        ));
+        let contents = hover[0]["result"]["contents"]["value"].as_str().unwrap();
+        assert!(contents.starts_with("```clarity\nuint\n```"));
+        assert!(contents.contains("`(var-get var-name)`"));
+
+        let completion = server.handle_message(&request(
+            3,
+            "textDocument/completion",
+            position_params(COUNTER_URI, 0, 0),
+        ));
+        let labels: Vec<_> = completion[0]["result"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap().to_string())
+            .collect();
+        assert!(labels.contains(&"stx-transfer?".to_string()));
+        assert!(labels.contains(&"define-public".to_string()));
+        assert!(labels.contains(&"increment".to_string()));
+    }
+
+    #[test]
+    fn test_definition() {
+        let mut server = LanguageServer::new();
+        open(&mut server, COUNTER_URI, COUNTER);
+        open(&mut server, CALLER_URI, CALLER);
+        assert!(server
+            .project
+            .get(CALLER_URI)
+            .unwrap()
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.level == Level::Warning));
+
+        // `increment` in the contract-call? resolves to the counter contract.
+        let definition = server.handle_message(&request(
+            4,
+            "textDocument/definition",
+            position_params(CALLER_URI, 1, 30),
+        ));
+        assert_eq!(definition[0]["result"]["uri"], COUNTER_URI);
+        assert_eq!(definition[0]["result"]["range"]["start"]["line"], 1);
+
+        // `count` resolves within the counter contract.
+        let definition = server.handle_message(&request(
+            5,
+            "textDocument/definition",
+            position_params(COUNTER_URI, 4, 18),
+        ));
+        assert_eq!(definition[0]["result"]["uri"], COUNTER_URI);
+        assert_eq!(definition[0]["result"]["range"]["start"]["line"], 0);
+
+        let unknown = server.handle_message(&request(6, "textDocument/formatting", json!({})));
+        assert_eq!(unknown[0]["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use url::Url;
+
+use vm::analysis::lints::{run_lints, LintConfig};
+use vm::analysis::{run_analysis_with_recovery, ContractAnalysis};
+use vm::ast::build_ast;
+use vm::costs::LimitedCostTracker;
+use vm::database::MemoryBackingStore;
+use vm::diagnostic::Diagnostic;
+use vm::representations::{ClarityName, ContractName, Span, SymbolicExpression};
+use vm::types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value};
+use vm::DefineFunctionsParsed;
+
+/// The principal which "deploys" the contracts of a project, so that sugared contract
+///  identifiers (e.g., `.tokens`) resolve to the other contracts of the project.
+pub const PROJECT_DEPLOYER: &str = "S1G2081040G2081040G2081040G208105NK8PE5";
+
+/// A contract source open in the editor, or found in the project's directory.
+pub struct ProjectContract {
+    pub uri: String,
+    pub source: String,
+    pub contract_identifier: QualifiedContractIdentifier,
+    /// The parsed program. Empty if the source did not parse.
+    pub expressions: Vec<SymbolicExpression>,
+    /// The result of the analysis passes, if they succeeded.
+    pub analysis: Option<ContractAnalysis>,
+    /// Parse and check errors, followed by lint warnings.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A Project is the set of contracts which can refer to one another, e.g., as
+///  `contract-call?` targets. Every contract is analyzed after the contracts it refers to.
+pub struct Project {
+    deployer: StandardPrincipalData,
+    contracts: BTreeMap<String, ProjectContract>,
+}
+
+pub fn uri_to_contract_name(uri: &str) -> Option<ContractName> {
+    let url = Url::parse(uri).ok()?;
+    let file_name = url.path_segments()?.last()?;
+    let contract_name = file_name.trim_end_matches(".clar");
+    ContractName::try_from(contract_name.to_string()).ok()
+}
+
+/// The top-level definitions of a program, by name.
+pub fn top_level_definitions(
+    expressions: &[SymbolicExpression],
+) -> Vec<(&ClarityName, &SymbolicExpression)> {
+    use vm::DefineFunctionsParsed::*;
+    let mut definitions = vec![];
+    for expr in expressions.iter() {
+        let name = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(define)) => match define {
+                Constant { name, .. }
+                | NonFungibleToken { name, .. }
+                | BoundedFungibleToken { name, .. }
+                | UnboundedFungibleToken { name }
+                | Map { name, .. }
+                | PersistedVariable { name, .. }
+                | Trait { name, .. }
+                | UseTrait { name, .. }
+                | Event { name, .. } => Some(name),
+                PrivateFunction { signature, .. }
+                | ReadOnlyFunction { signature, .. }
+                | PublicFunction { signature, .. } => signature.get(0).and_then(|x| x.match_atom()),
+                ImplTrait { .. } => None,
+            },
+            _ => None,
+        };
+        if let Some(name) = name {
+            definitions.push((name, expr));
+        }
+    }
+    definitions
+}
+
+/// The path of expressions enclosing the given (1-indexed) position, from the
+///  top-level expression to the innermost one.
+pub fn expressions_at(
+    expressions: &[SymbolicExpression],
+    line: u32,
+    column: u32,
+) -> Vec<&SymbolicExpression> {
+    let mut path = vec![];
+    let mut candidates = expressions;
+    while let Some(expr) = candidates
+        .iter()
+        .find(|expr| span_contains(&expr.span, line, column))
+    {
+        path.push(expr);
+        candidates = expr.match_list().unwrap_or(&[]);
+    }
+    path
+}
+
+fn span_contains(span: &Span, line: u32, column: u32) -> bool {
+    (span.start_line, span.start_column) <= (line, column)
+        && (line, column) <= (span.end_line, span.end_column)
+}
+
+/// The contracts which `expr` refers to, e.g., as `contract-call?` targets or trait definitions.
+fn collect_contract_references(
+    expr: &SymbolicExpression,
+    references: &mut HashSet<QualifiedContractIdentifier>,
+) {
+    if let Some(Value::Principal(PrincipalData::Contract(contract_identifier))) =
+        expr.match_literal_value()
+    {
+        references.insert(contract_identifier.clone());
+    }
+    if let Some(trait_identifier) = expr.match_field() {
+        references.insert(trait_identifier.contract_identifier.clone());
+    }
+    for child in expr.match_list().unwrap_or(&[]).iter() {
+        collect_contract_references(child, references);
+    }
+}
+
+impl Project {
+    pub fn new() -> Project {
+        Project {
+            deployer: PrincipalData::parse_standard_principal(PROJECT_DEPLOYER)
+                .expect("Failed to parse the project deployer"),
+            contracts: BTreeMap::new(),
+        }
+    }
+
+    /// Add every `.clar` file under `root` to the project.
+    pub fn load_directory(&mut self, root: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(root)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.load_directory(&path)?;
+            } else if path.extension().map(|ext| ext == "clar").unwrap_or(false) {
+                let uri = match Url::from_file_path(&path) {
+                    Ok(uri) => uri.to_string(),
+                    Err(_) => continue,
+                };
+                // sources which are open in the editor take precedence over the files.
+                if !self.contracts.contains_key(&uri) {
+                    let source = fs::read_to_string(&path)?;
+                    self.set_source(&uri, source);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add or replace the source of a contract. Returns false if the uri does not name a
+    ///  valid contract (its file name must be a valid contract name).
+    pub fn set_source(&mut self, uri: &str, source: String) -> bool {
+        let contract_name = match uri_to_contract_name(uri) {
+            Some(contract_name) => contract_name,
+            None => {
+                warn!("Not a valid contract file name: {}", uri);
+                return false;
+            }
+        };
+        let contract_identifier =
+            QualifiedContractIdentifier::new(self.deployer.clone(), contract_name);
+        self.contracts.insert(
+            uri.to_string(),
+            ProjectContract {
+                uri: uri.to_string(),
+                source,
+                contract_identifier,
+                expressions: vec![],
+                analysis: None,
+                diagnostics: vec![],
+            },
+        );
+        true
+    }
+
+    /// Remove a contract from the project, returning whether it was present.
+    pub fn remove(&mut self, uri: &str) -> bool {
+        self.contracts.remove(uri).is_some()
+    }
+
+    pub fn get(&self, uri: &str) -> Option<&ProjectContract> {
+        self.contracts.get(uri)
+    }
+
+    pub fn find_contract(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> Option<&ProjectContract> {
+        self.contracts
+            .values()
+            .find(|contract| &contract.contract_identifier == contract_identifier)
+    }
+
+    pub fn contracts(&self) -> impl Iterator<Item = &ProjectContract> {
+        self.contracts.values()
+    }
+
+    /// Parse and analyze every contract of the project, in dependency order.
+    pub fn analyze(&mut self) {
+        let mut dependencies = HashMap::new();
+        for contract in self.contracts.values_mut() {
+            contract.analysis = None;
+            contract.diagnostics = vec![];
+            contract.expressions =
+                match build_ast(&contract.contract_identifier, &contract.source, &mut ()) {
+                    Ok(ast) => ast.expressions,
+                    Err(e) => {
+                        contract.diagnostics.push(e.diagnostic);
+                        vec![]
+                    }
+                };
+
+            let mut references = HashSet::new();
+            for expr in contract.expressions.iter() {
+                collect_contract_references(expr, &mut references);
+            }
+            dependencies.insert(contract.uri.clone(), references);
+        }
+
+        let order = self.analysis_order(&dependencies);
+
+        let mut marf = MemoryBackingStore::new();
+        let lint_config = LintConfig::new();
+        for uri in order.into_iter() {
+            let contract = self.contracts.get_mut(&uri).expect("Unknown contract uri");
+            if contract.expressions.is_empty() {
+                continue;
+            }
+
+            let mut expressions = contract.expressions.clone();
+            let mut db = marf.as_analysis_db();
+            match run_analysis_with_recovery(
+                &contract.contract_identifier,
+                &mut expressions,
+                &mut db,
+                true,
+                LimitedCostTracker::new_free(),
+            ) {
+                Ok(analysis) => {
+                    for warning in run_lints(&analysis, &lint_config).into_iter() {
+                        contract.diagnostics.push(warning.diagnostic);
+                    }
+                    contract.analysis = Some(analysis);
+                }
+                Err((errors, _)) => {
+                    for e in errors.into_iter() {
+                        contract.diagnostics.push(e.diagnostic);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Order the contracts so that each contract comes after the contracts it refers to.
+    ///  Contracts in a reference cycle are ordered arbitrarily (and will fail analysis).
+    fn analysis_order(
+        &self,
+        dependencies: &HashMap<String, HashSet<QualifiedContractIdentifier>>,
+    ) -> Vec<String> {
+        let mut order = vec![];
+        let mut visited = HashSet::new();
+        for uri in self.contracts.keys() {
+            self.visit_dependencies(uri, dependencies, &mut visited, &mut order);
+        }
+        order
+    }
+
+    fn visit_dependencies(
+        &self,
+        uri: &str,
+        dependencies: &HashMap<String, HashSet<QualifiedContractIdentifier>>,
+        visited: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) {
+        if !visited.insert(uri.to_string()) {
+            return;
+        }
+        if let Some(references) = dependencies.get(uri) {
+            for contract_identifier in references.iter() {
+                if let Some(dependency) = self.find_contract(contract_identifier) {
+                    self.visit_dependencies(&dependency.uri, dependencies, visited, order);
+                }
+            }
+        }
+        order.push(uri.to_string());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vm::diagnostic::Level;
+
+    #[test]
+    fn test_analysis_order() {
+        let mut project = Project::new();
+        // the caller sorts before its target, so it must wait for the target's analysis.
+        project.set_source(
+            "file:///project/a-caller.clar",
+            "(define-public (call) (contract-call? .z-counter increment))".to_string(),
+        );
+        project.set_source(
+            "file:///project/z-counter.clar",
+            "(define-data-var count uint u0)
+             (define-public (increment) (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))"
+                .to_string(),
+        );
+        assert!(!project.set_source("file:///project/bad%20name.clar", "".to_string()));
+        project.analyze();
+
+        let caller = project.get("file:///project/a-caller.clar").unwrap();
+        assert!(caller.analysis.is_some());
+        assert!(caller
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.level == Level::Warning));
+
+        project.set_source(
+            "file:///project/a-caller.clar",
+            "(define-public (call) (contract-call? .z-counter decrement))".to_string(),
+        );
+        project.analyze();
+        let caller = project.get("file:///project/a-caller.clar").unwrap();
+        assert!(caller.analysis.is_none());
+        assert_eq!(caller.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_expressions_at() {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let expressions = build_ast(
+            &contract_identifier,
+            "(define-constant a 1)\n(define-read-only (get-a) (+ a 2))",
+            &mut (),
+        )
+        .unwrap()
+        .expressions;
+
+        let path = expressions_at(&expressions, 2, 30);
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[2].match_atom().unwrap().as_str(), "a");
+
+        let definitions = top_level_definitions(&expressions);
+        let names: Vec<_> = definitions.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["a", "get-a"]);
+
+        assert!(expressions_at(&expressions, 3, 1).is_empty());
+    }
+}
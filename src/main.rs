@@ -23,6 +23,7 @@
 #[macro_use]
 extern crate blockstack_lib;
 extern crate rusqlite;
+extern crate serde_json;
 
 #[macro_use(o, slog_log, slog_trace, slog_debug, slog_info, slog_warn, slog_error)]
 extern crate slog;
@@ -310,6 +311,46 @@ fn main() {
         return;
     }
 
+    if argv[1] == "marf-migrate-blobs" {
+        use chainstate::stacks::index::file::TrieFile;
+        use chainstate::stacks::index::storage::TrieFileStorage;
+
+        if argv.len() < 3 {
+            eprintln!(
+                "Usage: {} marf-migrate-blobs MARF_PATH [MARF_PATH ...]",
+                argv[0]
+            );
+            eprintln!("       Moves the tries stored in each MARF database into a blob file next to it (MARF_PATH.blobs).");
+            eprintln!("       The node's MARFs are chainstate/chain-*/vm/index, chainstate/chain-*/vm/clarity/marf, and burnchain/db/*/*/sortition.db/marf.");
+            eprintln!("       Stop the node before migrating its MARFs.");
+            process::exit(1);
+        }
+
+        for path in argv[2..].iter() {
+            if fs::metadata(path).is_err() {
+                eprintln!("No such MARF: {}", path);
+                process::exit(1);
+            }
+
+            // the block hash type does not matter for moving tries around
+            let result = TrieFileStorage::<StacksBlockId>::open_with_blobs(path)
+                .and_then(|mut storage| storage.migrate_to_blobs());
+            match result {
+                Ok(num_migrated) => println!(
+                    "Moved {} tries of {} to {}",
+                    num_migrated,
+                    path,
+                    TrieFile::blobs_path(path)
+                ),
+                Err(e) => {
+                    eprintln!("Failed to migrate {}: {:?}", path, &e);
+                    process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+
     if argv[1] == "get-ancestors" {
         let path = &argv[2];
         let tip = BlockHeaderHash::from_hex(&argv[3]).unwrap();
@@ -373,6 +414,52 @@ fn main() {
         return;
     }
 
+    if argv[1] == "snapshot" {
+        use chainstate::stacks::db::snapshot::{export_snapshot, import_snapshot};
+
+        let usage = || {
+            eprintln!(
+                "Usage: {} snapshot export CHAINSTATE_DIR BURNCHAIN_DIR ARCHIVE_PATH",
+                argv[0]
+            );
+            eprintln!(
+                "       {} snapshot import ARCHIVE_PATH CHAINSTATE_DIR BURNCHAIN_DIR",
+                argv[0]
+            );
+            eprintln!("       CHAINSTATE_DIR is the chain-* directory in the node's chainstate directory (e.g., $WORKING_DIR/chainstate/chain-80000000-testnet)");
+            eprintln!("       BURNCHAIN_DIR is the directory with sortition.db and burnchain.db (e.g., $WORKING_DIR/burnchain/db/bitcoin/regtest)");
+            eprintln!("       Snapshots are taken at the canonical Stacks chain tip.");
+            process::exit(1);
+        };
+
+        if argv.len() < 6 {
+            usage();
+        }
+
+        let result = if argv[2] == "export" {
+            export_snapshot(&argv[3], &argv[4], &argv[5])
+        } else if argv[2] == "import" {
+            import_snapshot(&argv[3], &argv[4], &argv[5])
+        } else {
+            usage();
+            unreachable!()
+        };
+
+        match result {
+            Ok(manifest) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&manifest).expect("Failed to serialize manifest")
+                );
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Snapshot {} failed: {}", &argv[2], &e);
+                process::exit(1);
+            }
+        }
+    }
+
     if argv[1] == "replay-chainstate" {
         use burnchains::bitcoin::indexer::BitcoinIndexer;
         use burnchains::db::BurnchainDB;
@@ -470,6 +557,8 @@ fn main() {
             get_bulk_initial_balances: None,
             get_bulk_initial_namespaces: None,
             get_bulk_initial_names: None,
+            marf_blob_files: false,
+            clarity_key_index: false,
         };
 
         let (mut new_chainstate, _) = StacksChainState::open_and_exec(
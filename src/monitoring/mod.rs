@@ -117,6 +117,16 @@ pub fn increment_errors_emitted_counter() {
     prometheus::ERRORS_EMITTED_COUNTER.inc();
 }
 
+pub fn increment_marf_cache_hits_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MARF_CACHE_HITS_COUNTER.inc();
+}
+
+pub fn increment_marf_cache_misses_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MARF_CACHE_MISSES_COUNTER.inc();
+}
+
 #[allow(unused_variables)]
 pub fn update_active_miners_count_gauge(value: i64) {
     #[cfg(feature = "monitoring_prom")]
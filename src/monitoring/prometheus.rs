@@ -137,6 +137,18 @@ lazy_static! {
         labels! {"handler" => "all",}
     )).unwrap();
 
+    pub static ref MARF_CACHE_HITS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_marf_cache_hits_total",
+        "Total number of MARF node reads served from the node cache.",
+        labels! {"handler" => "all",}
+    )).unwrap();
+
+    pub static ref MARF_CACHE_MISSES_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_marf_cache_misses_total",
+        "Total number of MARF node reads that missed the node cache.",
+        labels! {"handler" => "all",}
+    )).unwrap();
+
     pub static ref ACTIVE_MINERS_COUNT_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_active_miners_total",
         "Total number of active miners.",
@@ -63,6 +63,7 @@ use net::HTTP_PREAMBLE_MAX_NUM_HEADERS;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_MICROBLOCKS_UNCONFIRMED;
 use net::{GetAttachmentResponse, GetAttachmentsInvResponse, PostTransactionRequestBody};
+use net::{MAP_ENTRIES_PAGE_DEFAULT, MAP_ENTRIES_PAGE_MAX};
 use net::{MAX_MESSAGE_LEN, MAX_PAYLOAD_LEN};
 
 use util::hash::hex_bytes;
@@ -116,6 +117,11 @@ lazy_static! {
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_MAP_ENTRIES: Regex = Regex::new(&format!(
+        "^/v2/map_entries/(?P<address>{})/(?P<contract>{})/(?P<map>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
+    ))
+    .unwrap();
     static ref PATH_POST_CALL_READ_ONLY: Regex = Regex::new(&format!(
         "^/v2/contracts/call-read/(?P<address>{})/(?P<contract>{})/(?P<function>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
@@ -1482,6 +1488,11 @@ impl HttpRequestType {
                 &PATH_GET_MAP_ENTRY,
                 &HttpRequestType::parse_get_map_entry,
             ),
+            (
+                "GET",
+                &PATH_GET_MAP_ENTRIES,
+                &HttpRequestType::parse_get_map_entries,
+            ),
             (
                 "GET",
                 &PATH_GET_TRANSFER_COST,
@@ -1750,6 +1761,72 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_map_entries<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
+        })?;
+        let contract_name = ContractName::try_from(captures["contract"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+        let map_name = ClarityName::try_from(captures["map"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse map name".into()))?;
+
+        let mut after = None;
+        let mut limit = MAP_ENTRIES_PAGE_DEFAULT;
+
+        if let Some(query) = query {
+            for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+                if key == "after" {
+                    let cursor = if value.starts_with("0x") {
+                        &value[2..]
+                    } else {
+                        &value[..]
+                    };
+                    if hex_bytes(cursor).is_err() {
+                        return Err(net_error::DeserializeError(
+                            "Failed to parse cursor: expected a hex string".into(),
+                        ));
+                    }
+                    after = Some(cursor.to_string());
+                } else if key == "limit" {
+                    limit = value.parse::<u32>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse page limit".into())
+                    })?;
+                }
+            }
+        }
+
+        if limit == 0 || limit > MAP_ENTRIES_PAGE_MAX {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid page limit: expected a limit between 1 and {}",
+                MAP_ENTRIES_PAGE_MAX
+            )));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetMapEntries(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            map_name,
+            after,
+            limit,
+            tip,
+        ))
+    }
+
     fn parse_call_read_only<R: Read>(
         protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1804,6 +1881,8 @@ impl HttpRequestType {
             func_name,
             arguments,
             tip,
+            body.trace,
+            body.proof,
         ))
     }
 
@@ -2268,6 +2347,7 @@ impl HttpRequestType {
             HttpRequestType::PostMicroblock(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
             HttpRequestType::GetMapEntry(ref md, ..) => md,
+            HttpRequestType::GetMapEntries(ref md, ..) => md,
             HttpRequestType::GetTransferCost(ref md) => md,
             HttpRequestType::GetContractABI(ref md, ..) => md,
             HttpRequestType::GetContractSrc(ref md, ..) => md,
@@ -2293,6 +2373,7 @@ impl HttpRequestType {
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
             HttpRequestType::GetMapEntry(ref mut md, ..) => md,
+            HttpRequestType::GetMapEntries(ref mut md, ..) => md,
             HttpRequestType::GetTransferCost(ref mut md) => md,
             HttpRequestType::GetContractABI(ref mut md, ..) => md,
             HttpRequestType::GetContractSrc(ref mut md, ..) => md,
@@ -2364,6 +2445,32 @@ impl HttpRequestType {
                 map_name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), *with_proof)
             ),
+            HttpRequestType::GetMapEntries(
+                _md,
+                contract_addr,
+                contract_name,
+                map_name,
+                after_opt,
+                limit,
+                tip_opt,
+            ) => {
+                let tip_query = HttpRequestType::make_query_string(tip_opt.as_ref(), true);
+                let prefix = if tip_query.len() > 0 { "&" } else { "?" };
+                let after_query = match after_opt {
+                    Some(after) => format!("&after={}", after),
+                    None => "".to_string(),
+                };
+                format!(
+                    "/v2/map_entries/{}/{}/{}{}{}limit={}{}",
+                    &contract_addr.to_string(),
+                    contract_name.as_str(),
+                    map_name.as_str(),
+                    tip_query,
+                    prefix,
+                    limit,
+                    after_query
+                )
+            }
             HttpRequestType::GetTransferCost(_md) => "/v2/fees/transfer".into(),
             HttpRequestType::GetContractABI(_, contract_addr, contract_name, tip_opt) => format!(
                 "/v2/contracts/interface/{}/{}{}",
@@ -2391,6 +2498,8 @@ impl HttpRequestType {
                 func_name,
                 _,
                 tip_opt,
+                _,
+                _,
             ) => format!(
                 "/v2/contracts/call-read/{}/{}/{}{}",
                 contract_addr,
@@ -2530,7 +2639,9 @@ impl HttpRequestType {
                 sender,
                 _func_name,
                 func_args,
-                ..,
+                _tip_opt,
+                trace,
+                proof,
             ) => {
                 let mut args = vec![];
                 for arg in func_args.iter() {
@@ -2543,6 +2654,8 @@ impl HttpRequestType {
                 let request_body = CallReadOnlyRequestBody {
                     sender: sender.to_string(),
                     arguments: args,
+                    trace: *trace,
+                    proof: *proof,
                 };
 
                 let mut request_body_bytes = vec![];
@@ -2835,6 +2948,10 @@ impl HttpResponseType {
                 &HttpResponseType::parse_microblock_hash,
             ),
             (&PATH_GET_ACCOUNT, &HttpResponseType::parse_get_account),
+            (
+                &PATH_GET_MAP_ENTRIES,
+                &HttpResponseType::parse_get_map_entries,
+            ),
             (
                 &PATH_GET_CONTRACT_SRC,
                 &HttpResponseType::parse_get_contract_src,
@@ -3008,6 +3125,21 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_map_entries<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let map_entries =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetMapEntries(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            map_entries,
+        ))
+    }
+
     fn parse_get_contract_src<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3240,6 +3372,7 @@ impl HttpResponseType {
             HttpResponseType::MicroblockHash(ref md, _) => md,
             HttpResponseType::TokenTransferCost(ref md, _) => md,
             HttpResponseType::GetMapEntry(ref md, _) => md,
+            HttpResponseType::GetMapEntries(ref md, _) => md,
             HttpResponseType::GetAccount(ref md, _) => md,
             HttpResponseType::GetContractABI(ref md, _) => md,
             HttpResponseType::GetContractSrc(ref md, _) => md,
@@ -3347,6 +3480,10 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, map_data)?;
             }
+            HttpResponseType::GetMapEntries(ref md, ref map_entries) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, map_entries)?;
+            }
             HttpResponseType::PeerInfo(ref md, ref peer_info) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, peer_info)?;
@@ -3553,6 +3690,7 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
                 HttpRequestType::GetMapEntry(..) => "HTTP(GetMapEntry)",
+                HttpRequestType::GetMapEntries(..) => "HTTP(GetMapEntries)",
                 HttpRequestType::GetTransferCost(_) => "HTTP(GetTransferCost)",
                 HttpRequestType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpRequestType::GetContractSrc(..) => "HTTP(GetContractSrc)",
@@ -3565,6 +3703,7 @@ impl MessageSequence for StacksHttpMessage {
             StacksHttpMessage::Response(ref res) => match res {
                 HttpResponseType::TokenTransferCost(_, _) => "HTTP(TokenTransferCost)",
                 HttpResponseType::GetMapEntry(_, _) => "HTTP(GetMapEntry)",
+                HttpResponseType::GetMapEntries(_, _) => "HTTP(GetMapEntries)",
                 HttpResponseType::GetAccount(_, _) => "HTTP(GetAccount)",
                 HttpResponseType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpResponseType::GetContractSrc(..) => "HTTP(GetContractSrc)",
@@ -4125,6 +4264,8 @@ mod test {
     use rand;
     use rand::RngCore;
 
+    use std::convert::TryInto;
+
     /// Simulate reading variable-length segments
     struct SegmentReader {
         segments: Vec<Vec<u8>>,
@@ -6053,6 +6194,82 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_get_map_entries_query() {
+        let md = HttpRequestMetadata {
+            version: HttpVersion::Http11,
+            peer: PeerHost::DNS("www.foo.com".to_string(), 80),
+            keep_alive: true,
+        };
+        let contract_addr =
+            StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let tip = StacksBlockId([1u8; 32]);
+
+        let tests = vec![
+            (None, MAP_ENTRIES_PAGE_DEFAULT, None),
+            (Some("0c00000001".to_string()), 10, Some(tip.clone())),
+        ];
+
+        for (after, limit, tip_opt) in tests {
+            let request = HttpRequestType::GetMapEntries(
+                md.clone(),
+                contract_addr.clone(),
+                "hello-world".try_into().unwrap(),
+                "unit-map".try_into().unwrap(),
+                after.clone(),
+                limit,
+                tip_opt.clone(),
+            );
+
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(request.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::GetMapEntries(
+                    _,
+                    parsed_addr,
+                    parsed_contract,
+                    parsed_map,
+                    parsed_after,
+                    parsed_limit,
+                    parsed_tip,
+                )) => {
+                    assert_eq!(parsed_addr, contract_addr);
+                    assert_eq!(parsed_contract.as_str(), "hello-world");
+                    assert_eq!(parsed_map.as_str(), "unit-map");
+                    assert_eq!(parsed_after, after);
+                    assert_eq!(parsed_limit, limit);
+                    assert_eq!(parsed_tip, tip_opt);
+                }
+                _ => panic!("Did not parse a GetMapEntries request"),
+            }
+        }
+
+        // the cursor may be 0x-prefixed, but must be hex, and the page limit is bounded
+        let requests = vec![
+            ("after=0x0c00000001&limit=1", true),
+            ("after=0c0000000&limit=1", false),
+            ("after=zz", false),
+            ("limit=0", false),
+            ("limit=501", false),
+            ("limit=500", true),
+        ];
+        for (query, parses) in requests {
+            let request = format!(
+                "GET /v2/map_entries/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world/unit-map?{} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: www.foo.com:80\r\n\r\n",
+                query
+            );
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let res = http.read_payload(&preamble, &request.as_bytes()[offset..]);
+            assert_eq!(res.is_ok(), parses, "query: {}", query);
+        }
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
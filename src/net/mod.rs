@@ -1048,6 +1048,21 @@ pub struct MapEntryResponse {
     pub marf_proof: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapEntriesResponse {
+    pub entries: Vec<MapEntriesResponseEntry>,
+    /// pass this as `after` to get the next page of entries, if there may be more
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapEntriesResponseEntry {
+    pub key: String,
+    pub value: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContractSrcResponse {
     pub source: String,
@@ -1067,6 +1082,20 @@ pub struct CallReadOnlyResponse {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cause: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<serde_json::Value>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proofs: Option<Vec<CallReadOnlyProof>>,
+}
+
+/// A key read by a read-only call, with the value read and a MARF proof of the pair.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallReadOnlyProof {
+    pub key: String,
+    pub value: String,
+    pub proof: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1157,6 +1186,10 @@ impl HttpRequestMetadata {
 pub struct CallReadOnlyRequestBody {
     pub sender: String,
     pub arguments: Vec<String>,
+    #[serde(default)]
+    pub trace: bool,
+    #[serde(default)]
+    pub proof: bool,
 }
 
 /// Items in the NeighborsInfo -- combines NeighborKey and NeighborAddress
@@ -1220,6 +1253,15 @@ pub enum HttpRequestType {
         Option<StacksBlockId>,
         bool,
     ),
+    GetMapEntries(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        ClarityName,
+        Option<String>,
+        u32,
+        Option<StacksBlockId>,
+    ),
     CallReadOnlyFunction(
         HttpRequestMetadata,
         StacksAddress,
@@ -1228,6 +1270,8 @@ pub enum HttpRequestType {
         ClarityName,
         Vec<Value>,
         Option<StacksBlockId>,
+        bool,
+        bool,
     ),
     GetTransferCost(HttpRequestMetadata),
     GetContractSrc(
@@ -1331,6 +1375,7 @@ pub enum HttpResponseType {
     MicroblockHash(HttpResponseMetadata, BlockHeaderHash),
     TokenTransferCost(HttpResponseMetadata, u64),
     GetMapEntry(HttpResponseMetadata, MapEntryResponse),
+    GetMapEntries(HttpResponseMetadata, MapEntriesResponse),
     CallReadOnlyFunction(HttpResponseMetadata, CallReadOnlyResponse),
     GetAccount(HttpResponseMetadata, AccountEntryResponse),
     GetContractABI(HttpResponseMetadata, ContractInterface),
@@ -1651,6 +1696,10 @@ pub const NUM_NEIGHBORS: usize = 32;
 // maximum number of unconfirmed microblocks can get streamed to us
 pub const MAX_MICROBLOCKS_UNCONFIRMED: usize = 1024;
 
+// default and maximum number of data map entries (or NFTs) returned in one page
+pub const MAP_ENTRIES_PAGE_DEFAULT: u32 = 50;
+pub const MAP_ENTRIES_PAGE_MAX: u32 = 500;
+
 // how long a peer will be denied for if it misbehaves
 #[cfg(test)]
 pub const DENY_BAN_DURATION: u64 = 30; // seconds
@@ -2364,6 +2413,9 @@ pub mod test {
                     Some(Box::new(move || Box::new(lockups.into_iter().map(|e| e))));
             }
 
+            // so the RPC tests can list map entries
+            boot_data.clarity_key_index = true;
+
             let (chainstate, _) = StacksChainState::open_and_exec(
                 false,
                 config.network_id,
@@ -55,8 +55,9 @@ use net::UrlString;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_NEIGHBORS_DATA_LEN;
 use net::{
-    AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ContractSrcResponse,
-    GetAttachmentResponse, GetAttachmentsInvResponse, MapEntryResponse,
+    AccountEntryResponse, AttachmentPage, CallReadOnlyProof, CallReadOnlyResponse,
+    ContractSrcResponse, GetAttachmentResponse, GetAttachmentsInvResponse, MapEntriesResponse,
+    MapEntriesResponseEntry, MapEntryResponse,
 };
 use net::{RPCNeighbor, RPCNeighborsInfo};
 use net::{RPCPeerInfoData, RPCPoxInfoData};
@@ -95,8 +96,10 @@ use vm::{
     database::{
         marf::ContractCommitment, ClarityDatabase, ClaritySerializable, MarfedKV, STXBalance,
     },
+    errors::CheckErrors,
     errors::Error as ClarityRuntimeError,
     errors::InterpreterError,
+    trace::CallTracer,
     types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
     ClarityName, ContractName, SymbolicExpression, Value,
 };
@@ -1013,6 +1016,7 @@ impl ConversationHttp {
                     let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
                     let (balance, balance_proof) = clarity_db
                         .get_with_proof::<STXBalance>(&key)
+                        .expect("BUG: failed to read from the MARF with a proof")
                         .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
                         .unwrap_or_else(|| (STXBalance::zero(), "".into()));
                     let balance_proof = if with_proof {
@@ -1023,6 +1027,7 @@ impl ConversationHttp {
                     let key = ClarityDatabase::make_key_for_account_nonce(&account);
                     let (nonce, nonce_proof) = clarity_db
                         .get_with_proof(&key)
+                        .expect("BUG: failed to read from the MARF with a proof")
                         .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
                         .unwrap_or_else(|| (0, "".into()));
                     let nonce_proof = if with_proof { Some(nonce_proof) } else { None };
@@ -1082,6 +1087,7 @@ impl ConversationHttp {
                     );
                     let (value, marf_proof) = clarity_db
                         .get_with_proof::<Value>(&key)
+                        .expect("BUG: failed to read from the MARF with a proof")
                         .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
                         .unwrap_or_else(|| {
                             test_debug!("No value for '{}' in {}", &key, tip);
@@ -1111,8 +1117,93 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a GET to list a page of the entries of a smart contract's data map -- or of the
+    /// tokens of an NFT with their owners -- given the current chain tip.  Entries are listed in
+    /// order of their serialized keys, starting after the serialized key `after`.
+    fn handle_get_map_entries<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        map_name: &ClarityName,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let response =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let page = clarity_db.list_map_or_nft_entries(
+                        &contract_identifier,
+                        map_name,
+                        after,
+                        limit,
+                    );
+
+                    page.map(|(entries, next)| {
+                        test_debug!(
+                            "Listed {} entries of '{}' in {}",
+                            entries.len(),
+                            map_name.as_str(),
+                            tip
+                        );
+                        let entries = entries
+                            .into_iter()
+                            .map(|(key, value)| MapEntriesResponseEntry {
+                                key: format!("0x{}", key.serialize()),
+                                value: format!("0x{}", value.serialize()),
+                            })
+                            .collect();
+                        let next = next.map(|cursor| format!("0x{}", cursor));
+                        MapEntriesResponse { entries, next }
+                    })
+                })
+            }) {
+                Ok(Some(Ok(data))) => HttpResponseType::GetMapEntries(response_metadata, data),
+                Ok(Some(Err(ClarityRuntimeError::Unchecked(CheckErrors::NoSuchMap(_)))))
+                | Ok(Some(Err(ClarityRuntimeError::Unchecked(CheckErrors::NoSuchNFT(_)))))
+                | Ok(Some(Err(ClarityRuntimeError::Unchecked(CheckErrors::NoSuchContract(_))))) => {
+                    HttpResponseType::NotFound(response_metadata, "No such data map or NFT".into())
+                }
+                Ok(Some(Err(ClarityRuntimeError::Interpreter(
+                    InterpreterError::KeyIndexUnavailable,
+                )))) => HttpResponseType::NotFound(
+                    response_metadata,
+                    "Data map and NFT entries are not indexed by this node".into(),
+                ),
+                Ok(Some(Err(e))) => {
+                    debug!(
+                        "Failed to list entries of '{}' in {}: {:?}",
+                        map_name.as_str(),
+                        tip,
+                        &e
+                    );
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        format!("Failed to list entries of '{}'", map_name.as_str()),
+                    )
+                }
+                Ok(None) | Err(_) => {
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                }
+            };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a POST to run a read-only function call with the given parameters on the given chain
     /// tip.  Returns the result of the function call.  Returns a CallReadOnlyResponse on success.
+    /// If `trace` is set, the response also carries the call tree of the function's execution.
+    /// If `with_proof` is set, the response also carries a MARF proof for each key the call read
+    /// from the chain tip, so that the call can be re-run against only the proven keys (see
+    /// `chainstate::stacks::index::proofs::verify_read_only_call`).
     fn handle_readonly_function_call<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
@@ -1125,6 +1216,8 @@ impl ConversationHttp {
         function: &ClarityName,
         sender: &PrincipalData,
         args: &[Value],
+        trace: bool,
+        with_proof: bool,
         options: &ConnectionOptions,
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
@@ -1136,6 +1229,8 @@ impl ConversationHttp {
             .map(|x| SymbolicExpression::atom_value(x.clone()))
             .collect();
         let mainnet = chainstate.mainnet;
+        let mut call_trace = None;
+        let mut call_proofs = None;
         let data_opt_res =
             chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
                 let cost_track = clarity_tx
@@ -1151,7 +1246,34 @@ impl ConversationHttp {
                     })?;
 
                 clarity_tx.with_readonly_clarity_env(mainnet, sender.clone(), cost_track, |env| {
-                    env.execute_contract(&contract_identifier, function.as_str(), &args, true)
+                    if trace {
+                        env.global_context.tracer = Some(CallTracer::new());
+                    }
+                    if with_proof {
+                        env.global_context.database.begin_read_proofs();
+                    }
+                    let result =
+                        env.execute_contract(&contract_identifier, function.as_str(), &args, true);
+                    call_trace = env
+                        .global_context
+                        .tracer
+                        .take()
+                        .map(|tracer| tracer.json_serialize());
+                    if with_proof {
+                        let proofs = env
+                            .global_context
+                            .database
+                            .take_read_proofs()
+                            .into_iter()
+                            .map(|(key, value, proof)| CallReadOnlyProof {
+                                key,
+                                value,
+                                proof: format!("0x{}", proof.to_hex()),
+                            })
+                            .collect();
+                        call_proofs = Some(proofs);
+                    }
+                    result
                 })
             });
 
@@ -1162,6 +1284,8 @@ impl ConversationHttp {
                     okay: true,
                     result: Some(format!("0x{}", data.serialize())),
                     cause: None,
+                    trace: call_trace,
+                    proofs: call_proofs,
                 },
             ),
             Ok(Some(Err(e))) => HttpResponseType::CallReadOnlyFunction(
@@ -1170,6 +1294,8 @@ impl ConversationHttp {
                     okay: false,
                     result: None,
                     cause: Some(e.to_string()),
+                    trace: call_trace,
+                    proofs: call_proofs,
                 },
             ),
             Ok(None) | Err(_) => {
@@ -1205,6 +1331,7 @@ impl ConversationHttp {
                         MarfedKV::make_contract_hash_key(&contract_identifier);
                     let (contract_commit, proof) = db
                         .get_with_proof::<ContractCommitment>(&contract_commit_key)
+                        .expect("BUG: failed to read from the MARF with a proof")
                         .expect("BUG: obtained source, but couldn't get MARF proof.");
                     let marf_proof = if with_proof {
                         Some(proof.to_hex())
@@ -1428,7 +1555,18 @@ impl ConversationHttp {
         chainstate: &StacksChainState,
     ) -> Result<Option<StacksBlockId>, net_error> {
         match tip_opt {
-            Some(tip) => Ok(Some(*tip).clone()),
+            Some(tip) => {
+                if chainstate.clarity_state.is_pruned(tip) {
+                    // non-archival node
+                    let response_metadata = HttpResponseMetadata::from(req);
+                    let response = HttpResponseType::NotFound(
+                        response_metadata,
+                        format!("State at chain tip {} has been pruned", tip),
+                    );
+                    return response.send(http, fd).and_then(|_| Ok(None));
+                }
+                Ok(Some(*tip).clone())
+            }
             None => match chainstate.get_stacks_chain_tip(sortdb)? {
                 Some(tip) => Ok(Some(StacksBlockHeader::make_index_block_hash(
                     &tip.consensus_hash,
@@ -1754,6 +1892,39 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetMapEntries(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref map_name,
+                ref after_opt,
+                ref limit,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_map_entries(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        contract_addr,
+                        contract_name,
+                        map_name,
+                        after_opt.as_ref().map(|x| x.as_str()),
+                        *limit,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetTransferCost(ref _md) => {
                 ConversationHttp::handle_token_transfer_cost(
                     &mut self.connection.protocol,
@@ -1797,6 +1968,8 @@ impl ConversationHttp {
                 ref func_name,
                 ref args,
                 ref tip_opt,
+                ref trace,
+                ref with_proof,
             ) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -1818,6 +1991,8 @@ impl ConversationHttp {
                         func_name,
                         as_sender,
                         args,
+                        *trace,
+                        *with_proof,
                         &self.connection.options,
                     )?;
                 }
@@ -2471,6 +2646,27 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for a page of the entries of a data map (or an NFT)
+    pub fn new_getmapentries(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        map_name: ClarityName,
+        after: Option<String>,
+        limit: u32,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetMapEntries(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            map_name,
+            after,
+            limit,
+            tip_opt,
+        )
+    }
+
     /// Make a new request to get a contract's source
     pub fn new_getcontractsrc(
         &self,
@@ -2512,6 +2708,8 @@ impl ConversationHttp {
         function_name: ClarityName,
         function_args: Vec<Value>,
         tip_opt: Option<StacksBlockId>,
+        trace: bool,
+        with_proof: bool,
     ) -> HttpRequestType {
         HttpRequestType::CallReadOnlyFunction(
             HttpRequestMetadata::from_host(self.peer_host.clone()),
@@ -2521,6 +2719,8 @@ impl ConversationHttp {
             function_name,
             function_args,
             tip_opt,
+            trace,
+            with_proof,
         )
     }
 
@@ -4080,6 +4280,67 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_get_map_entries() {
+        test_rpc(
+            "test_rpc_get_map_entries",
+            40210,
+            40211,
+            50210,
+            50211,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getmapentries(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "unit-map".try_into().unwrap(),
+                    None,
+                    10,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetMapEntries(response_md, data) => {
+                        let principal =
+                            StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                                .unwrap()
+                                .to_account_principal();
+                        assert_eq!(data.entries.len(), 1);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.entries[0].key).unwrap(),
+                            Value::Tuple(
+                                TupleData::from_data(vec![(
+                                    "account".into(),
+                                    Value::Principal(principal)
+                                )])
+                                .unwrap()
+                            )
+                        );
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.entries[0].value).unwrap(),
+                            Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(123))])
+                                    .unwrap()
+                            )
+                        );
+                        assert!(data.next.is_none());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_get_contract_abi() {
@@ -4179,6 +4440,8 @@ mod test {
                     "ro-test".try_into().unwrap(),
                     vec![],
                     None,
+                    false,
+                    false,
                 )
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
@@ -4230,6 +4493,8 @@ mod test {
                     "ro-test".try_into().unwrap(),
                     vec![],
                     Some(unconfirmed_tip),
+                    false,
+                    false,
                 )
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
@@ -4254,6 +4519,116 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_call_read_only_trace() {
+        test_rpc(
+            "test_rpc_call_read_only_trace",
+            40190,
+            40191,
+            50190,
+            50191,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_callreadonlyfunction(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    "ro-test".try_into().unwrap(),
+                    vec![],
+                    Some(unconfirmed_tip),
+                    true,
+                    false,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
+                        assert!(data.okay);
+                        let trace = data.trace.clone().unwrap();
+                        assert_eq!(trace[0]["function"], "ro-test");
+                        assert_eq!(trace[0]["result"], "(ok 1)");
+                        assert_eq!(trace[0]["children"].as_array().unwrap().len(), 0);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_call_read_only_proof() {
+        test_rpc(
+            "test_rpc_call_read_only_proof",
+            40200,
+            40201,
+            50200,
+            50201,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_callreadonlyfunction(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    "ro-test".try_into().unwrap(),
+                    vec![],
+                    Some(unconfirmed_tip),
+                    false,
+                    true,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
+                        assert!(data.okay);
+                        // the call reads its contract's commitment.
+                        let proofs = data.proofs.clone().unwrap();
+                        assert!(proofs
+                            .iter()
+                            .any(|proof| proof.key.contains("hello-world-unconfirmed")));
+                        assert!(proofs.iter().all(|proof| proof.proof.starts_with("0x")));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_getattachmentsinv_limit_reached() {
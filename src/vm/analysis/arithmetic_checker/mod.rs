@@ -118,6 +118,7 @@ impl ArithmeticOnlyChecker {
                 Trait { .. } => Err(Error::DefineTypeForbidden(DefineFunctions::Trait)),
                 UseTrait { .. } => Err(Error::DefineTypeForbidden(DefineFunctions::UseTrait)),
                 ImplTrait { .. } => Err(Error::DefineTypeForbidden(DefineFunctions::ImplTrait)),
+                Event { .. } => Err(Error::DefineTypeForbidden(DefineFunctions::Event)),
             }
         } else {
             self.check_expression(expr)
@@ -168,11 +169,14 @@ impl ArithmeticOnlyChecker {
             FetchVar | GetBlockInfo | GetTokenBalance | GetAssetOwner | FetchEntry | SetEntry
             | DeleteEntry | InsertEntry | SetVar | MintAsset | MintToken | TransferAsset
             | TransferToken | ContractCall | StxTransfer | StxBurn | AtBlock | GetStxBalance
-            | GetTokenSupply | BurnToken | BurnAsset => {
+            | StxGetAccount | GetTokenSupply | BurnToken | BurnAsset => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Append | Concat | AsMaxLen | ContractOf | PrincipalOf | ListCons | Print
-            | AsContract | ElementAt | IndexOf | Map | Filter | Fold => {
+            | AsContract | ElementAt | IndexOf | Map | Filter | Fold | BuffToIntLe
+            | BuffToUIntLe | BuffToIntBe | BuffToUIntBe | IntToAscii | StringToInt | Slice
+            | ReplaceAt | IsStandard | PrincipalDestruct | PrincipalConstruct | ToConsensusBuff
+            | FromConsensusBuff | EmitEvent => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Sha512 | Sha512Trunc256 | Secp256k1Recover | Secp256k1Verify | Hash160 | Sha256
@@ -180,10 +184,11 @@ impl ArithmeticOnlyChecker {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Add | Subtract | Divide | Multiply | CmpGeq | CmpLeq | CmpLess | CmpGreater
-            | Modulo | Power | Sqrti | Log2 | BitwiseXOR | And | Or | Not | Equals | If
-            | ConsSome | ConsOkay | ConsError | DefaultTo | UnwrapRet | UnwrapErrRet | IsOkay
-            | IsNone | Asserts | Unwrap | UnwrapErr | IsErr | IsSome | TryRet | ToUInt | ToInt
-            | Len | Begin | TupleMerge => self.check_all(args),
+            | Modulo | Power | Sqrti | Log2 | BitwiseXOR | BitwiseAnd | BitwiseOr | BitwiseNot
+            | BitwiseLShift | BitwiseRShift | And | Or | Not | Equals | If | ConsSome
+            | ConsOkay | ConsError | DefaultTo | UnwrapRet | UnwrapErrRet | IsOkay | IsNone
+            | Asserts | Unwrap | UnwrapErr | IsErr | IsSome | TryRet | ToUInt | ToInt | Len
+            | Begin | TupleMerge => self.check_all(args),
             // we need to treat all the remaining functions specially, because these
             //   do not eval all of their arguments (rather, one or more of their arguments
             //   is a name)
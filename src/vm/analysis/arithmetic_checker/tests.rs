@@ -197,6 +197,13 @@ fn test_functions() {
          FunctionNotPermitted(NativeFunctions::Sha512)),
         ("(define-private (foo) (sha512/256 0))",
          FunctionNotPermitted(NativeFunctions::Sha512Trunc256)),
+        ("(define-private (foo) (buff-to-uint-le 0x01))",
+         FunctionNotPermitted(NativeFunctions::BuffToUIntLe)),
+        ("(define-private (foo) (int-to-ascii 1))",
+         FunctionNotPermitted(NativeFunctions::IntToAscii)),
+        ("(define-private (foo (a (list 3 uint)))
+           (slice a u0 u2))",
+         FunctionNotPermitted(NativeFunctions::Slice)),
 
     ];
 
@@ -225,6 +232,8 @@ fn test_functions() {
                  (c (if (and (< 3 2) (<= 3 2) (>= 4 5) (or (is-eq (mod 5 4) 0) (not (> 3 2))))
                     (pow u2 (log2 (sqrti u100000)))
                     (xor u120 u280)))
+                 (e (bit-or (bit-and u12 u10) (bit-not u0)))
+                 (f (bit-shift-right (bit-shift-left 1 u4) u2))
                  (d (default-to u0 (some u2))))
              (begin (unwrap! (some u3) u1)
                     (unwrap-err! (err u5) u4)
@@ -31,6 +31,7 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         map_types,
         fungible_tokens,
         non_fungible_tokens,
+        defined_events,
         defined_traits: _,
         implemented_traits: _,
         expressions: _,
@@ -90,6 +91,10 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
             fungible_tokens,
         ));
 
+    contract_interface
+        .events
+        .append(&mut ContractInterfaceEvent::from_map(defined_events));
+
     contract_interface
 }
 
@@ -348,6 +353,25 @@ impl ContractInterfaceMap {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceEvent {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_f: ContractInterfaceAtomType,
+}
+
+impl ContractInterfaceEvent {
+    pub fn from_map(events: &BTreeMap<ClarityName, TypeSignature>) -> Vec<Self> {
+        events
+            .iter()
+            .map(|(name, type_sig)| Self {
+                name: name.clone().into(),
+                type_f: ContractInterfaceAtomType::from_type_signature(type_sig),
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContractInterface {
     pub functions: Vec<ContractInterfaceFunction>,
@@ -355,6 +379,8 @@ pub struct ContractInterface {
     pub maps: Vec<ContractInterfaceMap>,
     pub fungible_tokens: Vec<ContractInterfaceFungibleTokens>,
     pub non_fungible_tokens: Vec<ContractInterfaceNonFungibleTokens>,
+    #[serde(default)]
+    pub events: Vec<ContractInterfaceEvent>,
 }
 
 impl ContractInterface {
@@ -365,6 +391,7 @@ impl ContractInterface {
             maps: Vec::new(),
             fungible_tokens: Vec::new(),
             non_fungible_tokens: Vec::new(),
+            events: Vec::new(),
         }
     }
 
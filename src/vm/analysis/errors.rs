@@ -105,10 +105,15 @@ pub enum CheckErrors {
     BadMapName,
     NoSuchMap(String),
 
+    // events
+    BadEventName,
+    NoSuchEvent(String),
+
     // defines
     DefineFunctionBadSignature,
     BadFunctionName,
     BadMapTypeDefinition,
+    BadEventTypeDefinition,
     PublicFunctionMustReturnResponse(TypeSignature),
     DefineVariableBadSignature,
     ReturnTypesMustMatch(TypeSignature, TypeSignature),
@@ -353,9 +358,12 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::BadBurnFTArguments => format!("burn expects a uint amount and from principal"),
             CheckErrors::BadMapName => format!("invalid map name"),
             CheckErrors::NoSuchMap(map_name) => format!("use of unresolved map '{}'", map_name),
+            CheckErrors::BadEventName => format!("expecting an event name as an argument"),
+            CheckErrors::NoSuchEvent(event_name) => format!("use of undeclared event '{}'", event_name),
             CheckErrors::DefineFunctionBadSignature => format!("invalid function definition"),
             CheckErrors::BadFunctionName => format!("invalid function name"),
             CheckErrors::BadMapTypeDefinition => format!("invalid map definition"),
+            CheckErrors::BadEventTypeDefinition => format!("invalid event definition"),
             CheckErrors::PublicFunctionMustReturnResponse(found_type) => format!("public functions must return an expression of type 'response', found '{}'", found_type),
             CheckErrors::DefineVariableBadSignature => format!("invalid variable definition"),
             CheckErrors::ReturnTypesMustMatch(type_1, type_2) => format!("detected two execution paths, returning two different expression types (got '{}' and '{}')", type_1, type_2),
@@ -0,0 +1,628 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use vm::analysis::types::ContractAnalysis;
+use vm::diagnostic::{DiagnosableError, Diagnostic};
+use vm::functions::define::DefineFunctionsParsed;
+use vm::functions::NativeFunctions;
+use vm::representations::SymbolicExpressionType::TraitReference;
+use vm::representations::{ClarityName, SymbolicExpression};
+use vm::types::{FunctionType, TypeSignature};
+use vm::variables::NativeVariables;
+
+#[cfg(test)]
+mod tests;
+
+define_named_enum!(LintRule {
+    UnusedPrivateFunction("unused-private-function"),
+    UnusedConstant("unused-constant"),
+    UnusedLetBinding("unused-let-binding"),
+    UnwrapPanicInPublic("unwrap-panic-in-public"),
+    TxSenderAuthorization("tx-sender-authorization"),
+    UnreachableErr("unreachable-err"),
+    AsContractUserPrincipal("as-contract-user-principal"),
+});
+
+/// The warnings reported by lint passes. Unlike CheckErrors, these never reject a
+///  contract: they flag valid code which is likely to be a mistake.
+#[derive(Debug, PartialEq)]
+pub enum LintWarnings {
+    UnusedPrivateFunction(ClarityName),
+    UnusedConstant(ClarityName),
+    UnusedLetBinding(ClarityName),
+    UnwrapPanicInPublic(ClarityName),
+    TxSenderAuthorization(ClarityName),
+    UnreachableErrReturn(ClarityName),
+    UnreachableErrBranch,
+    AsContractUserPrincipal(ClarityName),
+}
+
+impl LintWarnings {
+    pub fn rule(&self) -> LintRule {
+        match self {
+            LintWarnings::UnusedPrivateFunction(_) => LintRule::UnusedPrivateFunction,
+            LintWarnings::UnusedConstant(_) => LintRule::UnusedConstant,
+            LintWarnings::UnusedLetBinding(_) => LintRule::UnusedLetBinding,
+            LintWarnings::UnwrapPanicInPublic(_) => LintRule::UnwrapPanicInPublic,
+            LintWarnings::TxSenderAuthorization(_) => LintRule::TxSenderAuthorization,
+            LintWarnings::UnreachableErrReturn(_) | LintWarnings::UnreachableErrBranch => {
+                LintRule::UnreachableErr
+            }
+            LintWarnings::AsContractUserPrincipal(_) => LintRule::AsContractUserPrincipal,
+        }
+    }
+}
+
+impl DiagnosableError for LintWarnings {
+    fn message(&self) -> String {
+        match self {
+            LintWarnings::UnusedPrivateFunction(name) => {
+                format!("private function '{}' is never called", name)
+            }
+            LintWarnings::UnusedConstant(name) => format!("constant '{}' is never used", name),
+            LintWarnings::UnusedLetBinding(name) => {
+                format!("let binding '{}' is never used", name)
+            }
+            LintWarnings::UnwrapPanicInPublic(name) => format!(
+                "public function '{}' can abort with a runtime error via unwrap-panic",
+                name
+            ),
+            LintWarnings::TxSenderAuthorization(name) => format!(
+                "public function '{}' authorizes the caller by comparing tx-sender",
+                name
+            ),
+            LintWarnings::UnreachableErrReturn(name) => format!(
+                "function '{}' returns a response, but never returns an err",
+                name
+            ),
+            LintWarnings::UnreachableErrBranch => {
+                "the err branch is unreachable: the response is never an err".into()
+            }
+            LintWarnings::AsContractUserPrincipal(name) => format!(
+                "as-contract acts on '{}', which is supplied by the caller",
+                name
+            ),
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match self {
+            LintWarnings::UnusedPrivateFunction(_)
+            | LintWarnings::UnusedConstant(_)
+            | LintWarnings::UnusedLetBinding(_) => Some("remove it, or use it".into()),
+            LintWarnings::UnwrapPanicInPublic(_) => {
+                Some("use unwrap! or try! to return an err the caller can handle".into())
+            }
+            LintWarnings::TxSenderAuthorization(_) => Some(
+                "any contract the sender calls can act as tx-sender; compare contract-caller instead"
+                    .into(),
+            ),
+            LintWarnings::UnreachableErrReturn(_) => {
+                Some("if the function can't fail, it doesn't need to return a response".into())
+            }
+            LintWarnings::UnreachableErrBranch => None,
+            LintWarnings::AsContractUserPrincipal(_) => Some(
+                "check the principal against an allow-list before acting on it as the contract"
+                    .into(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LintWarning {
+    pub warning: LintWarnings,
+    pub diagnostic: Diagnostic,
+}
+
+impl LintWarning {
+    pub fn new(warning: LintWarnings, expr: &SymbolicExpression) -> LintWarning {
+        let mut diagnostic = Diagnostic::warn(&warning);
+        diagnostic.spans = vec![expr.span.clone()];
+        LintWarning {
+            warning,
+            diagnostic,
+        }
+    }
+
+    pub fn rule(&self) -> LintRule {
+        self.warning.rule()
+    }
+}
+
+/// Which lint rules to run. By default, every rule is enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintConfig {
+    allowed: HashSet<LintRule>,
+}
+
+impl LintConfig {
+    pub fn new() -> LintConfig {
+        LintConfig {
+            allowed: HashSet::new(),
+        }
+    }
+
+    /// Stop reporting `rule`.
+    pub fn allow(&mut self, rule: LintRule) {
+        self.allowed.insert(rule);
+    }
+
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        !self.allowed.contains(&rule)
+    }
+}
+
+/// A lint pass inspects an already type-checked contract.
+pub trait LintPass {
+    fn run_pass(contract_analysis: &ContractAnalysis, warnings: &mut Vec<LintWarning>);
+}
+
+/// Run every lint pass over a type-checked contract, returning the warnings of the enabled
+///  rules in source order.
+pub fn run_lints(contract_analysis: &ContractAnalysis, config: &LintConfig) -> Vec<LintWarning> {
+    let mut warnings = vec![];
+    UnusedDefinitionsLint::run_pass(contract_analysis, &mut warnings);
+    UnusedLetBindingsLint::run_pass(contract_analysis, &mut warnings);
+    PublicFunctionLint::run_pass(contract_analysis, &mut warnings);
+    UnreachableErrLint::run_pass(contract_analysis, &mut warnings);
+
+    warnings.retain(|warning| config.is_enabled(warning.rule()));
+    warnings.sort_by_key(|warning| {
+        warning
+            .diagnostic
+            .spans
+            .get(0)
+            .map(|span| (span.start_line, span.start_column))
+    });
+    warnings
+}
+
+fn for_each_expression<F>(expr: &SymbolicExpression, f: &mut F)
+where
+    F: FnMut(&SymbolicExpression),
+{
+    f(expr);
+    if let Some(list) = expr.match_list() {
+        for child in list.iter() {
+            for_each_expression(child, f);
+        }
+    }
+}
+
+/// If `expr` is an application of the named native, return its arguments.
+fn match_native<'a>(
+    expr: &'a SymbolicExpression,
+    function: NativeFunctions,
+) -> Option<&'a [SymbolicExpression]> {
+    let (head, args) = expr.match_list()?.split_first()?;
+    if head.match_atom()?.as_str() == function.get_name_str() {
+        Some(args)
+    } else {
+        None
+    }
+}
+
+fn is_native_variable(expr: &SymbolicExpression, variable: NativeVariables) -> bool {
+    expr.match_atom()
+        .map(|name| name.as_str() == variable.get_name_str())
+        .unwrap_or(false)
+}
+
+/// The evaluated parts of a top-level expression: function bodies, constant values, etc.
+fn evaluated_expressions(expr: &SymbolicExpression) -> Vec<&SymbolicExpression> {
+    use vm::functions::define::DefineFunctionsParsed::*;
+    match DefineFunctionsParsed::try_parse(expr) {
+        Ok(Some(define)) => match define {
+            Constant { value, .. } => vec![value],
+            PrivateFunction { body, .. }
+            | ReadOnlyFunction { body, .. }
+            | PublicFunction { body, .. } => vec![body],
+            BoundedFungibleToken { max_supply, .. } => vec![max_supply],
+            PersistedVariable { initial, .. } => vec![initial],
+            _ => vec![],
+        },
+        Ok(None) => vec![expr],
+        Err(_) => vec![],
+    }
+}
+
+/// Reports private functions and constants which are never referred to. A name is only
+///  referred to where it is evaluated: not where it is bound by a `let`, a `match` or a
+///  function argument (or shadowed by one), nor where it names a tuple field or a function
+///  of another contract.
+pub struct UnusedDefinitionsLint();
+
+impl LintPass for UnusedDefinitionsLint {
+    fn run_pass(contract_analysis: &ContractAnalysis, warnings: &mut Vec<LintWarning>) {
+        let mut definitions = vec![];
+        let mut references = HashSet::new();
+        for expr in contract_analysis.expressions.iter() {
+            let mut locals = vec![];
+            match DefineFunctionsParsed::try_parse(expr) {
+                Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, .. })) => {
+                    if let Some(name) = signature.get(0).and_then(|x| x.match_atom()) {
+                        definitions.push((LintWarnings::UnusedPrivateFunction(name.clone()), expr));
+                    }
+                    locals = UnusedDefinitionsLint::argument_names(signature);
+                }
+                Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, .. }))
+                | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, .. })) => {
+                    locals = UnusedDefinitionsLint::argument_names(signature);
+                }
+                Ok(Some(DefineFunctionsParsed::Constant { name, .. })) => {
+                    definitions.push((LintWarnings::UnusedConstant(name.clone()), expr));
+                }
+                _ => {}
+            }
+
+            for evaluated in evaluated_expressions(expr) {
+                UnusedDefinitionsLint::collect_references(evaluated, &mut locals, &mut references);
+            }
+        }
+
+        for (warning, expr) in definitions.into_iter() {
+            let name = match warning {
+                LintWarnings::UnusedPrivateFunction(ref name)
+                | LintWarnings::UnusedConstant(ref name) => name.clone(),
+                _ => continue,
+            };
+            if !references.contains(&name) {
+                warnings.push(LintWarning::new(warning, expr));
+            }
+        }
+    }
+}
+
+impl UnusedDefinitionsLint {
+    fn argument_names(signature: &[SymbolicExpression]) -> Vec<ClarityName> {
+        signature
+            .iter()
+            .skip(1)
+            .filter_map(|argument| Some(argument.match_list()?.get(0)?.match_atom()?.clone()))
+            .collect()
+    }
+
+    /// Add the names `body` refers to, with `name` bound in it, to `references`.
+    fn collect_bound_references(
+        name: &SymbolicExpression,
+        body: &SymbolicExpression,
+        locals: &mut Vec<ClarityName>,
+        references: &mut HashSet<ClarityName>,
+    ) {
+        let scope = locals.len();
+        if let Some(name) = name.match_atom() {
+            locals.push(name.clone());
+        }
+        UnusedDefinitionsLint::collect_references(body, locals, references);
+        locals.truncate(scope);
+    }
+
+    /// Add the names `expr` refers to, other than the `locals` in scope, to `references`.
+    fn collect_references(
+        expr: &SymbolicExpression,
+        locals: &mut Vec<ClarityName>,
+        references: &mut HashSet<ClarityName>,
+    ) {
+        if let Some(name) = expr.match_atom() {
+            if !locals.contains(name) {
+                references.insert(name.clone());
+            }
+        } else if let Some(args) = match_native(expr, NativeFunctions::Let) {
+            // each binding is in scope in the values of the bindings after it, and in the body.
+            let scope = locals.len();
+            if let Some((bindings, body)) = args.split_first() {
+                for binding in bindings.match_list().unwrap_or(&[]).iter() {
+                    let pair = binding.match_list().unwrap_or(&[]);
+                    if let Some(value) = pair.get(1) {
+                        UnusedDefinitionsLint::collect_references(value, locals, references);
+                    }
+                    if let Some(name) = pair.get(0).and_then(|x| x.match_atom()) {
+                        locals.push(name.clone());
+                    }
+                }
+                for x in body.iter() {
+                    UnusedDefinitionsLint::collect_references(x, locals, references);
+                }
+            }
+            locals.truncate(scope);
+        } else if let Some(args) = match_native(expr, NativeFunctions::Match) {
+            // (match input some-name some-branch none-branch), or
+            //  (match input ok-name ok-branch err-name err-branch)
+            if let Some(input) = args.get(0) {
+                UnusedDefinitionsLint::collect_references(input, locals, references);
+            }
+            match args.get(1..).unwrap_or(&[]) {
+                [some_name, some_branch, none_branch] => {
+                    UnusedDefinitionsLint::collect_bound_references(
+                        some_name,
+                        some_branch,
+                        locals,
+                        references,
+                    );
+                    UnusedDefinitionsLint::collect_references(none_branch, locals, references);
+                }
+                [ok_name, ok_branch, err_name, err_branch] => {
+                    UnusedDefinitionsLint::collect_bound_references(
+                        ok_name, ok_branch, locals, references,
+                    );
+                    UnusedDefinitionsLint::collect_bound_references(
+                        err_name, err_branch, locals, references,
+                    );
+                }
+                branches => {
+                    for x in branches.iter() {
+                        UnusedDefinitionsLint::collect_references(x, locals, references);
+                    }
+                }
+            }
+        } else if let Some(args) = match_native(expr, NativeFunctions::TupleCons) {
+            // skip the field names
+            for pair in args.iter() {
+                if let Some(value) = pair.match_list().and_then(|pair| pair.get(1)) {
+                    UnusedDefinitionsLint::collect_references(value, locals, references);
+                }
+            }
+        } else if let Some(args) = match_native(expr, NativeFunctions::TupleGet) {
+            for x in args.iter().skip(1) {
+                UnusedDefinitionsLint::collect_references(x, locals, references);
+            }
+        } else if let Some(args) = match_native(expr, NativeFunctions::ContractCall) {
+            // the second argument names a function of the called contract
+            for (ix, x) in args.iter().enumerate() {
+                if ix != 1 {
+                    UnusedDefinitionsLint::collect_references(x, locals, references);
+                }
+            }
+        } else if let Some(list) = expr.match_list() {
+            for x in list.iter() {
+                UnusedDefinitionsLint::collect_references(x, locals, references);
+            }
+        }
+    }
+}
+
+/// Reports `let` bindings which are never referred to in the body of the `let`.
+pub struct UnusedLetBindingsLint();
+
+impl LintPass for UnusedLetBindingsLint {
+    fn run_pass(contract_analysis: &ContractAnalysis, warnings: &mut Vec<LintWarning>) {
+        for expr in contract_analysis.expressions.iter() {
+            for evaluated in evaluated_expressions(expr) {
+                for_each_expression(evaluated, &mut |x| {
+                    if let Some(args) = match_native(x, NativeFunctions::Let) {
+                        UnusedLetBindingsLint::check_let(args, warnings);
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl UnusedLetBindingsLint {
+    fn check_let(args: &[SymbolicExpression], warnings: &mut Vec<LintWarning>) {
+        let (bindings, body) = match args.split_first() {
+            Some((bindings, body)) => (bindings, body),
+            None => return,
+        };
+        let mut references = HashSet::new();
+        for expr in body.iter() {
+            UnusedLetBindingsLint::collect_references(expr, &mut references);
+        }
+
+        // a binding is in scope in the body, and in the values of the bindings after it,
+        //  so the bindings are checked last to first.
+        let mut unused = vec![];
+        for binding in bindings.match_list().unwrap_or(&[]).iter().rev() {
+            let pair = match binding.match_list() {
+                Some(pair) => pair,
+                None => continue,
+            };
+            if let Some(name) = pair.get(0).and_then(|x| x.match_atom()) {
+                if !references.contains(name) {
+                    unused.push(LintWarning::new(
+                        LintWarnings::UnusedLetBinding(name.clone()),
+                        binding,
+                    ));
+                }
+            }
+            if let Some(value) = pair.get(1) {
+                UnusedLetBindingsLint::collect_references(value, &mut references);
+            }
+        }
+        warnings.extend(unused.into_iter().rev());
+    }
+
+    fn collect_references(expr: &SymbolicExpression, references: &mut HashSet<ClarityName>) {
+        for_each_expression(expr, &mut |x| {
+            if let Some(name) = x.match_atom() {
+                references.insert(name.clone());
+            }
+        });
+    }
+}
+
+/// Reports risky patterns in the bodies of public functions: aborting with `unwrap-panic`,
+///  authorizing with `tx-sender`, and using `as-contract` on caller-supplied principals.
+pub struct PublicFunctionLint();
+
+impl LintPass for PublicFunctionLint {
+    fn run_pass(contract_analysis: &ContractAnalysis, warnings: &mut Vec<LintWarning>) {
+        for expr in contract_analysis.expressions.iter() {
+            if let Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) =
+                DefineFunctionsParsed::try_parse(expr)
+            {
+                let name = match signature.get(0).and_then(|x| x.match_atom()) {
+                    Some(name) => name,
+                    None => continue,
+                };
+                PublicFunctionLint::check_function(name, &signature[1..], body, warnings);
+            }
+        }
+    }
+}
+
+impl PublicFunctionLint {
+    /// The names of the arguments which are principals (or traits, i.e., contract principals).
+    fn principal_arguments(arguments: &[SymbolicExpression]) -> HashSet<ClarityName> {
+        arguments
+            .iter()
+            .filter_map(|argument| {
+                let pair = argument.match_list()?;
+                let name = pair.get(0)?.match_atom()?;
+                let is_principal = match pair.get(1)?.expr {
+                    TraitReference(..) => true,
+                    _ => pair
+                        .get(1)?
+                        .match_atom()
+                        .map(|type_name| type_name.as_str() == "principal")
+                        .unwrap_or(false),
+                };
+                if is_principal {
+                    Some(name.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn check_function(
+        name: &ClarityName,
+        arguments: &[SymbolicExpression],
+        body: &SymbolicExpression,
+        warnings: &mut Vec<LintWarning>,
+    ) {
+        let principal_arguments = PublicFunctionLint::principal_arguments(arguments);
+
+        for_each_expression(body, &mut |x| {
+            if match_native(x, NativeFunctions::Unwrap).is_some()
+                || match_native(x, NativeFunctions::UnwrapErr).is_some()
+            {
+                warnings.push(LintWarning::new(
+                    LintWarnings::UnwrapPanicInPublic(name.clone()),
+                    x,
+                ));
+            }
+
+            if let Some(args) = match_native(x, NativeFunctions::Equals) {
+                if args
+                    .iter()
+                    .any(|arg| is_native_variable(arg, NativeVariables::TxSender))
+                {
+                    warnings.push(LintWarning::new(
+                        LintWarnings::TxSenderAuthorization(name.clone()),
+                        x,
+                    ));
+                }
+            }
+
+            if let Some(args) = match_native(x, NativeFunctions::AsContract) {
+                let mut used_arguments = vec![];
+                for arg in args.iter() {
+                    for_each_expression(arg, &mut |y| {
+                        if let Some(variable) = y.match_atom() {
+                            if principal_arguments.contains(variable)
+                                && !used_arguments.contains(variable)
+                            {
+                                used_arguments.push(variable.clone());
+                            }
+                        }
+                    });
+                }
+                for variable in used_arguments.into_iter() {
+                    warnings.push(LintWarning::new(
+                        LintWarnings::AsContractUserPrincipal(variable),
+                        x,
+                    ));
+                }
+            }
+        });
+    }
+}
+
+/// Reports responses whose err case can never occur: functions returning a response that is
+///  never an err, and unwrapping forms whose err branch can't be taken.
+pub struct UnreachableErrLint();
+
+fn is_never_err(response_type: &TypeSignature) -> bool {
+    match response_type {
+        TypeSignature::ResponseType(ref types) => types.1 == TypeSignature::NoType,
+        _ => false,
+    }
+}
+
+impl LintPass for UnreachableErrLint {
+    fn run_pass(contract_analysis: &ContractAnalysis, warnings: &mut Vec<LintWarning>) {
+        let mut function_types = HashMap::new();
+        for (name, function_type) in contract_analysis
+            .private_function_types
+            .iter()
+            .chain(contract_analysis.read_only_function_types.iter())
+            .chain(contract_analysis.public_function_types.iter())
+        {
+            if let FunctionType::Fixed(ref function) = function_type {
+                function_types.insert(name.clone(), &function.returns);
+            }
+        }
+
+        for expr in contract_analysis.expressions.iter() {
+            if let Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, .. }))
+            | Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, .. }))
+            | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, .. })) =
+                DefineFunctionsParsed::try_parse(expr)
+            {
+                if let Some(name) = signature.get(0).and_then(|x| x.match_atom()) {
+                    if function_types
+                        .get(name)
+                        .map(|returns| is_never_err(returns))
+                        .unwrap_or(false)
+                    {
+                        warnings.push(LintWarning::new(
+                            LintWarnings::UnreachableErrReturn(name.clone()),
+                            expr,
+                        ));
+                    }
+                }
+            }
+
+            let type_map = match contract_analysis.type_map {
+                Some(ref type_map) => type_map,
+                None => continue,
+            };
+            for evaluated in evaluated_expressions(expr) {
+                for_each_expression(evaluated, &mut |x| {
+                    let input = [
+                        NativeFunctions::UnwrapRet,
+                        NativeFunctions::TryRet,
+                        NativeFunctions::Match,
+                    ]
+                    .iter()
+                    .filter_map(|function| match_native(x, *function))
+                    .next()
+                    .and_then(|args| args.get(0));
+                    if let Some(input) = input {
+                        if type_map.get_type(input).map(is_never_err).unwrap_or(false) {
+                            warnings.push(LintWarning::new(LintWarnings::UnreachableErrBranch, x));
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::TryFrom;
+use vm::analysis::errors::CheckErrors;
+use vm::analysis::lints::LintWarnings::*;
+use vm::analysis::lints::{run_lints, LintConfig, LintRule, LintWarnings};
+use vm::analysis::mem_type_check;
+use vm::diagnostic::Level;
+use vm::representations::ClarityName;
+
+fn name(name: &str) -> ClarityName {
+    ClarityName::try_from(name.to_string()).unwrap()
+}
+
+fn lint(contract: &str) -> Vec<LintWarnings> {
+    lint_with_config(contract, &LintConfig::new())
+}
+
+fn lint_with_config(contract: &str, config: &LintConfig) -> Vec<LintWarnings> {
+    let analysis = mem_type_check(contract).unwrap().1;
+    run_lints(&analysis, config)
+        .into_iter()
+        .map(|warning| {
+            assert_eq!(warning.diagnostic.level, Level::Warning);
+            warning.warning
+        })
+        .collect()
+}
+
+#[test]
+fn test_unused_definitions() {
+    let contract = "(define-constant used-limit u10)
+         (define-constant unused-limit u20)
+         (define-private (helper (x uint)) (+ x used-limit))
+         (define-private (unused-helper) u1)
+         (define-read-only (get-value) (helper u1))";
+    assert_eq!(
+        lint(contract),
+        vec![
+            UnusedConstant(name("unused-limit")),
+            UnusedPrivateFunction(name("unused-helper"))
+        ]
+    );
+
+    // referring to a function from another private function's body counts as a use,
+    //  as does passing it to a higher-order function.
+    let contract = "(define-private (add-one (x int)) (+ x 1))
+         (define-private (add-all (xs (list 5 int))) (map add-one xs))
+         (define-read-only (get-value) (add-all (list 1 2)))";
+    assert_eq!(lint(contract), vec![]);
+
+    // tuple fields and the functions of other contracts which share a definition's name
+    //  don't refer to it.
+    let contract = "(define-trait token-trait ((helper () (response uint uint))))
+         (define-constant limit u10)
+         (define-private (helper) u1)
+         (define-read-only (get-limit) (get limit {limit: u1}))
+         (define-public (call-helper (token <token-trait>)) (contract-call? token helper))";
+    assert_eq!(
+        lint(contract),
+        vec![
+            UnusedConstant(name("limit")),
+            UnusedPrivateFunction(name("helper"))
+        ]
+    );
+
+    // names bound by let, match and function arguments are resolved to the innermost binding.
+    //  (bindings can't shadow a definition, so only the type checker sees those contracts.)
+    let contract = "(define-constant limit u10)
+         (define-read-only (get-limit (x (optional uint)))
+           (let ((y u1)) (match x z (+ y z) limit)))";
+    assert_eq!(lint(contract), vec![]);
+    for contract in [
+        "(define-constant limit u10)
+         (define-read-only (get-limit) (let ((limit u1)) limit))",
+        "(define-constant limit u10)
+         (define-read-only (get-limit (limit uint)) limit)",
+        "(define-constant limit u10)
+         (define-read-only (get-limit (x (optional uint))) (match x limit limit u0))",
+    ]
+    .iter()
+    {
+        assert_eq!(
+            mem_type_check(contract).unwrap_err().err,
+            CheckErrors::NameAlreadyUsed("limit".into())
+        );
+    }
+}
+
+#[test]
+fn test_unused_let_bindings() {
+    let contract = "(define-read-only (get-value (x int))
+           (let ((doubled (* x 2))
+                 (tripled (* x 3)))
+             doubled))";
+    assert_eq!(lint(contract), vec![UnusedLetBinding(name("tripled"))]);
+
+    // a binding which is only used by a later binding is used.
+    let contract = "(define-read-only (get-value)
+           (let ((a 1)
+                 (b (+ a 1)))
+             b))";
+    assert_eq!(lint(contract), vec![]);
+
+    let contract = "(define-read-only (get-value)
+           (let ((a 1)
+                 (b (+ a 1))
+                 (c (* b 2)))
+             a))";
+    assert_eq!(lint(contract), vec![UnusedLetBinding(name("c"))]);
+}
+
+#[test]
+fn test_unwrap_panic_in_public() {
+    let contract = "(define-map balances principal uint)
+         (define-read-only (get-balance) (unwrap-panic (map-get? balances tx-sender)))
+         (define-public (spend (amount uint))
+           (begin
+             (asserts! (>= (unwrap-panic (map-get? balances contract-caller)) amount) (err u1))
+             (ok amount)))
+         (define-public (refund) (begin (unwrap-err-panic (spend u1)) (err u2)))";
+    assert_eq!(
+        lint(contract),
+        vec![
+            UnwrapPanicInPublic(name("spend")),
+            UnwrapPanicInPublic(name("refund"))
+        ]
+    );
+}
+
+#[test]
+fn test_tx_sender_authorization() {
+    let contract = "(define-constant owner tx-sender)
+         (define-public (withdraw (amount uint))
+           (begin
+             (asserts! (is-eq tx-sender owner) (err u1))
+             (asserts! (is-eq contract-caller owner) (err u2))
+             (ok amount)))";
+    let config = {
+        let mut config = LintConfig::new();
+        config.allow(LintRule::UnreachableErr);
+        config
+    };
+    assert_eq!(
+        lint_with_config(contract, &config),
+        vec![TxSenderAuthorization(name("withdraw"))]
+    );
+}
+
+#[test]
+fn test_unreachable_err() {
+    let contract = "(define-private (always-ok) (ok u1))
+         (define-private (may-fail (x uint)) (if (> x u1) (ok x) (err u1)))
+         (define-read-only (get-value)
+           (+ (unwrap! (always-ok) u0) (unwrap! (may-fail u2) u0)))";
+    assert_eq!(
+        lint(contract),
+        vec![
+            UnreachableErrReturn(name("always-ok")),
+            UnreachableErrBranch
+        ]
+    );
+}
+
+#[test]
+fn test_as_contract_user_principal() {
+    let contract = "(define-public (pay-out (amount uint) (recipient principal) (sender principal))
+           (begin
+             (asserts! (is-eq contract-caller sender) (err u1))
+             (as-contract (stx-transfer? amount tx-sender recipient))))";
+    assert_eq!(
+        lint(contract),
+        vec![AsContractUserPrincipal(name("recipient"))]
+    );
+}
+
+#[test]
+fn test_lint_config() {
+    let contract = "(define-constant unused-limit u20)
+         (define-private (unused-helper) u1)";
+    let mut config = LintConfig::new();
+    assert!(config.is_enabled(LintRule::UnusedConstant));
+    config.allow(LintRule::UnusedConstant);
+    assert!(!config.is_enabled(LintRule::UnusedConstant));
+    assert_eq!(
+        lint_with_config(contract, &config),
+        vec![UnusedPrivateFunction(name("unused-helper"))]
+    );
+
+    for name in LintRule::ALL_NAMES.iter() {
+        assert!(LintRule::lookup_by_name(name).is_some());
+    }
+}
@@ -18,6 +18,7 @@ pub mod analysis_db;
 pub mod arithmetic_checker;
 pub mod contract_interface_builder;
 pub mod errors;
+pub mod lints;
 pub mod read_only_checker;
 pub mod trait_checker;
 pub mod type_checker;
@@ -114,5 +115,64 @@ pub fn run_analysis(
     }
 }
 
+/// Like `run_analysis`, but reports all of the errors in the contract rather than only the
+///  first, ordered by their position in the source (see `TypeChecker::run_pass_with_recovery`).
+///  This is for developer tooling, and must not be used to analyze published contracts.
+pub fn run_analysis_with_recovery(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    analysis_db: &mut AnalysisDatabase,
+    save_contract: bool,
+    cost_tracker: LimitedCostTracker,
+) -> Result<ContractAnalysis, (Vec<CheckError>, LimitedCostTracker)> {
+    let mut contract_analysis = ContractAnalysis::new(
+        contract_identifier.clone(),
+        expressions.to_vec(),
+        cost_tracker,
+    );
+    let result = analysis_db.execute(|db| {
+        // the read-only and type checks are independent, so report the errors of both.
+        let mut errors = vec![];
+        if let Err(e) = ReadOnlyChecker::run_pass(&mut contract_analysis, db) {
+            errors.push(e);
+        }
+        if let Err(mut type_errors) =
+            TypeChecker::run_pass_with_recovery(&mut contract_analysis, db)
+        {
+            errors.append(&mut type_errors);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        TraitChecker::run_pass(&mut contract_analysis, db).map_err(|e| vec![e])?;
+        ArithmeticOnlyChecker::check_contract_cost_eligible(&mut contract_analysis);
+
+        if STORE_CONTRACT_SRC_INTERFACE {
+            let interface = build_contract_interface(&contract_analysis);
+            contract_analysis.contract_interface = Some(interface);
+        }
+        if save_contract {
+            db.insert_contract(&contract_identifier, &contract_analysis)
+                .map_err(|e| vec![e])?;
+        }
+        Ok(())
+    });
+    match result {
+        Ok(_) => Ok(contract_analysis),
+        Err(mut errors) => {
+            errors.sort_by_key(|error| {
+                error
+                    .diagnostic
+                    .spans
+                    .get(0)
+                    .map(|span| (span.start_line, span.start_column))
+                    .unwrap_or((u32::max_value(), u32::max_value()))
+            });
+            Err((errors, contract_analysis.take_contract_cost_tracker()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests;
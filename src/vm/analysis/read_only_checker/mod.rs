@@ -117,8 +117,11 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
                         self.defined_functions.insert(f_name, is_read_only);
                     }
                 }
-                Map { .. } | NonFungibleToken { .. } | UnboundedFungibleToken { .. } => {
-                    // No arguments to (define-map ...) or (define-non-fungible-token) or fungible tokens without max supplies are eval'ed.
+                Map { .. }
+                | NonFungibleToken { .. }
+                | UnboundedFungibleToken { .. }
+                | Event { .. } => {
+                    // No arguments to (define-map ...), (define-non-fungible-token), (define-event ...) or fungible tokens without max supplies are eval'ed.
                 }
                 Trait { .. } | UseTrait { .. } | ImplTrait { .. } => {
                     // No arguments to (use-trait ...), (define-trait ...). or (impl-trait) are eval'ed.
@@ -178,8 +181,23 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
             | UnwrapErrRet | IsOkay | IsNone | Asserts | Unwrap | UnwrapErr | Match | IsErr
             | IsSome | TryRet | ToUInt | ToInt | Append | Concat | AsMaxLen | ContractOf
             | PrincipalOf | ListCons | GetBlockInfo | TupleGet | TupleMerge | Len | Print
-            | AsContract | Begin | FetchVar | GetStxBalance | GetTokenBalance | GetAssetOwner
-            | GetTokenSupply | ElementAt | IndexOf => self.check_all_read_only(args),
+            | AsContract | Begin | FetchVar | GetStxBalance | StxGetAccount | GetTokenBalance
+            | GetAssetOwner | GetTokenSupply | ElementAt | IndexOf | BuffToIntLe | BuffToUIntLe
+            | BuffToIntBe | BuffToUIntBe | IntToAscii | StringToInt | Slice | ReplaceAt
+            | IsStandard | PrincipalDestruct | PrincipalConstruct | ToConsensusBuff
+            | BitwiseAnd | BitwiseOr | BitwiseNot | BitwiseLShift | BitwiseRShift => {
+                self.check_all_read_only(args)
+            }
+            FromConsensusBuff => {
+                // the first argument is a type, not an expression.
+                check_argument_count(2, args)?;
+                self.check_read_only(&args[1])
+            }
+            EmitEvent => {
+                // the first argument is the event's name.
+                check_argument_count(2, args)?;
+                self.check_read_only(&args[1])
+            }
             AtBlock => {
                 check_argument_count(2, args)?;
 
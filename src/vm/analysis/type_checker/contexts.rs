@@ -46,6 +46,7 @@ pub struct ContractContext {
     fungible_tokens: HashSet<ClarityName>,
     non_fungible_tokens: HashMap<ClarityName, TypeSignature>,
     traits: HashMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
+    events: HashMap<ClarityName, TypeSignature>,
     pub implemented_traits: HashSet<TraitIdentifier>,
 }
 
@@ -85,6 +86,7 @@ impl ContractContext {
             fungible_tokens: HashSet::new(),
             non_fungible_tokens: HashMap::new(),
             traits: HashMap::new(),
+            events: HashMap::new(),
             implemented_traits: HashSet::new(),
         }
     }
@@ -98,6 +100,7 @@ impl ContractContext {
             || self.non_fungible_tokens.contains_key(name)
             || self.traits.contains_key(name)
             || self.map_types.contains_key(name)
+            || self.events.contains_key(name)
         {
             Err(CheckError::new(CheckErrors::NameAlreadyUsed(
                 name.to_string(),
@@ -205,6 +208,16 @@ impl ContractContext {
         Ok(())
     }
 
+    pub fn add_event(
+        &mut self,
+        event_name: ClarityName,
+        event_type: TypeSignature,
+    ) -> CheckResult<()> {
+        self.check_name_used(&event_name)?;
+        self.events.insert(event_name, event_type);
+        Ok(())
+    }
+
     pub fn add_implemented_trait(&mut self, trait_identifier: TraitIdentifier) -> CheckResult<()> {
         self.implemented_traits.insert(trait_identifier);
         Ok(())
@@ -214,6 +227,10 @@ impl ContractContext {
         self.traits.get(trait_name)
     }
 
+    pub fn get_event_type(&self, event_name: &str) -> Option<&TypeSignature> {
+        self.events.get(event_name)
+    }
+
     pub fn get_map_type(&self, map_name: &str) -> Option<&(TypeSignature, TypeSignature)> {
         self.map_types.get(map_name)
     }
@@ -278,6 +295,10 @@ impl ContractContext {
         for trait_identifier in self.implemented_traits.drain() {
             contract_analysis.add_implemented_trait(trait_identifier);
         }
+
+        for (name, event_type) in self.events.drain() {
+            contract_analysis.add_defined_event(name, event_type);
+        }
     }
 }
 
@@ -75,6 +75,8 @@ pub struct TypeChecker<'a, 'b> {
     function_return_tracker: Option<Option<TypeSignature>>,
     db: &'a mut AnalysisDatabase<'b>,
     pub cost_track: LimitedCostTracker,
+    /// In recovery mode (see `run_pass_with_recovery`), the errors found so far.
+    recovered_errors: Option<Vec<CheckError>>,
 }
 
 impl CostTracker for TypeChecker<'_, '_> {
@@ -133,6 +135,79 @@ impl AnalysisPass for TypeChecker<'_, '_> {
     }
 }
 
+impl TypeChecker<'_, '_> {
+    /// Type check a contract, reporting every error found rather than only the first.
+    ///  When an expression fails to type check, the error is recorded, the expression is
+    ///  assigned the unknown type (NoType), and checking continues. Errors which only
+    ///  follow from an earlier error (e.g., a mismatch with the unknown type) are not
+    ///  reported.
+    ///
+    /// This is for developer tooling: the analysis of a published contract must stop at
+    ///  the first error, because it is charged for the work it performs.
+    pub fn run_pass_with_recovery(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+    ) -> Result<(), Vec<CheckError>> {
+        let cost_track = contract_analysis.take_contract_cost_tracker();
+        let mut command = TypeChecker::new(analysis_db, cost_track);
+        command.recovered_errors = Some(vec![]);
+
+        let result = command.run(contract_analysis);
+        let mut errors = command.recovered_errors.take().unwrap_or_default();
+        if let Err(e) = result {
+            // an error that could not be recovered from.
+            errors.push(e);
+        }
+
+        if errors.is_empty() {
+            let cost_track = command.into_contract_analysis(contract_analysis);
+            contract_analysis.replace_contract_cost_tracker(cost_track);
+            Ok(())
+        } else {
+            let TypeChecker { cost_track, .. } = command;
+            contract_analysis.replace_contract_cost_tracker(cost_track);
+            Err(errors)
+        }
+    }
+}
+
+/// Errors which leave the checker unable to continue: exceeding the cost limits, or
+///  a failure of the checker itself.
+fn is_recoverable(error: &CheckErrors) -> bool {
+    match error {
+        CheckErrors::CostOverflow
+        | CheckErrors::CostBalanceExceeded(..)
+        | CheckErrors::MemoryBalanceExceeded(..)
+        | CheckErrors::CostComputationFailed(_)
+        | CheckErrors::TypeAlreadyAnnotatedFailure
+        | CheckErrors::TypeAnnotationExpectedFailure
+        | CheckErrors::CheckerImplementationFailure => false,
+        _ => true,
+    }
+}
+
+/// Errors about a type which is unknown because of an earlier, recovered error.
+fn is_caused_by_recovery(error: &CheckErrors) -> bool {
+    use vm::analysis::errors::CheckErrors::*;
+    match error {
+        TypeError(_, actual)
+        | UnionTypeError(_, actual)
+        | ExpectedOptionalType(actual)
+        | ExpectedResponseType(actual)
+        | ExpectedOptionalOrResponseType(actual)
+        | ExpectedSequence(actual)
+        | ExpectedTuple(actual)
+        | BadMatchInput(actual)
+        | PublicFunctionMustReturnResponse(actual) => actual.is_no_type(),
+        NoSuperType(a, b)
+        | ReturnTypesMustMatch(a, b)
+        | IfArmsMustMatch(a, b)
+        | MatchArmsMustMatch(a, b)
+        | DefaultTypesMustMatch(a, b) => a.is_no_type() || b.is_no_type(),
+        _ => false,
+    }
+}
+
 pub type TypeResult = CheckResult<TypeSignature>;
 
 impl FunctionType {
@@ -234,6 +309,28 @@ impl FunctionType {
 
                 Ok(TypeSignature::BoolType)
             }
+            FunctionType::ArithmeticShift => {
+                check_argument_count(2, args)?;
+                let (input, shamt) = (&args[0], &args[1]);
+                analysis_typecheck_cost(accounting, &TypeSignature::IntType, input)?;
+                analysis_typecheck_cost(accounting, &TypeSignature::UIntType, shamt)?;
+
+                if input != &TypeSignature::IntType && input != &TypeSignature::UIntType {
+                    return Err(CheckErrors::UnionTypeError(
+                        vec![TypeSignature::IntType, TypeSignature::UIntType],
+                        input.clone(),
+                    )
+                    .into());
+                }
+
+                if shamt != &TypeSignature::UIntType {
+                    return Err(
+                        CheckErrors::TypeError(TypeSignature::UIntType, shamt.clone()).into(),
+                    );
+                }
+
+                Ok(input.clone())
+            }
         }
     }
 
@@ -322,6 +419,29 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
             contract_context: ContractContext::new(),
             function_return_tracker: None,
             type_map: TypeMap::new(),
+            recovered_errors: None,
+        }
+    }
+
+    fn recovered_error_count(&self) -> usize {
+        self.recovered_errors
+            .as_ref()
+            .map(|errors| errors.len())
+            .unwrap_or(0)
+    }
+
+    /// Outside of recovery mode, this just returns `result`. In recovery mode, a failed
+    ///  result is recorded (unless it follows from errors recovered since `errors_before`)
+    ///  and replaced with the unknown type.
+    fn recover(&mut self, result: TypeResult, errors_before: usize) -> TypeResult {
+        match (result, self.recovered_errors.as_mut()) {
+            (Err(error), Some(errors)) if is_recoverable(&error.err) => {
+                if errors.len() == errors_before && !is_caused_by_recovery(&error.err) {
+                    errors.push(error);
+                }
+                Ok(no_type())
+            }
+            (result, _) => result,
         }
     }
 
@@ -383,13 +503,20 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         let mut local_context = TypingContext::new();
 
         for exp in contract_analysis.expressions.iter() {
+            let errors_before = self.recovered_error_count();
             let mut result_res = self.try_type_check_define(&exp, &mut local_context);
             if let Err(ref mut error) = result_res {
                 if !error.has_expression() {
                     error.set_expression(&exp);
                 }
             }
-            let result = result_res?;
+            let result = match result_res {
+                Ok(result) => result,
+                Err(error) => {
+                    self.recover(Err(error), errors_before)?;
+                    continue;
+                }
+            };
             if result.is_none() {
                 // was _not_ a define statement, so handle like a normal statement.
                 self.type_check(&exp, &local_context)?;
@@ -447,6 +574,7 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
     pub fn type_check(&mut self, expr: &SymbolicExpression, context: &TypingContext) -> TypeResult {
         runtime_cost(ClarityCostFunction::AnalysisVisit, self, 0)?;
 
+        let errors_before = self.recovered_error_count();
         let mut result = self.inner_type_check(expr, context);
 
         if let Err(ref mut error) = result {
@@ -455,7 +583,7 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
             }
         }
 
-        result
+        self.recover(result, errors_before)
     }
 
     fn type_check_consecutive_statements(
@@ -712,7 +840,10 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         let expected_type = TypeSignature::parse_type_repr::<()>(var_type, &mut ())
             .map_err(|_e| CheckErrors::DefineVariableBadSignature)?;
 
-        self.type_check_expects(initial, context, &expected_type)?;
+        let errors_before = self.recovered_error_count();
+        let result = self.type_check_expects(initial, context, &expected_type);
+        // the variable's type is declared, so it can be defined even if `initial` is mistyped.
+        self.recover(result, errors_before)?;
 
         Ok((var_name.clone(), expected_type))
     }
@@ -724,7 +855,9 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         context: &mut TypingContext,
     ) -> CheckResult<ClarityName> {
         if let Some(bound) = bound {
-            self.type_check_expects(bound, context, &TypeSignature::UIntType)?;
+            let errors_before = self.recovered_error_count();
+            let result = self.type_check_expects(bound, context, &TypeSignature::UIntType);
+            self.recover(result, errors_before)?;
         }
 
         Ok(token_name.clone())
@@ -742,6 +875,17 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         Ok((asset_name.clone(), asset_type))
     }
 
+    fn type_check_define_event(
+        &mut self,
+        event_name: &ClarityName,
+        event_type: &SymbolicExpression,
+    ) -> CheckResult<(ClarityName, TypeSignature)> {
+        let event_type = TypeSignature::parse_type_repr::<()>(&event_type, &mut ())
+            .or_else(|_| Err(CheckErrors::BadEventTypeDefinition))?;
+
+        Ok((event_name.clone(), event_type))
+    }
+
     fn type_check_define_trait(
         &mut self,
         trait_name: &ClarityName,
@@ -791,7 +935,11 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                         f_type.total_type_size()?,
                     )?;
 
-                    if f_type.returns.is_response_type() {
+                    // in recovery mode, a function whose return type is unknown is still
+                    //  defined, so that calling it is not reported as an error.
+                    if f_type.returns.is_response_type()
+                        || (self.recovered_errors.is_some() && f_type.returns.is_no_type())
+                    {
                         self.contract_context
                             .add_public_function_type(f_name, FunctionType::Fixed(f_type))?;
                         return Ok(Some(()));
@@ -909,6 +1057,16 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                     self.contract_context
                         .add_implemented_trait(trait_identifier.clone())?;
                 }
+                DefineFunctionsParsed::Event { name, event_type } => {
+                    let (event_name, event_type) =
+                        self.type_check_define_event(name, event_type)?;
+                    runtime_cost(
+                        ClarityCostFunction::AnalysisBindName,
+                        self,
+                        event_type.type_size()?,
+                    )?;
+                    self.contract_context.add_event(event_name, event_type)?;
+                }
             };
             Ok(Some(()))
         } else {
@@ -22,9 +22,9 @@ use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use vm::errors::{Error as InterpError, RuntimeErrorType};
 use vm::functions::{handle_binding_list, NativeFunctions};
 use vm::types::{
-    BlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature, FunctionType, PrincipalData,
-    TupleTypeSignature, TypeSignature, Value, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
-    MAX_VALUE_SIZE,
+    BlockInfoProperty, BufferLength, FixedFunction, FunctionArg, FunctionSignature, FunctionType,
+    PrincipalData, SequenceSubtype, StringSubtype, TupleTypeSignature, TypeSignature, Value,
+    BUFF_1, BUFF_16, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65, MAX_VALUE_SIZE,
 };
 use vm::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 
@@ -73,6 +73,31 @@ fn check_special_print(
     checker.type_check(&args[0], context)
 }
 
+fn check_special_emit_event(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let event_name = args[0].match_atom().ok_or(CheckErrors::BadEventName)?;
+
+    let expected_type = checker
+        .contract_context
+        .get_event_type(event_name)
+        .ok_or(CheckErrors::NoSuchEvent(event_name.to_string()))?
+        .clone();
+
+    runtime_cost(
+        ClarityCostFunction::AnalysisTypeLookup,
+        &mut checker.cost_track,
+        expected_type.type_size()?,
+    )?;
+
+    checker.type_check_expects(&args[1], context, &expected_type)?;
+    Ok(expected_type)
+}
+
 fn check_special_as_contract(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -474,6 +499,97 @@ fn check_principal_of(
     Ok(TypeSignature::new_response(TypeSignature::PrincipalType, TypeSignature::UIntType).unwrap())
 }
 
+fn check_is_standard(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    checker.type_check_expects(&args[0], context, &TypeSignature::PrincipalType)?;
+    Ok(TypeSignature::BoolType)
+}
+
+fn check_principal_destruct(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    checker.type_check_expects(&args[0], context, &TypeSignature::PrincipalType)?;
+
+    let tuple_type = TypeSignature::TupleType(TupleTypeSignature::try_from(vec![
+        ("version".into(), BUFF_1.clone()),
+        ("hash-bytes".into(), BUFF_20.clone()),
+        (
+            "name".into(),
+            TypeSignature::new_option(TypeSignature::contract_name_string_ascii_type())?,
+        ),
+    ])?);
+    Ok(TypeSignature::new_response(tuple_type.clone(), tuple_type)?)
+}
+
+fn check_principal_construct(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_arguments_at_least(2, args)?;
+    if args.len() > 3 {
+        return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+    }
+    checker.type_check_expects(&args[0], context, &BUFF_1)?;
+    checker.type_check_expects(&args[1], context, &BUFF_20)?;
+    if args.len() > 2 {
+        checker.type_check_expects(
+            &args[2],
+            context,
+            &TypeSignature::contract_name_string_ascii_type(),
+        )?;
+    }
+
+    let error_type = TypeSignature::TupleType(TupleTypeSignature::try_from(vec![
+        ("error_code".into(), TypeSignature::UIntType),
+        (
+            "value".into(),
+            TypeSignature::new_option(TypeSignature::PrincipalType)?,
+        ),
+    ])?);
+    Ok(TypeSignature::new_response(
+        TypeSignature::PrincipalType,
+        error_type,
+    )?)
+}
+
+fn check_to_consensus_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    let input_type = checker.type_check(&args[0], context)?;
+    // values which may serialize to more than MAX_VALUE_SIZE bytes evaluate to none.
+    let buffer_size = match input_type.max_serialized_size() {
+        Some(size) if size < MAX_VALUE_SIZE => size,
+        _ => MAX_VALUE_SIZE,
+    };
+    let buffer_type = TypeSignature::SequenceType(SequenceSubtype::BufferType(
+        BufferLength::try_from(buffer_size)?,
+    ));
+    Ok(TypeSignature::new_option(buffer_type)?)
+}
+
+fn check_from_consensus_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+    checker.type_map.set_type(&args[0], no_type())?;
+    let result_type = TypeSignature::parse_type_repr(&args[0], checker)?;
+    checker.type_check_expects(&args[1], context, &TypeSignature::max_buffer())?;
+    Ok(TypeSignature::new_option(result_type)?)
+}
+
 fn check_secp256k1_recover(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -538,16 +654,21 @@ impl TypedNativeFunction {
         use self::TypedNativeFunction::{Simple, Special};
         use vm::functions::NativeFunctions::*;
         match function {
-            Add | Subtract | Divide | Multiply => {
+            Add | Subtract | Divide | Multiply | BitwiseAnd | BitwiseOr => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticVariadic))
             }
             CmpGeq | CmpLeq | CmpLess | CmpGreater => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticComparison))
             }
-            Sqrti | Log2 => Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary)),
+            Sqrti | Log2 | BitwiseNot => {
+                Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary))
+            }
             Modulo | Power | BitwiseXOR => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticBinary))
             }
+            BitwiseLShift | BitwiseRShift => {
+                Simple(SimpleNativeFunction(FunctionType::ArithmeticShift))
+            }
             And | Or => Simple(SimpleNativeFunction(FunctionType::Variadic(
                 TypeSignature::BoolType,
                 TypeSignature::BoolType,
@@ -626,6 +747,21 @@ impl TypedNativeFunction {
                 )],
                 returns: TypeSignature::UIntType,
             }))),
+            StxGetAccount => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("owner".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::TupleType(
+                    TupleTypeSignature::try_from(vec![
+                        ("locked".into(), TypeSignature::UIntType),
+                        ("unlocked".into(), TypeSignature::UIntType),
+                        ("unlock-height".into(), TypeSignature::UIntType),
+                    ])
+                    .expect("FAIL: PreDefined type failed to construct"),
+                ),
+            }))),
             StxTransfer => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![
                     FunctionArg::new(
@@ -669,6 +805,54 @@ impl TypedNativeFunction {
                 )
                 .unwrap(),
             }))),
+            BuffToIntLe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::IntType,
+            }))),
+            BuffToUIntLe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::UIntType,
+            }))),
+            BuffToIntBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::IntType,
+            }))),
+            BuffToUIntBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::UIntType,
+            }))),
+            IntToAscii => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                // 40 characters is enough for the longest uint (39 digits) or int (39 digits and a sign)
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                    BufferLength::try_from(40u32)
+                        .expect("FAIL: (string-ascii 40) should be a valid type"),
+                ))),
+            ))),
+            StringToInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::new_option(TypeSignature::IntType)
+                    .expect("FAIL: (optional int) should be a valid type"),
+            ))),
             GetTokenBalance => Special(SpecialNativeFunction(&assets::check_special_get_balance)),
             GetAssetOwner => Special(SpecialNativeFunction(&assets::check_special_get_owner)),
             TransferToken => Special(SpecialNativeFunction(&assets::check_special_transfer_token)),
@@ -694,6 +878,8 @@ impl TypedNativeFunction {
             Len => Special(SpecialNativeFunction(&sequences::check_special_len)),
             ElementAt => Special(SpecialNativeFunction(&sequences::check_special_element_at)),
             IndexOf => Special(SpecialNativeFunction(&sequences::check_special_index_of)),
+            Slice => Special(SpecialNativeFunction(&sequences::check_special_slice)),
+            ReplaceAt => Special(SpecialNativeFunction(&sequences::check_special_replace_at)),
             ListCons => Special(SpecialNativeFunction(&check_special_list_cons)),
             FetchEntry => Special(SpecialNativeFunction(&maps::check_special_fetch_entry)),
             SetEntry => Special(SpecialNativeFunction(&maps::check_special_set_entry)),
@@ -704,10 +890,16 @@ impl TypedNativeFunction {
             TupleMerge => Special(SpecialNativeFunction(&check_special_merge)),
             Begin => Special(SpecialNativeFunction(&check_special_begin)),
             Print => Special(SpecialNativeFunction(&check_special_print)),
+            EmitEvent => Special(SpecialNativeFunction(&check_special_emit_event)),
             AsContract => Special(SpecialNativeFunction(&check_special_as_contract)),
             ContractCall => Special(SpecialNativeFunction(&check_contract_call)),
             ContractOf => Special(SpecialNativeFunction(&check_contract_of)),
             PrincipalOf => Special(SpecialNativeFunction(&check_principal_of)),
+            IsStandard => Special(SpecialNativeFunction(&check_is_standard)),
+            PrincipalDestruct => Special(SpecialNativeFunction(&check_principal_destruct)),
+            PrincipalConstruct => Special(SpecialNativeFunction(&check_principal_construct)),
+            ToConsensusBuff => Special(SpecialNativeFunction(&check_to_consensus_buff)),
+            FromConsensusBuff => Special(SpecialNativeFunction(&check_from_consensus_buff)),
             GetBlockInfo => Special(SpecialNativeFunction(&check_get_block_info)),
             ConsSome => Special(SpecialNativeFunction(&options::check_special_some)),
             ConsOkay => Special(SpecialNativeFunction(&options::check_special_okay)),
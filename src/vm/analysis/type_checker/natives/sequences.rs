@@ -19,7 +19,7 @@ use std::convert::TryInto;
 use vm::functions::NativeFunctions;
 use vm::representations::{SymbolicExpression, SymbolicExpressionType};
 pub use vm::types::signatures::{BufferLength, ListTypeData, StringUTF8Length, BUFF_1};
-use vm::types::{FunctionType, TypeSignature};
+use vm::types::{FunctionType, SequenceSubtype, TypeSignature};
 use vm::types::{SequenceSubtype::*, StringSubtype::*};
 use vm::types::{Value, MAX_VALUE_SIZE};
 
@@ -389,3 +389,77 @@ pub fn check_special_index_of(
 
     TypeSignature::new_option(TypeSignature::UIntType).map_err(|e| e.into())
 }
+
+/// The type of a sequence of the same kind as `sequence`, with a max length of `max_len`.
+fn resize_sequence(sequence: SequenceSubtype, max_len: u32) -> TypeResult {
+    let resized = match sequence {
+        ListType(list) => {
+            let (entry_type, _) = list.destruct();
+            ListType(ListTypeData::new_list(entry_type, max_len)?)
+        }
+        BufferType(_) => BufferType(BufferLength::try_from(max_len)?),
+        StringType(ASCII(_)) => StringType(ASCII(BufferLength::try_from(max_len)?)),
+        StringType(UTF8(_)) => StringType(UTF8(StringUTF8Length::try_from(max_len)?)),
+    };
+    Ok(TypeSignature::SequenceType(resized))
+}
+
+fn sequence_max_len(sequence: &SequenceSubtype) -> u32 {
+    match sequence {
+        ListType(list) => list.get_max_len(),
+        BufferType(len) => u32::from(len),
+        StringType(ASCII(len)) => u32::from(len),
+        StringType(UTF8(len)) => u32::from(len),
+    }
+}
+
+pub fn check_special_slice(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(3, args)?;
+
+    let sequence_type = checker.type_check(&args[0], context)?;
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+    checker.type_check_expects(&args[2], context, &TypeSignature::UIntType)?;
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+
+    let sequence = match sequence_type {
+        TypeSignature::SequenceType(sequence) => sequence,
+        _ => return Err(CheckErrors::ExpectedSequence(sequence_type).into()),
+    };
+
+    // if both positions are literals, the slice can be no longer than the distance
+    //  between them. otherwise, it is bounded only by the input sequence.
+    let max_len = sequence_max_len(&sequence);
+    let slice_len = match (args[1].match_literal_value(), args[2].match_literal_value()) {
+        (Some(Value::UInt(left_position)), Some(Value::UInt(right_position))) => {
+            let distance = right_position.saturating_sub(*left_position);
+            u32::try_from(distance).unwrap_or(max_len).min(max_len)
+        }
+        _ => max_len,
+    };
+
+    TypeSignature::new_option(resize_sequence(sequence, slice_len)?).map_err(|e| e.into())
+}
+
+pub fn check_special_replace_at(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(3, args)?;
+
+    let sequence_type = checker.type_check(&args[0], context)?;
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+
+    let expected_element_type = match sequence_type {
+        TypeSignature::SequenceType(ref sequence) => sequence.unit_type(),
+        _ => return Err(CheckErrors::ExpectedSequence(sequence_type).into()),
+    };
+    checker.type_check_expects(&args[2], context, &expected_element_type)?;
+
+    TypeSignature::new_option(sequence_type).map_err(|e| e.into())
+}
@@ -123,6 +123,8 @@ fn test_names_tokens_contracts_interface() {
         (define-map map2 { k-name-1: bool } { v-name-1: (buff 33) })
         (define-map map3 { k-name-2: bool } { v-name-2: (tuple (n1 int) (n2 bool)) })
 
+        (define-event transfer { sender: principal, amount: uint })
+
         (define-private (f00 (a1 int)) true)
         (define-private (f01 (a1 bool)) true)
         (define-private (f02 (a1 principal)) true)
@@ -367,7 +369,18 @@ fn test_names_tokens_contracts_interface() {
             { "name": "d-var3", "access": "variable", "type": { "buffer": { "length": 5 } } }
         ],
         "fungible_tokens": [],
-        "non_fungible_tokens": []
+        "non_fungible_tokens": [],
+        "events": [
+            {
+                "name": "transfer",
+                "type": {
+                    "tuple": [
+                        { "name": "amount", "type": "uint128" },
+                        { "name": "sender", "type": "principal" }
+                    ]
+                }
+            }
+        ]
     }"#).unwrap();
 
     eprintln!("{}", test_contract_json_str);
@@ -59,6 +59,9 @@ fn test_get_block_info() {
         "(get-block-info? header-hash u1)",
         "(get-block-info? burnchain-header-hash u1)",
         "(get-block-info? miner-address u1)",
+        "(get-block-info? burnchain-block-height u1)",
+        "(get-block-info? miner-reward u1)",
+        "(get-block-info? total-fees u1)",
     ];
     let expected = [
         "(optional uint)",
@@ -67,6 +70,9 @@ fn test_get_block_info() {
         "(optional (buff 32))",
         "(optional (buff 32))",
         "(optional principal)",
+        "(optional uint)",
+        "(optional uint)",
+        "(optional uint)",
     ];
 
     let bad = [
@@ -181,8 +187,14 @@ fn test_stx_ops() {
         "(stx-burn? u10 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
         "(stx-transfer? u10 tx-sender 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
         "(stx-get-balance 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+        "(stx-account 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+    ];
+    let expected = [
+        "(response bool uint)",
+        "(response bool uint)",
+        "uint",
+        "(tuple (locked uint) (unlock-height uint) (unlocked uint))",
     ];
-    let expected = ["(response bool uint)", "(response bool uint)", "uint"];
 
     let bad = [
         "(stx-transfer? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
@@ -194,7 +206,8 @@ fn test_stx_ops() {
         "(stx-burn? u4 true)",
         "(stx-burn? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         "(stx-get-balance true)",
-        "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)"
+        "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        "(stx-account true)",
     ];
     let bad_expected = [
         CheckErrors::IncorrectArgumentCount(3, 2),
@@ -207,6 +220,7 @@ fn test_stx_ops() {
         CheckErrors::IncorrectArgumentCount(2, 3),
         CheckErrors::TypeError(PrincipalType, BoolType),
         CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(PrincipalType, BoolType),
     ];
 
     for (good_test, expected) in good.iter().zip(expected.iter()) {
@@ -457,6 +471,46 @@ fn test_unexpected_use_of_field_or_trait_reference() {
     }
 }
 
+#[test]
+fn test_bitwise_checks() {
+    let good = [
+        "(bit-and 1 2 3)",
+        "(bit-or u1 u2)",
+        "(bit-not u1)",
+        "(bit-shift-left 1 u2)",
+        "(bit-shift-right u1 u2)",
+    ];
+    let expected = ["int", "uint", "uint", "int", "uint"];
+
+    let bad = [
+        "(bit-and 1 u2)",
+        "(bit-or)",
+        "(bit-not 0x01)",
+        "(bit-shift-left 1 2)",
+        "(bit-shift-right true u2)",
+        "(bit-shift-right u1)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(IntType, UIntType),
+        CheckErrors::RequiresAtLeastArguments(1, 0),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], buff_type(1)),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_simple_arithmetic_checks() {
     let good = [
@@ -552,6 +606,194 @@ fn test_simple_hash_checks() {
     }
 }
 
+#[test]
+fn test_simple_conversion_checks() {
+    let good = [
+        "(buff-to-int-le 0x01)",
+        "(buff-to-uint-le 0x0102030405060708090a0b0c0d0e0f10)",
+        "(buff-to-int-be (sha256 u1))",
+        "(buff-to-uint-be 0x)",
+        "(int-to-ascii 10)",
+        "(int-to-ascii u10)",
+        "(string-to-int? \"10\")",
+        "(string-to-int? u\"10\")",
+    ];
+    let expected = [
+        "int",
+        "uint",
+        "int",
+        "uint",
+        "(string-ascii 40)",
+        "(string-ascii 40)",
+        "(optional int)",
+        "(optional int)",
+    ];
+
+    let bad = [
+        "(buff-to-int-le 0x0102030405060708090a0b0c0d0e0f1011)",
+        "(buff-to-uint-be u1)",
+        "(int-to-ascii 0x01)",
+        "(string-to-int? 10)",
+        "(int-to-ascii 1 2)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(buff_type(16), buff_type(17)),
+        CheckErrors::TypeError(buff_type(16), UIntType),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], buff_type(1)),
+        CheckErrors::UnionTypeError(
+            vec![
+                TypeSignature::max_string_ascii(),
+                TypeSignature::max_string_utf8(),
+            ],
+            IntType,
+        ),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_principal_checks() {
+    let good = [
+        "(is-standard tx-sender)",
+        "(principal-destruct? tx-sender)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 \"foo\")",
+    ];
+    let expected = [
+        "bool",
+        "(response (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))) (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))))",
+        "(response principal (tuple (error_code uint) (value (optional principal))))",
+        "(response principal (tuple (error_code uint) (value (optional principal))))",
+    ];
+
+    let bad = [
+        "(is-standard 0x01)",
+        "(principal-destruct? 1)",
+        "(principal-construct? 0x1a1a 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 u\"foo\")",
+        "(principal-construct? 0x1a)",
+        "(principal-construct? 0x1a 0x01 \"foo\" \"bar\")",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(PrincipalType, buff_type(1)),
+        CheckErrors::TypeError(PrincipalType, IntType),
+        CheckErrors::TypeError(buff_type(1), buff_type(2)),
+        CheckErrors::TypeError(
+            ascii_type(40),
+            TypeSignature::SequenceType(StringType(UTF8(3u32.try_into().unwrap()))),
+        ),
+        CheckErrors::RequiresAtLeastArguments(2, 1),
+        CheckErrors::IncorrectArgumentCount(3, 4),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_consensus_buff_checks() {
+    let good = [
+        "(to-consensus-buff 1)",
+        "(to-consensus-buff (some true))",
+        "(to-consensus-buff (list 0x01 0x0203))",
+        "(to-consensus-buff (tuple (a 1)))",
+        "(from-consensus-buff int 0x00)",
+        "(from-consensus-buff (tuple (a (list 2 uint))) (sha256 1))",
+    ];
+    let expected = [
+        "(optional (buff 17))",
+        "(optional (buff 2))",
+        "(optional (buff 19))",
+        "(optional (buff 24))",
+        "(optional int)",
+        "(optional (tuple (a (list 2 uint))))",
+    ];
+
+    let bad = [
+        "(to-consensus-buff 1 2)",
+        "(from-consensus-buff int 1)",
+        "(from-consensus-buff 0x00)",
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(TypeSignature::max_buffer(), IntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_emit_event_checks() {
+    let good = [
+        "(define-event paused bool) (emit-event paused true)",
+        "(define-event transfer { amount: uint, memo: (buff 4) })
+         (emit-event transfer { amount: u10, memo: 0x01 })",
+        "(define-event logged (list 3 int)) (emit-event logged (list 1 2))",
+    ];
+    let expected = [
+        "bool",
+        "(tuple (amount uint) (memo (buff 4)))",
+        "(list 3 int)",
+    ];
+
+    let bad = [
+        "(emit-event paused true)",
+        "(define-event paused bool) (emit-event paused u1)",
+        "(define-event paused bool) (emit-event paused)",
+        "(define-event paused bool) (emit-event \"paused\" true)",
+        "(define-event paused 1)",
+        "(define-event paused bool) (define-data-var paused bool false)",
+    ];
+    let bad_expected = [
+        CheckErrors::NoSuchEvent("paused".to_string()),
+        CheckErrors::TypeError(BoolType, UIntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::BadEventName,
+        CheckErrors::BadEventTypeDefinition,
+        CheckErrors::NameAlreadyUsed("paused".to_string()),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &mem_type_check(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_simple_ifs() {
     let good = [
@@ -715,6 +957,96 @@ fn test_element_at() {
     }
 }
 
+#[test]
+fn test_slice() {
+    let good = [
+        "(slice (list 1 2 3 4 5) u1 u3)",
+        "(slice (list 1 2 3 4 5) u1 u30)",
+        "(slice (list 1 2 3 4 5) u3 u1)",
+        "(slice (list 1 2 3 4 5) (+ u1 u2) u4)",
+        "(slice \"abcd\" u0 u2)",
+        "(slice 0xfedb u0 u1)",
+        "(slice u\"abcd\" u1 (len u\"ab\"))",
+    ];
+
+    let expected = [
+        "(optional (list 2 int))",
+        "(optional (list 5 int))",
+        "(optional (list 0 int))",
+        "(optional (list 5 int))",
+        "(optional (string-ascii 2))",
+        "(optional (buff 1))",
+        "(optional (string-utf8 4))",
+    ];
+
+    let bad = [
+        "(slice (list 1 2 3 4 5) u1 3)",
+        "(slice 3 u1 u2)",
+        "(slice \"abcd\" u1)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::TypeError(TypeSignature::UIntType, TypeSignature::IntType),
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::IncorrectArgumentCount(3, 2),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_replace_at() {
+    let good = [
+        "(replace-at (list 1 2 3 4 5) u1 3)",
+        "(replace-at (list (some 1) none) u1 none)",
+        "(replace-at \"abcd\" u0 \"e\")",
+        "(replace-at 0xfedb u0 0x01)",
+        "(replace-at u\"abcd\" u1 u\"e\")",
+    ];
+
+    let expected = [
+        "(optional (list 5 int))",
+        "(optional (list 2 (optional int)))",
+        "(optional (string-ascii 4))",
+        "(optional (buff 2))",
+        "(optional (string-utf8 4))",
+    ];
+
+    let bad = [
+        "(replace-at (list 1 2 3 4 5) u1 u3)",
+        "(replace-at \"abcd\" u0 \"ef\")",
+        "(replace-at 0xfedb 0 0x01)",
+        "(replace-at 3 u1 u2)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::UIntType),
+        CheckErrors::TypeError(ascii_type(1), ascii_type(2)),
+        CheckErrors::TypeError(TypeSignature::UIntType, TypeSignature::IntType),
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_eqs() {
     let good = [
@@ -2225,3 +2557,72 @@ fn test_string_utf8_negative_len() {
         _ => false,
     });
 }
+
+fn recovering_type_check(contract: &str) -> Vec<CheckErrors> {
+    use vm::analysis::run_analysis_with_recovery;
+    use vm::costs::LimitedCostTracker;
+
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let mut expressions = parse(&contract_identifier, contract).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    match run_analysis_with_recovery(
+        &contract_identifier,
+        &mut expressions,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+    ) {
+        Ok(_) => vec![],
+        Err((errors, _)) => {
+            for error in errors.iter() {
+                assert_eq!(error.diagnostic.spans.len(), 1);
+            }
+            errors.into_iter().map(|error| error.err).collect()
+        }
+    }
+}
+
+#[test]
+fn test_error_recovery() {
+    // each of the independent errors is reported, in source order.
+    let contract = "(define-data-var counter uint 1)
+         (define-read-only (get-counter) (+ (var-get counter) 1))
+         (define-public (reset) (begin (var-set counter u0) (ok (is-none u1))))
+         (define-private (plus-one) (+ (var-get counter) u1))";
+    assert_eq!(
+        recovering_type_check(contract),
+        vec![
+            CheckErrors::TypeError(UIntType, IntType),
+            CheckErrors::TypeError(UIntType, IntType),
+            CheckErrors::ExpectedOptionalType(UIntType),
+        ]
+    );
+
+    // errors which follow from an earlier error are not reported: neither the use of
+    //  `broken` in `(+ broken 1)`, nor the public function with an unknown return type,
+    //  nor the calls to that function.
+    let contract = "(define-constant broken (+ 1 true))
+         (define-private (use-broken) (+ broken 1))
+         (define-public (undefined) (begin (+ u1 2)))
+         (define-public (call-undefined) (begin (unwrap-panic (undefined)) (ok (undefined-variable))))";
+    assert_eq!(
+        recovering_type_check(contract),
+        vec![
+            CheckErrors::TypeError(IntType, BoolType),
+            CheckErrors::TypeError(UIntType, IntType),
+            CheckErrors::UnknownFunction("undefined-variable".to_string()),
+        ]
+    );
+
+    // without recovery, only the first error is reported.
+    assert_eq!(
+        mem_type_check(contract).unwrap_err().err,
+        CheckErrors::TypeError(IntType, BoolType)
+    );
+
+    assert_eq!(
+        recovering_type_check("(define-read-only (ok-fn) (+ 1 2))"),
+        vec![]
+    );
+}
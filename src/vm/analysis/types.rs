@@ -49,6 +49,8 @@ pub struct ContractAnalysis {
     pub non_fungible_tokens: BTreeMap<ClarityName, TypeSignature>,
     pub defined_traits: BTreeMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: BTreeSet<TraitIdentifier>,
+    #[serde(default)]
+    pub defined_events: BTreeMap<ClarityName, TypeSignature>,
     pub contract_interface: Option<ContractInterface>,
     pub is_cost_contract_eligible: bool,
     #[serde(skip)]
@@ -80,6 +82,7 @@ impl ContractAnalysis {
             implemented_traits: BTreeSet::new(),
             fungible_tokens: BTreeSet::new(),
             non_fungible_tokens: BTreeMap::new(),
+            defined_events: BTreeMap::new(),
             cost_track: Some(cost_track),
             is_cost_contract_eligible: false,
         }
@@ -150,6 +153,10 @@ impl ContractAnalysis {
         self.implemented_traits.insert(trait_identifier);
     }
 
+    pub fn add_defined_event(&mut self, name: ClarityName, event_type: TypeSignature) {
+        self.defined_events.insert(name, event_type);
+    }
+
     pub fn get_public_function_type(&self, name: &str) -> Option<&FunctionType> {
         self.public_function_types.get(name)
     }
@@ -174,6 +181,10 @@ impl ContractAnalysis {
         self.persisted_variable_types.get(name)
     }
 
+    pub fn get_defined_event(&self, name: &str) -> Option<&TypeSignature> {
+        self.defined_events.get(name)
+    }
+
     pub fn get_defined_trait(
         &self,
         name: &str,
@@ -96,15 +96,18 @@ impl<'a> DefinitionSorter {
 
         if let Some(deps) = walker.get_cycling_dependencies(&self.graph, &sorted_indexes) {
             let mut deps_props = vec![];
+            let mut deps_exprs = vec![];
             for i in deps.iter() {
                 let exp = &contract_ast.pre_expressions[*i];
                 if let Some(def) = self.find_expression_definition(&exp) {
                     deps_props.push(def);
+                    deps_exprs.push(exp.clone());
                 }
             }
             let functions_names = deps_props.iter().map(|i| i.0.to_string()).collect();
 
-            let error = ParseError::new(ParseErrors::CircularReference(functions_names));
+            let mut error = ParseError::new(ParseErrors::CircularReference(functions_names));
+            error.set_pre_expressions(deps_exprs);
             return Err(error);
         }
 
@@ -200,7 +203,9 @@ impl<'a> DefinitionSorter {
                                 DefineFunctions::ImplTrait | DefineFunctions::UseTrait => {
                                     return Ok(())
                                 }
-                                DefineFunctions::NonFungibleToken => return Ok(()),
+                                DefineFunctions::NonFungibleToken | DefineFunctions::Event => {
+                                    return Ok(())
+                                }
                                 DefineFunctions::FungibleToken => {
                                     // probe_for_dependencies if the supply arg (optional) is being passed
                                     if function_args.len() == 2 {
@@ -0,0 +1,383 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use vm::ast::errors::{ParseError, ParseErrors, ParseResult};
+use vm::ast::parser::{lex_with_offsets, parse, LexItem};
+
+pub const MAX_LINE_WIDTH: usize = 80;
+const INDENT_WIDTH: usize = 2;
+
+enum Node {
+    Atom(String),
+    Comment(String),
+    List(Vec<Element>),
+    Tuple(Vec<Element>),
+}
+
+struct Element {
+    node: Node,
+    /// the source had at least one blank line before this element.
+    blank_line_before: bool,
+    /// this is a comment which shared its line with the preceding token.
+    trailing: bool,
+}
+
+impl Element {
+    fn is_comment(&self) -> bool {
+        match self.node {
+            Node::Comment(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Reformat a Clarity program into its canonical layout, preserving its comments and
+///  (collapsed) blank lines. Fails if the program does not parse.
+pub fn format_source(input: &str) -> ParseResult<String> {
+    // the formatter works from the lexed tokens, but only ever formats valid programs.
+    parse(input)?;
+    let elements = build_elements(input)?;
+
+    let mut output = String::new();
+    for (ix, element) in elements.iter().enumerate() {
+        if ix > 0 {
+            if element.trailing {
+                output.push(' ');
+            } else {
+                output.push('\n');
+                if element.blank_line_before {
+                    output.push('\n');
+                }
+            }
+        }
+        output.push_str(&format_node(&element.node, 0, 0));
+    }
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+fn build_elements(input: &str) -> ParseResult<Vec<Element>> {
+    let lexed = lex_with_offsets(input, true)?;
+    let offsets: Vec<_> = lexed.iter().map(|(_, _, _, offset)| *offset).collect();
+
+    // each open list or tuple, along with whether a blank line preceded its opening token.
+    let mut stack: Vec<(Vec<Element>, bool)> = vec![];
+    let mut top_level = vec![];
+    let mut previous_end = 0;
+
+    for (ix, (item, _, _, start)) in lexed.into_iter().enumerate() {
+        let end = offsets.get(ix + 1).cloned().unwrap_or(input.len());
+        if let LexItem::Whitespace = item {
+            continue;
+        }
+
+        let newlines = input[previous_end..start].matches('\n').count();
+        let blank_line_before = newlines > 1;
+        let mut trailing = newlines == 0 && previous_end > 0;
+        previous_end = end;
+
+        let node = match item {
+            LexItem::LeftParen | LexItem::LeftCurly => {
+                stack.push((vec![], blank_line_before));
+                continue;
+            }
+            LexItem::RightParen | LexItem::RightCurly => {
+                let (elements, blank_line_before) = stack
+                    .pop()
+                    .ok_or_else(|| ParseError::new(ParseErrors::ClosingParenthesisUnexpected))?;
+                let node = match item {
+                    LexItem::RightParen => Node::List(elements),
+                    _ => Node::Tuple(elements),
+                };
+                let element = Element {
+                    node,
+                    blank_line_before,
+                    trailing: false,
+                };
+                match stack.last_mut() {
+                    Some((ref mut parent, _)) => parent.push(element),
+                    None => top_level.push(element),
+                }
+                continue;
+            }
+            // tuple separators are re-derived from the tuple's entries.
+            LexItem::CommaSeparator | LexItem::ColonSeparator => continue,
+            LexItem::Comment(text) => Node::Comment(text.trim_end().to_string()),
+            _ => {
+                // only comments can trail another token on its line.
+                trailing = false;
+                Node::Atom(input[start..end].trim_end().to_string())
+            }
+        };
+
+        let element = Element {
+            node,
+            blank_line_before,
+            trailing,
+        };
+        match stack.last_mut() {
+            Some((ref mut parent, _)) => parent.push(element),
+            None => top_level.push(element),
+        }
+    }
+
+    if stack.len() > 0 {
+        return Err(ParseError::new(ParseErrors::ClosingParenthesisExpected));
+    }
+    Ok(top_level)
+}
+
+/// The single-line rendering of a node, if it has one (i.e., it contains no comments).
+fn format_flat(node: &Node) -> Option<String> {
+    match node {
+        Node::Atom(text) => Some(text.clone()),
+        Node::Comment(_) => None,
+        Node::List(elements) => {
+            let items: Option<Vec<_>> = elements.iter().map(|e| format_flat(&e.node)).collect();
+            Some(format!("({})", items?.join(" ")))
+        }
+        Node::Tuple(elements) => {
+            let items: Option<Vec<_>> = elements.iter().map(|e| format_flat(&e.node)).collect();
+            let entries: Vec<_> = items?.chunks(2).map(|entry| entry.join(": ")).collect();
+            Some(format!("{{{}}}", entries.join(", ")))
+        }
+    }
+}
+
+/// The column at which the next character appended to `output` will be printed, given
+///  that `output` began at `start_column`.
+fn current_column(output: &str, start_column: usize) -> usize {
+    match output.rfind('\n') {
+        Some(ix) => output.len() - ix - 1,
+        None => start_column + output.len(),
+    }
+}
+
+/// The indentation of the line that `output` currently ends on, given that `output`
+///  began on a line indented by `start_indent`.
+fn current_indent(output: &str, start_indent: usize) -> usize {
+    match output.rfind('\n') {
+        Some(ix) => output[ix + 1..].len() - output[ix + 1..].trim_start().len(),
+        None => start_indent,
+    }
+}
+
+fn new_line(output: &mut String, column: usize, blank_line_before: bool) {
+    output.push('\n');
+    if blank_line_before {
+        output.push('\n');
+    }
+    output.push_str(&" ".repeat(column));
+}
+
+/// Format `node`, which begins at `column` on a line indented by `indent`. Broken lists
+///  indent their bodies relative to their opening paren, while broken tuples indent
+///  their entries relative to the line they open on.
+fn format_node(node: &Node, column: usize, indent: usize) -> String {
+    if let Some(flat) = format_flat(node) {
+        if column + flat.len() <= MAX_LINE_WIDTH {
+            return flat;
+        }
+    }
+
+    match node {
+        Node::Atom(text) | Node::Comment(text) => text.clone(),
+        Node::List(elements) => format_broken_list(elements, column, indent),
+        Node::Tuple(elements) => format_broken_tuple(elements, indent),
+    }
+}
+
+/// Which elements of a broken list stay on the same line as the element before them.
+fn joins_previous(elements: &[Element]) -> Vec<bool> {
+    let mut joins = vec![false; elements.len()];
+    let head = match elements.get(0).map(|e| &e.node) {
+        Some(Node::Atom(head)) => head.as_str(),
+        _ => return joins,
+    };
+
+    let has_comments = elements.iter().any(|e| e.is_comment());
+    match head {
+        "begin" => {}
+        "match" if !has_comments => {
+            // (match input
+            //   name-a expr-a
+            //   name-b expr-b)
+            for (ix, joins_previous) in joins.iter_mut().enumerate().skip(1) {
+                *joins_previous = ix == 1 || (ix >= 3 && ix % 2 == 1);
+            }
+        }
+        _ => {
+            if elements.len() > 1 {
+                joins[1] = !elements[1].is_comment();
+            }
+        }
+    }
+    joins
+}
+
+fn format_broken_list(elements: &[Element], column: usize, indent: usize) -> String {
+    let body_column = match elements.get(0).map(|e| &e.node) {
+        Some(Node::Atom(_)) => column + INDENT_WIDTH,
+        _ => column + 1,
+    };
+    let joins = joins_previous(elements);
+
+    let mut output = "(".to_string();
+    let mut ended_with_comment = false;
+    for (ix, element) in elements.iter().enumerate() {
+        if ix > 0 {
+            if element.trailing {
+                output.push(' ');
+            } else if joins[ix] && !ended_with_comment {
+                output.push(' ');
+            } else {
+                new_line(&mut output, body_column, element.blank_line_before);
+            }
+        }
+        let element_column = current_column(&output, column);
+        let element_indent = current_indent(&output, indent);
+        output.push_str(&format_node(&element.node, element_column, element_indent));
+        ended_with_comment = element.is_comment();
+    }
+    if ended_with_comment {
+        new_line(&mut output, column, false);
+    }
+    output.push(')');
+    output
+}
+
+fn format_broken_tuple(elements: &[Element], indent: usize) -> String {
+    let body_column = indent + INDENT_WIDTH;
+    let entry_count = elements.iter().filter(|e| !e.is_comment()).count() / 2;
+
+    let mut output = "{".to_string();
+    let mut entries_written = 0;
+    let mut pending_key: Option<&Element> = None;
+    for element in elements.iter() {
+        match element.node {
+            Node::Comment(ref text) => {
+                if element.trailing {
+                    output.push(' ');
+                } else {
+                    new_line(&mut output, body_column, element.blank_line_before);
+                }
+                output.push_str(text);
+            }
+            _ => match pending_key.take() {
+                None => pending_key = Some(element),
+                Some(key) => {
+                    new_line(&mut output, body_column, key.blank_line_before);
+                    output.push_str(&format_node(&key.node, body_column, body_column));
+                    output.push_str(": ");
+                    let value_column = current_column(&output, indent);
+                    output.push_str(&format_node(&element.node, value_column, body_column));
+                    entries_written += 1;
+                    if entries_written < entry_count {
+                        output.push(',');
+                    }
+                }
+            },
+        }
+    }
+    new_line(&mut output, indent, false);
+    output.push('}');
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_formats_to(input: &str, expected: &str) {
+        let formatted = format_source(input).unwrap();
+        assert_eq!(formatted, expected);
+        // formatting is idempotent.
+        assert_eq!(format_source(&formatted).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_flat_forms() {
+        assert_formats_to(
+            "(define-data-var  counter   uint u0)\n\n\n\n(define-read-only (get-counter)\n  (var-get counter))",
+            "(define-data-var counter uint u0)\n\n(define-read-only (get-counter) (var-get counter))\n",
+        );
+        assert_formats_to(
+            "(define-constant config {a: 1,b:   \"x\"})",
+            "(define-constant config {a: 1, b: \"x\"})\n",
+        );
+        assert_formats_to("", "");
+    }
+
+    #[test]
+    fn test_format_broken_forms() {
+        let input = "(define-public (transfer (amount uint) (recipient principal)) (begin (asserts! (> amount u0) (err u1)) (match (stx-transfer? amount tx-sender recipient) success (ok success) error (err error))))";
+        let expected = "\
+(define-public (transfer (amount uint) (recipient principal))
+  (begin
+    (asserts! (> amount u0) (err u1))
+    (match (stx-transfer? amount tx-sender recipient)
+      success (ok success)
+      error (err error))))
+";
+        assert_formats_to(input, expected);
+
+        let input = "(define-read-only (get-info) (ok {owner: tx-sender, balance: (stx-get-balance tx-sender), height: block-height}))";
+        let expected = "\
+(define-read-only (get-info)
+  (ok {
+    owner: tx-sender,
+    balance: (stx-get-balance tx-sender),
+    height: block-height
+  }))
+";
+        assert_formats_to(input, expected);
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let input = ";; counter contract
+(define-data-var counter uint u0) ;; the count
+
+;; increment the count
+(define-public (increment)
+  (begin ;; start
+    (var-set counter (+ (var-get counter) u1))
+    ;; return the new count
+    (ok (var-get counter)) ;; done
+  ))";
+        let expected = "\
+;; counter contract
+(define-data-var counter uint u0) ;; the count
+
+;; increment the count
+(define-public (increment)
+  (begin ;; start
+    (var-set counter (+ (var-get counter) u1))
+    ;; return the new count
+    (ok (var-get counter)) ;; done
+  ))
+";
+        assert_formats_to(input, expected);
+    }
+
+    #[test]
+    fn test_format_invalid() {
+        assert!(format_source("(define-data-var counter uint u0").is_err());
+        assert!(format_source("(foo))").is_err());
+    }
+}
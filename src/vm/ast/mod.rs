@@ -16,6 +16,7 @@
 
 pub mod definition_sorter;
 pub mod expression_identifier;
+pub mod formatter;
 pub mod parser;
 pub mod traits_resolver;
 
@@ -157,4 +158,58 @@ mod tests {
             .unwrap();
         }
     }
+
+    #[test]
+    fn test_expanded_spans() {
+        let progn = "(define-constant info
+  { a: 1, b: (+ 1 2) })";
+
+        let ast = build_ast(
+            &QualifiedContractIdentifier::transient(),
+            &progn,
+            &mut LimitedCostTracker::new_free(),
+        )
+        .unwrap();
+
+        // every expression, including those added by the sugar expander, has a span.
+        for expr in ast.expressions.iter() {
+            depth_traverse::<_, _, ()>(expr, |x| {
+                assert!(x.start_line().is_some());
+                Ok(())
+            })
+            .unwrap();
+        }
+
+        // the expanded tuple keeps the spans of its pairs.
+        let tuple = ast.expressions[0].match_list().unwrap()[2]
+            .match_list()
+            .unwrap();
+        let pair = tuple[2].match_list().unwrap();
+        let span = &tuple[2].span;
+        assert_eq!(
+            (
+                span.start_line,
+                span.start_column,
+                span.end_line,
+                span.end_column
+            ),
+            (2, 11, 2, 20)
+        );
+        assert_eq!(pair[1].span.end_column, 20);
+    }
+
+    #[test]
+    fn test_parse_error_spans() {
+        let errors = [
+            "(define-constant a 1)\n(define-trait)",
+            "(define-private (a) (b))\n(define-private (b) (a))",
+            "(define-constant a 1)\n(define-map m <t> int)",
+        ];
+        for progn in errors.iter() {
+            let err =
+                build_ast(&QualifiedContractIdentifier::transient(), progn, &mut ()).unwrap_err();
+            assert!(!err.diagnostic.spans.is_empty());
+            assert!(err.diagnostic.spans.iter().all(|span| span.start_line >= 1));
+        }
+    }
 }
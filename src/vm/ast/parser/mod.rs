@@ -43,11 +43,13 @@ pub enum LexItem {
     CommaSeparator,
     ColonSeparator,
     Whitespace,
+    Comment(String),
 }
 
 #[derive(Debug)]
 enum TokenType {
     Whitespace,
+    Comment,
     Comma,
     Colon,
     LParens,
@@ -127,6 +129,21 @@ lazy_static! {
 }
 
 pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+    let lexed = lex_with_offsets(input, false)?;
+    Ok(lexed
+        .into_iter()
+        .map(|(item, line, column, _)| (item, line, column))
+        .collect())
+}
+
+/// Like `lex()`, but each item also carries the byte offset in `input` at which it starts.
+///  Items are contiguous, so each one extends up to the start of the next. Comments are
+///  lexed as whitespace unless `keep_comments` is set, in which case each one is a `Comment`
+///  item holding its text.
+pub fn lex_with_offsets(
+    input: &str,
+    keep_comments: bool,
+) -> ParseResult<Vec<(LexItem, u32, u32, usize)>> {
     // Aaron: I'd like these to be static, but that'd require using
     //    lazy_static (or just hand implementing that), and I'm not convinced
     //    it's worth either (1) an extern macro, or (2) the complexity of hand implementing.
@@ -140,7 +157,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
             r##""(?P<value>((\\")|([[ -~]&&[^"]]))*)""##,
             TokenType::StringASCIILiteral,
         ),
-        LexMatcher::new(";;[ -~]*", TokenType::Whitespace), // ;; comments.
+        LexMatcher::new(";;[ -~]*", TokenType::Comment),
         LexMatcher::new("[\n]+", TokenType::Whitespace),
         LexMatcher::new("[ \t]+", TokenType::Whitespace),
         LexMatcher::new("[,]", TokenType::Comma),
@@ -215,6 +232,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
             if let Some(captures) = matcher.matcher.captures(current_slice) {
                 let whole_match = captures.get(0).unwrap();
                 assert_eq!(whole_match.start(), 0);
+                let token_offset = munch_index;
                 munch_index += whole_match.end();
 
                 match context {
@@ -227,6 +245,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             TokenType::RParens => Ok(()),
                             TokenType::RCurly => Ok(()),
                             TokenType::Whitespace => Ok(()),
+                            TokenType::Comment => Ok(()),
                             TokenType::Comma => Ok(()),
                             TokenType::Colon => Ok(()),
                             _ => Err(ParseError::new(ParseErrors::SeparatorExpected(
@@ -240,6 +259,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             TokenType::RParens => Ok(()),
                             TokenType::RCurly => Ok(()),
                             TokenType::Whitespace => Ok(()),
+                            TokenType::Comment => Ok(()),
                             TokenType::Comma => Ok(()),
                             TokenType::Colon => Ok(()),
                             _ => Err(ParseError::new(ParseErrors::SeparatorExpectedAfterColon(
@@ -262,6 +282,16 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                         context = LexContext::ExpectNothing;
                         Ok(LexItem::Whitespace)
                     }
+                    TokenType::Comment => {
+                        context = LexContext::ExpectNothing;
+                        if keep_comments {
+                            Ok(LexItem::Comment(
+                                current_slice[..whole_match.end()].to_string(),
+                            ))
+                        } else {
+                            Ok(LexItem::Whitespace)
+                        }
+                    }
                     TokenType::Comma => {
                         context = LexContext::ExpectNothing;
                         Ok(LexItem::CommaSeparator)
@@ -410,7 +440,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                     }
                 }?;
 
-                result.push((token, current_line, column_pos));
+                result.push((token, current_line, column_pos, token_offset));
                 column_pos += whole_match.end() as u32;
                 did_match = true;
                 break;
@@ -666,7 +696,7 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                     }
                 };
             }
-            LexItem::Whitespace => (),
+            LexItem::Whitespace | LexItem::Comment(_) => (),
         };
     }
 
@@ -1229,4 +1259,35 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_lex_comments() {
+        use vm::ast::parser::LexItem;
+
+        // comments are only kept as items when asked for.
+        let input = "(foo) ;; a comment\n;; another";
+        assert!(ast::parser::lex(input)
+            .unwrap()
+            .iter()
+            .all(|(item, ..)| match item {
+                LexItem::Comment(_) => false,
+                _ => true,
+            }));
+
+        let comments: Vec<_> = ast::parser::lex_with_offsets(input, true)
+            .unwrap()
+            .into_iter()
+            .filter_map(|(item, line, column, offset)| match item {
+                LexItem::Comment(text) => Some((text, line, column, offset)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                (";; a comment".to_string(), 1, 7, 6),
+                (";; another".to_string(), 2, 1, 19)
+            ]
+        );
+    }
 }
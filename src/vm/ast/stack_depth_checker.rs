@@ -32,7 +32,13 @@ fn check(args: &[PreSymbolicExpression], depth: u64) -> ParseResult<()> {
     }
     for expression in args.iter() {
         match expression.pre_expr {
-            List(ref exprs) => check(exprs, depth + 1),
+            List(ref exprs) => check(exprs, depth + 1).map_err(|mut e| {
+                // point at the innermost list, i.e., the one which is too deep.
+                if !e.has_pre_expression() {
+                    e.set_pre_expression(expression);
+                }
+                e
+            }),
             _ => {
                 // Other symbolic expressions don't have depth
                 //  impacts.
@@ -65,6 +65,7 @@ impl SugarExpander {
         let mut expressions = Vec::new();
 
         for pre_expr in pre_exprs_iter {
+            let span = pre_expr.span().cloned();
             let mut expr = match pre_expr.pre_expr {
                 PreSymbolicExpressionType::AtomValue(content) => {
                     SymbolicExpression::literal_value(content)
@@ -78,15 +79,37 @@ impl SugarExpander {
                 PreSymbolicExpressionType::Tuple(pre_exprs) => {
                     let drain = PreExpressionsDrain::new(pre_exprs.to_vec().drain(..), None);
                     let expression = self.transform(drain, contract_ast)?;
+                    // each pair spans from its key to its value.
                     let mut pairs = expression
                         .chunks(2)
-                        .map(|pair| pair.to_vec().into_boxed_slice())
-                        .map(SymbolicExpression::list)
+                        .map(|pair| {
+                            let mut list =
+                                SymbolicExpression::list(pair.to_vec().into_boxed_slice());
+                            if let (Some(start), Some(end)) =
+                                (pair[0].span(), pair[pair.len() - 1].span())
+                            {
+                                list.set_span(
+                                    start.start_line,
+                                    start.start_column,
+                                    end.end_line,
+                                    end.end_column,
+                                );
+                            }
+                            list
+                        })
                         .collect::<Vec<_>>();
-                    pairs.insert(
-                        0,
-                        SymbolicExpression::atom("tuple".to_string().try_into().unwrap()),
-                    );
+                    // the implicit `tuple` spans the opening brace.
+                    let mut tuple_atom =
+                        SymbolicExpression::atom("tuple".to_string().try_into().unwrap());
+                    if let Some(ref span) = span {
+                        tuple_atom.set_span(
+                            span.start_line,
+                            span.start_column,
+                            span.start_line,
+                            span.start_column,
+                        );
+                    }
+                    pairs.insert(0, tuple_atom);
                     SymbolicExpression::list(pairs.into_boxed_slice())
                 }
                 PreSymbolicExpressionType::SugaredContractIdentifier(contract_name) => {
@@ -111,7 +134,10 @@ impl SugarExpander {
                     if let Some(trait_reference) = contract_ast.get_referenced_trait(&name) {
                         SymbolicExpression::trait_reference(name, trait_reference.clone())
                     } else {
-                        return Err(ParseErrors::TraitReferenceUnknown(name.to_string()).into());
+                        let mut err =
+                            ParseError::new(ParseErrors::TraitReferenceUnknown(name.to_string()));
+                        err.diagnostic.spans = span.into_iter().collect();
+                        return Err(err);
                     }
                 }
             };
@@ -453,12 +479,12 @@ mod test {
             1,
             9,
             Box::new([
-                make_atom("tuple", 0, 0, 0, 0),
+                make_atom("tuple", 1, 1, 1, 1),
                 make_list(
-                    0,
-                    0,
-                    0,
-                    0,
+                    1,
+                    2,
+                    1,
+                    8,
                     Box::new([
                         make_atom("id", 1, 2, 1, 3),
                         make_literal_value(Value::Int(1337), 1, 5, 1, 8),
@@ -54,77 +54,77 @@ impl TraitsResolver {
                 None => continue,
             };
 
-            match define_type {
-                DefineFunctions::Trait => {
-                    if args.len() != 2 {
-                        return Err(ParseErrors::DefineTraitBadSignature.into());
+            self.resolve_definition(define_type, args, contract_ast, &mut referenced_traits)
+                .map_err(|mut e| {
+                    if !e.has_pre_expression() {
+                        e.set_pre_expression(exp);
                     }
+                    e
+                })?;
+        }
 
-                    match (&args[0].pre_expr, &args[1].pre_expr) {
-                        (Atom(trait_name), List(trait_definition)) => {
-                            // Check for collisions
-                            if contract_ast.referenced_traits.contains_key(trait_name) {
-                                return Err(
-                                    ParseErrors::NameAlreadyUsed(trait_name.to_string()).into()
-                                );
-                            }
+        for (trait_reference, expr) in referenced_traits {
+            if !contract_ast
+                .referenced_traits
+                .contains_key(&trait_reference)
+            {
+                let mut err = ParseError::new(ParseErrors::TraitReferenceUnknown(
+                    trait_reference.to_string(),
+                ));
+                err.set_pre_expression(&expr);
+                return Err(err.into());
+            }
+        }
 
-                            // Traverse and probe for generics nested in the trait definition
-                            self.probe_for_generics(
-                                trait_definition,
-                                &mut referenced_traits,
-                                true,
-                            )?;
-
-                            let trait_id = TraitIdentifier {
-                                name: trait_name.clone(),
-                                contract_identifier: contract_ast.contract_identifier.clone(),
-                            };
-                            contract_ast
-                                .referenced_traits
-                                .insert(trait_name.clone(), TraitDefinition::Defined(trait_id));
-                        }
-                        _ => return Err(ParseErrors::DefineTraitBadSignature.into()),
-                    }
+        Ok(())
+    }
+
+    fn resolve_definition(
+        &mut self,
+        define_type: DefineFunctions,
+        args: &[PreSymbolicExpression],
+        contract_ast: &mut ContractAST,
+        referenced_traits: &mut HashMap<ClarityName, PreSymbolicExpression>,
+    ) -> ParseResult<()> {
+        match define_type {
+            DefineFunctions::Trait => {
+                if args.len() != 2 {
+                    return Err(ParseErrors::DefineTraitBadSignature.into());
                 }
-                DefineFunctions::UseTrait => {
-                    if args.len() != 2 {
-                        return Err(ParseErrors::ImportTraitBadSignature.into());
-                    }
 
-                    if let Some(trait_name) = args[0].match_atom() {
+                match (&args[0].pre_expr, &args[1].pre_expr) {
+                    (Atom(trait_name), List(trait_definition)) => {
                         // Check for collisions
                         if contract_ast.referenced_traits.contains_key(trait_name) {
                             return Err(ParseErrors::NameAlreadyUsed(trait_name.to_string()).into());
                         }
 
-                        let trait_id = match &args[1].pre_expr {
-                            SugaredFieldIdentifier(contract_name, name) => {
-                                let contract_identifier = QualifiedContractIdentifier::new(
-                                    contract_ast.contract_identifier.issuer.clone(),
-                                    contract_name.clone(),
-                                );
-                                TraitIdentifier {
-                                    name: name.clone(),
-                                    contract_identifier,
-                                }
-                            }
-                            FieldIdentifier(trait_identifier) => trait_identifier.clone(),
-                            _ => return Err(ParseErrors::ImportTraitBadSignature.into()),
+                        // Traverse and probe for generics nested in the trait definition
+                        self.probe_for_generics(trait_definition, referenced_traits, true)?;
+
+                        let trait_id = TraitIdentifier {
+                            name: trait_name.clone(),
+                            contract_identifier: contract_ast.contract_identifier.clone(),
                         };
                         contract_ast
                             .referenced_traits
-                            .insert(trait_name.clone(), TraitDefinition::Imported(trait_id));
-                    } else {
-                        return Err(ParseErrors::ImportTraitBadSignature.into());
+                            .insert(trait_name.clone(), TraitDefinition::Defined(trait_id));
                     }
+                    _ => return Err(ParseErrors::DefineTraitBadSignature.into()),
+                }
+            }
+            DefineFunctions::UseTrait => {
+                if args.len() != 2 {
+                    return Err(ParseErrors::ImportTraitBadSignature.into());
                 }
-                DefineFunctions::ImplTrait => {
-                    if args.len() != 1 {
-                        return Err(ParseErrors::ImplTraitBadSignature.into());
+
+                if let Some(trait_name) = args[0].match_atom() {
+                    // Check for collisions
+                    if contract_ast.referenced_traits.contains_key(trait_name) {
+                        return Err(ParseErrors::NameAlreadyUsed(trait_name.to_string()).into());
                     }
 
-                    let trait_id = match &args[0].pre_expr {
+                    let trait_id = match &args[1].pre_expr {
                         SugaredFieldIdentifier(contract_name, name) => {
                             let contract_identifier = QualifiedContractIdentifier::new(
                                 contract_ast.contract_identifier.issuer.clone(),
@@ -136,39 +136,51 @@ impl TraitsResolver {
                             }
                         }
                         FieldIdentifier(trait_identifier) => trait_identifier.clone(),
-                        _ => return Err(ParseErrors::ImplTraitBadSignature.into()),
+                        _ => return Err(ParseErrors::ImportTraitBadSignature.into()),
                     };
-                    contract_ast.implemented_traits.insert(trait_id);
-                }
-                DefineFunctions::PublicFunction
-                | DefineFunctions::PrivateFunction
-                | DefineFunctions::ReadOnlyFunction => {
-                    // Traverse and probe for generics in functions type definitions
-                    self.probe_for_generics(&args, &mut referenced_traits, true)?;
+                    contract_ast
+                        .referenced_traits
+                        .insert(trait_name.clone(), TraitDefinition::Imported(trait_id));
+                } else {
+                    return Err(ParseErrors::ImportTraitBadSignature.into());
                 }
-                DefineFunctions::Constant
-                | DefineFunctions::Map
-                | DefineFunctions::PersistedVariable
-                | DefineFunctions::FungibleToken
-                | DefineFunctions::NonFungibleToken => {
-                    self.probe_for_generics(&args[1..], &mut referenced_traits, false)?;
+            }
+            DefineFunctions::ImplTrait => {
+                if args.len() != 1 {
+                    return Err(ParseErrors::ImplTraitBadSignature.into());
                 }
-            };
-        }
 
-        for (trait_reference, expr) in referenced_traits {
-            if !contract_ast
-                .referenced_traits
-                .contains_key(&trait_reference)
-            {
-                let mut err = ParseError::new(ParseErrors::TraitReferenceUnknown(
-                    trait_reference.to_string(),
-                ));
-                err.set_pre_expression(&expr);
-                return Err(err.into());
+                let trait_id = match &args[0].pre_expr {
+                    SugaredFieldIdentifier(contract_name, name) => {
+                        let contract_identifier = QualifiedContractIdentifier::new(
+                            contract_ast.contract_identifier.issuer.clone(),
+                            contract_name.clone(),
+                        );
+                        TraitIdentifier {
+                            name: name.clone(),
+                            contract_identifier,
+                        }
+                    }
+                    FieldIdentifier(trait_identifier) => trait_identifier.clone(),
+                    _ => return Err(ParseErrors::ImplTraitBadSignature.into()),
+                };
+                contract_ast.implemented_traits.insert(trait_id);
+            }
+            DefineFunctions::PublicFunction
+            | DefineFunctions::PrivateFunction
+            | DefineFunctions::ReadOnlyFunction => {
+                // Traverse and probe for generics in functions type definitions
+                self.probe_for_generics(&args, referenced_traits, true)?;
+            }
+            DefineFunctions::Constant
+            | DefineFunctions::Map
+            | DefineFunctions::PersistedVariable
+            | DefineFunctions::FungibleToken
+            | DefineFunctions::NonFungibleToken
+            | DefineFunctions::Event => {
+                self.probe_for_generics(&args[1..], referenced_traits, false)?;
             }
         }
-
         Ok(())
     }
 
@@ -198,7 +210,9 @@ impl TraitsResolver {
                     if should_reference {
                         referenced_traits.insert(trait_name.clone(), expression.clone());
                     } else {
-                        return Err(ParseErrors::TraitReferenceNotAllowed.into());
+                        let mut err = ParseError::new(ParseErrors::TraitReferenceNotAllowed);
+                        err.set_pre_expression(expression);
+                        return Err(err);
                     }
                 }
                 Tuple(atoms) => {
@@ -181,7 +181,7 @@ impl DefinedFunction {
         match result {
             Ok(r) => Ok(r),
             Err(e) => match e {
-                Error::ShortReturn(v) => Ok(v.into()),
+                Error::ShortReturn(v, _) => Ok(v.into()),
                 _ => Err(e),
             },
         }
@@ -220,7 +220,13 @@ impl DefinedFunction {
 
     pub fn apply(&self, args: &[Value], env: &mut Environment) -> Result<Value> {
         match self.define_type {
-            DefineType::Private => self.execute_apply(args, env),
+            DefineType::Private => {
+                let contract_identifier = env.contract_context.contract_identifier.clone();
+                env.trace_enter(&contract_identifier, self, args);
+                let result = self.execute_apply(args, env);
+                env.trace_exit(&result);
+                result
+            }
             DefineType::Public => env.execute_function_as_transaction(self, args, None),
             DefineType::ReadOnly => env.execute_function_as_transaction(self, args, None),
         }
@@ -237,6 +243,10 @@ impl DefinedFunction {
     pub fn get_identifier(&self) -> FunctionIdentifier {
         self.identifier.clone()
     }
+
+    pub fn get_name(&self) -> ClarityName {
+        self.name.clone()
+    }
 }
 
 impl CallableType {
@@ -250,6 +260,10 @@ impl CallableType {
 }
 
 impl FunctionIdentifier {
+    pub fn is_native(&self) -> bool {
+        self.identifier.starts_with("_native_:")
+    }
+
     fn new_native_function(name: &str) -> FunctionIdentifier {
         let identifier = format!("_native_:{}", name);
         FunctionIdentifier {
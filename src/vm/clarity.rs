@@ -251,6 +251,43 @@ impl ClarityInstance {
         f(self.datastore.get_marf())
     }
 
+    /// Prune the Clarity state of the blocks `keep` or more blocks below `tip`, a bounded step
+    /// at a time.
+    pub fn prune(
+        &mut self,
+        tip: &StacksBlockId,
+        keep: u32,
+        max_nodes: u64,
+    ) -> Result<u64, InterpreterError> {
+        self.datastore.prune(tip, keep, max_nodes)
+    }
+
+    /// Is a pass over the Clarity state to prune it in progress?
+    pub fn is_pruning(&self) -> bool {
+        self.datastore.is_pruning()
+    }
+
+    /// Cache up to (approximately) `max_size` bytes of the Clarity MARF's nodes.
+    pub fn set_marf_cache_size(&mut self, max_size: usize) {
+        self.datastore.set_node_cache_size(max_size)
+    }
+
+    /// Maintain (or stop maintaining) the index of data map and NFT keys which lets their entries
+    /// be listed.  See MarfedKV::set_key_index().
+    pub fn set_key_index(&mut self, enabled: bool) -> Result<(), InterpreterError> {
+        self.datastore.set_key_index(enabled)
+    }
+
+    /// Is the index of data map and NFT keys maintained?
+    pub fn is_key_index_enabled(&self) -> bool {
+        self.datastore.is_key_index_enabled()
+    }
+
+    /// Has the Clarity state at this block been pruned?
+    pub fn is_pruned(&self, block: &StacksBlockId) -> bool {
+        self.datastore.is_pruned(block)
+    }
+
     pub fn begin_block<'a>(
         &'a mut self,
         current: &StacksBlockId,
@@ -23,6 +23,7 @@ use vm::ast;
 use vm::ast::ContractAST;
 use vm::callables::{DefinedFunction, FunctionIdentifier};
 use vm::contracts::Contract;
+use vm::costs::profiler::{CostProfile, ProfilingCostTracker};
 use vm::costs::{
     cost_functions, runtime_cost, ClarityCostFunctionReference, CostErrors, CostTracker,
     ExecutionCost, LimitedCostTracker,
@@ -33,14 +34,15 @@ use vm::database::{
 };
 use vm::errors::{CheckErrors, InterpreterError, InterpreterResult as Result, RuntimeErrorType};
 use vm::functions::handle_contract_call_special_cases;
-use vm::representations::{ClarityName, ContractName, SymbolicExpression};
+use vm::representations::{ClarityName, ContractName, Span, SymbolicExpression};
 use vm::stx_transfer_consolidated;
+use vm::trace::CallTracer;
 use vm::types::signatures::FunctionSignature;
 use vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature,
     Value,
 };
-use vm::{eval, is_reserved};
+use vm::{eval, is_reserved, EvalHook};
 
 use chainstate::burn::{BlockHeaderHash, VRFSeed};
 use chainstate::stacks::db::StacksChainState;
@@ -109,6 +111,9 @@ pub struct GlobalContext<'a> {
     read_only: Vec<bool>,
     pub cost_track: LimitedCostTracker,
     pub mainnet: bool,
+    pub eval_hooks: Option<Vec<&'a mut dyn EvalHook>>,
+    pub tracer: Option<CallTracer>,
+    pub cost_profile: Option<CostProfile>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -127,6 +132,9 @@ pub struct ContractContext {
     pub meta_nft: HashMap<ClarityName, NonFungibleTokenMetadata>,
     pub meta_ft: HashMap<ClarityName, FungibleTokenMetadata>,
     pub data_size: u64,
+    // the types of the events declared with define-event.
+    #[serde(default)]
+    pub defined_events: HashMap<ClarityName, TypeSignature>,
 }
 
 pub struct LocalContext<'a> {
@@ -139,11 +147,30 @@ pub struct LocalContext<'a> {
 
 pub struct CallStack {
     stack: Vec<FunctionIdentifier>,
+    // the span of the application which pushed each frame of `stack`, if it was applied from source.
+    //  call sites are only recorded for stack traces, in developer mode.
+    #[cfg(feature = "developer-mode")]
+    call_sites: Vec<Option<Span>>,
+    // the contract whose source contains the call sites, from the frame at the given
+    //  index onwards. only the frames where it changes (i.e., a `contract-call?`) are recorded.
+    #[cfg(feature = "developer-mode")]
+    contracts: Vec<(usize, QualifiedContractIdentifier)>,
     set: HashSet<FunctionIdentifier>,
     apply_depth: usize,
 }
 
-pub type StackTrace = Vec<FunctionIdentifier>;
+/// A frame of a runtime error's stack trace: the function which was applied, and the span
+///  of its application. The span is in the source of `contract_identifier`, the caller's
+///  contract, i.e., the contract of the nearest enclosing user function (or the contract
+///  being executed, at the top level).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub function: FunctionIdentifier,
+    pub call_site: Option<Span>,
+}
+
+pub type StackTrace = Vec<StackFrame>;
 
 pub const TRANSIENT_CONTRACT_NAME: &str = "__transient";
 
@@ -483,6 +510,34 @@ impl<'a> OwnedEnvironment<'a> {
         }
     }
 
+    /// Register an EvalHook which will be notified of every expression evaluated
+    ///  in this environment.
+    pub fn add_eval_hook(&mut self, hook: &'a mut dyn EvalHook) {
+        self.context.add_eval_hook(hook);
+    }
+
+    /// Begin recording a call tree of every user-defined function invoked in
+    ///  this environment. Any previously recorded trace is discarded.
+    pub fn enable_tracing(&mut self) {
+        self.context.tracer = Some(CallTracer::new());
+    }
+
+    /// Stop tracing, returning the calls recorded since `enable_tracing()`.
+    pub fn take_trace(&mut self) -> Option<CallTracer> {
+        self.context.tracer.take()
+    }
+
+    /// Begin attributing every cost charged in this environment to the function and
+    ///  expression being evaluated. Any previously recorded profile is discarded.
+    pub fn enable_profiling(&mut self) {
+        self.context.cost_profile = Some(CostProfile::new());
+    }
+
+    /// Stop profiling, returning the costs attributed since `enable_profiling()`.
+    pub fn take_profile(&mut self) -> Option<CostProfile> {
+        self.context.cost_profile.take()
+    }
+
     pub fn get_exec_environment<'b>(&'b mut self, sender: Option<Value>) -> Environment<'b, 'a> {
         Environment::new(
             &mut self.context,
@@ -655,21 +710,19 @@ impl CostTracker for Environment<'_, '_> {
         cost_function: ClarityCostFunction,
         input: &[u64],
     ) -> std::result::Result<ExecutionCost, CostErrors> {
-        self.global_context
-            .cost_track
-            .compute_cost(cost_function, input)
+        self.global_context.compute_cost(cost_function, input)
     }
     fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
-        self.global_context.cost_track.add_cost(cost)
+        self.global_context.add_cost(cost)
     }
     fn add_memory(&mut self, memory: u64) -> std::result::Result<(), CostErrors> {
-        self.global_context.cost_track.add_memory(memory)
+        self.global_context.add_memory(memory)
     }
     fn drop_memory(&mut self, memory: u64) {
-        self.global_context.cost_track.drop_memory(memory)
+        self.global_context.drop_memory(memory)
     }
     fn reset_memory(&mut self) {
-        self.global_context.cost_track.reset_memory()
+        self.global_context.reset_memory()
     }
     fn short_circuit_contract_call(
         &mut self,
@@ -678,7 +731,6 @@ impl CostTracker for Environment<'_, '_> {
         input: &[u64],
     ) -> std::result::Result<bool, CostErrors> {
         self.global_context
-            .cost_track
             .short_circuit_contract_call(contract, function, input)
     }
 }
@@ -693,7 +745,12 @@ impl CostTracker for GlobalContext<'_> {
     }
 
     fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
-        self.cost_track.add_cost(cost)
+        match self.cost_profile {
+            Some(ref mut profile) => {
+                ProfilingCostTracker::new(&mut self.cost_track, profile).add_cost(cost)
+            }
+            None => self.cost_track.add_cost(cost),
+        }
     }
     fn add_memory(&mut self, memory: u64) -> std::result::Result<(), CostErrors> {
         self.cost_track.add_memory(memory)
@@ -710,8 +767,13 @@ impl CostTracker for GlobalContext<'_> {
         function: &ClarityName,
         input: &[u64],
     ) -> std::result::Result<bool, CostErrors> {
-        self.cost_track
-            .short_circuit_contract_call(contract, function, input)
+        match self.cost_profile {
+            Some(ref mut profile) => ProfilingCostTracker::new(&mut self.cost_track, profile)
+                .short_circuit_contract_call(contract, function, input),
+            None => self
+                .cost_track
+                .short_circuit_contract_call(contract, function, input),
+        }
     }
 }
 
@@ -848,6 +910,21 @@ impl<'a, 'b> Environment<'a, 'b> {
         tx_name: &str,
         args: &[SymbolicExpression],
         read_only: bool,
+    ) -> Result<Value> {
+        self.profile_enter(|env| {
+            CostProfile::contract_call_stack(env.call_stack, contract_identifier, tx_name)
+        });
+        let result = self.execute_contract_profiled(contract_identifier, tx_name, args, read_only);
+        self.profile_exit();
+        result
+    }
+
+    fn execute_contract_profiled(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        tx_name: &str,
+        args: &[SymbolicExpression],
+        read_only: bool,
     ) -> Result<Value> {
         let contract_size = self
             .global_context
@@ -883,7 +960,12 @@ impl<'a, 'b> Environment<'a, 'b> {
             if self.call_stack.contains(&func_identifier) {
                 return Err(CheckErrors::CircularReference(vec![func_identifier.to_string()]).into())
             }
-            self.call_stack.insert(&func_identifier, true);
+            self.call_stack.insert(
+                &func_identifier,
+                true,
+                &self.contract_context.contract_identifier,
+                None,
+            );
             let res = self.execute_function_as_transaction(&func, &args, Some(&contract.contract_context));
             self.call_stack.remove(&func_identifier, true)?;
 
@@ -914,6 +996,8 @@ impl<'a, 'b> Environment<'a, 'b> {
 
         let next_contract_context = next_contract_context.unwrap_or(self.contract_context);
 
+        self.trace_enter(&next_contract_context.contract_identifier, function, args);
+
         let result = {
             let mut nested_env = Environment::new(
                 &mut self.global_context,
@@ -926,6 +1010,8 @@ impl<'a, 'b> Environment<'a, 'b> {
             function.execute_apply(args, &mut nested_env)
         };
 
+        self.trace_exit(&result);
+
         if make_read_only {
             self.global_context.roll_back();
             result
@@ -934,6 +1020,64 @@ impl<'a, 'b> Environment<'a, 'b> {
         }
     }
 
+    /// If profiling is enabled, attribute costs to the stack made by `make_stack` until the
+    ///  matching `profile_exit()`.
+    pub fn profile_enter<F>(&mut self, make_stack: F)
+    where
+        F: FnOnce(&Environment) -> String,
+    {
+        if self.global_context.cost_profile.is_some() {
+            let stack = make_stack(self);
+            if let Some(ref mut profile) = self.global_context.cost_profile {
+                profile.enter(stack);
+            }
+        }
+    }
+
+    /// If profiling is enabled, stop attributing costs to the most recently entered stack.
+    pub fn profile_exit(&mut self) {
+        if let Some(ref mut profile) = self.global_context.cost_profile {
+            profile.exit();
+        }
+    }
+
+    /// If tracing is enabled, record entry into `function`.
+    pub fn trace_enter(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        function: &DefinedFunction,
+        args: &[Value],
+    ) {
+        let global_context = &mut *self.global_context;
+        if let Some(ref mut tracer) = global_context.tracer {
+            let events = global_context
+                .event_batches
+                .last()
+                .map(|batch| &batch.events[..])
+                .unwrap_or(&[]);
+            tracer.enter(
+                contract_identifier,
+                &function.get_name(),
+                args,
+                global_context.cost_track.get_total(),
+                events,
+            );
+        }
+    }
+
+    /// If tracing is enabled, record exit from the most recently entered function.
+    pub fn trace_exit(&mut self, result: &Result<Value>) {
+        let global_context = &mut *self.global_context;
+        if let Some(ref mut tracer) = global_context.tracer {
+            let events = global_context
+                .event_batches
+                .last()
+                .map(|batch| &batch.events[..])
+                .unwrap_or(&[]);
+            tracer.exit(result, global_context.cost_track.get_total(), events);
+        }
+    }
+
     pub fn evaluate_at_block(
         &mut self,
         bhh: StacksBlockId,
@@ -967,8 +1111,11 @@ impl<'a, 'b> Environment<'a, 'b> {
         contract_identifier: QualifiedContractIdentifier,
         contract_content: &str,
     ) -> Result<()> {
-        let contract_ast = ast::build_ast(&contract_identifier, contract_content, self)?;
-        self.initialize_contract_from_ast(contract_identifier, &contract_ast, &contract_content)
+        // the contract is parsed in its own frame, so that its parsing costs are attributed to it.
+        self.profile_enter(|_| contract_identifier.to_string());
+        let contract_ast = ast::build_ast(&contract_identifier, contract_content, self);
+        self.profile_exit();
+        self.initialize_contract_from_ast(contract_identifier, &contract_ast?, &contract_content)
     }
 
     pub fn initialize_contract_from_ast(
@@ -978,6 +1125,7 @@ impl<'a, 'b> Environment<'a, 'b> {
         contract_string: &str,
     ) -> Result<()> {
         self.global_context.begin();
+        self.profile_enter(|_| contract_identifier.to_string());
 
         // wrap in a closure so that `?` can be caught and the global_context can roll_back()
         //  before returning.
@@ -1015,6 +1163,7 @@ impl<'a, 'b> Environment<'a, 'b> {
             self.drop_memory(memory_use);
             result
         })();
+        self.profile_exit();
 
         match result {
             Ok(contract) => {
@@ -1104,6 +1253,24 @@ impl<'a, 'b> Environment<'a, 'b> {
         Ok(())
     }
 
+    /// Register an event declared with `define-event`. Its topic is the event's name.
+    pub fn register_contract_event(&mut self, name: &ClarityName, value: Value) -> Result<()> {
+        let contract_event = SmartContractEventData {
+            key: (
+                self.contract_context.contract_identifier.clone(),
+                name.to_string(),
+            ),
+            value,
+        };
+
+        if let Some(batch) = self.global_context.event_batches.last_mut() {
+            batch
+                .events
+                .push(StacksTransactionEvent::SmartContractEvent(contract_event));
+        }
+        Ok(())
+    }
+
     pub fn register_stx_transfer_event(
         &mut self,
         sender: PrincipalData,
@@ -1278,6 +1445,25 @@ impl<'a> GlobalContext<'a> {
             asset_maps: Vec::new(),
             event_batches: Vec::new(),
             mainnet,
+            eval_hooks: None,
+            tracer: None,
+            cost_profile: None,
+        }
+    }
+
+    pub fn add_eval_hook(&mut self, hook: &'a mut dyn EvalHook) {
+        if let Some(ref mut hooks) = self.eval_hooks {
+            hooks.push(hook);
+        } else {
+            self.eval_hooks = Some(vec![hook]);
+        }
+    }
+
+    /// Are any EvalHooks registered?
+    pub fn has_eval_hooks(&self) -> bool {
+        match self.eval_hooks {
+            Some(ref hooks) => !hooks.is_empty(),
+            None => false,
         }
     }
 
@@ -1291,6 +1477,11 @@ impl<'a> GlobalContext<'a> {
             .expect("Failed to obtain asset map")
     }
 
+    /// Get the asset map of the innermost open transaction, if any.
+    pub fn get_readonly_asset_map(&self) -> Option<&AssetMap> {
+        self.asset_maps.last()
+    }
+
     pub fn log_asset_transfer(
         &mut self,
         sender: &PrincipalData,
@@ -1455,6 +1646,7 @@ impl ContractContext {
             meta_data_var: HashMap::new(),
             meta_nft: HashMap::new(),
             meta_ft: HashMap::new(),
+            defined_events: HashMap::new(),
         }
     }
 
@@ -1473,6 +1665,10 @@ impl ContractContext {
         self.defined_traits.get(name).cloned()
     }
 
+    pub fn lookup_event_type(&self, name: &str) -> Option<&TypeSignature> {
+        self.defined_events.get(name)
+    }
+
     pub fn is_explicitly_implementing_trait(&self, trait_identifier: &TraitIdentifier) -> bool {
         self.implemented_traits.contains(trait_identifier)
     }
@@ -1483,6 +1679,7 @@ impl ContractContext {
             || self.functions.contains_key(name)
             || self.persisted_names.contains(name)
             || self.defined_traits.contains_key(name)
+            || self.defined_events.contains_key(name)
     }
 }
 
@@ -1540,10 +1737,38 @@ impl<'a> LocalContext<'a> {
     }
 }
 
+impl StackFrame {
+    pub fn json_serialize(&self) -> serde_json::Value {
+        json!({
+            "contract_identifier": self.contract_identifier.to_string(),
+            "function": self.function.to_string(),
+            "call_site": self.call_site,
+        })
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.function)?;
+        match self.call_site {
+            Some(ref span) => write!(
+                f,
+                " ({}, line {}, column {})",
+                self.contract_identifier, span.start_line, span.start_column
+            ),
+            None => write!(f, " ({})", self.contract_identifier),
+        }
+    }
+}
+
 impl CallStack {
     pub fn new() -> CallStack {
         CallStack {
             stack: Vec::new(),
+            #[cfg(feature = "developer-mode")]
+            call_sites: Vec::new(),
+            #[cfg(feature = "developer-mode")]
+            contracts: Vec::new(),
             set: HashSet::new(),
             apply_depth: 0,
         }
@@ -1557,13 +1782,56 @@ impl CallStack {
         self.set.contains(function)
     }
 
-    pub fn insert(&mut self, function: &FunctionIdentifier, track: bool) {
+    /// The functions currently on the stack, outermost first.
+    pub fn frames(&self) -> &[FunctionIdentifier] {
+        &self.stack
+    }
+
+    /// Push a frame for `function`, applied at `call_site` in the source of `caller`.
+    pub fn insert(
+        &mut self,
+        function: &FunctionIdentifier,
+        track: bool,
+        caller: &QualifiedContractIdentifier,
+        call_site: Option<&Span>,
+    ) {
+        self.push_call_site(caller, call_site);
         self.stack.push(function.clone());
         if track {
             self.set.insert(function.clone());
         }
     }
 
+    #[cfg(feature = "developer-mode")]
+    fn push_call_site(&mut self, caller: &QualifiedContractIdentifier, call_site: Option<&Span>) {
+        let caller_changed = match self.contracts.last() {
+            Some((_, contract)) => contract != caller,
+            None => true,
+        };
+        if caller_changed {
+            self.contracts.push((self.stack.len(), caller.clone()));
+        }
+        self.call_sites.push(call_site.cloned());
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    fn push_call_site(&mut self, _caller: &QualifiedContractIdentifier, _call_site: Option<&Span>) {
+    }
+
+    #[cfg(feature = "developer-mode")]
+    fn pop_call_site(&mut self) {
+        self.call_sites.pop();
+        let depth = self.stack.len();
+        if let Some((index, _)) = self.contracts.last() {
+            if *index >= depth {
+                self.contracts.pop();
+            }
+        }
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    fn pop_call_site(&mut self) {}
+
     pub fn incr_apply_depth(&mut self) {
         self.apply_depth += 1;
     }
@@ -1574,6 +1842,7 @@ impl CallStack {
 
     pub fn remove(&mut self, function: &FunctionIdentifier, tracked: bool) -> Result<()> {
         if let Some(removed) = self.stack.pop() {
+            self.pop_call_site();
             if removed != *function {
                 return Err(InterpreterError::InterpreterError(
                     "Tried to remove item from empty call stack.".to_string(),
@@ -1593,13 +1862,38 @@ impl CallStack {
     }
 
     #[cfg(feature = "developer-mode")]
-    pub fn make_stack_trace(&self) -> StackTrace {
-        self.stack.clone()
+    pub fn make_stack_trace(&self) -> Result<StackTrace> {
+        let mut contracts = self.contracts.iter().peekable();
+        let mut contract_identifier = None;
+        let mut trace = Vec::with_capacity(self.stack.len());
+        for (index, (function, call_site)) in
+            self.stack.iter().zip(self.call_sites.iter()).enumerate()
+        {
+            while contracts
+                .peek()
+                .map(|(start, _)| *start <= index)
+                .unwrap_or(false)
+            {
+                contract_identifier = contracts.next().map(|(_, contract)| contract);
+            }
+            // the first frame always records its caller's contract.
+            let contract_identifier = contract_identifier.ok_or_else(|| {
+                InterpreterError::InterpreterError(
+                    "Call stack frame without a caller contract".to_string(),
+                )
+            })?;
+            trace.push(StackFrame {
+                contract_identifier: contract_identifier.clone(),
+                function: function.clone(),
+                call_site: call_site.clone(),
+            });
+        }
+        Ok(trace)
     }
 
     #[cfg(not(feature = "developer-mode"))]
-    pub fn make_stack_trace(&self) -> StackTrace {
-        Vec::new()
+    pub fn make_stack_trace(&self) -> Result<StackTrace> {
+        Ok(Vec::new())
     }
 }
 
@@ -129,5 +129,25 @@ define_named_enum!(ClarityCostFunction {
     NftTransfer("cost_nft_transfer"),
     NftOwner("cost_nft_owner"),
     NftBurn("cost_nft_burn"),
+    BuffToIntLe("cost_buff_to_int_le"),
+    BuffToUIntLe("cost_buff_to_uint_le"),
+    BuffToIntBe("cost_buff_to_int_be"),
+    BuffToUIntBe("cost_buff_to_uint_be"),
+    IntToAscii("cost_int_to_ascii"),
+    StringToInt("cost_string_to_int"),
+    Slice("cost_slice"),
+    ReplaceAt("cost_replace_at"),
+    IsStandard("cost_is_standard"),
+    PrincipalDestruct("cost_principal_destruct"),
+    PrincipalConstruct("cost_principal_construct"),
+    ToConsensusBuff("cost_to_consensus_buff"),
+    FromConsensusBuff("cost_from_consensus_buff"),
+    StxGetAccount("cost_stx_account"),
+    BitwiseAnd("cost_bitwise_and"),
+    BitwiseOr("cost_bitwise_or"),
+    BitwiseNot("cost_bitwise_not"),
+    BitwiseLShift("cost_bitwise_left_shift"),
+    BitwiseRShift("cost_bitwise_right_shift"),
+    EmitEvent("cost_emit_event"),
     PoisonMicroblock("poison_microblock"),
 });
@@ -16,6 +16,7 @@
 
 pub mod constants;
 pub mod cost_functions;
+pub mod profiler;
 
 use regex::internal::Exec;
 use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
@@ -25,7 +26,7 @@ use std::{cmp, fmt};
 
 use std::collections::{BTreeMap, HashMap};
 
-use chainstate::stacks::boot::boot_code_id;
+use chainstate::stacks::boot::{boot_code_id, BOOT_CODE_COSTS};
 
 use vm::ast::ContractAST;
 use vm::contexts::{ContractContext, Environment, GlobalContext, OwnedEnvironment};
@@ -635,6 +636,55 @@ impl LimitedCostTracker {
         }
     }
 
+    /// Create a cost tracker which evaluates costs with the boot code cost functions,
+    ///  without loading them from a chainstate (which may not have the costs contract,
+    ///  e.g., a clarity-cli database).
+    pub fn new_with_boot_costs(mainnet: bool, limit: ExecutionCost) -> Result<LimitedCostTracker> {
+        let boot_costs_id = boot_code_id("costs", mainnet);
+
+        let mut costs_store = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new_cost_limited(
+            mainnet,
+            costs_store.as_clarity_db(),
+            LimitedCostTracker::new_free(),
+        );
+        owned_env
+            .initialize_contract(boot_costs_id.clone(), BOOT_CODE_COSTS)
+            .map_err(|e| {
+                error!("Failed to initialize boot code cost contract"; "error" => %e);
+                CostErrors::CostContractLoadFailure
+            })?;
+        let (mut costs_db, _) = owned_env
+            .destruct()
+            .ok_or(CostErrors::CostContractLoadFailure)?;
+        let contract_context = costs_db
+            .get_contract(&boot_costs_id)
+            .map_err(|_| CostErrors::CostContractLoadFailure)?
+            .contract_context;
+
+        let mut cost_function_references = HashMap::new();
+        for f in ClarityCostFunction::ALL.iter() {
+            cost_function_references.insert(
+                f,
+                ClarityCostFunctionReference::new(boot_costs_id.clone(), f.get_name()),
+            );
+        }
+        let mut cost_contracts = HashMap::new();
+        cost_contracts.insert(boot_costs_id, contract_context);
+
+        Ok(LimitedCostTracker {
+            cost_function_references,
+            cost_contracts,
+            contract_call_circuits: HashMap::new(),
+            limit,
+            memory_limit: CLARITY_MEMORY_LIMIT,
+            total: ExecutionCost::zero(),
+            memory: 0,
+            free: false,
+            mainnet,
+        })
+    }
+
     /// `apply_updates` - tells this function to look for any changes in the cost voting contract
     ///   which would need to be applied. if `false`, just load the last computed cost state in this
     ///   fork.
@@ -0,0 +1,313 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+
+use vm::contexts::CallStack;
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::{CostErrors, CostTracker, ExecutionCost, LimitedCostTracker};
+use vm::representations::{ClarityName, SymbolicExpression};
+use vm::types::QualifiedContractIdentifier;
+
+/// The ExecutionCost dimension reported in a profile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileMetric {
+    Runtime,
+    ReadCount,
+    ReadLength,
+    WriteCount,
+    WriteLength,
+}
+
+impl ProfileMetric {
+    pub fn parse(name: &str) -> Option<ProfileMetric> {
+        match name {
+            "runtime" => Some(ProfileMetric::Runtime),
+            "read_count" => Some(ProfileMetric::ReadCount),
+            "read_length" => Some(ProfileMetric::ReadLength),
+            "write_count" => Some(ProfileMetric::WriteCount),
+            "write_length" => Some(ProfileMetric::WriteLength),
+            _ => None,
+        }
+    }
+
+    pub fn select(&self, cost: &ExecutionCost) -> u64 {
+        match self {
+            ProfileMetric::Runtime => cost.runtime,
+            ProfileMetric::ReadCount => cost.read_count,
+            ProfileMetric::ReadLength => cost.read_length,
+            ProfileMetric::WriteCount => cost.write_count,
+            ProfileMetric::WriteLength => cost.write_length,
+        }
+    }
+}
+
+/// The stack which costs charged outside of any profiled frame are attributed to.
+pub const TOP_LEVEL_STACK: &str = "<top-level>";
+
+/// A CostProfile holds the costs charged through a ProfilingCostTracker, keyed by the stack
+///  that was being evaluated at the time of each charge. Stacks are made of the enclosing
+///  user-defined functions and the source line of the innermost expression, e.g.,
+///  `S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;S1G2081040G2081040G2081040G208105NK8PE5.tokens:14`,
+///  which is the "folded stacks" format consumed by flamegraph tools.
+///
+/// The interpreter enters a frame for every expression it evaluates, every contract it
+///  initializes, and every public function it calls through `execute_contract()`, so that
+///  contract storage and call setup costs are attributed too.
+pub struct CostProfile {
+    frames: Vec<String>,
+    samples: BTreeMap<String, ExecutionCost>,
+}
+
+impl CostProfile {
+    pub fn new() -> CostProfile {
+        CostProfile {
+            frames: vec![],
+            samples: BTreeMap::new(),
+        }
+    }
+
+    /// The stack of an expression, evaluated with the given call stack in the given contract.
+    pub fn expression_stack(
+        call_stack: &CallStack,
+        contract_identifier: &QualifiedContractIdentifier,
+        expr: &SymbolicExpression,
+    ) -> String {
+        let stack = CostProfile::make_stack(
+            call_stack,
+            expr.start_line()
+                .map(|line| format!("{}:{}", contract_identifier, line)),
+        );
+        if stack.is_empty() {
+            contract_identifier.to_string()
+        } else {
+            stack
+        }
+    }
+
+    /// The stack of a call to a public function through `execute_contract()` (by a transaction,
+    ///  or a `contract-call?`), made before the function is pushed onto the call stack.
+    pub fn contract_call_stack(
+        call_stack: &CallStack,
+        contract_identifier: &QualifiedContractIdentifier,
+        function_name: &str,
+    ) -> String {
+        CostProfile::make_stack(
+            call_stack,
+            Some(format!("{}:{}", contract_identifier, function_name)),
+        )
+    }
+
+    fn make_stack(call_stack: &CallStack, innermost: Option<String>) -> String {
+        let mut frames: Vec<_> = call_stack
+            .frames()
+            .iter()
+            .filter(|frame| !frame.is_native())
+            .map(|frame| frame.to_string())
+            .collect();
+        frames.extend(innermost);
+        frames.join(";")
+    }
+
+    /// Attribute charges to `stack` until the matching `exit()`.
+    pub fn enter(&mut self, stack: String) {
+        self.frames.push(stack);
+    }
+
+    pub fn exit(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Attribute a charge to the current stack.
+    pub fn record(&mut self, cost: &ExecutionCost) {
+        if *cost == ExecutionCost::zero() {
+            return;
+        }
+        let stack = self
+            .frames
+            .last()
+            .map(|stack| stack.as_str())
+            .unwrap_or(TOP_LEVEL_STACK);
+        self.samples
+            .entry(stack.to_string())
+            .or_insert_with(ExecutionCost::zero)
+            .add(cost)
+            .expect("BUG: sample overflowed the cost that was just charged");
+    }
+
+    /// The cost attributed to each stack, keyed by its folded representation.
+    pub fn samples(&self) -> &BTreeMap<String, ExecutionCost> {
+        &self.samples
+    }
+
+    /// The sum of all the costs attributed in this profile.
+    pub fn total(&self) -> ExecutionCost {
+        let mut total = ExecutionCost::zero();
+        for cost in self.samples.values() {
+            total
+                .add(cost)
+                .expect("BUG: profile total overflowed the charged costs");
+        }
+        total
+    }
+
+    /// Write this profile as folded stacks (one `frame;frame;... count` line per stack),
+    ///  counting the given metric. Stacks which were never charged for the metric are omitted.
+    pub fn write_folded<W: Write>(&self, metric: ProfileMetric, output: &mut W) -> io::Result<()> {
+        for (stack, cost) in self.samples.iter() {
+            let count = metric.select(cost);
+            if count > 0 {
+                writeln!(output, "{} {}", stack, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A CostTracker which charges costs to the wrapped LimitedCostTracker, and attributes
+///  whatever the wrapped tracker actually charged (nothing, if it is free) to the current
+///  stack of a CostProfile.
+pub struct ProfilingCostTracker<'a> {
+    tracker: &'a mut LimitedCostTracker,
+    profile: &'a mut CostProfile,
+}
+
+impl<'a> ProfilingCostTracker<'a> {
+    pub fn new(
+        tracker: &'a mut LimitedCostTracker,
+        profile: &'a mut CostProfile,
+    ) -> ProfilingCostTracker<'a> {
+        ProfilingCostTracker { tracker, profile }
+    }
+
+    fn record_charge<F, R>(&mut self, charge: F) -> R
+    where
+        F: FnOnce(&mut LimitedCostTracker) -> R,
+    {
+        let before = self.tracker.get_total();
+        let result = charge(self.tracker);
+        let mut charged = self.tracker.get_total();
+        charged
+            .sub(&before)
+            .expect("BUG: cost tracker total decreased while charging");
+        self.profile.record(&charged);
+        result
+    }
+}
+
+impl<'a> CostTracker for ProfilingCostTracker<'a> {
+    fn compute_cost(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        input: &[u64],
+    ) -> std::result::Result<ExecutionCost, CostErrors> {
+        self.tracker.compute_cost(cost_function, input)
+    }
+    fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
+        self.record_charge(|tracker| tracker.add_cost(cost))
+    }
+    fn add_memory(&mut self, memory: u64) -> std::result::Result<(), CostErrors> {
+        self.tracker.add_memory(memory)
+    }
+    fn drop_memory(&mut self, memory: u64) {
+        self.tracker.drop_memory(memory)
+    }
+    fn reset_memory(&mut self) {
+        self.tracker.reset_memory()
+    }
+    fn short_circuit_contract_call(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        function: &ClarityName,
+        input: &[u64],
+    ) -> std::result::Result<bool, CostErrors> {
+        self.record_charge(|tracker| tracker.short_circuit_contract_call(contract, function, input))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::MemoryBackingStore;
+    use vm::types::PrincipalData;
+    use vm::Value;
+
+    const CONTRACT: &str = "(define-map balances principal uint)
+(define-private (double (x int)) (* x 2))
+(define-public (quadruple (x int))
+  (begin
+    (map-set balances tx-sender u1)
+    (ok (double (double x)))))";
+
+    #[test]
+    fn test_profile_attribution() {
+        let contract_id = QualifiedContractIdentifier::local("profiled").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+
+        let mut marf = MemoryBackingStore::new();
+        let cost_tracker =
+            LimitedCostTracker::new_with_boot_costs(false, ExecutionCost::max_value()).unwrap();
+        let mut owned_env =
+            OwnedEnvironment::new_cost_limited(false, marf.as_clarity_db(), cost_tracker);
+        owned_env.enable_profiling();
+        owned_env
+            .initialize_contract(contract_id.clone(), CONTRACT)
+            .unwrap();
+        owned_env
+            .execute_transaction(
+                Value::Principal(sender),
+                contract_id.clone(),
+                "quadruple",
+                &[SymbolicExpression::atom_value(Value::Int(3))],
+            )
+            .unwrap();
+        let profile = owned_env.take_profile().unwrap();
+        let (_, cost_tracker) = owned_env.destruct().unwrap();
+
+        // every charge is attributed, including those made outside of any expression
+        assert_eq!(profile.total(), cost_tracker.get_total());
+
+        let quadruple = format!("{}:quadruple", contract_id);
+        let double = format!("{}:double", contract_id);
+        let map_set_line = format!("{};{}:5", quadruple, contract_id);
+        let double_line = format!("{};{};{}:2", quadruple, double, contract_id);
+
+        let samples = profile.samples();
+        assert!(samples[&map_set_line].write_count > 0);
+        assert!(samples[&double_line].runtime > 0);
+        assert_eq!(samples[&double_line].write_count, 0);
+        // parsing and storing the contract
+        assert!(samples[&contract_id.to_string()].runtime > 0);
+        // loading the contract to call quadruple
+        assert!(samples[&quadruple].runtime > 0);
+        assert!(!samples.contains_key(TOP_LEVEL_STACK));
+
+        let mut folded = vec![];
+        profile
+            .write_folded(ProfileMetric::WriteCount, &mut folded)
+            .unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded.contains(&format!(
+            "{} {}\n",
+            map_set_line, samples[&map_set_line].write_count
+        )));
+        // stacks which never wrote are omitted
+        assert!(!folded.contains(&double_line));
+    }
+}
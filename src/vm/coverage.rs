@@ -0,0 +1,352 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::io::Write;
+
+use vm::contexts::{Environment, LocalContext};
+use vm::errors::InterpreterResult as Result;
+use vm::functions::define::DefineFunctionsParsed;
+use vm::representations::SymbolicExpression;
+use vm::types::QualifiedContractIdentifier;
+use vm::{EvalHook, Value};
+
+/// Special forms which choose between two branches. The branch point is identified
+///  by the form's first argument, which is what the interpreter reports to
+///  `EvalHook::did_take_branch`.
+const BRANCHING_FORMS: &[&str] = &[
+    "if",
+    "match",
+    "asserts!",
+    "unwrap!",
+    "unwrap-err!",
+    "try!",
+    "and",
+    "or",
+];
+
+const BRANCHES_PER_POINT: usize = 2;
+
+struct BranchPoint {
+    line: u32,
+    taken: [u64; BRANCHES_PER_POINT],
+}
+
+#[derive(Default)]
+struct ContractCoverage {
+    lines: BTreeMap<u32, u64>,
+    branches: BTreeMap<u64, BranchPoint>,
+}
+
+/// The CoverageReporter records which source lines of each contract were evaluated,
+///  and which branches of each branching form were taken, and writes the result as
+///  an LCOV tracefile.
+///
+/// Contracts should be registered with `register_contract()` before they are evaluated,
+///  so that lines and branches which are never reached are reported as well.
+pub struct CoverageReporter {
+    contracts: HashMap<QualifiedContractIdentifier, ContractCoverage>,
+}
+
+impl ContractCoverage {
+    fn register_expression(&mut self, expr: &SymbolicExpression) {
+        let line = match expr.start_line() {
+            Some(line) => line,
+            None => return,
+        };
+        self.lines.entry(line).or_insert(0);
+
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => return,
+        };
+
+        if let (Some(form), Some(branch_expr)) =
+            (list.get(0).and_then(|x| x.match_atom()), list.get(1))
+        {
+            if BRANCHING_FORMS.contains(&form.as_str()) {
+                self.branches.entry(branch_expr.id).or_insert(BranchPoint {
+                    line,
+                    taken: [0; BRANCHES_PER_POINT],
+                });
+            }
+        }
+
+        for child in list.iter() {
+            self.register_expression(child);
+        }
+    }
+
+    fn register_top_level(&mut self, expr: &SymbolicExpression) {
+        // only the evaluated parts of a definition are registered: type signatures
+        //  and function signatures are never evaluated.
+        match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(define)) => match define {
+                DefineFunctionsParsed::Constant { value, .. } => self.register_expression(value),
+                DefineFunctionsParsed::PrivateFunction { body, .. }
+                | DefineFunctionsParsed::ReadOnlyFunction { body, .. }
+                | DefineFunctionsParsed::PublicFunction { body, .. } => {
+                    self.register_expression(body)
+                }
+                DefineFunctionsParsed::BoundedFungibleToken { max_supply, .. } => {
+                    self.register_expression(max_supply)
+                }
+                DefineFunctionsParsed::PersistedVariable { initial, .. } => {
+                    self.register_expression(initial)
+                }
+                _ => {}
+            },
+            Ok(None) => self.register_expression(expr),
+            Err(_) => {}
+        }
+    }
+}
+
+impl CoverageReporter {
+    pub fn new() -> CoverageReporter {
+        CoverageReporter {
+            contracts: HashMap::new(),
+        }
+    }
+
+    /// Register the lines and branch points of a contract's top-level expressions.
+    pub fn register_contract(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        expressions: &[SymbolicExpression],
+    ) {
+        let coverage = self
+            .contracts
+            .entry(contract_identifier.clone())
+            .or_insert_with(ContractCoverage::default);
+        for expr in expressions.iter() {
+            coverage.register_top_level(expr);
+        }
+    }
+
+    /// The number of times each line of the contract began an evaluation.
+    pub fn line_hits(&self, contract_identifier: &QualifiedContractIdentifier) -> Vec<(u32, u64)> {
+        self.contracts
+            .get(contract_identifier)
+            .map(|coverage| {
+                coverage
+                    .lines
+                    .iter()
+                    .map(|(line, hits)| (*line, *hits))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The number of times each branch was taken, as (line, [first, second]) per branch point,
+    ///  ordered by line.
+    pub fn branch_hits(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> Vec<(u32, [u64; BRANCHES_PER_POINT])> {
+        let mut hits: Vec<_> = self
+            .contracts
+            .get(contract_identifier)
+            .map(|coverage| {
+                coverage
+                    .branches
+                    .values()
+                    .map(|point| (point.line, point.taken))
+                    .collect()
+            })
+            .unwrap_or_default();
+        hits.sort_by_key(|(line, _)| *line);
+        hits
+    }
+
+    /// Write the coverage data as an LCOV tracefile. `source_paths` maps contracts to the
+    ///  source file reported for them; contracts without one are reported by their identifier.
+    pub fn write_lcov<W: Write>(
+        &self,
+        test_name: &str,
+        source_paths: &HashMap<QualifiedContractIdentifier, String>,
+        output: &mut W,
+    ) -> io::Result<()> {
+        let mut contract_identifiers: Vec<_> = self.contracts.keys().collect();
+        contract_identifiers.sort_by_key(|id| id.to_string());
+
+        for contract_identifier in contract_identifiers {
+            let coverage = &self.contracts[contract_identifier];
+            let source = source_paths
+                .get(contract_identifier)
+                .cloned()
+                .unwrap_or_else(|| contract_identifier.to_string());
+
+            writeln!(output, "TN:{}", test_name)?;
+            writeln!(output, "SF:{}", source)?;
+
+            let mut branch_points: Vec<_> = coverage.branches.iter().collect();
+            branch_points.sort_by_key(|(block, point)| (point.line, **block));
+            let mut branches_hit = 0;
+            for (block, point) in branch_points.iter() {
+                let reached = point.taken.iter().any(|taken| *taken > 0);
+                for (branch, taken) in point.taken.iter().enumerate() {
+                    if *taken > 0 {
+                        branches_hit += 1;
+                    }
+                    if reached {
+                        writeln!(output, "BRDA:{},{},{},{}", point.line, block, branch, taken)?;
+                    } else {
+                        writeln!(output, "BRDA:{},{},{},-", point.line, block, branch)?;
+                    }
+                }
+            }
+            writeln!(output, "BRF:{}", branch_points.len() * BRANCHES_PER_POINT)?;
+            writeln!(output, "BRH:{}", branches_hit)?;
+
+            for (line, hits) in coverage.lines.iter() {
+                writeln!(output, "DA:{},{}", line, hits)?;
+            }
+            writeln!(output, "LF:{}", coverage.lines.len())?;
+            writeln!(
+                output,
+                "LH:{}",
+                coverage.lines.values().filter(|hits| **hits > 0).count()
+            )?;
+            writeln!(output, "end_of_record")?;
+        }
+        Ok(())
+    }
+}
+
+impl EvalHook for CoverageReporter {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        if let Some(line) = expr.start_line() {
+            let coverage = self
+                .contracts
+                .entry(env.contract_context.contract_identifier.clone())
+                .or_insert_with(ContractCoverage::default);
+            *coverage.lines.entry(line).or_insert(0) += 1;
+        }
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        _env: &mut Environment,
+        _context: &LocalContext,
+        _expr: &SymbolicExpression,
+        _result: &Result<Value>,
+    ) {
+    }
+
+    fn did_take_branch(
+        &mut self,
+        env: &mut Environment,
+        branch_expr: &SymbolicExpression,
+        branch: usize,
+    ) {
+        if branch >= BRANCHES_PER_POINT {
+            return;
+        }
+        let coverage = self
+            .contracts
+            .entry(env.contract_context.contract_identifier.clone())
+            .or_insert_with(ContractCoverage::default);
+        // branch points that were not registered are attributed to the line of the
+        //  deciding expression.
+        let line = branch_expr.start_line().unwrap_or(0);
+        let point = coverage
+            .branches
+            .entry(branch_expr.id)
+            .or_insert(BranchPoint {
+                line,
+                taken: [0; BRANCHES_PER_POINT],
+            });
+        point.taken[branch] += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vm::ast;
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::MemoryBackingStore;
+    use vm::types::PrincipalData;
+
+    const CONTRACT: &str = "(define-constant limit 10)
+(define-public (check (x int))
+  (if (> x limit)
+    (err u1)
+    (ok x)))
+(define-public (never-called)
+  (ok (unwrap! (some 1) (err u2))))";
+
+    #[test]
+    fn test_line_and_branch_coverage() {
+        let contract_id = QualifiedContractIdentifier::local("covered").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+
+        let mut reporter = CoverageReporter::new();
+        let parsed = ast::build_ast(&contract_id, CONTRACT, &mut ()).unwrap();
+        reporter.register_contract(&contract_id, &parsed.expressions);
+
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db());
+        owned_env
+            .initialize_contract(contract_id.clone(), CONTRACT)
+            .unwrap();
+
+        owned_env.add_eval_hook(&mut reporter);
+        owned_env
+            .execute_transaction(
+                Value::Principal(sender),
+                contract_id.clone(),
+                "check",
+                &[SymbolicExpression::atom_value(Value::Int(3))],
+            )
+            .unwrap();
+        owned_env.destruct().unwrap();
+
+        assert_eq!(
+            reporter.line_hits(&contract_id),
+            vec![(1, 0), (3, 4), (4, 0), (5, 2), (7, 0)]
+        );
+        assert_eq!(
+            reporter.branch_hits(&contract_id),
+            vec![(3, [0, 1]), (7, [0, 0])]
+        );
+
+        let mut sources = HashMap::new();
+        sources.insert(contract_id.clone(), "covered.clar".to_string());
+        let mut lcov = vec![];
+        reporter.write_lcov("", &sources, &mut lcov).unwrap();
+        let lcov = String::from_utf8(lcov).unwrap();
+        let lcov_lines: Vec<_> = lcov.lines().collect();
+
+        assert_eq!(lcov_lines[0], "TN:");
+        assert_eq!(lcov_lines[1], "SF:covered.clar");
+        assert!(lcov_lines[2].starts_with("BRDA:3,"));
+        assert!(lcov_lines[2].ends_with(",0,0"));
+        assert!(lcov_lines[3].ends_with(",1,1"));
+        assert!(lcov_lines[4].starts_with("BRDA:7,"));
+        assert!(lcov_lines[4].ends_with(",0,-"));
+        assert!(lcov.contains("\nBRF:4\nBRH:1\n"));
+        assert!(lcov.contains("\nDA:3,4\nDA:4,0\nDA:5,2\n"));
+        assert!(lcov.contains("\nLF:5\nLH:2\nend_of_record\n"));
+    }
+}
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use rusqlite::OptionalExtension;
+use std::cmp;
 use std::collections::{HashMap, VecDeque};
 use std::convert::{TryFrom, TryInto};
 
@@ -60,6 +61,10 @@ use core::{
 
 pub const STORE_CONTRACT_SRC_INTERFACE: bool = true;
 
+// listing the entries of a data map or NFT scans at most this many indexed keys per entry asked
+//   for, so that a page of a sparse map can't require a scan of its whole key index.
+pub const INDEXED_KEYS_SCAN_FACTOR: u32 = 10;
+
 #[repr(u8)]
 pub enum StoreType {
     DataMap = 0x00,
@@ -97,6 +102,8 @@ pub trait HeadersDB {
     fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64>;
     fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32>;
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress>;
+    fn get_miner_reward_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128>;
+    fn get_total_fees_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128>;
 }
 
 pub trait BurnStateDB {
@@ -158,6 +165,14 @@ impl HeadersDB for DBConn {
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         get_miner_info(self, id_bhh).map(|x| x.address)
     }
+
+    fn get_miner_reward_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128> {
+        get_miner_info(self, id_bhh).map(|x| x.coinbase)
+    }
+
+    fn get_total_fees_for_block(&self, id_bhh: &StacksBlockId) -> Option<u128> {
+        get_miner_info(self, id_bhh).map(|x| x.tx_fees_anchored + x.tx_fees_streamed)
+    }
 }
 
 impl HeadersDB for &dyn HeadersDB {
@@ -182,6 +197,12 @@ impl HeadersDB for &dyn HeadersDB {
     fn get_miner_address(&self, bhh: &StacksBlockId) -> Option<StacksAddress> {
         (*self).get_miner_address(bhh)
     }
+    fn get_miner_reward_for_block(&self, bhh: &StacksBlockId) -> Option<u128> {
+        (*self).get_miner_reward_for_block(bhh)
+    }
+    fn get_total_fees_for_block(&self, bhh: &StacksBlockId) -> Option<u128> {
+        (*self).get_total_fees_for_block(bhh)
+    }
 }
 
 impl BurnStateDB for SortitionHandleTx<'_> {
@@ -315,6 +336,12 @@ impl HeadersDB for NullHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_miner_reward_for_block(&self, _id_bhh: &StacksBlockId) -> Option<u128> {
+        None
+    }
+    fn get_total_fees_for_block(&self, _id_bhh: &StacksBlockId) -> Option<u128> {
+        None
+    }
 }
 
 impl BurnStateDB for NullBurnStateDB {
@@ -400,13 +427,27 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_value(key, expected)
     }
 
-    pub fn get_with_proof<T>(&mut self, key: &str) -> Option<(T, TrieMerkleProof<StacksBlockId>)>
+    pub fn get_with_proof<T>(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(T, TrieMerkleProof<StacksBlockId>)>>
     where
         T: ClarityDeserializable<T>,
     {
         self.store.get_with_proof(key)
     }
 
+    /// Start collecting the MARF proofs of the keys that are read from the underlying store,
+    ///  e.g., so that a read-only call's result can be verified against the state root.
+    pub fn begin_read_proofs(&mut self) {
+        self.store.begin_read_proofs()
+    }
+
+    /// Stop collecting MARF proofs, and return each key read, with its value and proof.
+    pub fn take_read_proofs(&mut self) -> Vec<(String, String, TrieMerkleProof<StacksBlockId>)> {
+        self.store.take_read_proofs()
+    }
+
     pub fn make_key_for_trip(
         contract_identifier: &QualifiedContractIdentifier,
         data: StoreType,
@@ -431,6 +472,26 @@ impl<'a> ClarityDatabase<'a> {
         )
     }
 
+    /// Split the key of a data map entry or an NFT into the prefix of its map (or asset), and its
+    ///   serialized key value.  Returns None for any other kind of key.
+    pub fn split_indexed_key(key: &str) -> Option<(&str, &str)> {
+        let mut parts = key.rsplitn(2, "::");
+        let key_value = parts.next()?;
+        let prefix = parts.next()?;
+
+        let fields: Vec<&str> = prefix.split("::").collect();
+        if fields.len() != 4 || fields[0] != "vm" {
+            return None;
+        }
+        if fields[2] == (StoreType::DataMap as u8).to_string()
+            || fields[2] == (StoreType::NonFungibleToken as u8).to_string()
+        {
+            Some((prefix, key_value))
+        } else {
+            None
+        }
+    }
+
     pub fn insert_contract_hash(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -719,6 +780,29 @@ impl<'a> ClarityDatabase<'a> {
             .into()
     }
 
+    /// Get the burnchain block height in which the Stacks block at `block_height` was mined.
+    pub fn get_block_burnchain_height(&mut self, block_height: u32) -> u32 {
+        let id_bhh = self.get_index_block_header_hash(block_height);
+        self.get_burnchain_block_height(&id_bhh)
+            .expect("Failed to get block data.")
+    }
+
+    /// Get the coinbase paid to the miner of the Stacks block at `block_height`.
+    pub fn get_block_miner_reward(&mut self, block_height: u32) -> u128 {
+        let id_bhh = self.get_index_block_header_hash(block_height);
+        self.headers_db
+            .get_miner_reward_for_block(&id_bhh)
+            .expect("Failed to get block data.")
+    }
+
+    /// Get the transaction fees (anchored and streamed) of the Stacks block at `block_height`.
+    pub fn get_block_total_fees(&mut self, block_height: u32) -> u128 {
+        let id_bhh = self.get_index_block_header_hash(block_height);
+        self.headers_db
+            .get_total_fees_for_block(&id_bhh)
+            .expect("Failed to get block data.")
+    }
+
     pub fn get_stx_btc_ops_processed(&mut self) -> u64 {
         self.get("vm_pox::stx_btc_ops::processed_blocks")
             .unwrap_or(0)
@@ -1404,6 +1488,130 @@ impl<'a> ClarityDatabase<'a> {
     }
 }
 
+// Key iteration over data maps and NFTs
+impl<'a> ClarityDatabase<'a> {
+    /// List up to `limit` of the entries of a data map, in order of their serialized keys,
+    ///   starting after the serialized key `after`.  Returns the entries, and the cursor to pass
+    ///   as `after` for the next page, if there may be more.
+    /// Only entries that have been committed to the backing store are listed.  Fails with
+    ///   InterpreterError::KeyIndexUnavailable unless the store's key index covers every entry
+    ///   (see MarfedKV::set_key_index()).
+    pub fn list_map_entries(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        map_name: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<(Value, Value)>, Option<String>)> {
+        let descriptor = self.load_map(contract_identifier, map_name)?;
+        let prefix =
+            ClarityDatabase::make_key_for_trip(contract_identifier, StoreType::DataMap, map_name);
+        let stored_type = TypeSignature::new_option(descriptor.value_type)?;
+        self.list_indexed_entries(&prefix, &descriptor.key_type, &stored_type, after, limit)
+    }
+
+    /// List up to `limit` of the tokens of an NFT with their owners, in order of their serialized
+    ///   values, starting after the serialized value `after`.  Burnt tokens are not listed.
+    ///   Pagination works as in `list_map_entries`.
+    pub fn list_nft_owners(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        asset_name: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<(Value, PrincipalData)>, Option<String>)> {
+        let descriptor = self.load_nft(contract_identifier, asset_name)?;
+        let prefix = ClarityDatabase::make_key_for_trip(
+            contract_identifier,
+            StoreType::NonFungibleToken,
+            asset_name,
+        );
+        let stored_type = TypeSignature::new_option(TypeSignature::PrincipalType)?;
+        let (entries, next) =
+            self.list_indexed_entries(&prefix, &descriptor.key_type, &stored_type, after, limit)?;
+        let owners = entries
+            .into_iter()
+            .map(|(asset, owner)| (asset, owner.expect_principal()))
+            .collect();
+        Ok((owners, next))
+    }
+
+    /// List a page of the entries of the data map or NFT called `name` (a contract can't define
+    ///   both), where the entries of an NFT are its tokens and their owners.
+    pub fn list_map_or_nft_entries(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        name: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<(Value, Value)>, Option<String>)> {
+        match self.list_map_entries(contract_identifier, name, after, limit) {
+            Err(Error::Unchecked(CheckErrors::NoSuchMap(_))) => {
+                let (owners, next) =
+                    self.list_nft_owners(contract_identifier, name, after, limit)?;
+                let entries = owners
+                    .into_iter()
+                    .map(|(asset, owner)| (asset, Value::Principal(owner)))
+                    .collect();
+                Ok((entries, next))
+            }
+            res => res,
+        }
+    }
+
+    /// Page through the indexed keys under `prefix`, skipping those with no value (or a `none`
+    ///   value) as of the current chain tip, until `limit` entries are found.  At most
+    ///   `limit * INDEXED_KEYS_SCAN_FACTOR` keys are scanned: if that many are scanned first,
+    ///   the page is returned short, with the cursor of the last scanned key.
+    fn list_indexed_entries(
+        &mut self,
+        prefix: &str,
+        key_type: &TypeSignature,
+        stored_type: &TypeSignature,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<(Value, Value)>, Option<String>)> {
+        if !self.store.has_complete_key_index() {
+            return Err(InterpreterError::KeyIndexUnavailable.into());
+        }
+
+        let mut entries = vec![];
+        let mut cursor = after.map(|x| x.to_string());
+        if limit == 0 {
+            return Ok((entries, cursor));
+        }
+
+        let max_scanned = limit.saturating_mul(INDEXED_KEYS_SCAN_FACTOR);
+        let mut scanned = 0;
+        loop {
+            let batch = cmp::min(limit, max_scanned - scanned);
+            let keys =
+                self.store
+                    .get_indexed_keys(prefix, cursor.as_ref().map(|x| x.as_str()), batch);
+            let exhausted = keys.len() < (batch as usize);
+
+            for key in keys.into_iter() {
+                let value = self
+                    .get_value(&format!("{}::{}", prefix, &key), stored_type)
+                    .and_then(|value| value.expect_optional());
+                if let Some(value) = value {
+                    entries.push((Value::deserialize(&key, key_type), value));
+                }
+                cursor = Some(key);
+                scanned += 1;
+
+                if entries.len() >= (limit as usize) || scanned >= max_scanned {
+                    return Ok((entries, cursor));
+                }
+            }
+
+            if exhausted {
+                return Ok((entries, None));
+            }
+        }
+    }
+}
+
 // load/store STX token state and account nonces
 impl<'a> ClarityDatabase<'a> {
     fn make_key_for_account(principal: &PrincipalData, data: StoreType) -> String {
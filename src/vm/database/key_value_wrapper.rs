@@ -18,7 +18,7 @@ use super::{ClarityBackingStore, ClarityDeserializable, MarfedKV};
 use chainstate::{
     burn::BlockHeaderHash, stacks::index::proofs::TrieMerkleProof, stacks::StacksBlockId,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::{clone::Clone, cmp::Eq, hash::Hash};
 use util::hash::Sha512Trunc256Sum;
 use vm::errors::InterpreterResult as Result;
@@ -118,6 +118,8 @@ pub struct RollbackWrapper<'a> {
     //   to indicate a given contexts "start depth".
     stack: Vec<RollbackContext>,
     query_pending_data: bool,
+    // if set, the values and MARF proofs of the keys read from the underlying store.
+    read_proofs: Option<BTreeMap<String, (String, TrieMerkleProof<StacksBlockId>)>>,
 }
 
 // This is used for preserving rollback data longer
@@ -188,6 +190,7 @@ impl<'a> RollbackWrapper<'a> {
             metadata_lookup_map: HashMap::new(),
             stack: Vec::new(),
             query_pending_data: true,
+            read_proofs: None,
         }
     }
 
@@ -201,6 +204,7 @@ impl<'a> RollbackWrapper<'a> {
             metadata_lookup_map: log.metadata_lookup_map,
             stack: log.stack,
             query_pending_data: true,
+            read_proofs: None,
         }
     }
 
@@ -316,13 +320,48 @@ impl<'a> RollbackWrapper<'a> {
 
     /// this function will only return commitment proofs for values _already_ materialized
     ///  in the underlying store. otherwise it returns None.
-    pub fn get_with_proof<T>(&mut self, key: &str) -> Option<(T, TrieMerkleProof<StacksBlockId>)>
+    pub fn get_with_proof<T>(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(T, TrieMerkleProof<StacksBlockId>)>>
     where
         T: ClarityDeserializable<T>,
     {
-        self.store
-            .get_with_proof(key)
-            .map(|(value, proof)| (T::deserialize(&value), proof))
+        let entry = self.store.get_with_proof(key)?;
+        Ok(entry.map(|(value, proof)| (T::deserialize(&value), proof)))
+    }
+
+    /// Start recording the values and MARF proofs of the keys read from the underlying store.
+    pub fn begin_read_proofs(&mut self) {
+        self.read_proofs = Some(BTreeMap::new());
+    }
+
+    /// Stop recording, and return the recorded keys, with their values and proofs.
+    ///  Keys which were read but are not in the underlying store have no proof, and are omitted.
+    pub fn take_read_proofs(&mut self) -> Vec<(String, String, TrieMerkleProof<StacksBlockId>)> {
+        self.read_proofs
+            .take()
+            .map(|read_proofs| {
+                read_proofs
+                    .into_iter()
+                    .map(|(key, (value, proof))| (key, value, proof))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn get_from_store(&mut self, key: &str) -> Option<String> {
+        match self.read_proofs {
+            Some(ref mut read_proofs) => {
+                let (value, proof) = self
+                    .store
+                    .get_with_proof(key)
+                    .expect("ERROR: Clarity VM recorded read proofs from a store without proofs")?;
+                read_proofs.insert(key.to_string(), (value.clone(), proof));
+                Some(value)
+            }
+            None => self.store.get(key),
+        }
     }
 
     pub fn get<T>(&mut self, key: &str) -> Option<T>
@@ -342,7 +381,7 @@ impl<'a> RollbackWrapper<'a> {
             None
         };
 
-        lookup_result.or_else(|| self.store.get(key).map(|x| T::deserialize(&x)))
+        lookup_result.or_else(|| self.get_from_store(key).map(|x| T::deserialize(&x)))
     }
 
     pub fn get_value(&mut self, key: &str, expected: &TypeSignature) -> Option<Value> {
@@ -360,8 +399,7 @@ impl<'a> RollbackWrapper<'a> {
         };
 
         lookup_result.or_else(|| {
-            self.store
-                .get(key)
+            self.get_from_store(key)
                 .map(|x| Value::deserialize(&x, expected))
         })
     }
@@ -374,6 +412,21 @@ impl<'a> RollbackWrapper<'a> {
         self.store.get_block_at_height(block_height)
     }
 
+    /// Get candidate keys under `prefix` from the store's key index.  Keys that are only written
+    ///   in this wrapper's pending edits are not indexed until they are committed to the store.
+    pub fn get_indexed_keys(
+        &mut self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Vec<String> {
+        self.store.get_indexed_keys(prefix, after, limit)
+    }
+
+    pub fn has_complete_key_index(&mut self) -> bool {
+        self.store.has_complete_key_index()
+    }
+
     pub fn prepare_for_contract_metadata(
         &mut self,
         contract: &QualifiedContractIdentifier,
@@ -429,7 +482,13 @@ impl<'a> RollbackWrapper<'a> {
 
         match lookup_result {
             Some(x) => Ok(Some(x)),
-            None => self.store.get_metadata(contract, key),
+            None => {
+                if self.read_proofs.is_some() {
+                    // prove the contract's commitment, which attests to the contract's code.
+                    self.get_from_store(&MarfedKV::make_contract_hash_key(contract));
+                }
+                self.store.get_metadata(contract, key)
+            }
         }
     }
 
@@ -468,6 +527,8 @@ impl<'a> RollbackWrapper<'a> {
             .expect("ERROR: Clarity VM attempted GET on non-nested context.");
         if self.query_pending_data && self.lookup_map.contains_key(key) {
             true
+        } else if self.read_proofs.is_some() {
+            self.get_from_store(key).is_some()
         } else {
             self.store.has_entry(key)
         }
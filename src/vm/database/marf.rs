@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
 
 use burnchains::BurnchainHeaderHash;
@@ -23,7 +24,8 @@ use chainstate::stacks::index::proofs::TrieMerkleProof;
 use chainstate::stacks::index::storage::TrieFileStorage;
 use chainstate::stacks::index::{Error as MarfError, MARFValue, MarfTrieId, TrieHash};
 use chainstate::stacks::{StacksBlockHeader, StacksBlockId};
-use rusqlite::Connection;
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, NO_PARAMS};
 use std::convert::TryInto;
 use util::hash::{hex_bytes, to_hex, Hash160, Sha512Trunc256Sum};
 use vm::analysis::AnalysisDatabase;
@@ -51,11 +53,19 @@ use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 pub struct MarfedKV {
     chain_tip: StacksBlockId,
     marf: MARF<StacksBlockId>,
+    /// whether or not to index the keys of data map entries and NFTs (see set_key_index())
+    key_index: bool,
+    /// the key index's state in the side store, as of when this store was opened
+    key_index_state: Option<bool>,
 }
 
 pub struct WritableMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: MarfTransaction<'a, StacksBlockId>,
+    key_index: bool,
+    /// if set, the side store has a complete key index, but this store does not maintain it, so
+    ///   the index must be marked incomplete once an indexed key is written.
+    invalidate_key_index: bool,
 }
 
 pub struct ReadOnlyMarfStore<'a> {
@@ -65,10 +75,24 @@ pub struct ReadOnlyMarfStore<'a> {
 
 pub struct MemoryBackingStore {
     side_store: Connection,
+    /// the height of the simulated chain, if this store simulates one
+    simulated_height: Option<u32>,
 }
 
 pub struct NullBackingStore {}
 
+/// A read-only store whose data is a set of proven key/value pairs, e.g., the keys read
+///  by a read-only call, as verified against a MARF root hash. Block lookups, contract
+///  code and analysis are served from `local_store`, but a contract is only visible if its
+///  code in `local_store` matches the proven contract commitment.
+/// The MARF has no proofs of non-inclusion, so a key which is not proven may just have
+///  been left out: every read of one is recorded, so that callers can reject the result.
+pub struct ProvenBackingStore<'a> {
+    proven: HashMap<String, String>,
+    unproven_reads: BTreeSet<String>,
+    local_store: &'a mut dyn ClarityBackingStore,
+}
+
 // These functions generally _do not_ return errors, rather, any errors in the underlying storage
 //    will _panic_. The rationale for this is that under no condition should the interpreter
 //    attempt to continue processing in the event of an unexpected storage error.
@@ -77,7 +101,12 @@ pub trait ClarityBackingStore {
     fn put_all(&mut self, items: Vec<(String, String)>);
     /// fetch K-V out of the committed datastore
     fn get(&mut self, key: &str) -> Option<String>;
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)>;
+    /// fetch K-V out of the committed datastore, along with its MARF proof. Fails if this
+    ///  store can't produce proofs.
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>>;
     fn has_entry(&mut self, key: &str) -> bool {
         self.get(key).is_some()
     }
@@ -179,6 +208,29 @@ pub trait ClarityBackingStore {
             self.insert_metadata(&contract, &key, &value);
         }
     }
+
+    /// Add the keys of any data map entries and NFTs among `keys` to the side store's key index,
+    ///   so that the entries of a map (or the tokens of an NFT) can be iterated over.
+    fn index_keys(&mut self, keys: &[String]) {
+        for key in keys.iter() {
+            if let Some((prefix, key_value)) = ClarityDatabase::split_indexed_key(key) {
+                SqliteConnection::insert_indexed_key(self.get_side_store(), prefix, key_value);
+            }
+        }
+    }
+
+    /// Get up to `limit` of the serialized keys that were ever written under `prefix`, in order,
+    ///   starting after `after`.  These may include keys that were deleted, or only ever written
+    ///   in another fork, so each one must still be looked up.
+    fn get_indexed_keys(&mut self, prefix: &str, after: Option<&str>, limit: u32) -> Vec<String> {
+        SqliteConnection::get_indexed_keys(self.get_side_store(), prefix, after, limit)
+    }
+
+    /// Does the key index cover every data map entry and NFT ever written to this store?  If
+    ///   not, listing them from it could silently miss some.
+    fn has_complete_key_index(&mut self) -> bool {
+        SqliteConnection::get_key_index_state(self.get_side_store()) == Some(true)
+    }
 }
 
 pub struct ContractCommitment {
@@ -234,6 +286,16 @@ impl MarfedKV {
         Ok(marf)
     }
 
+    fn from_marf(marf: MARF<StacksBlockId>, chain_tip: StacksBlockId) -> MarfedKV {
+        let key_index_state = SqliteConnection::get_key_index_state(marf.sqlite_conn());
+        MarfedKV {
+            chain_tip,
+            marf,
+            key_index: key_index_state == Some(true),
+            key_index_state,
+        }
+    }
+
     pub fn open(path_str: &str, miner_tip: Option<&StacksBlockId>) -> Result<MarfedKV> {
         let marf = MarfedKV::setup_db(path_str, false)?;
         let chain_tip = match miner_tip {
@@ -241,7 +303,7 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV::from_marf(marf, chain_tip))
     }
 
     pub fn open_unconfirmed(path_str: &str, miner_tip: Option<&StacksBlockId>) -> Result<MarfedKV> {
@@ -251,7 +313,7 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV::from_marf(marf, chain_tip))
     }
 
     // used by benchmarks
@@ -272,7 +334,71 @@ impl MarfedKV {
 
         let chain_tip = StacksBlockId::sentinel();
 
-        MarfedKV { marf, chain_tip }
+        MarfedKV::from_marf(marf, chain_tip)
+    }
+
+    /// Prune the state of the blocks `keep` or more blocks below `tip`, doing up to about
+    /// `max_nodes` nodes' worth of work.  See MARF::prune().
+    pub fn prune(&mut self, tip: &StacksBlockId, keep: u32, max_nodes: u64) -> Result<u64> {
+        self.marf
+            .prune(tip, keep, max_nodes)
+            .map_err(|err| InterpreterError::MarfFailure(IncomparableError { err }).into())
+    }
+
+    /// Is a pruning pass in progress?  See MARF::prune().
+    pub fn is_pruning(&self) -> bool {
+        self.marf.is_pruning()
+    }
+
+    /// Cache up to (approximately) `max_size` bytes of MARF nodes.  See MARF::set_node_cache_size().
+    pub fn set_node_cache_size(&mut self, max_size: usize) {
+        self.marf.set_node_cache_size(max_size)
+    }
+
+    /// Maintain (or stop maintaining) the side store's index of the keys of data map entries and
+    ///   NFTs, which lets their entries be listed (see ClarityDatabase::list_map_entries()).  This
+    ///   costs an extra insert for every such write, so it is off unless enabled.
+    /// The MARF only stores the hashes of keys, so the index can't be backfilled from it: the
+    ///   index is only complete if it is enabled before anything is written to the store.  Enabling
+    ///   it on an existing store records it as incomplete, as does writing an indexed key while it
+    ///   is off, and entries can't be listed from an incomplete index.
+    /// Once enabled, the store records it, so every later open of the store keeps the index up to
+    ///   date until it is explicitly disabled.
+    pub fn set_key_index(&mut self, enabled: bool) -> Result<()> {
+        if enabled && self.key_index_state.is_none() {
+            let tx = self
+                .marf
+                .storage_tx()
+                .map_err(|err| InterpreterError::DBError(IncomparableError { err }))?;
+            let complete = !SqliteConnection::has_data(&tx);
+            SqliteConnection::set_key_index_state(&tx, complete);
+            tx.commit()
+                .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+
+            if !complete {
+                warn!("Key index enabled on an existing Clarity store: data map and NFT entries can't be listed from it");
+            }
+            self.key_index_state = Some(complete);
+        }
+        self.key_index = enabled;
+        Ok(())
+    }
+
+    /// Is the key index maintained?  See set_key_index().
+    pub fn is_key_index_enabled(&self) -> bool {
+        self.key_index
+    }
+
+    fn invalidates_key_index(&self) -> bool {
+        !self.key_index && self.key_index_state == Some(true)
+    }
+
+    /// Has the state at this block been pruned?
+    pub fn is_pruned(&self, block: &StacksBlockId) -> bool {
+        self.marf.is_pruned(block).unwrap_or_else(|e| {
+            error!("Failed to query pruned status of {}: {:?}", block, &e);
+            false
+        })
     }
 
     pub fn begin_read_only<'a>(
@@ -305,6 +431,13 @@ impl MarfedKV {
                     err: MarfError::NotFoundError,
                 })
             })?;
+            if self.is_pruned(at_block) {
+                debug!(
+                    "Failed to open read only connection at pruned block {}",
+                    at_block
+                );
+                return Err(InterpreterError::BlockPruned(BlockHeaderHash(at_block.0)).into());
+            }
             at_block.clone()
         } else {
             self.chain_tip.clone()
@@ -329,6 +462,8 @@ impl MarfedKV {
         current: &StacksBlockId,
         next: &StacksBlockId,
     ) -> WritableMarfStore<'a> {
+        let key_index = self.key_index;
+        let invalidate_key_index = self.invalidates_key_index();
         let mut tx = self.marf.begin_tx().expect(&format!(
             "ERROR: Failed to begin new MARF block {} - {})",
             current, next
@@ -346,10 +481,14 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            key_index,
+            invalidate_key_index,
         }
     }
 
     pub fn begin_unconfirmed<'a>(&'a mut self, current: &StacksBlockId) -> WritableMarfStore<'a> {
+        let key_index = self.key_index;
+        let invalidate_key_index = self.invalidates_key_index();
         let mut tx = self.marf.begin_tx().expect(&format!(
             "ERROR: Failed to begin new unconfirmed MARF block for {})",
             current
@@ -367,6 +506,8 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            key_index,
+            invalidate_key_index,
         }
     }
 
@@ -396,7 +537,11 @@ impl MarfedKV {
     }
 
     pub fn make_contract_hash_key(contract: &QualifiedContractIdentifier) -> String {
-        format!("clarity-contract::{}", contract)
+        format!("{}{}", MarfedKV::make_contract_hash_key_prefix(), contract)
+    }
+
+    fn make_contract_hash_key_prefix() -> &'static str {
+        "clarity-contract::"
     }
 
     pub fn index_conn<'a, C>(&'a self, context: C) -> IndexDBConn<'a, C, StacksBlockId> {
@@ -446,6 +591,10 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
                 _ => panic!("ERROR: Unexpected MARF failure: {}", e),
             })?;
 
+        if self.marf.is_pruned(&bhh).unwrap_or(false) {
+            return Err(InterpreterError::BlockPruned(BlockHeaderHash(bhh.0)).into());
+        }
+
         let result = Ok(self.chain_tip);
         self.chain_tip = bhh;
 
@@ -515,8 +664,12 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
             .expect("Attempted to get the open chain tip from an unopened context.")
     }
 
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
-        self.marf
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
+        let entry = self
+            .marf
             .get_with_proof(&self.chain_tip, key)
             .or_else(|e| match e {
                 MarfError::NotFoundError => Ok(None),
@@ -531,7 +684,8 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
                         side_key
                     ));
                 (data, proof)
-            })
+            });
+        Ok(entry)
     }
 
     fn get(&mut self, key: &str) -> Option<String> {
@@ -570,13 +724,136 @@ impl MemoryBackingStore {
     pub fn new() -> MemoryBackingStore {
         let side_store = SqliteConnection::memory().unwrap();
 
-        let mut memory_marf = MemoryBackingStore { side_store };
+        let mut memory_marf = MemoryBackingStore {
+            side_store,
+            simulated_height: None,
+        };
 
         memory_marf.as_clarity_db().initialize();
 
         memory_marf
     }
 
+    /// Make a store which simulates a chain of blocks, whose height can be moved with
+    ///  `advance_block_height()`. The simulated chain has no real history: every block shares
+    ///  the same block hash (the first Stacks block's index hash), so header lookups for past
+    ///  blocks return the genesis header data.
+    pub fn new_simulated_chain() -> MemoryBackingStore {
+        let mut memory_marf = MemoryBackingStore::new();
+        memory_marf.simulated_height = Some(0);
+        memory_marf
+    }
+
+    /// Advance the height of the simulated chain by `blocks`.
+    pub fn advance_block_height(&mut self, blocks: u32) {
+        let height = self
+            .simulated_height
+            .as_mut()
+            .expect("BUG: advanced the block height of a store which does not simulate a chain");
+        *height = height.saturating_add(blocks);
+    }
+
+    /// Write a copy of this store, including the height of its simulated chain (if any), to a
+    ///  new sqlite database at `path`. Fails if a database already exists at `path`.
+    pub fn save(&self, path: &str) -> Result<()> {
+        self.side_store
+            .execute("VACUUM INTO ?", &[path])
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        let saved = SqliteConnection::open(path)?;
+        let block_height = self.simulated_height.map(|height| height as i64);
+        let params: [&dyn ToSql; 1] = [&block_height];
+        saved
+            .execute(
+                "CREATE TABLE memory_store_info (block_height INTEGER)",
+                NO_PARAMS,
+            )
+            .and_then(|_| {
+                saved.execute(
+                    "INSERT INTO memory_store_info (block_height) VALUES (?)",
+                    &params,
+                )
+            })
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        Ok(())
+    }
+
+    /// Load a store previously written with `save()`. The loaded store is held in memory,
+    ///  so changes to it are not written back to `path`.
+    pub fn load(path: &str) -> Result<MemoryBackingStore> {
+        let saved = SqliteConnection::open(path)?;
+        let block_height: Option<i64> = saved
+            .query_row(
+                "SELECT block_height FROM memory_store_info",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        let side_store = SqliteConnection::memory()?;
+        side_store
+            .execute("ATTACH DATABASE ? AS saved", &[path])
+            .and_then(|_| {
+                side_store.execute(
+                    "INSERT INTO data_table SELECT key, value FROM saved.data_table",
+                    NO_PARAMS,
+                )
+            })
+            .and_then(|_| {
+                side_store.execute(
+                    "INSERT INTO metadata_table SELECT key, blockhash, value FROM saved.metadata_table",
+                    NO_PARAMS,
+                )
+            })
+            .and_then(|_| side_store.execute("DETACH DATABASE saved", NO_PARAMS))
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        // a memory store's keys are not hashed, so its key index can be rebuilt from them
+        //  (e.g., for a store saved before it had one).
+        let keys = {
+            let mut stmt = side_store
+                .prepare("SELECT key FROM data_table ORDER BY key")
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+            let keys = stmt
+                .query_map(NO_PARAMS, |row| row.get::<_, String>(0))
+                .and_then(|rows| rows.collect::<std::result::Result<Vec<_>, _>>())
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+            keys
+        };
+
+        let mut store = MemoryBackingStore {
+            side_store,
+            simulated_height: block_height.map(|height| height as u32),
+        };
+        store.index_keys(&keys);
+        Ok(store)
+    }
+
+    /// The identifiers of every contract published in this store.
+    pub fn get_contract_identifiers(&self) -> Result<Vec<QualifiedContractIdentifier>> {
+        let contract_key_prefix = MarfedKV::make_contract_hash_key_prefix();
+        let params: [&dyn ToSql; 1] = [&format!("{}%", contract_key_prefix)];
+        let mut stmt = self
+            .side_store
+            .prepare("SELECT key FROM data_table WHERE key LIKE ? ORDER BY key")
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        let keys = stmt
+            .query_map(&params, |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<std::result::Result<Vec<_>, _>>())
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        keys.iter()
+            .map(|key| QualifiedContractIdentifier::parse(&key[contract_key_prefix.len()..]))
+            .collect()
+    }
+
+    fn mock_block_hash() -> StacksBlockId {
+        StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        )
+    }
+
     pub fn as_clarity_db<'a>(&'a mut self) -> ClarityDatabase<'a> {
         ClarityDatabase::new(self, &NULL_HEADER_DB, &NULL_BURN_STATE_DB)
     }
@@ -595,37 +872,51 @@ impl ClarityBackingStore for MemoryBackingStore {
         SqliteConnection::get(self.get_side_store(), key)
     }
 
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
-        SqliteConnection::get(self.get_side_store(), key).map(|x| (x, TrieMerkleProof(vec![])))
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
+        Ok(SqliteConnection::get(self.get_side_store(), key).map(|x| (x, TrieMerkleProof(vec![]))))
     }
 
     fn get_side_store(&mut self) -> &Connection {
         &self.side_store
     }
 
+    /// Every key written to a MemoryBackingStore is indexed (see MemoryBackingStore::load()).
+    fn has_complete_key_index(&mut self) -> bool {
+        true
+    }
+
     fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
-        if height == 0 {
-            Some(StacksBlockId::sentinel())
-        } else {
-            None
+        match self.simulated_height {
+            Some(simulated_height) if height <= simulated_height => {
+                Some(MemoryBackingStore::mock_block_hash())
+            }
+            None if height == 0 => Some(StacksBlockId::sentinel()),
+            _ => None,
         }
     }
 
     fn get_open_chain_tip(&mut self) -> StacksBlockId {
-        StacksBlockId::sentinel()
+        match self.simulated_height {
+            Some(_) => MemoryBackingStore::mock_block_hash(),
+            None => StacksBlockId::sentinel(),
+        }
     }
 
     fn get_open_chain_tip_height(&mut self) -> u32 {
-        0
+        self.simulated_height.unwrap_or(0)
     }
 
     fn get_current_block_height(&mut self) -> u32 {
-        0
+        self.simulated_height.unwrap_or(0)
     }
 
     fn put_all(&mut self, items: Vec<(String, String)>) {
         for (key, value) in items.into_iter() {
             SqliteConnection::put(self.get_side_store(), &key, &value);
+            self.index_keys(&[key]);
         }
     }
 }
@@ -653,7 +944,10 @@ impl ClarityBackingStore for NullBackingStore {
         panic!("NullBackingStore can't retrieve data")
     }
 
-    fn get_with_proof(&mut self, _key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
+    fn get_with_proof(
+        &mut self,
+        _key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
         panic!("NullBackingStore can't retrieve data")
     }
 
@@ -682,6 +976,101 @@ impl ClarityBackingStore for NullBackingStore {
     }
 }
 
+impl<'a> ProvenBackingStore<'a> {
+    pub fn new(
+        proven: HashMap<String, String>,
+        local_store: &'a mut dyn ClarityBackingStore,
+    ) -> ProvenBackingStore<'a> {
+        ProvenBackingStore {
+            proven,
+            unproven_reads: BTreeSet::new(),
+            local_store,
+        }
+    }
+
+    /// The keys which were read, but are not proven.
+    pub fn unproven_reads(&self) -> &BTreeSet<String> {
+        &self.unproven_reads
+    }
+
+    pub fn as_clarity_db<'b>(
+        &'b mut self,
+        headers_db: &'b dyn HeadersDB,
+        burn_state_db: &'b dyn BurnStateDB,
+    ) -> ClarityDatabase<'b> {
+        ClarityDatabase::new(self, headers_db, burn_state_db)
+    }
+}
+
+impl<'a> ClarityBackingStore for ProvenBackingStore<'a> {
+    fn set_block_hash(&mut self, bhh: StacksBlockId) -> Result<StacksBlockId> {
+        // proofs are only ever checked against a single root hash.
+        Err(RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0)).into())
+    }
+
+    fn get(&mut self, key: &str) -> Option<String> {
+        let value = self.proven.get(key).cloned();
+        if value.is_none() {
+            self.unproven_reads.insert(key.to_string());
+        }
+        value
+    }
+
+    fn get_with_proof(
+        &mut self,
+        _key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
+        Err(
+            InterpreterError::InterpreterError("ProvenBackingStore can't produce proofs".into())
+                .into(),
+        )
+    }
+
+    fn get_contract_hash(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Result<(StacksBlockId, Sha512Trunc256Sum)> {
+        let key = MarfedKV::make_contract_hash_key(contract);
+        let proven_hash = self
+            .get(&key)
+            .map(|x| ContractCommitment::deserialize(&x).hash)
+            .ok_or_else(|| CheckErrors::NoSuchContract(contract.to_string()))?;
+        let (bhh, local_hash) = self.local_store.get_contract_hash(contract)?;
+        if local_hash != proven_hash {
+            warn!(
+                "Local code for {} does not match its proven commitment",
+                contract
+            );
+            return Err(CheckErrors::NoSuchContract(contract.to_string()).into());
+        }
+        Ok((bhh, local_hash))
+    }
+
+    fn get_side_store(&mut self) -> &Connection {
+        self.local_store.get_side_store()
+    }
+
+    fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
+        self.local_store.get_block_at_height(height)
+    }
+
+    fn get_open_chain_tip(&mut self) -> StacksBlockId {
+        self.local_store.get_open_chain_tip()
+    }
+
+    fn get_open_chain_tip_height(&mut self) -> u32 {
+        self.local_store.get_open_chain_tip_height()
+    }
+
+    fn get_current_block_height(&mut self) -> u32 {
+        self.local_store.get_current_block_height()
+    }
+
+    fn put_all(&mut self, _items: Vec<(String, String)>) {
+        panic!("ProvenBackingStore cannot put")
+    }
+}
+
 impl<'a> WritableMarfStore<'a> {
     pub fn as_clarity_db<'b>(
         &'b mut self,
@@ -782,6 +1171,10 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
                 _ => panic!("ERROR: Unexpected MARF failure: {}", e),
             })?;
 
+        if self.marf.is_pruned(&bhh).unwrap_or(false) {
+            return Err(InterpreterError::BlockPruned(BlockHeaderHash(bhh.0)).into());
+        }
+
         let result = Ok(self.chain_tip);
         self.chain_tip = bhh;
 
@@ -814,8 +1207,12 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
             })
     }
 
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
-        self.marf
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
+        let entry = self
+            .marf
             .get_with_proof(&self.chain_tip, key)
             .or_else(|e| match e {
                 MarfError::NotFoundError => Ok(None),
@@ -830,7 +1227,8 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
                         side_key
                     ));
                 (data, proof)
-            })
+            });
+        Ok(entry)
     }
 
     fn get_side_store(&mut self) -> &Connection {
@@ -906,6 +1304,16 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
             keys.push(key);
             values.push(marf_value);
         }
+        if self.key_index {
+            self.index_keys(&keys);
+        } else if self.invalidate_key_index
+            && keys
+                .iter()
+                .any(|key| ClarityDatabase::split_indexed_key(key).is_some())
+        {
+            SqliteConnection::set_key_index_state(self.get_side_store(), false);
+            self.invalidate_key_index = false;
+        }
         self.marf
             .insert_batch(&keys, values)
             .expect("ERROR: Unexpected MARF Failure");
@@ -27,7 +27,7 @@ pub use self::clarity_db::{
     STORE_CONTRACT_SRC_INTERFACE,
 };
 pub use self::key_value_wrapper::{RollbackWrapper, RollbackWrapperPersistedLog};
-pub use self::marf::{ClarityBackingStore, MarfedKV, MemoryBackingStore};
+pub use self::marf::{ClarityBackingStore, MarfedKV, MemoryBackingStore, ProvenBackingStore};
 pub use self::sqlite::SqliteConnection;
 pub use self::structures::{
     ClarityDeserializable, ClaritySerializable, DataMapMetadata, DataVariableMetadata,
@@ -155,6 +155,105 @@ impl SqliteConnection {
     pub fn has_entry(conn: &Connection, key: &str) -> bool {
         sqlite_has_entry(conn, key)
     }
+
+    /// Record that `key` was written under `prefix` (i.e., a data map or NFT).  The key index is
+    /// not part of the consensus state: it is only ever added to, and it records keys written in
+    /// every fork.
+    pub fn insert_indexed_key(conn: &Connection, prefix: &str, key: &str) {
+        let params: [&dyn ToSql; 2] = [&prefix, &key];
+        if let Err(e) = conn.execute(
+            "INSERT OR IGNORE INTO key_index_table (prefix, key) VALUES (?, ?)",
+            &params,
+        ) {
+            error!("Failed to index ({},{}): {:?}", prefix, key, &e);
+            panic!(SQL_FAIL_MESSAGE);
+        }
+    }
+
+    /// Get up to `limit` of the keys indexed under `prefix`, in order, starting after `after`.
+    pub fn get_indexed_keys(
+        conn: &Connection,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Vec<String> {
+        let after = after.unwrap_or("");
+        let limit = limit as i64;
+        let params: [&dyn ToSql; 3] = [&prefix, &after, &limit];
+
+        let mut stmt = match conn.prepare(
+            "SELECT key FROM key_index_table WHERE prefix = ? AND key > ? ORDER BY key LIMIT ?",
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                error!("Failed to prepare query for indexed keys: {:?}", &e);
+                panic!(SQL_FAIL_MESSAGE);
+            }
+        };
+
+        let res: std::result::Result<Vec<String>, SqliteError> = stmt
+            .query_map(&params, |row| row.get(0))
+            .and_then(|rows| rows.collect());
+        match res {
+            Ok(keys) => keys,
+            Err(e) => {
+                error!(
+                    "Failed to query keys of '{}' after '{}': {:?}",
+                    prefix, after, &e
+                );
+                panic!(SQL_FAIL_MESSAGE);
+            }
+        }
+    }
+
+    /// Whether or not the key index is complete, i.e., whether every data map and NFT key
+    /// written to this store has been indexed.  None if the key index was never enabled.
+    pub fn get_key_index_state(conn: &Connection) -> Option<bool> {
+        match conn
+            .query_row("SELECT complete FROM key_index_state", NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .optional()
+        {
+            Ok(complete) => complete,
+            Err(e) => {
+                error!("Failed to query key index state: {:?}", &e);
+                panic!(SQL_FAIL_MESSAGE);
+            }
+        }
+    }
+
+    /// Record whether or not the key index is complete.
+    pub fn set_key_index_state(conn: &Connection, complete: bool) {
+        let res = conn
+            .execute("DELETE FROM key_index_state", NO_PARAMS)
+            .and_then(|_| {
+                conn.execute(
+                    "INSERT INTO key_index_state (complete) VALUES (?)",
+                    &[&complete as &dyn ToSql],
+                )
+            });
+        if let Err(e) = res {
+            error!("Failed to set key index state to {}: {:?}", complete, &e);
+            panic!(SQL_FAIL_MESSAGE);
+        }
+    }
+
+    /// Has anything been written to this store's data table?
+    pub fn has_data(conn: &Connection) -> bool {
+        match conn
+            .query_row("SELECT 1 FROM data_table LIMIT 1", NO_PARAMS, |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()
+        {
+            Ok(row) => row.is_some(),
+            Err(e) => {
+                error!("Failed to query data table: {:?}", &e);
+                panic!(SQL_FAIL_MESSAGE);
+            }
+        }
+    }
 }
 
 impl SqliteConnection {
@@ -174,6 +273,20 @@ impl SqliteConnection {
         )
         .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS key_index_table
+                      (prefix TEXT NOT NULL, key TEXT NOT NULL,
+                       PRIMARY KEY (prefix, key))",
+            NO_PARAMS,
+        )
+        .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS key_index_state (complete INTEGER NOT NULL)",
+            NO_PARAMS,
+        )
+        .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
         Self::check_schema(conn)?;
 
         Ok(())
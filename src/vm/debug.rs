@@ -0,0 +1,483 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+
+use vm::ast;
+use vm::contexts::{Environment, LocalContext};
+use vm::errors::{InterpreterResult as Result, RuntimeErrorType};
+use vm::representations::SymbolicExpression;
+use vm::types::QualifiedContractIdentifier;
+use vm::{eval, EvalHook, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BreakpointLocation {
+    Function(String),
+    Line(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub contract: Option<QualifiedContractIdentifier>,
+    pub location: BreakpointLocation,
+}
+
+impl Breakpoint {
+    /// Parse a breakpoint specification. Accepted forms are:
+    ///   `<function-name>`, `<line>`, `<contract-identifier>:<function-name>`
+    ///   and `<contract-identifier>:<line>`.
+    pub fn parse(id: usize, spec: &str) -> Option<Breakpoint> {
+        let spec = spec.trim();
+        if spec.len() == 0 {
+            return None;
+        }
+
+        let (contract, location) = match spec.rfind(':') {
+            Some(ix) => {
+                let contract = QualifiedContractIdentifier::parse(&spec[..ix]).ok()?;
+                (Some(contract), &spec[ix + 1..])
+            }
+            None => (None, spec),
+        };
+
+        let location = match location.parse::<u32>() {
+            Ok(line) => BreakpointLocation::Line(line),
+            Err(_) if location.len() > 0 => BreakpointLocation::Function(location.to_string()),
+            Err(_) => return None,
+        };
+
+        Some(Breakpoint {
+            id,
+            contract,
+            location,
+        })
+    }
+
+    fn matches_contract(&self, contract: &QualifiedContractIdentifier) -> bool {
+        match self.contract {
+            Some(ref expected) => expected == contract,
+            None => true,
+        }
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#{} ", self.id)?;
+        if let Some(ref contract) = self.contract {
+            write!(f, "{}:", contract)?;
+        }
+        match self.location {
+            BreakpointLocation::Function(ref name) => write!(f, "{}", name),
+            BreakpointLocation::Line(line) => write!(f, "line {}", line),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StepMode {
+    /// stop before the next expression is evaluated
+    Step,
+    /// stop before the next expression at or above the given depth
+    Next(usize),
+    /// stop once the expression at the given depth finishes evaluating
+    Finish(usize),
+    /// only stop at breakpoints
+    Continue,
+}
+
+/// An interactive step debugger for Clarity, driven by the EvalHook interface.
+///  Commands are read line-by-line from `input` whenever execution stops.
+pub struct Debugger {
+    input: Box<dyn BufRead>,
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint_id: usize,
+    mode: StepMode,
+    depth: usize,
+    frame_count: usize,
+    last_line: Option<(QualifiedContractIdentifier, u32)>,
+    pub stops: usize,
+}
+
+fn eval_in_context(env: &mut Environment, context: &LocalContext, program: &str) -> Result<Value> {
+    let contract_identifier = env.contract_context.contract_identifier.clone();
+    let parsed = ast::build_ast(&contract_identifier, program, &mut ())?.expressions;
+    let expr = parsed.get(0).ok_or_else(|| {
+        RuntimeErrorType::ParseError("Expected a program of at least length 1".to_string())
+    })?;
+
+    // never let the debugger mutate the state being debugged.
+    env.global_context.begin_read_only();
+    let result = eval(expr, env, context);
+    env.global_context.roll_back();
+    result
+}
+
+const DEBUG_HELP: &str = "Commands:
+  s, step                 evaluate the next expression, stepping into sub-expressions
+  n, next                 evaluate the current expression, stepping over sub-expressions
+  f, finish               run until the current expression finishes, and print its value
+  c, continue             run until the next breakpoint
+  b, break <spec>         add a breakpoint on a function name or line number,
+                          optionally scoped as <contract-identifier>:<spec>
+  d, delete <id>          remove a breakpoint
+  i, info                 list breakpoints
+  l, locals               print the bindings visible in the current local context
+  bt, backtrace           print the call stack
+  a, assets               print the assets moved so far in the current transaction
+  p, print <expr>         evaluate an expression (read-only) in the current context
+  q, quit                 remove all breakpoints and run to completion
+  h, help                 print this message";
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        Debugger::from_reader(Box::new(BufReader::new(io::stdin())))
+    }
+
+    pub fn from_reader(input: Box<dyn BufRead>) -> Debugger {
+        Debugger {
+            input,
+            breakpoints: vec![],
+            next_breakpoint_id: 1,
+            mode: StepMode::Step,
+            depth: 0,
+            frame_count: 0,
+            last_line: None,
+            stops: 0,
+        }
+    }
+
+    /// Only stop at breakpoints, rather than at the first evaluated expression.
+    pub fn continue_to_breakpoint(&mut self) {
+        self.mode = StepMode::Continue;
+    }
+
+    pub fn add_breakpoint(&mut self, spec: &str) -> Option<usize> {
+        let breakpoint = Breakpoint::parse(self.next_breakpoint_id, spec)?;
+        self.next_breakpoint_id += 1;
+        let id = breakpoint.id;
+        self.breakpoints.push(breakpoint);
+        Some(id)
+    }
+
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|b| b.id != id);
+        count != self.breakpoints.len()
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    fn hit_breakpoint(&self, env: &Environment, expr: &SymbolicExpression) -> Option<usize> {
+        let contract = &env.contract_context.contract_identifier;
+        let line = expr.start_line();
+        let entered_line = match (line, &self.last_line) {
+            (Some(line), Some((last_contract, last_line))) => {
+                line != *last_line || contract != last_contract
+            }
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        let entered_function = env.call_stack.frames().len() > self.frame_count;
+
+        for breakpoint in self.breakpoints.iter() {
+            if !breakpoint.matches_contract(contract) {
+                continue;
+            }
+            match breakpoint.location {
+                BreakpointLocation::Line(target) => {
+                    if entered_line && line == Some(target) {
+                        return Some(breakpoint.id);
+                    }
+                }
+                BreakpointLocation::Function(ref name) => {
+                    if !entered_function {
+                        continue;
+                    }
+                    if let Some(frame) = env.call_stack.frames().last() {
+                        if frame.to_string().ends_with(&format!(":{}", name)) {
+                            return Some(breakpoint.id);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn print_location(&self, env: &Environment, expr: &SymbolicExpression) {
+        let contract = &env.contract_context.contract_identifier;
+        match expr.start_line() {
+            Some(line) => println!("{} (line {}):", contract, line),
+            None => println!("{}:", contract),
+        }
+        if let Some(frame) = env.call_stack.frames().last() {
+            println!("  in {}", frame);
+        }
+        println!("  -> {}", expr);
+    }
+
+    fn print_locals(&self, env: &Environment, context: &LocalContext) {
+        let mut current = Some(context);
+        while let Some(local) = current {
+            let mut bindings: Vec<_> = local.variables.iter().collect();
+            bindings.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, value) in bindings {
+                println!("  {} = {}", name, value);
+            }
+            let mut callables: Vec<_> = local.callable_contracts.iter().collect();
+            callables.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, (contract, trait_identifier)) in callables {
+                println!("  {} = {} ({})", name, contract, trait_identifier.name);
+            }
+            current = local.parent;
+        }
+
+        let mut constants: Vec<_> = env.contract_context.variables.iter().collect();
+        constants.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in constants {
+            println!("  {} = {} (constant)", name, value);
+        }
+    }
+
+    fn print_backtrace(&self, env: &Environment) {
+        for (ix, frame) in env.call_stack.frames().iter().rev().enumerate() {
+            println!("  #{} {}", ix, frame);
+        }
+    }
+
+    /// Stop execution, reading and handling commands until one resumes execution.
+    fn prompt(&mut self, env: &mut Environment, context: &LocalContext, expr: &SymbolicExpression) {
+        self.stops += 1;
+        self.print_location(env, expr);
+
+        loop {
+            print!("(debug) ");
+            io::stdout().flush().ok();
+
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    // input closed: run the rest of the program to completion.
+                    self.breakpoints.clear();
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+                Ok(_) => {}
+            }
+
+            let line = line.trim();
+            let (command, argument) = match line.find(' ') {
+                Some(ix) => (&line[..ix], line[ix + 1..].trim()),
+                None => (line, ""),
+            };
+
+            match command {
+                "s" | "step" => {
+                    self.mode = StepMode::Step;
+                    return;
+                }
+                "n" | "next" => {
+                    self.mode = StepMode::Next(self.depth);
+                    return;
+                }
+                "f" | "finish" => {
+                    self.mode = StepMode::Finish(self.depth);
+                    return;
+                }
+                "c" | "continue" => {
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+                "q" | "quit" => {
+                    self.breakpoints.clear();
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+                "b" | "break" => match self.add_breakpoint(argument) {
+                    Some(id) => println!("Added breakpoint #{}", id),
+                    None => println!("Invalid breakpoint: {}", argument),
+                },
+                "d" | "delete" => match argument.parse::<usize>() {
+                    Ok(id) if self.remove_breakpoint(id) => println!("Removed breakpoint #{}", id),
+                    _ => println!("No such breakpoint: {}", argument),
+                },
+                "i" | "info" => {
+                    for breakpoint in self.breakpoints.iter() {
+                        println!("  {}", breakpoint);
+                    }
+                }
+                "l" | "locals" => self.print_locals(env, context),
+                "bt" | "backtrace" => self.print_backtrace(env),
+                "a" | "assets" => match env.global_context.get_readonly_asset_map() {
+                    Some(asset_map) => println!("{}", asset_map),
+                    None => println!("No open transaction."),
+                },
+                "p" | "print" => match eval_in_context(env, context, argument) {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => println!("Evaluation error: {}", e),
+                },
+                "h" | "help" => println!("{}", DEBUG_HELP),
+                "" => {}
+                _ => println!(
+                    "Unknown command: {}. Type `help` for a list of commands.",
+                    command
+                ),
+            }
+        }
+    }
+}
+
+impl EvalHook for Debugger {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        self.depth += 1;
+
+        let should_stop = match self.mode {
+            StepMode::Step => true,
+            StepMode::Next(depth) => self.depth <= depth,
+            StepMode::Finish(_) | StepMode::Continue => false,
+        };
+
+        if let Some(id) = self.hit_breakpoint(env, expr) {
+            println!("Breakpoint #{} hit.", id);
+            self.prompt(env, context, expr);
+        } else if should_stop {
+            self.prompt(env, context, expr);
+        }
+
+        self.frame_count = env.call_stack.frames().len();
+        if let Some(line) = expr.start_line() {
+            self.last_line = Some((env.contract_context.contract_identifier.clone(), line));
+        }
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+        result: &Result<Value>,
+    ) {
+        if let StepMode::Finish(depth) = self.mode {
+            if self.depth == depth {
+                match result {
+                    Ok(value) => println!("Finished {}\n  => {}", expr, value),
+                    Err(e) => println!("Finished {}\n  => error: {}", expr, e),
+                }
+                self.mode = StepMode::Step;
+            }
+        }
+
+        self.frame_count = env.call_stack.frames().len();
+        self.depth -= 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::MemoryBackingStore;
+    use vm::types::PrincipalData;
+
+    const CONTRACT: &str = "(define-private (double (x int)) (* x 2))
+(define-public (quadruple (x int))
+  (ok (double (double x))))";
+
+    fn run_with_debugger(debugger: &mut Debugger) -> Value {
+        let contract_id = QualifiedContractIdentifier::local("debugged").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db());
+        owned_env
+            .initialize_contract(contract_id.clone(), CONTRACT)
+            .unwrap();
+        owned_env.add_eval_hook(debugger);
+        let (result, _, _) = owned_env
+            .execute_transaction(
+                Value::Principal(sender),
+                contract_id,
+                "quadruple",
+                &[SymbolicExpression::atom_value(Value::Int(3))],
+            )
+            .unwrap();
+        result
+    }
+
+    #[test]
+    fn test_parse_breakpoints() {
+        assert_eq!(
+            Breakpoint::parse(1, "double").unwrap().location,
+            BreakpointLocation::Function("double".into())
+        );
+        assert_eq!(
+            Breakpoint::parse(1, "12").unwrap().location,
+            BreakpointLocation::Line(12)
+        );
+        let scoped = Breakpoint::parse(2, "S1G2081040G2081040G2081040G208105NK8PE5.foo:3").unwrap();
+        assert_eq!(
+            scoped.contract,
+            Some(
+                QualifiedContractIdentifier::parse("S1G2081040G2081040G2081040G208105NK8PE5.foo")
+                    .unwrap()
+            )
+        );
+        assert_eq!(scoped.location, BreakpointLocation::Line(3));
+        assert!(Breakpoint::parse(3, "").is_none());
+        assert!(Breakpoint::parse(3, "not-a-contract:foo").is_none());
+    }
+
+    #[test]
+    fn test_function_breakpoint() {
+        let mut debugger = Debugger::from_reader(Box::new(Cursor::new("c\nc\n")));
+        debugger.continue_to_breakpoint();
+        debugger.add_breakpoint("double").unwrap();
+
+        let result = run_with_debugger(&mut debugger);
+        assert_eq!(result, Value::okay(Value::Int(12)).unwrap());
+        // `double` is entered twice.
+        assert_eq!(debugger.stops, 2);
+    }
+
+    #[test]
+    fn test_step_and_finish() {
+        // stop at the function body, step into `(double (double x))`, and finish it.
+        //  the body has nothing left to evaluate afterwards, so there are only two stops.
+        let mut debugger = Debugger::from_reader(Box::new(Cursor::new("s\nf\nq\n")));
+        let result = run_with_debugger(&mut debugger);
+        assert_eq!(result, Value::okay(Value::Int(12)).unwrap());
+        assert_eq!(debugger.stops, 2);
+    }
+
+    #[test]
+    fn test_closed_input_runs_to_completion() {
+        let mut debugger = Debugger::from_reader(Box::new(Cursor::new("")));
+        debugger.add_breakpoint("double").unwrap();
+        let result = run_with_debugger(&mut debugger);
+        assert_eq!(result, Value::okay(Value::Int(12)).unwrap());
+        assert_eq!(debugger.stops, 1);
+    }
+}
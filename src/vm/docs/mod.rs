@@ -244,6 +244,71 @@ const LOG2_API: SimpleFunctionAPI = SimpleFunctionAPI {
 "
 };
 
+const BITWISE_AND_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-and i1 i2...)",
+    description: "Returns the result of bitwise and'ing a variable number of integer inputs.",
+    example: "(bit-and 24 16) ;; Returns 16
+(bit-and 28 24 -1) ;; Returns 24
+(bit-and u24 u16) ;; Returns u16
+(bit-and -128 -64) ;; Returns -128
+",
+};
+
+const BITWISE_OR_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-or i1 i2...)",
+    description:
+        "Returns the result of bitwise inclusive or'ing a variable number of integer inputs.",
+    example: "(bit-or 4 8) ;; Returns 12
+(bit-or 1 2 4) ;; Returns 7
+(bit-or u4 u8) ;; Returns u12
+(bit-or -64 -2) ;; Returns -2
+",
+};
+
+const BITWISE_NOT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-not i1)",
+    description: "Returns the one's complement (sometimes also called the bitwise complement or not operator) of `i1`,
+effectively reversing the bits in `i1`. In other words, every bit that is 1 in `i1` will be 0 in the result.
+Conversely, every bit that is 0 in `i1` will be 1 in the result.",
+    example: "(bit-not 3) ;; Returns -4
+(bit-not -1) ;; Returns 0
+(bit-not u128) ;; Returns u340282366920938463463374607431768211327
+",
+};
+
+const BITWISE_LEFT_SHIFT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-shift-left i1 shamt)",
+    description:
+        "Shifts all the bits in `i1` to the left by the number of places specified in `shamt`
+modulo 128 (the bit width of Clarity integers). Bits shifted past the most significant bit are
+discarded, so unlike `*`, `bit-shift-left` never causes an overflow error, and shifting an `int`
+may change its sign.",
+    example: "(bit-shift-left 2 u1) ;; Returns 4
+(bit-shift-left 16 u2) ;; Returns 64
+(bit-shift-left -64 u1) ;; Returns -128
+(bit-shift-left u4 u2) ;; Returns u16
+(bit-shift-left 1 u128) ;; Returns 1
+",
+};
+
+const BITWISE_RIGHT_SHIFT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-shift-right i1 shamt)",
+    description: "Shifts all the bits in `i1` to the right by the number of places specified in `shamt`
+modulo 128 (the bit width of Clarity integers). When `i1` is a `uint`, the most significant bits are
+filled with zeros. When `i1` is an `int`, the sign bit is preserved, so negative numbers stay negative.",
+    example: "(bit-shift-right 2 u1) ;; Returns 1
+(bit-shift-right 128 u2) ;; Returns 32
+(bit-shift-right -64 u1) ;; Returns -32
+(bit-shift-right u128 u2) ;; Returns u32
+(bit-shift-right -1 u127) ;; Returns -1
+",
+};
+
 const XOR_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(xor i1 i2)",
@@ -337,6 +402,7 @@ pub fn get_input_type_string(function_type: &FunctionType) -> String {
         FunctionType::ArithmeticBinary | FunctionType::ArithmeticComparison => {
             "int, int | uint, uint".to_string()
         }
+        FunctionType::ArithmeticShift => "int, uint | uint, uint".to_string(),
     }
 }
 
@@ -347,7 +413,8 @@ pub fn get_output_type_string(function_type: &FunctionType) -> String {
         FunctionType::UnionArgs(_, ref out_type) => format!("{}", out_type),
         FunctionType::ArithmeticVariadic
         | FunctionType::ArithmeticUnary
-        | FunctionType::ArithmeticBinary => "int | uint".to_string(),
+        | FunctionType::ArithmeticBinary
+        | FunctionType::ArithmeticShift => "int | uint".to_string(),
         FunctionType::ArithmeticComparison => "bool".to_string(),
     }
 }
@@ -573,6 +640,43 @@ function returns `none`.",
 ",
 };
 
+const SLICE_API: SpecialAPI = SpecialAPI {
+    input_type: "buff|list A|string, uint, uint",
+    output_type: "(optional buff|list A|string)",
+    signature: "(slice sequence left-position right-position)",
+    description:
+        "The `slice` function attempts to return a sub-sequence of `sequence` that starts at
+`left-position` (inclusive) and ends at `right-position` (exclusive). If `left-position` is greater
+than `right-position`, or `right-position` is greater than `(len sequence)`, the function returns
+`none`. Otherwise it returns the sub-sequence wrapped in `some`.
+
+If both positions are literals, the maximum length of the result's type is the distance between
+them. Otherwise, it is the maximum length of `sequence`.",
+    example: "(slice \"blockstack\" u5 u10) ;; Returns (some \"stack\")
+(slice (list 1 2 3 4 5) u5 u9) ;; Returns none
+(slice (list 1 2 3 4 5) u3 u4) ;; Returns (some (4))
+(slice \"abcd\" u1 u3) ;; Returns (some \"bc\")
+(slice \"abcd\" u2 u2) ;; Returns (some \"\")
+(slice \"abcd\" u3 u1) ;; Returns none
+(slice 0xfb0102 u1 u3) ;; Returns (some 0x0102)
+",
+};
+
+const REPLACE_AT_API: SpecialAPI = SpecialAPI {
+    input_type: "buff|list A|string, uint, buff|A|string",
+    output_type: "(optional buff|list A|string)",
+    signature: "(replace-at sequence index element)",
+    description: "The `replace-at` function returns a copy of `sequence` in which the element at
+`index` is replaced by `element`. For strings and buffers, `element` must be a 1-length string or
+buffer of the same type as `sequence`. If `index` is greater than or equal to `(len sequence)`,
+this function returns `none`.",
+    example: "(replace-at \"abcd\" u1 \"x\") ;; Returns (some \"axcd\")
+(replace-at (list 1 2 3 4 5) u4 10) ;; Returns (some (1 2 3 4 10))
+(replace-at (list 1 2 3 4 5) u5 10) ;; Returns none
+(replace-at 0xfb01 u0 0x00) ;; Returns (some 0x0001)
+",
+};
+
 const LIST_API: SpecialAPI = SpecialAPI {
     input_type: "A, ...",
     output_type: "(list A)",
@@ -601,6 +705,19 @@ nodes configured for development (as opposed to production mining nodes), this f
     example: "(print (+ 1 2 3)) ;; Returns 6",
 };
 
+const EMIT_EVENT_API: SpecialAPI = SpecialAPI {
+    input_type: "EventName, A",
+    output_type: "A",
+    signature: "(emit-event event-name expr)",
+    description: "The `emit-event` function evaluates and returns its input expression, and emits it as an event
+of the contract. The event must have been declared with `define-event`, and the type of the expression
+must match the event's declared type. Unlike `print` events, whose topic is always `print`, the topic of
+the emitted event is the event's name.",
+    example: "(define-event transfer { sender: principal, amount: uint })
+(emit-event transfer { sender: tx-sender, amount: u10 }) ;; Returns (tuple (amount u10) (sender S1G2081040G2081040G2081040G208105NK8PE5))
+",
+};
+
 const FETCH_ENTRY_API: SpecialAPI = SpecialAPI {
     input_type: "MapName, tuple",
     output_type: "(optional (tuple))",
@@ -825,6 +942,86 @@ const PRINCIPAL_OF_API: SpecialAPI = SpecialAPI {
     example: "(principal-of? 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110) ;; Returns (ok ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)"
 };
 
+const IS_STANDARD_API: SpecialAPI = SpecialAPI {
+    input_type: "principal",
+    output_type: "bool",
+    signature: "(is-standard standard-or-contract-principal)",
+    description: "The `is-standard` function tests whether the version byte of `standard-or-contract-principal`
+is a standard (single-signature or multi-signature) address version for the network that the code is running
+on. For a contract principal, the version byte of the contract's issuer is tested. On mainnet, the versions
+are those of `SP` and `SM` addresses. On testnet, they are those of `ST` and `SN` addresses.",
+    example: "(is-standard 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK) ;; Returns true
+(is-standard 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo) ;; Returns true
+(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY) ;; Returns false
+"
+};
+
+const PRINCIPAL_DESTRUCT_API: SpecialAPI = SpecialAPI {
+    input_type: "principal",
+    output_type: "(response (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))) (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))))",
+    signature: "(principal-destruct? principal-address)",
+    description: "The `principal-destruct?` function decomposes a principal into its parts: its version byte, its
+20 hash bytes, and, for a contract principal, the contract's name.
+
+The parts are returned in a tuple. If the version byte of `principal-address` is for the network that the
+code is running on (see `is-standard`), the tuple is wrapped in `ok`. Otherwise, it is wrapped in `err`.",
+    example: "(principal-destruct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK) ;; Returns (ok (tuple (hash-bytes 0xfa6bf38ed557fe417333710d6033e9419391a320) (name none) (version 0x1a)))
+(principal-destruct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo) ;; Returns (ok (tuple (hash-bytes 0xfa6bf38ed557fe417333710d6033e9419391a320) (name (some \"foo\")) (version 0x1a)))
+(principal-destruct? 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY) ;; Returns (err (tuple (hash-bytes 0xfa6bf38ed557fe417333710d6033e9419391a320) (name none) (version 0x16)))
+"
+};
+
+const PRINCIPAL_CONSTRUCT_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff 1), (buff 20), [(string-ascii 40)]",
+    output_type: "(response principal (tuple (error_code uint) (value (optional principal))))",
+    signature: "(principal-construct? (buff 1) (buff 20) [(string-ascii 40)])",
+    description: "The `principal-construct?` function builds a standard principal from a version byte and 20
+hash bytes, or a contract principal if a contract name is also supplied.
+
+If the principal is valid and its version byte is for the network that the code is running on
+(see `is-standard`), the principal is returned wrapped in `ok`. Otherwise, an error tuple is returned:
+
+`(err (tuple (error_code u0) (value (some principal))))` -- the principal is valid, but its version byte
+is for another network
+`(err (tuple (error_code u1) (value none)))` -- the inputs do not describe a valid principal, e.g., the
+version byte is greater than `0x1f`, the buffers are too short, or the contract name is invalid",
+    example: "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320) ;; Returns (ok ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK)
+(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 \"foo\") ;; Returns (ok ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo)
+(principal-construct? 0x16 0xfa6bf38ed557fe417333710d6033e9419391a320) ;; Returns (err (tuple (error_code u0) (value (some SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY))))
+(principal-construct? 0x20 0xfa6bf38ed557fe417333710d6033e9419391a320) ;; Returns (err (tuple (error_code u1) (value none)))
+"
+};
+
+const TO_CONSENSUS_BUFF_API: SpecialAPI = SpecialAPI {
+    input_type: "A",
+    output_type: "(optional buff)",
+    signature: "(to-consensus-buff value)",
+    description: "The `to-consensus-buff` function serializes the input value into a buffer, using the
+same consensus serialization that is used to store values and to pass them to and from the node (e.g., in
+transaction payloads). If the serialization of the value is larger than the maximum buffer size, `none`
+is returned. Otherwise, the buffer is returned wrapped in `some`.",
+    example: "(to-consensus-buff 1) ;; Returns (some 0x0000000000000000000000000000000001)
+(to-consensus-buff u1) ;; Returns (some 0x0100000000000000000000000000000001)
+(to-consensus-buff true) ;; Returns (some 0x03)
+(to-consensus-buff (tuple (a 1))) ;; Returns (some 0x0c0000000101610000000000000000000000000000000001)
+"
+};
+
+const FROM_CONSENSUS_BUFF_API: SpecialAPI = SpecialAPI {
+    input_type: "type-signature(t), buff",
+    output_type: "(optional t)",
+    signature: "(from-consensus-buff type-signature buffer)",
+    description: "The `from-consensus-buff` function deserializes a buffer into a Clarity value of the
+given type, using the consensus serialization (see `to-consensus-buff`). If the buffer is not the
+serialization of a value of that type, or contains trailing bytes, `none` is returned. Otherwise, the value
+is returned wrapped in `some`.",
+    example: "(from-consensus-buff int 0x0000000000000000000000000000000001) ;; Returns (some 1)
+(from-consensus-buff uint 0x0000000000000000000000000000000001) ;; Returns none
+(from-consensus-buff bool 0x0300) ;; Returns none
+(from-consensus-buff (tuple (a int)) 0x0c0000000101610000000000000000000000000000000001) ;; Returns (some (tuple (a 1)))
+"
+};
+
 const AT_BLOCK: SpecialAPI = SpecialAPI {
     input_type: "(buff 32), A",
     output_type: "A",
@@ -1098,7 +1295,8 @@ const GET_BLOCK_INFO_API: SpecialAPI = SpecialAPI {
     description: "The `get-block-info?` function fetches data for a block of the given block height. The
 value and type returned are determined by the specified `BlockInfoPropertyName`. If the provided `BlockHeightInt` does
 not correspond to an existing block prior to the current block, the function returns `none`. The currently available property names
-are `time`, `header-hash`, `burnchain-header-hash`, `id-header-hash`, `miner-address`, and `vrf-seed`.
+are `time`, `header-hash`, `burnchain-header-hash`, `id-header-hash`, `miner-address`, `vrf-seed`,
+`burnchain-block-height`, `miner-reward`, and `total-fees`.
 
 The `time` property returns an integer value of the block header time field. This is a Unix epoch timestamp in seconds
 which roughly corresponds to when the block was mined. **Warning**: this does not increase monotonically with each block
@@ -1108,11 +1306,20 @@ The `header-hash`, `burnchain-header-hash`, `id-header-hash`, and `vrf-seed` pro
 
 The `miner-address` property returns a `principal` corresponding to the miner of the given block.
 
+The `burnchain-block-height` property returns the height of the burnchain block in which the given block was mined.
+
+The `miner-reward` property returns the coinbase (in microSTX) paid to the miner of the given block, and the
+`total-fees` property returns the sum of the transaction fees (in microSTX) of the given block and its confirmed
+microblocks.
+
 The `id-header-hash` is the block identifier value that must be used as input to the `at-block` function.
 ",
     example: "(get-block-info? time u0) ;; Returns (some u1557860301)
 (get-block-info? header-hash u0) ;; Returns (some 0x374708fff7719dd5979ec875d56cd2286f6d3cf7ec317a3b25632aab28ec37bb)
 (get-block-info? vrf-seed u0) ;; Returns (some 0xf490de2920c8a35fabeb13208852aa28c76f9be9b03a4dd2b3c075f7a26923b4)
+(get-block-info? burnchain-block-height u0) ;; Returns (some u567890)
+(get-block-info? miner-reward u0) ;; Returns (some u1000000000)
+(get-block-info? total-fees u0) ;; Returns (some u12345)
 "
 };
 
@@ -1338,6 +1545,23 @@ definition (i.e., you cannot put such a statement in the middle of a function bo
 "
 };
 
+const DEFINE_EVENT_API: DefineAPI = DefineAPI {
+    input_type: "EventName, TypeDefinition",
+    output_type: "Not Applicable",
+    signature: "(define-event event-name event-type)",
+    description: "`define-event` is used to declare a typed event, which the contract emits with `emit-event`.
+The type checker ensures that every emitted value matches the event's declared type, and the declared
+events are listed in the contract's interface, so that event observers know the shape of each event's payload.
+
+Like other kinds of definition statements, `define-event` may only be used at the top level of a smart contract
+definition (i.e., you cannot put a define statement in the middle of a function body).
+",
+    example: "
+(define-event transfer { sender: principal, recipient: principal, amount: uint })
+(define-event paused bool)
+"
+};
+
 const MINT_TOKEN: SpecialAPI = SpecialAPI {
     input_type: "TokenName, uint, principal",
     output_type: "(response bool uint)",
@@ -1515,6 +1739,23 @@ principal isn't materialized, it returns 0.
 ",
 };
 
+const STX_GET_ACCOUNT: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(stx-account owner)",
+    description: "`stx-account` is used to query the STX account of the `owner` principal.
+
+This function returns a tuple with the `unlocked` STX balance of the `owner` principal (the same
+amount returned by `stx-get-balance`), the amount of `locked` STX, and the burnchain block height
+at which the locked STX unlock (`unlock-height`). If the `owner` principal has no locked STX, both
+`locked` and `unlock-height` are 0. In the event that the `owner` principal isn't materialized,
+every field is 0.
+",
+    example: "
+(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; Returns (tuple (locked u0) (unlock-height u0) (unlocked u0))
+(stx-account (as-contract tx-sender)) ;; Returns (tuple (locked u0) (unlock-height u0) (unlocked u1000))
+",
+};
+
 const STX_TRANSFER: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(stx-transfer? amount sender recipient)",
@@ -1537,6 +1778,85 @@ one of the following error codes:
 "
 };
 
+const BUFF_TO_INT_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-le (buff 16))",
+    description: "Converts a byte buffer to a signed integer using a little-endian encoding.
+The byte buffer can be up to 16 bytes in length. If there are fewer than 16 bytes, as
+this function uses a little-endian encoding, the input behaves as if it is
+zero-padded on the _right_.",
+    example: "(buff-to-int-le 0x01) ;; Returns 1
+(buff-to-int-le 0x01000000000000000000000000000000) ;; Returns 1
+(buff-to-int-le 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-le 0x) ;; Returns 0
+",
+};
+
+const BUFF_TO_UINT_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-le (buff 16))",
+    description: "Converts a byte buffer to an unsigned integer using a little-endian encoding.
+The byte buffer can be up to 16 bytes in length. If there are fewer than 16 bytes, as
+this function uses a little-endian encoding, the input behaves as if it is
+zero-padded on the _right_.",
+    example: "(buff-to-uint-le 0x01) ;; Returns u1
+(buff-to-uint-le 0x01000000000000000000000000000000) ;; Returns u1
+(buff-to-uint-le 0xffffffffffffffffffffffffffffffff) ;; Returns u340282366920938463463374607431768211455
+(buff-to-uint-le 0x) ;; Returns u0
+"
+};
+
+const BUFF_TO_INT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-be (buff 16))",
+    description: "Converts a byte buffer to a signed integer using a big-endian encoding.
+The byte buffer can be up to 16 bytes in length. If there are fewer than 16 bytes, as
+this function uses a big-endian encoding, the input behaves as if it is
+zero-padded on the _left_.",
+    example: "(buff-to-int-be 0x01) ;; Returns 1
+(buff-to-int-be 0x00000000000000000000000000000001) ;; Returns 1
+(buff-to-int-be 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-be 0x) ;; Returns 0
+",
+};
+
+const BUFF_TO_UINT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-be (buff 16))",
+    description: "Converts a byte buffer to an unsigned integer using a big-endian encoding.
+The byte buffer can be up to 16 bytes in length. If there are fewer than 16 bytes, as
+this function uses a big-endian encoding, the input behaves as if it is
+zero-padded on the _left_.",
+    example: "(buff-to-uint-be 0x0100) ;; Returns u256
+(buff-to-uint-be 0x00000000000000000000000000000001) ;; Returns u1
+(buff-to-uint-be 0x) ;; Returns u0
+",
+};
+
+const INT_TO_ASCII_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-ascii (int|uint))",
+    description: "Converts an integer, either `int` or `uint`, to a `string-ascii` of its decimal
+representation.",
+    example: "(int-to-ascii 1) ;; Returns \"1\"
+(int-to-ascii u1) ;; Returns \"1\"
+(int-to-ascii -1) ;; Returns \"-1\"
+",
+};
+
+const STRING_TO_INT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-int? (string-ascii|string-utf8))",
+    description: "Converts a string, either `string-ascii` or `string-utf8`, of decimal digits
+(with an optional leading sign) to an optional-wrapped signed integer. If the input string does
+not represent a valid integer, then the function returns `none`. Otherwise it returns an integer
+wrapped in `some`.",
+    example: "(string-to-int? \"1\") ;; Returns (some 1)
+(string-to-int? u\"-1\") ;; Returns (some -1)
+(string-to-int? \"a\") ;; Returns none
+",
+};
+
 const STX_BURN: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(stx-burn? amount sender)",
@@ -1577,6 +1897,11 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         Sqrti => make_for_simple_native(&SQRTI_API, &Sqrti, name),
         Log2 => make_for_simple_native(&LOG2_API, &Log2, name),
         BitwiseXOR => make_for_simple_native(&XOR_API, &BitwiseXOR, name),
+        BitwiseAnd => make_for_simple_native(&BITWISE_AND_API, &BitwiseAnd, name),
+        BitwiseOr => make_for_simple_native(&BITWISE_OR_API, &BitwiseOr, name),
+        BitwiseNot => make_for_simple_native(&BITWISE_NOT_API, &BitwiseNot, name),
+        BitwiseLShift => make_for_simple_native(&BITWISE_LEFT_SHIFT_API, &BitwiseLShift, name),
+        BitwiseRShift => make_for_simple_native(&BITWISE_RIGHT_SHIFT_API, &BitwiseRShift, name),
         And => make_for_simple_native(&AND_API, &And, name),
         Or => make_for_simple_native(&OR_API, &Or, name),
         Not => make_for_simple_native(&NOT_API, &Not, name),
@@ -1594,6 +1919,8 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         Len => make_for_special(&LEN_API, name),
         ElementAt => make_for_special(&ELEMENT_AT_API, name),
         IndexOf => make_for_special(&INDEX_OF_API, name),
+        Slice => make_for_special(&SLICE_API, name),
+        ReplaceAt => make_for_special(&REPLACE_AT_API, name),
         ListCons => make_for_special(&LIST_API, name),
         FetchEntry => make_for_special(&FETCH_ENTRY_API, name),
         SetEntry => make_for_special(&SET_ENTRY_API, name),
@@ -1614,6 +1941,11 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         ContractCall => make_for_special(&CONTRACT_CALL_API, name),
         ContractOf => make_for_special(&CONTRACT_OF_API, name),
         PrincipalOf => make_for_special(&PRINCIPAL_OF_API, name),
+        IsStandard => make_for_special(&IS_STANDARD_API, name),
+        PrincipalDestruct => make_for_special(&PRINCIPAL_DESTRUCT_API, name),
+        PrincipalConstruct => make_for_special(&PRINCIPAL_CONSTRUCT_API, name),
+        ToConsensusBuff => make_for_special(&TO_CONSENSUS_BUFF_API, name),
+        FromConsensusBuff => make_for_special(&FROM_CONSENSUS_BUFF_API, name),
         AsContract => make_for_special(&AS_CONTRACT_API, name),
         GetBlockInfo => make_for_special(&GET_BLOCK_INFO_API, name),
         ConsOkay => make_for_special(&CONS_OK_API, name),
@@ -1642,8 +1974,16 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         GetTokenSupply => make_for_special(&GET_TOKEN_SUPPLY, name),
         AtBlock => make_for_special(&AT_BLOCK, name),
         GetStxBalance => make_for_simple_native(&STX_GET_BALANCE, &GetStxBalance, name),
+        StxGetAccount => make_for_simple_native(&STX_GET_ACCOUNT, &StxGetAccount, name),
+        EmitEvent => make_for_special(&EMIT_EVENT_API, name),
         StxTransfer => make_for_simple_native(&STX_TRANSFER, &StxTransfer, name),
         StxBurn => make_for_simple_native(&STX_BURN, &StxBurn, name),
+        BuffToIntLe => make_for_simple_native(&BUFF_TO_INT_LE_API, &BuffToIntLe, name),
+        BuffToUIntLe => make_for_simple_native(&BUFF_TO_UINT_LE_API, &BuffToUIntLe, name),
+        BuffToIntBe => make_for_simple_native(&BUFF_TO_INT_BE_API, &BuffToIntBe, name),
+        BuffToUIntBe => make_for_simple_native(&BUFF_TO_UINT_BE_API, &BuffToUIntBe, name),
+        IntToAscii => make_for_simple_native(&INT_TO_ASCII_API, &IntToAscii, name),
+        StringToInt => make_for_simple_native(&STRING_TO_INT_API, &StringToInt, name),
     }
 }
 
@@ -1698,6 +2038,7 @@ fn make_define_reference(define_type: &DefineFunctions) -> FunctionAPI {
         Trait => make_for_define(&DEFINE_TRAIT_API, name),
         UseTrait => make_for_define(&USE_TRAIT_API, name),
         ImplTrait => make_for_define(&IMPL_TRAIT_API, name),
+        Event => make_for_define(&DEFINE_EVENT_API, name),
     }
 }
 
@@ -1733,10 +2074,41 @@ pub fn make_json_api_reference() -> String {
     )
 }
 
+/// The reference documentation of a native function, define, or keyword, formatted as
+///  markdown (e.g., for display in an editor).
+pub fn make_markdown_reference(name: &str) -> Option<String> {
+    let function_api = if let Some(function) = NativeFunctions::lookup_by_name(name) {
+        make_api_reference(&function)
+    } else if let Some(define_type) = DefineFunctions::lookup_by_name(name) {
+        make_define_reference(&define_type)
+    } else {
+        let keyword_api = NativeVariables::lookup_by_name(name)
+            .as_ref()
+            .and_then(make_keyword_reference)?;
+        return Some(format!(
+            "`{}`: `{}`\n\n{}\n\n```clarity\n{}\n```",
+            keyword_api.name,
+            keyword_api.output_type,
+            keyword_api.description,
+            keyword_api.example.trim_end()
+        ));
+    };
+
+    Some(format!(
+        "`{}`\n\n`{}` -> `{}`\n\n{}\n\n```clarity\n{}\n```",
+        function_api.signature,
+        function_api.input_type,
+        function_api.output_type,
+        function_api.description,
+        function_api.example.trim_end()
+    ))
+}
+
 #[cfg(test)]
 mod test {
     use super::make_all_api_reference;
     use super::make_json_api_reference;
+    use super::make_markdown_reference;
     use burnchains::BurnchainHeaderHash;
     use chainstate::burn::db::sortdb::SortitionId;
     use chainstate::burn::{BlockHeaderHash, VRFSeed};
@@ -1790,6 +2162,12 @@ mod test {
         fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
             None
         }
+        fn get_miner_reward_for_block(&self, _id_bhh: &StacksBlockId) -> Option<u128> {
+            Some(1000000000)
+        }
+        fn get_total_fees_for_block(&self, _id_bhh: &StacksBlockId) -> Option<u128> {
+            Some(12345)
+        }
     }
 
     struct DocBurnStateDB {}
@@ -1876,6 +2254,18 @@ mod test {
         make_json_api_reference();
     }
 
+    #[test]
+    fn test_markdown_reference() {
+        assert!(make_markdown_reference("map-get?")
+            .unwrap()
+            .starts_with("`(map-get? "));
+        assert!(make_markdown_reference("define-public").is_some());
+        assert!(make_markdown_reference("block-height")
+            .unwrap()
+            .starts_with("`block-height`: `uint`"));
+        assert!(make_markdown_reference("no-such-function").is_none());
+    }
+
     #[test]
     fn test_examples() {
         let apis = make_all_api_reference();
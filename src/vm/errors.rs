@@ -41,7 +41,7 @@ pub enum Error {
     Unchecked(CheckErrors),
     Interpreter(InterpreterError),
     Runtime(RuntimeErrorType, Option<StackTrace>),
-    ShortReturn(ShortReturnType),
+    ShortReturn(ShortReturnType, Option<StackTrace>),
 }
 
 /// InterpreterErrors are errors that *should never* occur.
@@ -63,6 +63,11 @@ pub enum InterpreterError {
     InsufficientBalance,
     CostContractLoadFailure,
     DBError(IncomparableError<DatabaseError>),
+    /// The state at this block has been pruned from this (non-archival) node.
+    BlockPruned(BlockHeaderHash),
+    /// The store's key index does not cover every data map entry and NFT, so their entries
+    /// can't be listed.
+    KeyIndexUnavailable,
 }
 
 /// RuntimeErrors are errors that smart contracts are expected
@@ -117,7 +122,7 @@ impl PartialEq<Error> for Error {
         match (self, other) {
             (Error::Runtime(x, _), Error::Runtime(y, _)) => x == y,
             (Error::Unchecked(x), Error::Unchecked(y)) => x == y,
-            (Error::ShortReturn(x), Error::ShortReturn(y)) => x == y,
+            (Error::ShortReturn(x, _), Error::ShortReturn(y, _)) => x == y,
             (Error::Interpreter(x), Error::Interpreter(y)) => x == y,
             _ => false,
         }
@@ -131,20 +136,27 @@ impl fmt::Display for Error {
                 match err {
                     _ => write!(f, "{}", err),
                 }?;
-
-                if let Some(ref stack_trace) = stack {
-                    write!(f, "\n Stack Trace: \n")?;
-                    for item in stack_trace.iter() {
-                        write!(f, "{}\n", item)?;
-                    }
-                }
-                Ok(())
+                write_stack_trace(f, stack)
+            }
+            Error::ShortReturn(ref short_return, ref stack) => {
+                write!(f, "ShortReturn({:?})", short_return)?;
+                write_stack_trace(f, stack)
             }
             _ => write!(f, "{:?}", self),
         }
     }
 }
 
+fn write_stack_trace(f: &mut fmt::Formatter, stack: &Option<StackTrace>) -> fmt::Result {
+    if let Some(ref stack_trace) = stack {
+        write!(f, "\n Stack Trace: \n")?;
+        for item in stack_trace.iter() {
+            write!(f, "{}\n", item)?;
+        }
+    }
+    Ok(())
+}
+
 impl fmt::Display for RuntimeErrorType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -195,7 +207,7 @@ impl From<CheckErrors> for Error {
 
 impl From<ShortReturnType> for Error {
     fn from(err: ShortReturnType) -> Self {
-        Error::ShortReturn(err)
+        Error::ShortReturn(err, None)
     }
 }
 
@@ -238,15 +250,18 @@ _native_:native_div
     #[test]
     fn equality() {
         assert_eq!(
-            Error::ShortReturn(ShortReturnType::ExpectedValue(Value::Bool(true))),
-            Error::ShortReturn(ShortReturnType::ExpectedValue(Value::Bool(true)))
+            Error::ShortReturn(ShortReturnType::ExpectedValue(Value::Bool(true)), None),
+            Error::ShortReturn(
+                ShortReturnType::ExpectedValue(Value::Bool(true)),
+                Some(vec![])
+            )
         );
         assert_eq!(
             Error::Interpreter(InterpreterError::InterpreterError("".to_string())),
             Error::Interpreter(InterpreterError::InterpreterError("".to_string()))
         );
         assert!(
-            Error::ShortReturn(ShortReturnType::ExpectedValue(Value::Bool(true)))
+            Error::ShortReturn(ShortReturnType::ExpectedValue(Value::Bool(true)), None)
                 != Error::Interpreter(InterpreterError::InterpreterError("".to_string()))
         );
     }
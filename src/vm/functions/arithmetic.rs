@@ -52,6 +52,29 @@ macro_rules! type_force_binary_arithmetic {
     }};
 }
 
+// This macro checks that the shift amount is a uint and the shifted value is an integer,
+//   and then dispatches the evaluation to the correct arithmetic type handler. Only the
+//   low 7 bits of the shift amount are used, so shifts are always modulo 128.
+macro_rules! type_force_shift_arithmetic {
+    ($function: ident, $x: expr, $shamt: expr) => {{
+        match $shamt {
+            Value::UInt(shamt) => {
+                let shamt = (shamt % 128) as u32;
+                match $x {
+                    Value::Int(x) => I128Ops::$function(x, shamt),
+                    Value::UInt(x) => U128Ops::$function(x, shamt),
+                    x => Err(CheckErrors::UnionTypeValueError(
+                        vec![TypeSignature::IntType, TypeSignature::UIntType],
+                        x,
+                    )
+                    .into()),
+                }
+            }
+            shamt => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, shamt).into()),
+        }
+    }};
+}
+
 macro_rules! type_force_unary_arithmetic {
     ($function: ident, $x: expr) => {{
         match $x {
@@ -122,6 +145,29 @@ macro_rules! make_arithmetic_ops {
             fn xor(x: $type, y: $type) -> InterpreterResult<Value> {
                 Self::make_value(x ^ y)
             }
+            fn bitwise_and(args: &[$type]) -> InterpreterResult<Value> {
+                let (first, rest) = args
+                    .split_first()
+                    .ok_or(CheckErrors::IncorrectArgumentCount(1, 0))?;
+                Self::make_value(rest.iter().fold(*first, |acc, x| acc & x))
+            }
+            fn bitwise_or(args: &[$type]) -> InterpreterResult<Value> {
+                let (first, rest) = args
+                    .split_first()
+                    .ok_or(CheckErrors::IncorrectArgumentCount(1, 0))?;
+                Self::make_value(rest.iter().fold(*first, |acc, x| acc | x))
+            }
+            fn bitwise_not(x: $type) -> InterpreterResult<Value> {
+                Self::make_value(!x)
+            }
+            // bits shifted past the most significant bit are discarded.
+            fn shift_left(x: $type, shamt: u32) -> InterpreterResult<Value> {
+                Self::make_value(x.wrapping_shl(shamt))
+            }
+            // signed integers are shifted arithmetically (preserving their sign).
+            fn shift_right(x: $type, shamt: u32) -> InterpreterResult<Value> {
+                Self::make_value(x.wrapping_shr(shamt))
+            }
             fn leq(x: $type, y: $type) -> InterpreterResult<Value> {
                 Ok(Value::Bool(x <= y))
             }
@@ -282,6 +328,21 @@ pub fn native_log2(n: Value) -> InterpreterResult<Value> {
 pub fn native_mod(a: Value, b: Value) -> InterpreterResult<Value> {
     type_force_binary_arithmetic!(modulo, a, b)
 }
+pub fn native_bitwise_and(mut args: Vec<Value>) -> InterpreterResult<Value> {
+    type_force_variadic_arithmetic!(bitwise_and, args)
+}
+pub fn native_bitwise_or(mut args: Vec<Value>) -> InterpreterResult<Value> {
+    type_force_variadic_arithmetic!(bitwise_or, args)
+}
+pub fn native_bitwise_not(a: Value) -> InterpreterResult<Value> {
+    type_force_unary_arithmetic!(bitwise_not, a)
+}
+pub fn native_bitwise_left_shift(a: Value, shamt: Value) -> InterpreterResult<Value> {
+    type_force_shift_arithmetic!(shift_left, a, shamt)
+}
+pub fn native_bitwise_right_shift(a: Value, shamt: Value) -> InterpreterResult<Value> {
+    type_force_shift_arithmetic!(shift_right, a, shamt)
+}
 
 pub fn native_to_uint(input: Value) -> InterpreterResult<Value> {
     if let Value::Int(int_val) = input {
@@ -25,7 +25,8 @@ use vm::errors::{
 };
 use vm::representations::SymbolicExpression;
 use vm::types::{
-    AssetIdentifier, BlockInfoProperty, BuffData, OptionalData, PrincipalData, TypeSignature, Value,
+    AssetIdentifier, BlockInfoProperty, BuffData, OptionalData, PrincipalData, TupleData,
+    TypeSignature, Value,
 };
 use vm::{eval, Environment, LocalContext};
 
@@ -96,6 +97,38 @@ pub fn special_stx_balance(
     }
 }
 
+pub fn special_stx_account(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    runtime_cost(ClarityCostFunction::StxGetAccount, env, 0)?;
+
+    let owner = eval(&args[0], env, context)?;
+
+    if let Value::Principal(ref principal) = owner {
+        let (unlocked, (locked, unlock_height)) = {
+            let snapshot = env
+                .global_context
+                .database
+                .get_stx_balance_snapshot(principal);
+            (
+                snapshot.get_available_balance(),
+                snapshot.get_locked_balance(),
+            )
+        };
+        Ok(Value::Tuple(TupleData::from_data(vec![
+            ("locked".into(), Value::UInt(locked)),
+            ("unlocked".into(), Value::UInt(unlocked)),
+            ("unlock-height".into(), Value::UInt(unlock_height as u128)),
+        ])?))
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into())
+    }
+}
+
 /// Do a "consolidated" STX transfer.
 /// If the 'from' principal has locked STX, and they have unlocked, then process the STX unlock
 /// and update its balance in addition to spending tokens out of it.
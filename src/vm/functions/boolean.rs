@@ -21,7 +21,7 @@ use vm::errors::{
 };
 use vm::representations::SymbolicExpression;
 use vm::types::{TypeSignature, Value};
-use vm::{eval, Environment, LocalContext};
+use vm::{eval, notify_branch, Environment, LocalContext};
 
 fn type_force_bool(value: &Value) -> Result<bool> {
     match *value {
@@ -43,10 +43,12 @@ pub fn special_or(
         let evaluated = eval(&arg, env, context)?;
         let result = type_force_bool(&evaluated)?;
         if result {
+            notify_branch(env, &args[0], 0);
             return Ok(Value::Bool(true));
         }
     }
 
+    notify_branch(env, &args[0], 1);
     Ok(Value::Bool(false))
 }
 
@@ -63,10 +65,12 @@ pub fn special_and(
         let evaluated = eval(&arg, env, context)?;
         let result = type_force_bool(&evaluated)?;
         if !result {
+            notify_branch(env, &args[0], 0);
             return Ok(Value::Bool(false));
         }
     }
 
+    notify_branch(env, &args[0], 1);
     Ok(Value::Bool(true))
 }
 
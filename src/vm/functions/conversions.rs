@@ -0,0 +1,176 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::str;
+
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::runtime_cost;
+use vm::errors::{check_argument_count, CheckErrors, InterpreterResult};
+use vm::representations::SymbolicExpression;
+use vm::types::{BuffData, CharType, SequenceData, TypeSignature, Value, BUFF_16, MAX_VALUE_SIZE};
+use vm::{eval, Environment, LocalContext};
+
+enum EndianDirection {
+    LittleEndian,
+    BigEndian,
+}
+
+/// Read a buffer of at most 16 bytes into a 16-byte array, zero-padding
+///  its most significant end.
+fn buff_to_bytes(input: Value, direction: EndianDirection) -> InterpreterResult<[u8; 16]> {
+    match input {
+        Value::Sequence(SequenceData::Buffer(ref buff)) if buff.data.len() <= 16 => {
+            let mut bytes = [0u8; 16];
+            match direction {
+                EndianDirection::LittleEndian => {
+                    bytes[..buff.data.len()].copy_from_slice(&buff.data);
+                }
+                EndianDirection::BigEndian => {
+                    bytes[16 - buff.data.len()..].copy_from_slice(&buff.data);
+                }
+            }
+            Ok(bytes)
+        }
+        _ => Err(CheckErrors::TypeValueError(BUFF_16.clone(), input).into()),
+    }
+}
+
+pub fn native_buff_to_int_le(input: Value) -> InterpreterResult<Value> {
+    let bytes = buff_to_bytes(input, EndianDirection::LittleEndian)?;
+    Ok(Value::Int(i128::from_le_bytes(bytes)))
+}
+
+pub fn native_buff_to_uint_le(input: Value) -> InterpreterResult<Value> {
+    let bytes = buff_to_bytes(input, EndianDirection::LittleEndian)?;
+    Ok(Value::UInt(u128::from_le_bytes(bytes)))
+}
+
+pub fn native_buff_to_int_be(input: Value) -> InterpreterResult<Value> {
+    let bytes = buff_to_bytes(input, EndianDirection::BigEndian)?;
+    Ok(Value::Int(i128::from_be_bytes(bytes)))
+}
+
+pub fn native_buff_to_uint_be(input: Value) -> InterpreterResult<Value> {
+    let bytes = buff_to_bytes(input, EndianDirection::BigEndian)?;
+    Ok(Value::UInt(u128::from_be_bytes(bytes)))
+}
+
+pub fn native_int_to_ascii(input: Value) -> InterpreterResult<Value> {
+    let digits = match input {
+        Value::Int(int_val) => int_val.to_string(),
+        Value::UInt(uint_val) => uint_val.to_string(),
+        _ => {
+            return Err(CheckErrors::UnionTypeValueError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                input,
+            )
+            .into())
+        }
+    };
+    Value::string_ascii_from_bytes(digits.into_bytes())
+}
+
+/// Parse a string of decimal digits (with an optional sign) into an int,
+///  returning none if the string is not a valid int. The cost is linear
+///  in the length of the string, since all of it is copied and checked.
+pub fn special_string_to_int(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> InterpreterResult<Value> {
+    check_argument_count(1, args)?;
+
+    let input = eval(&args[0], env, context)?;
+    runtime_cost(ClarityCostFunction::StringToInt, env, input.size())?;
+
+    native_string_to_int(input)
+}
+
+fn native_string_to_int(input: Value) -> InterpreterResult<Value> {
+    let parsed = match input {
+        Value::Sequence(SequenceData::String(CharType::ASCII(ref ascii))) => {
+            str::from_utf8(&ascii.data)
+                .ok()
+                .and_then(|s| s.parse::<i128>().ok())
+        }
+        Value::Sequence(SequenceData::String(CharType::UTF8(ref utf8))) => {
+            let bytes: Vec<u8> = utf8.data.iter().flatten().cloned().collect();
+            String::from_utf8(bytes)
+                .ok()
+                .and_then(|s| s.parse::<i128>().ok())
+        }
+        _ => {
+            return Err(CheckErrors::UnionTypeValueError(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                input,
+            )
+            .into())
+        }
+    };
+    match parsed {
+        Some(int_val) => Value::some(Value::Int(int_val)),
+        None => Ok(Value::none()),
+    }
+}
+
+/// Serialize a value into a buffer using the consensus serialization, returning none if
+///  the serialization would be longer than the maximum buffer size.
+pub fn special_to_consensus_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> InterpreterResult<Value> {
+    check_argument_count(1, args)?;
+    let value = eval(&args[0], env, context)?;
+    runtime_cost(ClarityCostFunction::ToConsensusBuff, env, value.size())?;
+
+    let mut serialized = vec![];
+    value
+        .serialize_write(&mut serialized)
+        .expect("IOError filling byte buffer.");
+    if serialized.len() > MAX_VALUE_SIZE as usize {
+        return Ok(Value::none());
+    }
+    Value::some(Value::buff_from(serialized)?)
+}
+
+/// Deserialize a buffer into a value of the given type, returning none if the
+///  buffer is not exactly the consensus serialization of such a value.
+pub fn special_from_consensus_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> InterpreterResult<Value> {
+    check_argument_count(2, args)?;
+    let expected_type = TypeSignature::parse_type_repr(&args[0], env)?;
+    let input = eval(&args[1], env, context)?;
+
+    let data = match input {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) => data,
+        _ => return Err(CheckErrors::TypeValueError(TypeSignature::max_buffer(), input).into()),
+    };
+    runtime_cost(ClarityCostFunction::FromConsensusBuff, env, data.len())?;
+
+    let mut reader = data.as_slice();
+    match Value::deserialize_read(&mut reader, Some(&expected_type)) {
+        // trailing bytes mean the buffer is not the serialization of a single value.
+        Ok(value) if reader.is_empty() => Value::some(value),
+        _ => Ok(Value::none()),
+    }
+}
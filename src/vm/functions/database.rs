@@ -495,6 +495,27 @@ pub fn special_get_block_info(
             let miner_address = env.global_context.database.get_miner_address(height_value);
             Value::from(miner_address)
         }
+        BlockInfoProperty::BurnchainBlockHeight => {
+            let burnchain_height = env
+                .global_context
+                .database
+                .get_block_burnchain_height(height_value);
+            Value::UInt(burnchain_height as u128)
+        }
+        BlockInfoProperty::MinerReward => {
+            let miner_reward = env
+                .global_context
+                .database
+                .get_block_miner_reward(height_value);
+            Value::UInt(miner_reward)
+        }
+        BlockInfoProperty::TotalFees => {
+            let total_fees = env
+                .global_context
+                .database
+                .get_block_total_fees(height_value);
+            Value::UInt(total_fees)
+        }
     };
 
     Ok(Value::some(result)?)
@@ -42,6 +42,7 @@ define_named_enum!(DefineFunctions {
     Trait("define-trait"),
     UseTrait("use-trait"),
     ImplTrait("impl-trait"),
+    Event("define-event"),
 });
 
 pub enum DefineFunctionsParsed<'a> {
@@ -93,6 +94,10 @@ pub enum DefineFunctionsParsed<'a> {
     ImplTrait {
         trait_identifier: &'a TraitIdentifier,
     },
+    Event {
+        name: &'a ClarityName,
+        event_type: &'a SymbolicExpression,
+    },
 }
 
 pub enum DefineResult {
@@ -105,6 +110,7 @@ pub enum DefineResult {
     Trait(ClarityName, BTreeMap<ClarityName, FunctionSignature>),
     UseTrait(ClarityName, TraitIdentifier),
     ImplTrait(TraitIdentifier),
+    Event(ClarityName, TypeSignature),
     NoDefine,
 }
 
@@ -263,6 +269,18 @@ fn handle_impl_trait(trait_identifier: &TraitIdentifier) -> Result<DefineResult>
     Ok(DefineResult::ImplTrait(trait_identifier.clone()))
 }
 
+fn handle_define_event(
+    name: &ClarityName,
+    event_type: &SymbolicExpression,
+    env: &mut Environment,
+) -> Result<DefineResult> {
+    check_legal_define(&name, &env.contract_context)?;
+
+    let event_type_signature = TypeSignature::parse_type_repr(event_type, env)?;
+
+    Ok(DefineResult::Event(name.clone(), event_type_signature))
+}
+
 impl DefineFunctions {
     pub fn try_parse(
         expression: &SymbolicExpression,
@@ -391,6 +409,14 @@ impl<'a> DefineFunctionsParsed<'a> {
                     _ => return Err(CheckErrors::ExpectedTraitIdentifier.into()),
                 }
             }
+            DefineFunctions::Event => {
+                check_argument_count(2, args)?;
+                let name = args[0].match_atom().ok_or(CheckErrors::ExpectedName)?;
+                DefineFunctionsParsed::Event {
+                    name,
+                    event_type: &args[1],
+                }
+            }
         };
         Ok(Some(result))
     }
@@ -443,6 +469,9 @@ pub fn evaluate_define(
             DefineFunctionsParsed::ImplTrait { trait_identifier } => {
                 handle_impl_trait(trait_identifier)
             }
+            DefineFunctionsParsed::Event { name, event_type } => {
+                handle_define_event(name, event_type, env)
+            }
         }
     } else {
         Ok(DefineResult::NoDefine)
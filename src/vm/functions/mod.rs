@@ -17,10 +17,12 @@
 mod arithmetic;
 mod assets;
 mod boolean;
+mod conversions;
 mod crypto;
 mod database;
 pub mod define;
 mod options;
+mod principals;
 mod sequences;
 mod special;
 pub mod tuples;
@@ -41,7 +43,7 @@ use vm::types::{
     BuffData, CharType, PrincipalData, ResponseData, SequenceData, TypeSignature, Value, BUFF_32,
     BUFF_33, BUFF_65,
 };
-use vm::{eval, Environment, LocalContext};
+use vm::{eval, notify_branch, Environment, LocalContext};
 
 use address::AddressHashMode;
 use chainstate::stacks::{StacksAddress, C32_ADDRESS_VERSION_TESTNET_SINGLESIG};
@@ -132,8 +134,40 @@ define_named_enum!(NativeFunctions {
     GetStxBalance("stx-get-balance"),
     StxTransfer("stx-transfer?"),
     StxBurn("stx-burn?"),
+    BuffToIntLe("buff-to-int-le"),
+    BuffToUIntLe("buff-to-uint-le"),
+    BuffToIntBe("buff-to-int-be"),
+    BuffToUIntBe("buff-to-uint-be"),
+    IntToAscii("int-to-ascii"),
+    StringToInt("string-to-int?"),
+    Slice("slice"),
+    ReplaceAt("replace-at"),
+    IsStandard("is-standard"),
+    PrincipalDestruct("principal-destruct?"),
+    PrincipalConstruct("principal-construct?"),
+    ToConsensusBuff("to-consensus-buff"),
+    FromConsensusBuff("from-consensus-buff"),
+    StxGetAccount("stx-account"),
+    BitwiseAnd("bit-and"),
+    BitwiseOr("bit-or"),
+    BitwiseNot("bit-not"),
+    BitwiseLShift("bit-shift-left"),
+    BitwiseRShift("bit-shift-right"),
+    EmitEvent("emit-event"),
 });
 
+/// Whether the native charged by `cost_function` may short-return rather than return its
+///  result, i.e., whether it is a branch point for coverage purposes. Each native has its
+///  own cost function, so this identifies the native without comparing names.
+pub fn is_branching_native(cost_function: &ClarityCostFunction) -> bool {
+    match cost_function {
+        ClarityCostFunction::UnwrapRet
+        | ClarityCostFunction::UnwrapErrOrRet
+        | ClarityCostFunction::TryRet => true,
+        _ => false,
+    }
+}
+
 pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
     use vm::callables::CallableType::{NativeFunction, SpecialFunction};
     use vm::functions::NativeFunctions::*;
@@ -214,6 +248,31 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
                 NativeHandle::DoubleArg(&arithmetic::native_xor),
                 ClarityCostFunction::Xor,
             ),
+            BitwiseAnd => NativeFunction(
+                "native_bitwise_and",
+                NativeHandle::MoreArg(&arithmetic::native_bitwise_and),
+                ClarityCostFunction::BitwiseAnd,
+            ),
+            BitwiseOr => NativeFunction(
+                "native_bitwise_or",
+                NativeHandle::MoreArg(&arithmetic::native_bitwise_or),
+                ClarityCostFunction::BitwiseOr,
+            ),
+            BitwiseNot => NativeFunction(
+                "native_bitwise_not",
+                NativeHandle::SingleArg(&arithmetic::native_bitwise_not),
+                ClarityCostFunction::BitwiseNot,
+            ),
+            BitwiseLShift => NativeFunction(
+                "native_bitwise_left_shift",
+                NativeHandle::DoubleArg(&arithmetic::native_bitwise_left_shift),
+                ClarityCostFunction::BitwiseLShift,
+            ),
+            BitwiseRShift => NativeFunction(
+                "native_bitwise_right_shift",
+                NativeHandle::DoubleArg(&arithmetic::native_bitwise_right_shift),
+                ClarityCostFunction::BitwiseRShift,
+            ),
             And => SpecialFunction("special_and", &boolean::special_and),
             Or => SpecialFunction("special_or", &boolean::special_or),
             Not => NativeFunction(
@@ -397,6 +456,55 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
             GetStxBalance => SpecialFunction("special_stx_balance", &assets::special_stx_balance),
             StxTransfer => SpecialFunction("special_stx_transfer", &assets::special_stx_transfer),
             StxBurn => SpecialFunction("special_stx_burn", &assets::special_stx_burn),
+            BuffToIntLe => NativeFunction(
+                "native_buff_to_int_le",
+                NativeHandle::SingleArg(&conversions::native_buff_to_int_le),
+                ClarityCostFunction::BuffToIntLe,
+            ),
+            BuffToUIntLe => NativeFunction(
+                "native_buff_to_uint_le",
+                NativeHandle::SingleArg(&conversions::native_buff_to_uint_le),
+                ClarityCostFunction::BuffToUIntLe,
+            ),
+            BuffToIntBe => NativeFunction(
+                "native_buff_to_int_be",
+                NativeHandle::SingleArg(&conversions::native_buff_to_int_be),
+                ClarityCostFunction::BuffToIntBe,
+            ),
+            BuffToUIntBe => NativeFunction(
+                "native_buff_to_uint_be",
+                NativeHandle::SingleArg(&conversions::native_buff_to_uint_be),
+                ClarityCostFunction::BuffToUIntBe,
+            ),
+            IntToAscii => NativeFunction(
+                "native_int_to_ascii",
+                NativeHandle::SingleArg(&conversions::native_int_to_ascii),
+                ClarityCostFunction::IntToAscii,
+            ),
+            StringToInt => {
+                SpecialFunction("special_string_to_int", &conversions::special_string_to_int)
+            }
+            Slice => SpecialFunction("special_slice", &sequences::special_slice),
+            ReplaceAt => SpecialFunction("special_replace_at", &sequences::special_replace_at),
+            IsStandard => SpecialFunction("special_is_standard", &principals::special_is_standard),
+            PrincipalDestruct => SpecialFunction(
+                "special_principal_destruct",
+                &principals::special_principal_destruct,
+            ),
+            PrincipalConstruct => SpecialFunction(
+                "special_principal_construct",
+                &principals::special_principal_construct,
+            ),
+            ToConsensusBuff => SpecialFunction(
+                "special_to_consensus_buff",
+                &conversions::special_to_consensus_buff,
+            ),
+            FromConsensusBuff => SpecialFunction(
+                "special_from_consensus_buff",
+                &conversions::special_from_consensus_buff,
+            ),
+            StxGetAccount => SpecialFunction("special_stx_account", &assets::special_stx_account),
+            EmitEvent => SpecialFunction("special_emit_event", &special_emit_event),
         };
         Some(callable)
     } else {
@@ -449,6 +557,30 @@ fn special_print(
     Ok(input)
 }
 
+fn special_emit_event(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(2, args)?;
+
+    let event_name = args[0].match_atom().ok_or(CheckErrors::BadEventName)?;
+    let input = eval(&args[1], env, context)?;
+
+    runtime_cost(ClarityCostFunction::EmitEvent, env, input.size())?;
+
+    let event_type = env
+        .contract_context
+        .lookup_event_type(event_name)
+        .ok_or(CheckErrors::NoSuchEvent(event_name.to_string()))?;
+    if !event_type.admits(&input) {
+        return Err(CheckErrors::TypeValueError(event_type.clone(), input).into());
+    }
+
+    env.register_contract_event(event_name, input.clone())?;
+    Ok(input)
+}
+
 fn special_if(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -462,8 +594,10 @@ fn special_if(
     match conditional {
         Value::Bool(result) => {
             if result {
+                notify_branch(env, &args[0], 0);
                 eval(&args[1], env, context)
             } else {
+                notify_branch(env, &args[0], 1);
                 eval(&args[2], env, context)
             }
         }
@@ -485,8 +619,10 @@ fn special_asserts(
     match conditional {
         Value::Bool(result) => {
             if result {
+                notify_branch(env, &args[0], 0);
                 Ok(conditional)
             } else {
+                notify_branch(env, &args[0], 1);
                 let thrown = eval(&args[1], env, context)?;
                 Err(ShortReturnType::AssertionFailed(thrown).into())
             }
@@ -198,6 +198,13 @@ pub fn special_match(
 
     runtime_cost(ClarityCostFunction::Match, env, 0)?;
 
+    let branch = match input {
+        Value::Response(ref data) if !data.committed => 1,
+        Value::Optional(ref data) if data.data.is_none() => 1,
+        _ => 0,
+    };
+    vm::notify_branch(env, &args[0], branch);
+
     match input {
         Value::Response(data) => special_match_resp(data, &args[1..], env, context),
         Value::Optional(data) => special_match_opt(data, &args[1..], env, context),
@@ -0,0 +1,223 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::TryFrom;
+
+use chainstate::stacks::{
+    C32_ADDRESS_VERSION_MAINNET_MULTISIG, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+    C32_ADDRESS_VERSION_TESTNET_MULTISIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+use vm::ast::parser::CONTRACT_MAX_NAME_LENGTH;
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::runtime_cost;
+use vm::errors::{
+    check_argument_count, check_arguments_at_least, CheckErrors, InterpreterResult as Result,
+};
+use vm::representations::{ContractName, SymbolicExpression};
+use vm::types::{
+    BuffData, CharType, PrincipalData, QualifiedContractIdentifier, SequenceData,
+    StandardPrincipalData, TupleData, TypeSignature, Value, BUFF_1, BUFF_20,
+};
+use vm::{eval, Environment, LocalContext};
+
+/// Error code returned by `principal-construct?` when the principal was built, but its version
+///  byte is not for the current network.
+pub const PRINCIPAL_CONSTRUCT_ERR_WRONG_NETWORK: u128 = 0;
+/// Error code returned by `principal-construct?` when the inputs do not describe a valid principal.
+pub const PRINCIPAL_CONSTRUCT_ERR_INVALID_INPUT: u128 = 1;
+
+/// Version bytes are c32 characters, so only the low 5 bits are usable.
+const MAX_VERSION_BYTE: u8 = 31;
+
+/// Whether `version` is a standard (single-sig or multi-sig) address version for the network.
+pub fn version_matches_network(version: u8, mainnet: bool) -> bool {
+    if mainnet {
+        version == C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+            || version == C32_ADDRESS_VERSION_MAINNET_MULTISIG
+    } else {
+        version == C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+            || version == C32_ADDRESS_VERSION_TESTNET_MULTISIG
+    }
+}
+
+pub fn special_is_standard(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+    runtime_cost(ClarityCostFunction::IsStandard, env, 0)?;
+    let owner = eval(&args[0], env, context)?;
+
+    let version = match owner {
+        Value::Principal(ref principal) => principal.version(),
+        _ => return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into()),
+    };
+
+    Ok(Value::Bool(version_matches_network(
+        version,
+        env.global_context.mainnet,
+    )))
+}
+
+/// The tuple representation of a principal:
+///  `{ version: (buff 1), hash-bytes: (buff 20), name: (optional (string-ascii 40)) }`
+fn create_principal_destruct_tuple(
+    version: u8,
+    hash_bytes: &[u8; 20],
+    name: Option<ContractName>,
+) -> Result<Value> {
+    let name = match name {
+        Some(name) => Value::some(Value::string_ascii_from_bytes(
+            name.as_str().as_bytes().to_vec(),
+        )?)?,
+        None => Value::none(),
+    };
+    Ok(Value::Tuple(TupleData::from_data(vec![
+        ("version".into(), Value::buff_from_byte(version)),
+        ("hash-bytes".into(), Value::buff_from(hash_bytes.to_vec())?),
+        ("name".into(), name),
+    ])?))
+}
+
+pub fn special_principal_destruct(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+    runtime_cost(ClarityCostFunction::PrincipalDestruct, env, 0)?;
+    let principal = eval(&args[0], env, context)?;
+
+    let (version, hash_bytes, name) = match principal {
+        Value::Principal(PrincipalData::Standard(StandardPrincipalData(version, hash_bytes))) => {
+            (version, hash_bytes, None)
+        }
+        Value::Principal(PrincipalData::Contract(QualifiedContractIdentifier { issuer, name })) => {
+            (issuer.0, issuer.1, Some(name))
+        }
+        _ => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, principal).into())
+        }
+    };
+
+    let tuple = create_principal_destruct_tuple(version, &hash_bytes, name)?;
+    if version_matches_network(version, env.global_context.mainnet) {
+        Value::okay(tuple)
+    } else {
+        Value::error(tuple)
+    }
+}
+
+fn create_principal_construct_error(error_code: u128, value: Option<Value>) -> Result<Value> {
+    let value = match value {
+        Some(principal) => Value::some(principal)?,
+        None => Value::none(),
+    };
+    Value::error(Value::Tuple(TupleData::from_data(vec![
+        ("error_code".into(), Value::UInt(error_code)),
+        ("value".into(), value),
+    ])?))
+}
+
+pub fn special_principal_construct(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_arguments_at_least(2, args)?;
+    if args.len() > 3 {
+        return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+    }
+    runtime_cost(ClarityCostFunction::PrincipalConstruct, env, 0)?;
+
+    let version = eval(&args[0], env, context)?;
+    let hash_bytes = eval(&args[1], env, context)?;
+    let name = match args.get(2) {
+        Some(name) => Some(eval(name, env, context)?),
+        None => None,
+    };
+
+    // the type checker admits shorter buffers, so their lengths are checked here.
+    let version = match version {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) => {
+            if data.len() != 1 || data[0] > MAX_VERSION_BYTE {
+                return create_principal_construct_error(
+                    PRINCIPAL_CONSTRUCT_ERR_INVALID_INPUT,
+                    None,
+                );
+            }
+            data[0]
+        }
+        _ => return Err(CheckErrors::TypeValueError(BUFF_1.clone(), version).into()),
+    };
+
+    let hash_bytes = match hash_bytes {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) => {
+            if data.len() != 20 {
+                return create_principal_construct_error(
+                    PRINCIPAL_CONSTRUCT_ERR_INVALID_INPUT,
+                    None,
+                );
+            }
+            let mut hash_bytes = [0u8; 20];
+            hash_bytes.copy_from_slice(data);
+            hash_bytes
+        }
+        _ => return Err(CheckErrors::TypeValueError(BUFF_20.clone(), hash_bytes).into()),
+    };
+
+    let issuer = StandardPrincipalData(version, hash_bytes);
+    let principal = match name {
+        None => PrincipalData::Standard(issuer),
+        Some(Value::Sequence(SequenceData::String(CharType::ASCII(ascii)))) => {
+            let contract_name = if ascii.data.len() > CONTRACT_MAX_NAME_LENGTH {
+                None
+            } else {
+                String::from_utf8(ascii.data)
+                    .ok()
+                    .and_then(|name| ContractName::try_from(name).ok())
+            };
+            match contract_name {
+                Some(name) => {
+                    PrincipalData::Contract(QualifiedContractIdentifier::new(issuer, name))
+                }
+                None => {
+                    return create_principal_construct_error(
+                        PRINCIPAL_CONSTRUCT_ERR_INVALID_INPUT,
+                        None,
+                    )
+                }
+            }
+        }
+        Some(name) => {
+            return Err(CheckErrors::TypeValueError(
+                TypeSignature::contract_name_string_ascii_type(),
+                name,
+            )
+            .into())
+        }
+    };
+
+    if version_matches_network(version, env.global_context.mainnet) {
+        Value::okay(Value::Principal(principal))
+    } else {
+        create_principal_construct_error(
+            PRINCIPAL_CONSTRUCT_ERR_WRONG_NETWORK,
+            Some(Value::Principal(principal)),
+        )
+    }
+}
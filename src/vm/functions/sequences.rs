@@ -311,3 +311,77 @@ pub fn native_element_at(sequence: Value, index: Value) -> Result<Value> {
         Ok(Value::none())
     }
 }
+
+pub fn special_slice(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let left_position = eval(&args[1], env, context)?;
+    let right_position = eval(&args[2], env, context)?;
+
+    runtime_cost(ClarityCostFunction::Slice, env, sequence.size())?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+
+    let (left_position, right_position) = match (left_position, right_position) {
+        (Value::UInt(left_position), Value::UInt(right_position)) => {
+            match (
+                usize::try_from(left_position),
+                usize::try_from(right_position),
+            ) {
+                (Ok(left_position), Ok(right_position)) => (left_position, right_position),
+                _ => return Ok(Value::none()),
+            }
+        }
+        (Value::UInt(_), right_position) => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, right_position).into())
+        }
+        (left_position, _) => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, left_position).into())
+        }
+    };
+
+    match sequence_data.slice(left_position, right_position)? {
+        Some(result) => Value::some(result),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn special_replace_at(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let index = eval(&args[1], env, context)?;
+    let element = eval(&args[2], env, context)?;
+
+    runtime_cost(ClarityCostFunction::ReplaceAt, env, sequence.size())?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+
+    let index = match index {
+        Value::UInt(index) => match usize::try_from(index) {
+            Ok(index) => index,
+            Err(_) => return Ok(Value::none()),
+        },
+        _ => return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, index).into()),
+    };
+
+    match sequence_data.replace_at(index, element)? {
+        Some(result) => Value::some(result),
+        None => Ok(Value::none()),
+    }
+}
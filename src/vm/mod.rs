@@ -37,7 +37,10 @@ mod functions;
 mod variables;
 
 pub mod analysis;
+pub mod coverage;
+pub mod debug;
 pub mod docs;
+pub mod trace;
 
 #[cfg(test)]
 pub mod tests;
@@ -57,6 +60,7 @@ use vm::functions::define::DefineResult;
 pub use vm::types::Value;
 use vm::types::{PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 
+use vm::representations::Span;
 pub use vm::representations::{
     ClarityName, ContractName, SymbolicExpression, SymbolicExpressionType,
 };
@@ -64,10 +68,57 @@ pub use vm::representations::{
 use std::convert::{TryFrom, TryInto};
 pub use vm::contexts::MAX_CONTEXT_DEPTH;
 use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::profiler::CostProfile;
+pub use vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
 pub use vm::functions::stx_transfer_consolidated;
+pub use vm::functions::NativeFunctions;
+pub use vm::variables::NativeVariables;
 
 const MAX_CALL_STACK_DEPTH: usize = 64;
 
+/// An EvalHook is notified before and after every expression is evaluated.
+///  Hooks are registered on the GlobalContext, and are used by developer
+///  tooling (e.g., the clarity-cli debugger) to observe or pause execution.
+pub trait EvalHook {
+    /// Called before `expr` is evaluated in `context`.
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    );
+
+    /// Called after `expr` has been evaluated, with the result of the evaluation.
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+        result: &Result<Value>,
+    );
+
+    /// Called when a branching form (`if`, `match`, `asserts!`, `unwrap!`, etc.) takes
+    ///  one of its branches. `branch_expr` is the expression that decided the branch
+    ///  (e.g., the condition of an `if`), and `branch` is the index of the branch taken.
+    fn did_take_branch(
+        &mut self,
+        _env: &mut Environment,
+        _branch_expr: &SymbolicExpression,
+        _branch: usize,
+    ) {
+    }
+}
+
+/// Notify the registered EvalHooks that a branching form took `branch`.
+pub fn notify_branch(env: &mut Environment, branch_expr: &SymbolicExpression, branch: usize) {
+    if let Some(mut eval_hooks) = env.global_context.eval_hooks.take() {
+        for hook in eval_hooks.iter_mut() {
+            hook.did_take_branch(env, branch_expr, branch);
+        }
+        env.global_context.eval_hooks = Some(eval_hooks);
+    }
+}
+
 fn lookup_variable(name: &str, context: &LocalContext, env: &mut Environment) -> Result<Value> {
     if name.starts_with(char::is_numeric) || name.starts_with('\'') {
         Err(InterpreterError::BadSymbolicRepresentation(format!(
@@ -117,9 +168,17 @@ pub fn lookup_function(name: &str, env: &mut Environment) -> Result<CallableType
 }
 
 fn add_stack_trace(result: &mut Result<Value>, env: &Environment) {
-    if let Err(Error::Runtime(_, ref mut stack_trace)) = result {
-        if stack_trace.is_none() {
-            stack_trace.replace(env.call_stack.make_stack_trace());
+    let stack_trace = match result {
+        Err(Error::Runtime(_, ref mut stack_trace))
+        | Err(Error::ShortReturn(_, ref mut stack_trace)) => stack_trace,
+        _ => return,
+    };
+    if stack_trace.is_none() {
+        match env.call_stack.make_stack_trace() {
+            Ok(trace) => {
+                stack_trace.replace(trace);
+            }
+            Err(e) => *result = Err(e),
         }
     }
 }
@@ -129,6 +188,18 @@ pub fn apply(
     args: &[SymbolicExpression],
     env: &mut Environment,
     context: &LocalContext,
+) -> Result<Value> {
+    apply_at(function, args, env, context, None)
+}
+
+/// Apply `function`, recording `call_site` (the span of the application) in the call stack,
+///  so that runtime errors' stack traces can point at it.
+fn apply_at(
+    function: &CallableType,
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+    call_site: Option<&Span>,
 ) -> Result<Value> {
     let identifier = function.get_identifier();
     // Aaron: in non-debug executions, we shouldn't track a full call-stack.
@@ -149,7 +220,12 @@ pub fn apply(
     }
 
     if let CallableType::SpecialFunction(_, function) = function {
-        env.call_stack.insert(&identifier, track_recursion);
+        env.call_stack.insert(
+            &identifier,
+            track_recursion,
+            &env.contract_context.contract_identifier,
+            call_site,
+        );
         let mut resp = function(args, env, context);
         add_stack_trace(&mut resp, env);
         env.call_stack.remove(&identifier, track_recursion)?;
@@ -181,12 +257,28 @@ pub fn apply(
         }
         env.call_stack.decr_apply_depth();
 
-        env.call_stack.insert(&identifier, track_recursion);
+        env.call_stack.insert(
+            &identifier,
+            track_recursion,
+            &env.contract_context.contract_identifier,
+            call_site,
+        );
         let mut resp = match function {
             CallableType::NativeFunction(_, function, cost_function) => {
-                runtime_cost(*cost_function, env, evaluated_args.len())
+                let resp = runtime_cost(*cost_function, env, evaluated_args.len())
                     .map_err(Error::from)
-                    .and_then(|_| function.apply(evaluated_args))
+                    .and_then(|_| function.apply(evaluated_args));
+                if env.global_context.has_eval_hooks()
+                    && functions::is_branching_native(cost_function)
+                {
+                    // the unwrapping natives take their second branch by short-returning.
+                    match resp {
+                        Ok(_) => notify_branch(env, &args[0], 0),
+                        Err(Error::ShortReturn(..)) => notify_branch(env, &args[0], 1),
+                        Err(_) => {}
+                    }
+                }
+                resp
             }
             CallableType::UserFunction(function) => function.apply(&evaluated_args, env),
             _ => panic!("Should be unreachable."),
@@ -207,21 +299,52 @@ pub fn eval<'a>(
         Atom, AtomValue, Field, List, LiteralValue, TraitReference,
     };
 
-    match exp.expr {
-        AtomValue(ref value) | LiteralValue(ref value) => Ok(value.clone()),
-        Atom(ref value) => lookup_variable(&value, context, env),
-        List(ref children) => {
-            let (function_variable, rest) = children
-                .split_first()
-                .ok_or(CheckErrors::NonFunctionApplication)?;
-            let function_name = function_variable
-                .match_atom()
-                .ok_or(CheckErrors::BadFunctionName)?;
-            let f = lookup_function(&function_name, env)?;
-            apply(&f, &rest, env, context)
+    // the hooks are taken out of the global context while they run, so that they
+    //  may be handed a mutable reference to the environment.
+    if let Some(mut eval_hooks) = env.global_context.eval_hooks.take() {
+        for hook in eval_hooks.iter_mut() {
+            hook.will_begin_eval(env, context, exp);
+        }
+        env.global_context.eval_hooks = Some(eval_hooks);
+    }
+
+    env.profile_enter(|env| {
+        CostProfile::expression_stack(
+            env.call_stack,
+            &env.contract_context.contract_identifier,
+            exp,
+        )
+    });
+
+    // wrap in a closure so that `?` doesn't skip the did_finish_eval hooks.
+    let res = (|| -> Result<Value> {
+        match exp.expr {
+            AtomValue(ref value) | LiteralValue(ref value) => Ok(value.clone()),
+            Atom(ref value) => lookup_variable(&value, context, env),
+            List(ref children) => {
+                let (function_variable, rest) = children
+                    .split_first()
+                    .ok_or(CheckErrors::NonFunctionApplication)?;
+                let function_name = function_variable
+                    .match_atom()
+                    .ok_or(CheckErrors::BadFunctionName)?;
+                let f = lookup_function(&function_name, env)?;
+                apply_at(&f, &rest, env, context, exp.span())
+            }
+            TraitReference(_, _) | Field(_) => unreachable!("can't be evaluated"),
+        }
+    })();
+
+    env.profile_exit();
+
+    if let Some(mut eval_hooks) = env.global_context.eval_hooks.take() {
+        for hook in eval_hooks.iter_mut() {
+            hook.did_finish_eval(env, context, exp, &res);
         }
-        TraitReference(_, _) | Field(_) => unreachable!("can't be evaluated"),
+        env.global_context.eval_hooks = Some(eval_hooks);
     }
+
+    res
 }
 
 pub fn is_reserved(name: &str) -> bool {
@@ -330,6 +453,13 @@ fn eval_all(
                 DefineResult::ImplTrait(trait_identifier) => {
                     contract_context.implemented_traits.insert(trait_identifier);
                 },
+                DefineResult::Event(name, event_type) => {
+                    runtime_cost(ClarityCostFunction::BindName, global_context, 0)?;
+                    global_context.add_memory(event_type.type_size()
+                                              .expect("type size should be realizable") as u64)?;
+
+                    contract_context.defined_events.insert(name, event_type);
+                },
                 DefineResult::NoDefine => {
                     // not a define function, evaluate normally.
                     global_context.execute(|global_context| {
@@ -356,11 +486,16 @@ fn eval_all(
  *  Only used by CLI.
  */
 pub fn execute(program: &str) -> Result<Option<Value>> {
+    execute_on_network(program, false)
+}
+
+/// Execute a program as `execute` does, but on mainnet if `mainnet` is set.
+pub fn execute_on_network(program: &str, mainnet: bool) -> Result<Option<Value>> {
     let contract_id = QualifiedContractIdentifier::transient();
     let mut contract_context = ContractContext::new(contract_id.clone());
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
-    let mut global_context = GlobalContext::new(false, conn, LimitedCostTracker::new_free());
+    let mut global_context = GlobalContext::new(mainnet, conn, LimitedCostTracker::new_free());
     global_context.execute(|g| {
         let parsed = ast::build_ast(&contract_id, program, &mut ())?.expressions;
         eval_all(&parsed, &mut contract_context, g)
@@ -190,6 +190,17 @@ impl PreSymbolicExpression {
     ) {
     }
 
+    /// This expression's span in its source, if spans are tracked.
+    #[cfg(feature = "developer-mode")]
+    pub fn span(&self) -> Option<&Span> {
+        Some(&self.span)
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn span(&self) -> Option<&Span> {
+        None
+    }
+
     pub fn sugared_contract_identifier(val: ContractName) -> PreSymbolicExpression {
         PreSymbolicExpression {
             pre_expr: PreSymbolicExpressionType::SugaredContractIdentifier(val),
@@ -377,6 +388,32 @@ impl SymbolicExpression {
     ) {
     }
 
+    /// This expression's span in its source, if spans are tracked.
+    #[cfg(feature = "developer-mode")]
+    pub fn span(&self) -> Option<&Span> {
+        Some(&self.span)
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn span(&self) -> Option<&Span> {
+        None
+    }
+
+    /// The source line this expression starts on, if it is known.
+    #[cfg(feature = "developer-mode")]
+    pub fn start_line(&self) -> Option<u32> {
+        if self.span.start_line > 0 {
+            Some(self.span.start_line)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn start_line(&self) -> Option<u32> {
+        None
+    }
+
     pub fn atom_value(val: Value) -> SymbolicExpression {
         SymbolicExpression {
             expr: SymbolicExpressionType::AtomValue(val),
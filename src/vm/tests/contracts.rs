@@ -103,6 +103,9 @@ fn test_get_block_info_eval() {
         "(define-private (test-func) (get-block-info? header-hash u1))",
         "(define-private (test-func) (get-block-info? burnchain-header-hash u1))",
         "(define-private (test-func) (get-block-info? vrf-seed u1))",
+        "(define-private (test-func) (get-block-info? burnchain-block-height u1))",
+        "(define-private (test-func) (get-block-info? miner-reward u1))",
+        "(define-private (test-func) (get-block-info? total-fees u1))",
     ];
 
     let expected = [
@@ -114,6 +117,9 @@ fn test_get_block_info_eval() {
         Ok(Value::none()),
         Ok(Value::none()),
         Ok(Value::none()),
+        Ok(Value::none()),
+        Ok(Value::none()),
+        Ok(Value::none()),
     ];
     /*    let expected = [
         Ok(Value::UInt(0)),
@@ -807,6 +813,66 @@ fn test_simple_contract_call(owned_env: &mut OwnedEnvironment) {
     }
 }
 
+fn test_contract_call_stack_trace(owned_env: &mut OwnedEnvironment) {
+    let contract_1 = "(define-map entries { id: int } { value: int })
+(define-public (get-entry (id int))
+  (ok (unwrap-panic (map-get? entries { id: id }))))";
+    let contract_2 = "(define-public (proxy-get-entry)
+  (contract-call? .entries get-entry 1))";
+
+    let mut env = owned_env.get_exec_environment(Some(get_principal()));
+
+    let callee = QualifiedContractIdentifier::local("entries").unwrap();
+    env.initialize_contract(callee.clone(), contract_1).unwrap();
+
+    let caller = QualifiedContractIdentifier::local("proxy").unwrap();
+    env.initialize_contract(caller.clone(), contract_2).unwrap();
+
+    let stack_trace = match env
+        .execute_contract(&caller, "proxy-get-entry", &[], false)
+        .unwrap_err()
+    {
+        Error::Runtime(RuntimeErrorType::UnwrapFailure, Some(stack_trace)) => stack_trace,
+        e => panic!("Unexpected error: {:?}", e),
+    };
+
+    // each frame records the contract in whose source it was applied.
+    let frames: Vec<_> = stack_trace
+        .iter()
+        .map(|frame| {
+            (
+                frame.contract_identifier.clone(),
+                frame.function.to_string(),
+                frame
+                    .call_site
+                    .as_ref()
+                    .map(|span| (span.start_line, span.start_column)),
+            )
+        })
+        .collect();
+    assert_eq!(
+        frames,
+        vec![
+            (
+                env.contract_context.contract_identifier.clone(),
+                format!("{}:proxy-get-entry", caller),
+                None
+            ),
+            (
+                caller.clone(),
+                "_native_:special_contract-call".to_string(),
+                Some((2, 3))
+            ),
+            (caller.clone(), format!("{}:get-entry", callee), None),
+            (
+                callee.clone(),
+                "_native_:native_unwrap".to_string(),
+                Some((3, 7))
+            ),
+        ]
+    );
+}
+
 fn test_aborts(owned_env: &mut OwnedEnvironment) {
     let contract_1 = "
 (define-map data { id: int } { value: int })
@@ -1208,6 +1274,7 @@ fn test_all() {
         test_fully_qualified_contract_call,
         test_simple_naming_system,
         test_simple_contract_call,
+        test_contract_call_stack_trace,
     ];
     for test in to_test.iter() {
         eprintln!("..");
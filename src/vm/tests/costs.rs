@@ -135,6 +135,26 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         GetStxBalance => "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxTransfer => "(stx-transfer? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxBurn => "(stx-burn? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        BuffToIntLe => "(buff-to-int-le 0x00000000000000000000000000000001)",
+        BuffToUIntLe => "(buff-to-uint-le 0x00000000000000000000000000000001)",
+        BuffToIntBe => "(buff-to-int-be 0x00000000000000000000000000000001)",
+        BuffToUIntBe => "(buff-to-uint-be 0x00000000000000000000000000000001)",
+        IntToAscii => "(int-to-ascii 1)",
+        StringToInt => "(string-to-int? \"1\")",
+        Slice => "(slice (list 1 2 3 4) u1 u3)",
+        ReplaceAt => "(replace-at (list 1 2 3 4) u1 5)",
+        IsStandard => "(is-standard 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        PrincipalDestruct => "(principal-destruct? 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        PrincipalConstruct => "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        ToConsensusBuff => "(to-consensus-buff u1)",
+        FromConsensusBuff => "(from-consensus-buff uint 0x0100000000000000000000000000000001)",
+        StxGetAccount => "(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        BitwiseAnd => "(bit-and 2 3)",
+        BitwiseOr => "(bit-or 2 3)",
+        BitwiseNot => "(bit-not 3)",
+        BitwiseLShift => "(bit-shift-left 2 u1)",
+        BitwiseRShift => "(bit-shift-right 2 u1)",
+        EmitEvent => "(emit-event event-foo 1)",
     }
 }
 
@@ -161,6 +181,7 @@ fn test_tracked_costs(prog: &str) -> ExecutionCost {
         (define-non-fungible-token nft-foo int)
         (define-fungible-token ft-foo)
         (define-data-var var-foo int 0)
+        (define-event event-foo int)
         (define-constant tuple-foo (tuple (a 1)))
         (define-constant list-foo (list true))
         (define-constant list-bar (list 1))
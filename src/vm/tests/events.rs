@@ -79,6 +79,27 @@ fn test_emit_print_nok() {
     assert_eq!(events.len(), 0);
 }
 
+#[test]
+fn test_emit_contract_event_ok() {
+    let contract = "(define-event transfer { amount: uint, memo: (buff 4) })
+        (define-public (emit-event-ok)
+            (begin
+                (emit-event transfer { amount: u10, memo: 0xdeadbeef })
+                (ok u1)))";
+
+    let (value, mut events) = helper_execute(contract, "emit-event-ok");
+    assert_eq!(value, Value::okay(Value::UInt(1)).unwrap());
+    assert_eq!(events.len(), 1);
+    match events.pop() {
+        Some(StacksTransactionEvent::SmartContractEvent(data)) => {
+            let contract_id = QualifiedContractIdentifier::local("contract").unwrap();
+            assert_eq!(data.key, (contract_id, "transfer".to_string()));
+            assert_eq!(data.value, execute("{ amount: u10, memo: 0xdeadbeef }"));
+        }
+        _ => panic!("assertion failed"),
+    };
+}
+
 #[test]
 fn test_emit_stx_transfer_ok() {
     let contract = "(define-constant sender 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)
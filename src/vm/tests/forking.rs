@@ -17,7 +17,7 @@
 use vm::analysis::errors::CheckErrors;
 use vm::contexts::OwnedEnvironment;
 use vm::database::{ClarityDatabase, MarfedKV, NULL_BURN_STATE_DB, NULL_HEADER_DB};
-use vm::errors::{Error, InterpreterResult as Result, RuntimeErrorType};
+use vm::errors::{Error, InterpreterError, InterpreterResult as Result, RuntimeErrorType};
 use vm::representations::SymbolicExpression;
 use vm::types::Value;
 use vm::types::{PrincipalData, QualifiedContractIdentifier};
@@ -240,6 +240,313 @@ fn test_at_block_missing_defines() {
     );
 }
 
+#[test]
+fn test_forking_map_entries() {
+    // keys written in one fork are indexed, but are only listed in the forks where they exist
+    let mut marf_kv = MarfedKV::temporary();
+    marf_kv.set_key_index(true).unwrap();
+    let p1 = execute(p1_str);
+    let c = QualifiedContractIdentifier::local("contract").unwrap();
+
+    {
+        let mut store = marf_kv.begin(&StacksBlockId::sentinel(), &StacksBlockId([0 as u8; 32]));
+        store
+            .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB)
+            .initialize();
+        store.test_commit();
+    }
+
+    {
+        let contract = format!(
+            "(define-map counts uint int)
+             (define-non-fungible-token badge uint)
+             (define-public (set-count (k uint) (v int)) (ok (map-set counts k v)))
+             (define-public (delete-count (k uint)) (ok (map-delete counts k)))
+             (define-public (burn-badge (id uint)) (nft-burn? badge id tx-sender))
+             (map-set counts u1 1)
+             (map-set counts u2 2)
+             (map-set counts u3 3)
+             (nft-mint? badge u1 {})
+             (nft-mint? badge u2 {})",
+            p1_str, p1_str
+        );
+
+        let mut store = marf_kv.begin(&StacksBlockId([0 as u8; 32]), &StacksBlockId([1 as u8; 32]));
+        let mut owned_env =
+            OwnedEnvironment::new(store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+        owned_env.initialize_contract(c.clone(), &contract).unwrap();
+        store.test_commit();
+    }
+
+    let branches = vec![
+        (
+            2,
+            vec![
+                ("set-count", vec![Value::UInt(4), Value::Int(4)]),
+                ("delete-count", vec![Value::UInt(2)]),
+                ("burn-badge", vec![Value::UInt(1)]),
+            ],
+        ),
+        (3, vec![("set-count", vec![Value::UInt(5), Value::Int(5)])]),
+        (
+            4,
+            (10..30)
+                .map(|k| ("set-count", vec![Value::UInt(k), Value::Int(k as i128)]))
+                .chain((10..30).map(|k| ("delete-count", vec![Value::UInt(k)])))
+                .collect(),
+        ),
+    ];
+    for (block, calls) in branches.into_iter() {
+        let mut store = marf_kv.begin(
+            &StacksBlockId([1 as u8; 32]),
+            &StacksBlockId([block as u8; 32]),
+        );
+        let mut owned_env =
+            OwnedEnvironment::new(store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+        for (function, args) in calls.into_iter() {
+            let (result, _, _) = owned_env
+                .execute_transaction(p1.clone(), c.clone(), function, &symbols_from_values(args))
+                .unwrap();
+            assert!(is_committed(&result));
+        }
+        store.test_commit();
+    }
+
+    fn list(
+        marf_kv: &mut MarfedKV,
+        block: u8,
+        name: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<(Value, Value)>, Option<String>)> {
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let mut store = marf_kv.begin_read_only(Some(&StacksBlockId([block; 32])));
+        let mut db = store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+        db.begin();
+        let result = db.list_map_or_nft_entries(&c, name, after, limit);
+        db.roll_back();
+        result
+    }
+
+    fn count(k: u128) -> (Value, Value) {
+        (Value::UInt(k), Value::Int(k as i128))
+    }
+
+    assert_eq!(
+        list(&mut marf_kv, 2, "counts", None, 10).unwrap(),
+        (vec![count(1), count(3), count(4)], None)
+    );
+    assert_eq!(
+        list(&mut marf_kv, 3, "counts", None, 10).unwrap(),
+        (vec![count(1), count(2), count(3), count(5)], None)
+    );
+
+    // pages skip over keys that only exist in the other fork
+    let (page, next) = list(&mut marf_kv, 3, "counts", None, 2).unwrap();
+    assert_eq!(page, vec![count(1), count(2)]);
+    assert_eq!(next, Some(Value::UInt(2).serialize()));
+
+    let (page, next) = list(
+        &mut marf_kv,
+        3,
+        "counts",
+        next.as_ref().map(|x| x.as_str()),
+        2,
+    )
+    .unwrap();
+    assert_eq!(page, vec![count(3), count(5)]);
+    assert_eq!(next, Some(Value::UInt(5).serialize()));
+
+    assert_eq!(
+        list(
+            &mut marf_kv,
+            3,
+            "counts",
+            next.as_ref().map(|x| x.as_str()),
+            2
+        )
+        .unwrap(),
+        (vec![], None)
+    );
+
+    // a page stops short once it has scanned `limit * INDEXED_KEYS_SCAN_FACTOR` keys, and the
+    //   next page picks up where it stopped.
+    let (page, next) = list(&mut marf_kv, 4, "counts", None, 2).unwrap();
+    assert_eq!(page, vec![count(1), count(2)]);
+    let (page, next) = list(
+        &mut marf_kv,
+        4,
+        "counts",
+        next.as_ref().map(|x| x.as_str()),
+        2,
+    )
+    .unwrap();
+    assert_eq!(page, vec![count(3)]);
+    assert_eq!(next, Some(Value::UInt(26).serialize()));
+    assert_eq!(
+        list(
+            &mut marf_kv,
+            4,
+            "counts",
+            next.as_ref().map(|x| x.as_str()),
+            2
+        )
+        .unwrap(),
+        (vec![], None)
+    );
+
+    // burnt tokens are not listed
+    assert_eq!(
+        list(&mut marf_kv, 2, "badge", None, 10).unwrap(),
+        (vec![(Value::UInt(2), p1.clone())], None)
+    );
+    assert_eq!(
+        list(&mut marf_kv, 3, "badge", None, 10).unwrap(),
+        (
+            vec![(Value::UInt(1), p1.clone()), (Value::UInt(2), p1.clone())],
+            None
+        )
+    );
+
+    match list(&mut marf_kv, 3, "nothing", None, 10).unwrap_err() {
+        Error::Unchecked(CheckErrors::NoSuchNFT(_)) => {}
+        e => panic!("Unexpected error listing a missing map: {:?}", e),
+    }
+}
+
+#[test]
+fn test_map_entries_key_index_migration() {
+    use rand::Rng;
+
+    // a store that has data when the key index is enabled, or that is written to while the index
+    //   is disabled, still reads its entries, but refuses to list them.
+    let contract = "(define-map counts uint int)
+         (define-public (set-count (k uint) (v int)) (ok (map-set counts k v)))
+         (map-set counts u1 1)";
+
+    fn begin_block(marf_kv: &mut MarfedKV, block: u8, contract: Option<&str>) {
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let mut store = marf_kv.begin(&StacksBlockId([block - 1; 32]), &StacksBlockId([block; 32]));
+        if let Some(contract) = contract {
+            let mut owned_env =
+                OwnedEnvironment::new(store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+            owned_env.initialize_contract(c, contract).unwrap();
+        }
+        store.test_commit();
+    }
+
+    fn set_count(marf_kv: &mut MarfedKV, block: u8, k: u128, v: i128) {
+        let p1 = execute(p1_str);
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let mut store = marf_kv.begin(&StacksBlockId([block - 1; 32]), &StacksBlockId([block; 32]));
+        let mut owned_env =
+            OwnedEnvironment::new(store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+        let (result, _, _) = owned_env
+            .execute_transaction(
+                p1,
+                c,
+                "set-count",
+                &symbols_from_values(vec![Value::UInt(k), Value::Int(v)]),
+            )
+            .unwrap();
+        assert!(is_committed(&result));
+        store.test_commit();
+    }
+
+    fn get_count(marf_kv: &mut MarfedKV, block: u8, k: u128) -> Value {
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let mut store = marf_kv.begin_read_only(Some(&StacksBlockId([block; 32])));
+        let mut db = store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+        db.begin();
+        let result = db
+            .fetch_entry_unknown_descriptor(&c, "counts", &Value::UInt(k))
+            .unwrap();
+        db.roll_back();
+        result
+    }
+
+    fn list(marf_kv: &mut MarfedKV, block: u8) -> Result<(Vec<(Value, Value)>, Option<String>)> {
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let mut store = marf_kv.begin_read_only(Some(&StacksBlockId([block; 32])));
+        let mut db = store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+        db.begin();
+        let result = db.list_map_entries(&c, "counts", None, 10);
+        db.roll_back();
+        result
+    }
+
+    fn assert_unavailable(result: Result<(Vec<(Value, Value)>, Option<String>)>) {
+        match result.unwrap_err() {
+            Error::Interpreter(InterpreterError::KeyIndexUnavailable) => {}
+            e => panic!("Unexpected error listing an unindexed map: {:?}", e),
+        }
+    }
+
+    fn some_count(v: i128) -> Value {
+        Value::some(Value::Int(v)).unwrap()
+    }
+
+    fn initialize(marf_kv: &mut MarfedKV) {
+        let mut store = marf_kv.begin(&StacksBlockId::sentinel(), &StacksBlockId([0 as u8; 32]));
+        store
+            .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB)
+            .initialize();
+        store.test_commit();
+    }
+
+    // existing store: the index is enabled after the contract was written
+    let mut marf_kv = MarfedKV::temporary();
+    initialize(&mut marf_kv);
+    begin_block(&mut marf_kv, 1, Some(contract));
+    marf_kv.set_key_index(true).unwrap();
+    assert!(marf_kv.is_key_index_enabled());
+    set_count(&mut marf_kv, 2, 2, 2);
+
+    assert_eq!(get_count(&mut marf_kv, 2, 1), some_count(1));
+    assert_eq!(get_count(&mut marf_kv, 2, 2), some_count(2));
+    assert_unavailable(list(&mut marf_kv, 1));
+    assert_unavailable(list(&mut marf_kv, 2));
+
+    // new store: the index is enabled before anything is written, and stays enabled on reopening
+    let path = format!(
+        "/tmp/key_index_migration_{}",
+        rand::thread_rng().gen::<u32>()
+    );
+    {
+        let mut marf_kv = MarfedKV::open(&path, None).unwrap();
+        assert!(!marf_kv.is_key_index_enabled());
+        marf_kv.set_key_index(true).unwrap();
+        initialize(&mut marf_kv);
+        begin_block(&mut marf_kv, 1, Some(contract));
+    }
+
+    let mut marf_kv = MarfedKV::open(&path, None).unwrap();
+    assert!(marf_kv.is_key_index_enabled());
+    set_count(&mut marf_kv, 2, 2, 2);
+    assert_eq!(
+        list(&mut marf_kv, 2).unwrap(),
+        (
+            vec![
+                (Value::UInt(1), Value::Int(1)),
+                (Value::UInt(2), Value::Int(2))
+            ],
+            None
+        )
+    );
+
+    // blocks without map writes leave the index intact even while it is disabled...
+    marf_kv.set_key_index(false).unwrap();
+    begin_block(&mut marf_kv, 3, None);
+    assert!(list(&mut marf_kv, 3).is_ok());
+
+    // ...but a map write invalidates it, and re-enabling it doesn't restore it
+    set_count(&mut marf_kv, 4, 3, 3);
+    marf_kv.set_key_index(true).unwrap();
+    assert_eq!(get_count(&mut marf_kv, 4, 3), some_count(3));
+    assert_unavailable(list(&mut marf_kv, 2));
+    assert_unavailable(list(&mut marf_kv, 4));
+}
+
 // execute:
 // f -> a -> z
 //    \--> b
@@ -174,6 +174,108 @@ fn test_element_at() {
     }
 }
 
+#[test]
+fn test_slice() {
+    let good = [
+        "(slice (list 1 2 3 4 5) u1 u3)",
+        "(slice \"abcd\" u0 u4)",
+        "(slice 0xfedb u1 u2)",
+        "(slice u\"ab\\u{a2}d\" u1 u3)",
+        "(slice \"abcd\" u2 u2)",
+        "(slice (list 1 2 3 4 5) u3 u1)",
+        "(slice \"abcd\" u0 u5)",
+        "(slice 0xfedb u3 u4)",
+    ];
+
+    let expected = [
+        "(some (2 3))",
+        "(some \"abcd\")",
+        "(some 0xdb)",
+        "(some u\"b\\u{c2a2}\")",
+        "(some \"\")",
+        "none",
+        "none",
+        "none",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = ["(slice 3 u1 u2)", "(slice (list 1 2 3) u1 2)"];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(2)),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        match execute(&bad_test).unwrap_err() {
+            Error::Unchecked(check_error) => {
+                assert_eq!(&check_error, expected);
+            }
+            _ => unreachable!("Should have raised unchecked errors"),
+        }
+    }
+}
+
+#[test]
+fn test_replace_at() {
+    let good = [
+        "(replace-at (list 1 2 3) u1 5)",
+        "(replace-at (list 0x01 0x02) u0 0x0304)",
+        "(replace-at \"abcd\" u3 \"e\")",
+        "(replace-at 0xfedb u0 0x00)",
+        "(replace-at u\"abcd\" u0 u\"\\u{a2}\")",
+        "(replace-at (list 1 2 3) u3 5)",
+        "(replace-at \"abcd\" u4 \"e\")",
+    ];
+
+    let expected = [
+        "(some (1 5 3))",
+        "(some (0x0304 0x02))",
+        "(some \"abce\")",
+        "(some 0x00db)",
+        "(some u\"\\u{c2a2}bcd\")",
+        "none",
+        "none",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = [
+        "(replace-at 3 u1 u2)",
+        "(replace-at (list 1 2 3) u1 u2)",
+        "(replace-at \"abcd\" u1 \"ef\")",
+    ];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeValueError(IntType, Value::UInt(2)),
+        CheckErrors::TypeValueError(
+            TypeSignature::min_string_ascii(),
+            Value::string_ascii_from_bytes("ef".into()).unwrap(),
+        ),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        match execute(&bad_test).unwrap_err() {
+            Error::Unchecked(check_error) => {
+                assert_eq!(&check_error, expected);
+            }
+            _ => unreachable!("Should have raised unchecked errors"),
+        }
+    }
+}
+
 #[test]
 fn test_string_ascii_admission() {
     let defines = "(define-private (set-name (x (string-ascii 11))) x)";
@@ -24,9 +24,9 @@ use vm::database::MemoryBackingStore;
 use vm::errors::{CheckErrors, Error, RuntimeErrorType, ShortReturnType};
 use vm::tests::execute;
 use vm::types::signatures::BufferLength;
-use vm::types::{BuffData, QualifiedContractIdentifier, TypeSignature};
-use vm::types::{PrincipalData, ResponseData, SequenceData, SequenceSubtype};
-use vm::{eval, execute as vm_execute};
+use vm::types::{BuffData, QualifiedContractIdentifier, TypeSignature, BUFF_16};
+use vm::types::{PrincipalData, ResponseData, SequenceData, SequenceSubtype, TupleData};
+use vm::{eval, execute as vm_execute, execute_on_network};
 use vm::{CallStack, ContractContext, Environment, GlobalContext, LocalContext, Value};
 
 use address::c32;
@@ -534,6 +534,81 @@ fn test_simple_arithmetic_functions() {
         .for_each(|(program, expectation)| assert_eq!(expectation.clone(), execute(program)));
 }
 
+#[test]
+fn test_bitwise_functions() {
+    let tests = [
+        "(bit-and 24 16)",
+        "(bit-and 28 24 -1)",
+        "(bit-and u24 u16)",
+        "(bit-and 7)",
+        "(bit-or 4 8)",
+        "(bit-or 1 2 4)",
+        "(bit-or u4 u8)",
+        "(bit-not 3)",
+        "(bit-not -1)",
+        "(bit-not u0)",
+        "(bit-shift-left 2 u1)",
+        "(bit-shift-left 1 u127)",
+        "(bit-shift-left u1 u128)",
+        "(bit-shift-left u3 u127)",
+        "(bit-shift-right 128 u2)",
+        "(bit-shift-right -64 u1)",
+        "(bit-shift-right -1 u127)",
+        "(bit-shift-right u340282366920938463463374607431768211455 u127)",
+        "(bit-shift-right u2 u129)",
+    ];
+
+    let expectations = [
+        Value::Int(16),
+        Value::Int(24),
+        Value::UInt(16),
+        Value::Int(7),
+        Value::Int(12),
+        Value::Int(7),
+        Value::UInt(12),
+        Value::Int(-4),
+        Value::Int(0),
+        Value::UInt(u128::max_value()),
+        Value::Int(4),
+        Value::Int(i128::min_value()),
+        Value::UInt(1),
+        Value::UInt(1 << 127),
+        Value::Int(32),
+        Value::Int(-32),
+        Value::Int(-1),
+        Value::UInt(1),
+        Value::UInt(1),
+    ];
+
+    for (program, expectation) in tests.iter().zip(expectations.iter()) {
+        assert_eq!(*expectation, execute(program));
+    }
+
+    let errors = [
+        "(bit-and)",
+        "(bit-or 1 u1)",
+        "(bit-not 1 2)",
+        "(bit-shift-left 1 1)",
+        "(bit-shift-right true u1)",
+    ];
+
+    let error_expectations: &[Error] = &[
+        CheckErrors::IncorrectArgumentCount(1, 0).into(),
+        CheckErrors::TypeValueError(TypeSignature::IntType, Value::UInt(1)).into(),
+        CheckErrors::IncorrectArgumentCount(1, 2).into(),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(1)).into(),
+        CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            Value::Bool(true),
+        )
+        .into(),
+    ];
+
+    for (program, expectation) in errors.iter().zip(error_expectations.iter()) {
+        assert_eq!(*expectation, vm_execute(program).unwrap_err());
+    }
+}
+
 #[test]
 fn test_simple_arithmetic_errors() {
     let tests = [
@@ -597,6 +672,46 @@ fn test_simple_arithmetic_errors() {
     }
 }
 
+#[test]
+fn test_runtime_error_stack_trace() {
+    let program = "(define-private (divide (x int))
+  (/ x 0))
+(divide 1)";
+
+    match vm_execute(program).unwrap_err() {
+        Error::Runtime(RuntimeErrorType::DivisionByZero, Some(stack_trace)) => {
+            // the frames are outermost first, each pointing at its application.
+            assert_eq!(stack_trace.len(), 2);
+            assert!(!stack_trace[0].function.is_native());
+            let call_site = stack_trace[0].call_site.as_ref().unwrap();
+            assert_eq!((call_site.start_line, call_site.start_column), (3, 1));
+            assert!(stack_trace[1].function.is_native());
+            let call_site = stack_trace[1].call_site.as_ref().unwrap();
+            assert_eq!((call_site.start_line, call_site.start_column), (2, 3));
+        }
+        e => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn test_short_return_stack_trace() {
+    let program = "(begin
+  (unwrap! (some 1) (err 0))
+  (unwrap! none (err 2)))";
+
+    match vm_execute(program).unwrap_err() {
+        Error::ShortReturn(ShortReturnType::ExpectedValue(_), Some(stack_trace)) => {
+            // the trace points at the unwrap! which short-returned.
+            assert_eq!(stack_trace.len(), 2);
+            let call_site = stack_trace[0].call_site.as_ref().unwrap();
+            assert_eq!((call_site.start_line, call_site.start_column), (1, 1));
+            let call_site = stack_trace[1].call_site.as_ref().unwrap();
+            assert_eq!((call_site.start_line, call_site.start_column), (3, 3));
+        }
+        e => panic!("Unexpected error: {:?}", e),
+    }
+}
+
 #[test]
 fn test_unsigned_arithmetic() {
     let tests = [
@@ -626,6 +741,226 @@ fn test_unsigned_arithmetic() {
     }
 }
 
+#[test]
+fn test_conversion_functions() {
+    let tests = [
+        "(buff-to-int-le 0x01)",
+        "(buff-to-int-le 0xfeffffffffffffffffffffffffffffff)",
+        "(buff-to-uint-le 0x0001)",
+        "(buff-to-int-be 0x01)",
+        "(buff-to-int-be 0xfffffffffffffffffffffffffffffffe)",
+        "(buff-to-uint-be 0x0100)",
+        "(buff-to-uint-be 0x)",
+        "(int-to-ascii -170141183460469231731687303715884105728)",
+        "(int-to-ascii u340282366920938463463374607431768211455)",
+        "(string-to-int? \"-12\")",
+        "(string-to-int? u\"+12\")",
+        "(string-to-int? \"12a\")",
+        "(string-to-int? \"\")",
+    ];
+
+    let expectations = [
+        Value::Int(1),
+        Value::Int(-2),
+        Value::UInt(256),
+        Value::Int(1),
+        Value::Int(-2),
+        Value::UInt(256),
+        Value::UInt(0),
+        Value::string_ascii_from_bytes(
+            "-170141183460469231731687303715884105728"
+                .as_bytes()
+                .to_vec(),
+        )
+        .unwrap(),
+        Value::string_ascii_from_bytes(
+            "340282366920938463463374607431768211455"
+                .as_bytes()
+                .to_vec(),
+        )
+        .unwrap(),
+        Value::some(Value::Int(-12)).unwrap(),
+        Value::some(Value::Int(12)).unwrap(),
+        Value::none(),
+        Value::none(),
+    ];
+
+    for (program, expectation) in tests.iter().zip(expectations.iter()) {
+        assert_eq!(*expectation, execute(program));
+    }
+
+    let too_long = "(buff-to-int-le 0x0102030405060708090a0b0c0d0e0f1011)";
+    assert_eq!(
+        vm_execute(too_long).unwrap_err(),
+        CheckErrors::TypeValueError(
+            BUFF_16.clone(),
+            Value::buff_from(hex_bytes("0102030405060708090a0b0c0d0e0f1011").unwrap()).unwrap()
+        )
+        .into()
+    );
+}
+
+#[test]
+fn test_principal_functions() {
+    let principal = |literal: &str| Value::Principal(PrincipalData::parse(literal).unwrap());
+    let destructed = |version: u8, name: Option<&str>| {
+        let name = match name {
+            Some(name) => {
+                Value::some(Value::string_ascii_from_bytes(name.as_bytes().to_vec()).unwrap())
+                    .unwrap()
+            }
+            None => Value::none(),
+        };
+        Value::Tuple(
+            TupleData::from_data(vec![
+                ("version".into(), Value::buff_from_byte(version)),
+                (
+                    "hash-bytes".into(),
+                    Value::buff_from(
+                        hex_bytes("fa6bf38ed557fe417333710d6033e9419391a320").unwrap(),
+                    )
+                    .unwrap(),
+                ),
+                ("name".into(), name),
+            ])
+            .unwrap(),
+        )
+    };
+    let construct_error = |error_code: u128, value: Option<Value>| {
+        let value = match value {
+            Some(value) => Value::some(value).unwrap(),
+            None => Value::none(),
+        };
+        Value::error(Value::Tuple(
+            TupleData::from_data(vec![
+                ("error_code".into(), Value::UInt(error_code)),
+                ("value".into(), value),
+            ])
+            .unwrap(),
+        ))
+        .unwrap()
+    };
+
+    let tests = [
+        "(is-standard 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK)",
+        "(is-standard 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo)",
+        "(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY)",
+        "(is-standard 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        "(principal-destruct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK)",
+        "(principal-destruct? 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.foo)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 \"foo\")",
+        "(principal-construct? 0x16 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        "(principal-construct? 0x20 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        "(principal-construct? 0x 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a3)",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 \"\")",
+        "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 \"foo.bar\")",
+    ];
+
+    let expectations = [
+        Value::Bool(true),
+        Value::Bool(true),
+        Value::Bool(false),
+        Value::Bool(false),
+        Value::okay(destructed(0x1a, None)).unwrap(),
+        Value::error(destructed(0x16, Some("foo"))).unwrap(),
+        Value::okay(principal("ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK")).unwrap(),
+        Value::okay(principal("ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo")).unwrap(),
+        construct_error(
+            0,
+            Some(principal("SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY")),
+        ),
+        construct_error(1, None),
+        construct_error(1, None),
+        construct_error(1, None),
+        construct_error(1, None),
+        construct_error(1, None),
+    ];
+
+    for (program, expectation) in tests.iter().zip(expectations.iter()) {
+        assert_eq!(*expectation, execute(program));
+    }
+
+    // on mainnet, the mainnet versions are the standard ones.
+    let mainnet_tests = [
+        "(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY)",
+        "(is-standard 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK)",
+        "(principal-destruct? 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY)",
+        "(principal-construct? 0x16 0xfa6bf38ed557fe417333710d6033e9419391a320)",
+    ];
+
+    let mainnet_expectations = [
+        Value::Bool(true),
+        Value::Bool(false),
+        Value::okay(destructed(0x16, None)).unwrap(),
+        Value::okay(principal("SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY")).unwrap(),
+    ];
+
+    for (program, expectation) in mainnet_tests.iter().zip(mainnet_expectations.iter()) {
+        assert_eq!(
+            *expectation,
+            execute_on_network(program, true).unwrap().unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_consensus_buff_functions() {
+    let some_buff =
+        |hex: &str| Value::some(Value::buff_from(hex_bytes(hex).unwrap()).unwrap()).unwrap();
+
+    let tests = [
+        "(to-consensus-buff 1)",
+        "(to-consensus-buff u1)",
+        "(to-consensus-buff none)",
+        "(to-consensus-buff (ok true))",
+        "(to-consensus-buff 0x0102)",
+        "(to-consensus-buff \"ab\")",
+        "(to-consensus-buff (tuple (a 1)))",
+        "(from-consensus-buff int 0x0000000000000000000000000000000001)",
+        "(from-consensus-buff uint 0x0000000000000000000000000000000001)",
+        "(from-consensus-buff int 0x000000000000000000000000000000000100)",
+        "(from-consensus-buff int 0x00)",
+        "(from-consensus-buff (buff 1) 0x020000000102)",
+        "(from-consensus-buff (buff 1) (unwrap-panic (to-consensus-buff 0x0102)))",
+        "(from-consensus-buff (list 5 int) (unwrap-panic (to-consensus-buff (list 1 2))))",
+        "(from-consensus-buff (tuple (a int)) (unwrap-panic (to-consensus-buff (tuple (a 1)))))",
+        "(from-consensus-buff (tuple (b int)) (unwrap-panic (to-consensus-buff (tuple (a 1)))))",
+        "(from-consensus-buff principal (unwrap-panic (to-consensus-buff 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo)))",
+    ];
+
+    let expectations = [
+        some_buff("0000000000000000000000000000000001"),
+        some_buff("0100000000000000000000000000000001"),
+        some_buff("09"),
+        some_buff("0703"),
+        some_buff("02000000020102"),
+        some_buff("0d000000026162"),
+        some_buff("0c0000000101610000000000000000000000000000000001"),
+        Value::some(Value::Int(1)).unwrap(),
+        Value::none(),
+        Value::none(),
+        Value::none(),
+        Value::some(Value::buff_from_byte(2)).unwrap(),
+        Value::none(),
+        Value::some(Value::list_from(vec![Value::Int(1), Value::Int(2)]).unwrap()).unwrap(),
+        Value::some(Value::Tuple(
+            TupleData::from_data(vec![("a".into(), Value::Int(1))]).unwrap(),
+        ))
+        .unwrap(),
+        Value::none(),
+        Value::some(Value::Principal(
+            PrincipalData::parse("ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo").unwrap(),
+        ))
+        .unwrap(),
+    ];
+
+    for (program, expectation) in tests.iter().zip(expectations.iter()) {
+        assert_eq!(*expectation, execute(program));
+    }
+}
+
 #[test]
 fn test_options_errors() {
     let tests = [
@@ -1000,12 +1335,14 @@ fn test_asserts_short_circuit() {
     ];
 
     let expectations: &[Error] = &[
-        Error::ShortReturn(ShortReturnType::AssertionFailed(
-            Value::error(Value::Int(0)).unwrap(),
-        )),
-        Error::ShortReturn(ShortReturnType::AssertionFailed(
-            Value::error(Value::Int(1)).unwrap(),
-        )),
+        Error::ShortReturn(
+            ShortReturnType::AssertionFailed(Value::error(Value::Int(0)).unwrap()),
+            None,
+        ),
+        Error::ShortReturn(
+            ShortReturnType::AssertionFailed(Value::error(Value::Int(1)).unwrap()),
+            None,
+        ),
     ];
 
     tests
@@ -0,0 +1,266 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use burnchains::Txid;
+use chainstate::stacks::events::StacksTransactionEvent;
+use vm::costs::ExecutionCost;
+use vm::errors::InterpreterResult as Result;
+use vm::representations::ClarityName;
+use vm::types::{QualifiedContractIdentifier, Value};
+
+/// A single function invocation recorded by the CallTracer, along with
+///  every invocation it made in turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallTrace {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub function_name: ClarityName,
+    pub args: Vec<Value>,
+    pub result: std::result::Result<Value, String>,
+    pub cost: ExecutionCost,
+    pub events: Vec<StacksTransactionEvent>,
+    pub children: Vec<CallTrace>,
+}
+
+struct OpenFrame {
+    trace: CallTrace,
+    cost_at_entry: ExecutionCost,
+    event_offset: usize,
+}
+
+/// The CallTracer is an opt-in recorder of function entries and exits. When
+///  enabled on a GlobalContext, each user-defined function invocation is recorded
+///  with its arguments, its result, the cost it consumed and the events it emitted.
+pub struct CallTracer {
+    open_frames: Vec<OpenFrame>,
+    roots: Vec<CallTrace>,
+}
+
+impl CallTrace {
+    fn is_committed(&self) -> bool {
+        match self.result {
+            Ok(Value::Response(ref data)) => data.committed,
+            Ok(_) => true,
+            Err(_) => false,
+        }
+    }
+
+    pub fn json_serialize(&self) -> serde_json::Value {
+        let committed = self.is_committed();
+        let no_txid = Txid([0u8; 32]);
+        let events: Vec<_> = self
+            .events
+            .iter()
+            .enumerate()
+            .map(|(event_index, event)| {
+                let mut event_json = event.json_serialize(event_index, &no_txid, committed);
+                // traced calls are not necessarily part of a transaction.
+                if let Some(event_obj) = event_json.as_object_mut() {
+                    event_obj.remove("txid");
+                }
+                event_json
+            })
+            .collect();
+        let args: Vec<_> = self.args.iter().map(|arg| arg.to_string()).collect();
+        let children: Vec<_> = self
+            .children
+            .iter()
+            .map(|child| child.json_serialize())
+            .collect();
+
+        let mut result = json!({
+            "contract_id": self.contract_identifier.to_string(),
+            "function": self.function_name.to_string(),
+            "args": args,
+            "cost": {
+                "runtime": self.cost.runtime,
+                "write_length": self.cost.write_length,
+                "write_count": self.cost.write_count,
+                "read_length": self.cost.read_length,
+                "read_count": self.cost.read_count,
+            },
+            "events": events,
+            "children": children,
+        });
+        match self.result {
+            Ok(ref value) => result["result"] = json!(value.to_string()),
+            Err(ref error) => result["error"] = json!(error),
+        }
+        result
+    }
+}
+
+impl CallTracer {
+    pub fn new() -> CallTracer {
+        CallTracer {
+            open_frames: vec![],
+            roots: vec![],
+        }
+    }
+
+    /// Record entry into a function. `events` are the events already in the
+    ///  current event batch, which are not attributed to this call.
+    pub fn enter(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        function_name: &ClarityName,
+        args: &[Value],
+        cost_at_entry: ExecutionCost,
+        events: &[StacksTransactionEvent],
+    ) {
+        self.open_frames.push(OpenFrame {
+            trace: CallTrace {
+                contract_identifier: contract_identifier.clone(),
+                function_name: function_name.clone(),
+                args: args.to_vec(),
+                result: Err("call did not return".to_string()),
+                cost: ExecutionCost::zero(),
+                events: vec![],
+                children: vec![],
+            },
+            cost_at_entry,
+            event_offset: events.len(),
+        });
+    }
+
+    /// Record exit from the innermost open function. `events` is the same event
+    ///  batch that was passed to `enter()`.
+    pub fn exit(
+        &mut self,
+        result: &Result<Value>,
+        cost_at_exit: ExecutionCost,
+        events: &[StacksTransactionEvent],
+    ) {
+        let OpenFrame {
+            mut trace,
+            cost_at_entry,
+            event_offset,
+        } = match self.open_frames.pop() {
+            Some(frame) => frame,
+            None => {
+                warn!("CallTracer exited a call it never entered");
+                return;
+            }
+        };
+
+        trace.result = match result {
+            Ok(value) => Ok(value.clone()),
+            Err(e) => Err(e.to_string()),
+        };
+        let mut cost = cost_at_exit;
+        trace.cost = match cost.sub(&cost_at_entry) {
+            Ok(_) => cost,
+            // the cost tracker was swapped out during the call (e.g., a short-circuited
+            //  contract-call), so there's no meaningful difference.
+            Err(_) => ExecutionCost::zero(),
+        };
+        if event_offset <= events.len() {
+            trace.events = events[event_offset..].to_vec();
+        }
+
+        match self.open_frames.last_mut() {
+            Some(parent) => parent.trace.children.push(trace),
+            None => self.roots.push(trace),
+        }
+    }
+
+    /// Consume the tracer, returning the top-level calls it recorded.
+    pub fn into_traces(self) -> Vec<CallTrace> {
+        self.roots
+    }
+
+    pub fn json_serialize(&self) -> serde_json::Value {
+        let roots: Vec<_> = self
+            .roots
+            .iter()
+            .map(|root| root.json_serialize())
+            .collect();
+        json!(roots)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::MemoryBackingStore;
+    use vm::types::PrincipalData;
+    use vm::SymbolicExpression;
+
+    #[test]
+    fn test_call_tree() {
+        let callee_id = QualifiedContractIdentifier::local("callee").unwrap();
+        let caller_id = QualifiedContractIdentifier::local("caller").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+
+        let callee = "(define-private (noisy (x int)) (begin (print x) x))
+                      (define-public (inner (x int)) (ok (noisy (+ x 1))))
+                      (define-public (fails) (err u1))";
+        let caller = "(define-public (outer (x int))
+                        (begin
+                          (unwrap-err! (contract-call? .callee fails) (err u2))
+                          (contract-call? .callee inner x)))";
+
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db());
+        owned_env
+            .initialize_contract(callee_id.clone(), callee)
+            .unwrap();
+        owned_env
+            .initialize_contract(caller_id.clone(), caller)
+            .unwrap();
+
+        owned_env.enable_tracing();
+        let (result, _, _) = owned_env
+            .execute_transaction(
+                Value::Principal(sender),
+                caller_id.clone(),
+                "outer",
+                &[SymbolicExpression::atom_value(Value::Int(1))],
+            )
+            .unwrap();
+        assert_eq!(result, Value::okay(Value::Int(2)).unwrap());
+
+        let traces = owned_env.take_trace().unwrap().into_traces();
+        assert_eq!(traces.len(), 1);
+
+        let outer = &traces[0];
+        assert_eq!(outer.contract_identifier, caller_id);
+        assert_eq!(outer.function_name.as_str(), "outer");
+        assert_eq!(outer.args, vec![Value::Int(1)]);
+        assert_eq!(outer.children.len(), 2);
+
+        let fails = &outer.children[0];
+        assert_eq!(fails.function_name.as_str(), "fails");
+        assert_eq!(fails.result, Ok(Value::error(Value::UInt(1)).unwrap()));
+
+        let inner = &outer.children[1];
+        assert_eq!(inner.contract_identifier, callee_id);
+        assert_eq!(inner.function_name.as_str(), "inner");
+        assert_eq!(inner.children.len(), 1);
+        assert_eq!(inner.events.len(), 1);
+
+        let noisy = &inner.children[0];
+        assert_eq!(noisy.function_name.as_str(), "noisy");
+        assert_eq!(noisy.args, vec![Value::Int(2)]);
+        assert_eq!(noisy.result, Ok(Value::Int(2)));
+        assert_eq!(noisy.events.len(), 1);
+
+        let json = outer.json_serialize();
+        assert_eq!(json["function"], "outer");
+        assert_eq!(json["result"], "(ok 2)");
+        assert_eq!(json["children"][1]["children"][0]["args"][0], "2");
+    }
+}
@@ -35,8 +35,8 @@ use vm::representations::{ClarityName, ContractName, SymbolicExpression, Symboli
 pub use vm::types::signatures::{
     parse_name_type_pairs, AssetIdentifier, BufferLength, FixedFunction, FunctionArg,
     FunctionSignature, FunctionType, ListTypeData, SequenceSubtype, StringSubtype,
-    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_20, BUFF_32, BUFF_33,
-    BUFF_64, BUFF_65,
+    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_16, BUFF_20, BUFF_32,
+    BUFF_33, BUFF_64, BUFF_65,
 };
 
 pub const MAX_VALUE_SIZE: u32 = 1024 * 1024; // 1MB
@@ -263,6 +263,90 @@ impl SequenceData {
         Some(result)
     }
 
+    /// The sub-sequence from `left_position` (inclusive) to `right_position` (exclusive),
+    ///  or None if the positions are out of bounds.
+    pub fn slice(self, left_position: usize, right_position: usize) -> Result<Option<Value>> {
+        if left_position > right_position || right_position > self.len() {
+            return Ok(None);
+        }
+        let result = match self {
+            SequenceData::Buffer(data) => {
+                Value::buff_from(data.data[left_position..right_position].to_vec())?
+            }
+            SequenceData::List(data) => {
+                let (entry_type, _) = data.type_signature.destruct();
+                let slice_type =
+                    ListTypeData::new_list(entry_type, (right_position - left_position) as u32)?;
+                Value::list_with_type(
+                    data.data[left_position..right_position].to_vec(),
+                    slice_type,
+                )?
+            }
+            SequenceData::String(CharType::ASCII(data)) => {
+                Value::string_ascii_from_bytes(data.data[left_position..right_position].to_vec())?
+            }
+            SequenceData::String(CharType::UTF8(data)) => {
+                Value::Sequence(SequenceData::String(CharType::UTF8(UTF8Data {
+                    data: data.data[left_position..right_position].to_vec(),
+                })))
+            }
+        };
+
+        Ok(Some(result))
+    }
+
+    /// Replace the element at `index` with `element`, which must be a single element of
+    ///  this sequence's type (e.g., a 1-byte buffer for buffers). Returns None if the index
+    ///  is out of bounds.
+    pub fn replace_at(self, index: usize, element: Value) -> Result<Option<Value>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        let result = match (self, element) {
+            (SequenceData::Buffer(mut data), Value::Sequence(SequenceData::Buffer(elem)))
+                if elem.data.len() == 1 =>
+            {
+                data.data[index] = elem.data[0];
+                Value::Sequence(SequenceData::Buffer(data))
+            }
+            (SequenceData::List(data), element) => {
+                let ListData {
+                    data: mut items,
+                    type_signature,
+                } = data;
+                let (entry_type, max_len) = type_signature.destruct();
+                let element_type = TypeSignature::type_of(&element);
+                let next_entry_type = TypeSignature::least_supertype(&entry_type, &element_type)
+                    .map_err(|_| CheckErrors::TypeValueError(entry_type, element.clone()))?;
+                items[index] = element;
+                Value::list_with_type(items, ListTypeData::new_list(next_entry_type, max_len)?)?
+            }
+            (
+                SequenceData::String(CharType::ASCII(mut data)),
+                Value::Sequence(SequenceData::String(CharType::ASCII(elem))),
+            ) if elem.data.len() == 1 => {
+                data.data[index] = elem.data[0];
+                Value::Sequence(SequenceData::String(CharType::ASCII(data)))
+            }
+            (
+                SequenceData::String(CharType::UTF8(mut data)),
+                Value::Sequence(SequenceData::String(CharType::UTF8(mut elem))),
+            ) if elem.data.len() == 1 => {
+                data.data[index] = elem.data.remove(0);
+                Value::Sequence(SequenceData::String(CharType::UTF8(data)))
+            }
+            (sequence, element) => {
+                let expected_type = match TypeSignature::type_of(&Value::Sequence(sequence)) {
+                    TypeSignature::SequenceType(sequence_type) => sequence_type.unit_type(),
+                    _ => unreachable!("BUG: sequence data must have a sequence type"),
+                };
+                return Err(CheckErrors::TypeValueError(expected_type, element).into());
+            }
+        };
+
+        Ok(Some(result))
+    }
+
     pub fn contains(&self, to_find: Value) -> Result<Option<usize>> {
         match self {
             SequenceData::Buffer(ref data) => {
@@ -564,6 +648,9 @@ define_named_enum!(BlockInfoProperty {
     IdentityHeaderHash("id-header-hash"),
     BurnchainHeaderHash("burnchain-header-hash"),
     MinerAddress("miner-address"),
+    BurnchainBlockHeight("burnchain-block-height"),
+    MinerReward("miner-reward"),
+    TotalFees("total-fees"),
 });
 
 impl OptionalData {
@@ -596,7 +683,7 @@ impl BlockInfoProperty {
     pub fn type_result(&self) -> TypeSignature {
         use self::BlockInfoProperty::*;
         match self {
-            Time => TypeSignature::UIntType,
+            Time | BurnchainBlockHeight | MinerReward | TotalFees => TypeSignature::UIntType,
             IdentityHeaderHash | VrfSeed | HeaderHash | BurnchainHeaderHash => BUFF_32.clone(),
             MinerAddress => TypeSignature::PrincipalType,
         }
@@ -650,6 +650,52 @@ impl Value {
     }
 }
 
+impl TypeSignature {
+    /// The largest number of bytes that the consensus serialization of a value of
+    ///  this type may occupy, or None if the bound does not fit in a u32.
+    pub fn max_serialized_size(&self) -> Option<u32> {
+        use super::SequenceSubtype::*;
+        use super::StringSubtype::*;
+        use super::TypeSignature::*;
+
+        // every serialized value starts with a 1-byte type prefix.
+        let payload_size = match self {
+            // NoType values are only ever the `none` or an unused response branch.
+            NoType | BoolType => 0,
+            IntType | UIntType => 16,
+            // version byte + 20-byte hash + a length-prefixed contract name.
+            PrincipalType | TraitReferenceType(_) => 1 + 20 + 1 + (MAX_STRING_LEN as u32),
+            OptionalType(inner) => inner.max_serialized_size()?,
+            ResponseType(branches) => {
+                let (ok_type, err_type) = (&branches.0, &branches.1);
+                std::cmp::max(
+                    ok_type.max_serialized_size()?,
+                    err_type.max_serialized_size()?,
+                )
+            }
+            SequenceType(ListType(list_type)) => list_type
+                .get_list_item_type()
+                .max_serialized_size()?
+                .checked_mul(list_type.get_max_len())?
+                .checked_add(4)?,
+            SequenceType(BufferType(len)) => u32::from(len).checked_add(4)?,
+            SequenceType(StringType(ASCII(len))) => u32::from(len).checked_add(4)?,
+            // utf8 characters are at most 4 bytes long.
+            SequenceType(StringType(UTF8(len))) => u32::from(len).checked_mul(4)?.checked_add(4)?,
+            TupleType(tuple_type) => {
+                let mut size = 4u32;
+                for (name, field_type) in tuple_type.get_type_map().iter() {
+                    size = size
+                        .checked_add(1 + name.len() as u32)?
+                        .checked_add(field_type.max_serialized_size()?)?;
+                }
+                size
+            }
+        };
+        payload_size.checked_add(1)
+    }
+}
+
 impl ClaritySerializable for Value {
     fn serialize(&self) -> String {
         let mut byte_serialization = Vec::new();
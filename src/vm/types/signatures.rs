@@ -22,6 +22,7 @@ use std::{cmp, fmt};
 
 use address::c32;
 use util::hash;
+use vm::ast::parser::CONTRACT_MAX_NAME_LENGTH;
 use vm::costs::{cost_functions, runtime_cost, CostOverflowingMath};
 use vm::errors::{CheckErrors, Error as VMError, IncomparableError, RuntimeErrorType};
 use vm::representations::{
@@ -130,6 +131,7 @@ pub const BUFF_65: TypeSignature = SequenceType(SequenceSubtype::BufferType(Buff
 pub const BUFF_32: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(32)));
 pub const BUFF_33: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(33)));
 pub const BUFF_20: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(20)));
+pub const BUFF_16: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(16)));
 pub const BUFF_1: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(1)));
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -160,6 +162,8 @@ pub enum FunctionType {
     ArithmeticUnary,
     ArithmeticBinary,
     ArithmeticComparison,
+    // Functions which shift an integer by a uint amount, e.g., bit-shift-left
+    ArithmeticShift,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -644,6 +648,32 @@ impl TypeSignature {
         )))
     }
 
+    /// The type of a contract name as a string, i.e., `(string-ascii 40)`.
+    pub fn contract_name_string_ascii_type() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+            BufferLength::try_from(CONTRACT_MAX_NAME_LENGTH)
+                .expect("FAIL: contract name max length exceeds the max buffer length"),
+        )))
+    }
+
+    pub fn max_string_ascii() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+            BufferLength(
+                u32::try_from(MAX_VALUE_SIZE)
+                    .expect("FAIL: Max Clarity Value Size is no longer realizable in ASCII Type"),
+            ),
+        )))
+    }
+
+    pub fn max_string_utf8() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+            StringUTF8Length(
+                u32::try_from(MAX_VALUE_SIZE / 4)
+                    .expect("FAIL: Max Clarity Value Size is no longer realizable in UTF8 Type"),
+            ),
+        )))
+    }
+
     /// If one of the types is a NoType, return Ok(the other type), otherwise return least_supertype(a, b)
     pub fn factor_out_no_type(a: &TypeSignature, b: &TypeSignature) -> Result<TypeSignature> {
         if a.is_no_type() {
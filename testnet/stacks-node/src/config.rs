@@ -469,6 +469,16 @@ impl Config {
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
                     use_test_genesis_chainstate: node.use_test_genesis_chainstate,
+                    marf_prune_depth: node.marf_prune_depth,
+                    marf_cache_size: node
+                        .marf_cache_size
+                        .unwrap_or(default_node_config.marf_cache_size),
+                    marf_blob_files: node
+                        .marf_blob_files
+                        .unwrap_or(default_node_config.marf_blob_files),
+                    clarity_key_index: node
+                        .clarity_key_index
+                        .unwrap_or(default_node_config.clarity_key_index),
                 };
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
@@ -1040,6 +1050,20 @@ pub struct NodeConfig {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: u64,
     pub use_test_genesis_chainstate: Option<bool>,
+    /// If set, drop the Clarity state of blocks this many or more blocks below the canonical
+    /// chain tip (non-archival mode).
+    pub marf_prune_depth: Option<u32>,
+    /// Approximate number of bytes of memory to spend caching MARF nodes, shared between the
+    /// chainstate index and the Clarity state (0 disables the cache).
+    pub marf_cache_size: u64,
+    /// Store the chainstate MARFs' confirmed tries in blob files next to their databases.  Only
+    /// takes effect when the chainstate is first created; existing chainstates can be moved over
+    /// with `blockstack-core marf-migrate-blobs`.
+    pub marf_blob_files: bool,
+    /// Index the keys of data map entries and NFTs, so that the RPC interface can list them.
+    /// The index can't be built for an existing chainstate, so this only lists entries if it is
+    /// set when the chainstate is first created, and unsetting it later drops the index for good.
+    pub clarity_key_index: bool,
 }
 
 impl NodeConfig {
@@ -1079,6 +1103,10 @@ impl NodeConfig {
             prometheus_bind: None,
             pox_sync_sample_secs: 30,
             use_test_genesis_chainstate: None,
+            marf_prune_depth: None,
+            marf_cache_size: 0,
+            marf_blob_files: false,
+            clarity_key_index: false,
         }
     }
 
@@ -1231,6 +1259,10 @@ pub struct NodeConfigFile {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: Option<u64>,
     pub use_test_genesis_chainstate: Option<bool>,
+    pub marf_prune_depth: Option<u32>,
+    pub marf_cache_size: Option<u64>,
+    pub marf_blob_files: Option<bool>,
+    pub clarity_key_index: Option<bool>,
 }
 
 #[derive(Clone, Deserialize, Default)]
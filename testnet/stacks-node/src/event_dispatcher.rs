@@ -181,6 +181,15 @@ impl EventObserver {
                 None => json!(null),
             }
         };
+        let stack_trace_json = match &receipt.stack_trace {
+            Some(stack_trace) => serde_json::Value::Array(
+                stack_trace
+                    .iter()
+                    .map(|frame| frame.json_serialize())
+                    .collect(),
+            ),
+            None => json!(null),
+        };
         json!({
             "txid": format!("0x{}", &txid),
             "tx_index": tx_index,
@@ -189,6 +198,7 @@ impl EventObserver {
             "raw_tx": format!("0x{}", &raw_tx),
             "contract_abi": contract_interface_json,
             "execution_cost": receipt.execution_cost,
+            "stack_trace": stack_trace_json,
         })
     }
 
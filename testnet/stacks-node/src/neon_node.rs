@@ -549,6 +549,10 @@ fn spawn_peer(
         block_limit,
     )
     .map_err(|e| NetError::ChainstateError(e.to_string()))?;
+    chainstate.set_marf_cache_size(config.node.marf_cache_size as usize);
+    chainstate
+        .set_clarity_key_index(config.node.clarity_key_index)
+        .map_err(|e| NetError::ChainstateError(e.to_string()))?;
 
     let mut mem_pool = MemPoolDB::open(
         is_mainnet,
@@ -718,6 +722,10 @@ fn spawn_miner_relayer(
         config.block_limit.clone(),
     )
     .map_err(|e| NetError::ChainstateError(e.to_string()))?;
+    chainstate.set_marf_cache_size(config.node.marf_cache_size as usize);
+    chainstate
+        .set_clarity_key_index(config.node.clarity_key_index)
+        .map_err(|e| NetError::ChainstateError(e.to_string()))?;
 
     let mut mem_pool = MemPoolDB::open(is_mainnet, chain_id, &stacks_chainstate_path)
         .map_err(NetError::DBError)?;
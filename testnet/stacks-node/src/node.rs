@@ -256,6 +256,8 @@ impl Node {
                 get_namespaces(use_test_genesis_data)
             })),
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
+            marf_blob_files: false,
+            clarity_key_index: false,
         };
 
         let chain_state_result = StacksChainState::open_and_exec(
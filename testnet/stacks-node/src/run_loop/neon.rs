@@ -218,9 +218,11 @@ impl RunLoop {
                 get_namespaces(USE_TEST_GENESIS_CHAINSTATE)
             })),
             get_bulk_initial_names: Some(Box::new(|| get_names(USE_TEST_GENESIS_CHAINSTATE))),
+            marf_blob_files: self.config.node.marf_blob_files,
+            clarity_key_index: self.config.node.clarity_key_index,
         };
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             mainnet,
             chainid,
             &chainstate_path,
@@ -228,6 +230,11 @@ impl RunLoop {
             block_limit,
         )
         .unwrap();
+        chain_state_db.marf_prune_depth = self.config.node.marf_prune_depth;
+        chain_state_db.set_marf_cache_size(self.config.node.marf_cache_size as usize);
+        chain_state_db
+            .set_clarity_key_index(self.config.node.clarity_key_index)
+            .unwrap();
         coordinator_dispatcher.dispatch_boot_receipts(receipts);
 
         let atlas_config = AtlasConfig::default(mainnet);
@@ -512,7 +512,9 @@ fn integration_test_get_info() {
 
                 let body = CallReadOnlyRequestBody {
                     sender: "'SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5".into(),
-                    arguments: vec![Value::UInt(1).serialize()]
+                    arguments: vec![Value::UInt(1).serialize()],
+                    trace: false,
+                    proof: false,
                 };
 
                 let res = client.post(&path)
@@ -534,7 +536,9 @@ fn integration_test_get_info() {
 
                 let body = CallReadOnlyRequestBody {
                     sender: "'SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5".into(),
-                    arguments: vec![Value::UInt(1).serialize()]
+                    arguments: vec![Value::UInt(1).serialize()],
+                    trace: false,
+                    proof: false,
                 };
 
                 let res = client.post(&path)
@@ -556,7 +560,9 @@ fn integration_test_get_info() {
 
                 let body = CallReadOnlyRequestBody {
                     sender: "'SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5".into(),
-                    arguments: vec![Value::UInt(100).serialize()]
+                    arguments: vec![Value::UInt(100).serialize()],
+                    trace: false,
+                    proof: false,
                 };
 
                 let res = client.post(&path)
@@ -574,7 +580,9 @@ fn integration_test_get_info() {
 
                 let body = CallReadOnlyRequestBody {
                     sender: "'SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5".into(),
-                    arguments: vec![]
+                    arguments: vec![],
+                    trace: false,
+                    proof: false,
                 };
 
                 let res = client.post(&path)